@@ -7,29 +7,41 @@
 use std::path::PathBuf;
 
 use clap::{ArgAction, Parser};
-use gps::SignalGeneratorBuilder;
+use gps::{Handover, Interpolation, ReEpoch, Signal, SignalGeneratorBuilder};
 
 use crate::Error;
 
 /*
 
 Options:
-  -e <gps_nav>     RINEX navigation file for GPS ephemerides (required)
+  -e <gps_nav>     RINEX navigation file(s) for GPS ephemerides, comma-separated and optionally .gz/.Z compressed (required unless --almanac or --synthetic)
   -u <user_motion> User motion file in ECEF x, y, z format (dynamic mode)
   -x <user_motion> User motion file in lat, lon, height format (dynamic mode)
+  --interpolation <method> Interpolation of timestamped user motion: linear or hermite (default: linear)
   -g <nmea_gga>    NMEA GGA stream (dynamic mode)
+  --profile <file> Motion profile of segments in TOML format (dynamic mode)
   -c <location>    ECEF X,Y,Z in meters (static mode) e.g. 3967283.154,1022538.181,4872414.484
   -l <location>    Lat, lon, height (static mode) e.g. 35.681298,139.766247,10.0
   -L <wnslf,dn,dtslf> User leap future event in GPS week number, day number, next leap second e.g. 2347,3,19
-  -t <date,time>   Scenario start time YYYY/MM/DD,hh:mm:ss
+  -t <date,time>   Scenario start time YYYY/MM/DD,hh:mm:ss in UTC, or followed by GPST, TAI, GST or BDT
   -T <date,time>   Overwrite TOC and TOE to scenario start time
-  -d <duration>    Duration [sec] (dynamic mode max: {}, static mode max: {})
+  --re-epoch <mode> How -T moves the ephemerides: shift or propagate (default: shift)
+  -d <duration>    Duration [sec]
   -o <output>      I/Q sampling data file (default: gpssim.bin)
   -s <frequency>   Sampling frequency [Hz] (default: 2600000)
+  --interval <step> Time step between position and Doppler updates, in s or ms (e.g. 0.01 or 10ms), 1 ms to 100 ms (default: 0.1)
   -b <iq_bits>     I/Q data format [1/8/16] (default: 16)
   -i               Disable ionospheric delay for spacecraft scenario
   -p [fixed_gain]  Disable path loss and hold power level constant
   -v               Show details about simulated channels
+  --signals <list> Signals to generate e.g. L1CA,L2C,L5,E1,B1I,L1OF,SBAS,QZSS (default: L1CA)
+  --galileo-codes <file> Galileo E1-B/E1-C memory code file (required for E1)
+  --glonass-center <frequency> Centre frequency of the GLONASS G1 output [Hz] (default: 1602000000)
+  --sbas-file <file> SBAS GEO satellite and message configuration (required for SBAS)
+  --almanac <file> YUMA or SEM almanac for GPS ephemerides propagated to any date
+  --synthetic <n>  Nominal GPS constellation of n satellites (e.g. 24 or 31) without navigation data
+  --sp3 <files>    SP3 precise orbit and clock file(s) for the true satellite positions, comma-separated
+  --handover <rule> Ephemeris handover: closest, tom, or seconds before TOE e.g. 7200 (default: closest)
 */
 /// Command-line arguments for the GPS signal simulator.
 ///
@@ -41,9 +53,18 @@ Options:
 #[command(version, about="gps-sdr-sim compatible", long_about = None)]
 #[command(propagate_version = true)]
 pub struct Args {
-    /// RINEX navigation file for GPS ephemerides (required)
-    #[arg(short, long, value_hint = clap::ValueHint::FilePath)]
-    ephemerides: std::path::PathBuf,
+    /// RINEX navigation file for GPS ephemerides (required unless
+    /// --almanac or --synthetic); several files, e.g. consecutive daily
+    /// files, are separated by commas and merged. Gzip (.gz) and Unix
+    /// compress (.Z) files are decompressed
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["almanac", "synthetic"],
+        value_delimiter = ',',
+        value_hint = clap::ValueHint::FilePath
+    )]
+    ephemerides: Vec<PathBuf>,
 
     /// User motion file in ECEF x, y, z format (dynamic mode)
     #[arg(short = 'u', long, value_hint = clap::ValueHint::FilePath)]
@@ -53,10 +74,22 @@ pub struct Args {
     #[arg(short = 'x', long, value_hint = clap::ValueHint::FilePath)]
     user_motion_llh: Option<PathBuf>,
 
+    /// Interpolation of the timestamped records of a user motion file onto
+    /// the simulation epochs: `linear` (default) or `hermite`, cubic curves
+    /// with the velocities of the records
+    #[arg(long)]
+    interpolation: Option<Interpolation>,
+
     /// NMEA GGA stream (dynamic mode)
     #[arg(short = 'g', long, value_hint = clap::ValueHint::FilePath)]
     nmea_gga: Option<PathBuf>,
 
+    /// Motion profile in TOML format (dynamic mode): hold, straight,
+    /// accelerate, turn, climb, circle and waypoint segments with
+    /// jerk-limited transitions
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    profile: Option<PathBuf>,
+
     /// ECEF X,Y,Z in meters (static mode) e.g.
     /// 3967283.154,1022538.181,4872414.484
     #[arg(short = 'c', long, value_parser, value_delimiter = ',')]
@@ -71,7 +104,9 @@ pub struct Args {
     #[arg(short = 'L', long, value_parser, value_delimiter = ',')]
     leap: Option<Vec<i32>>,
 
-    /// Scenario start time YYYY-MM-DDTHH:MM:SSZ
+    /// Scenario start time YYYY-MM-DDTHH:MM:SSZ or YYYY/MM/DD,hh:mm:ss in
+    /// UTC, or "now"; seconds may have a fraction, and a date followed by a
+    /// space and GPST, TAI, GST or BDT is in that time scale instead
     #[arg(short = 't', long)]
     time: Option<String>,
 
@@ -79,7 +114,13 @@ pub struct Args {
     #[arg(short = 'T', long)]
     time_override: Option<bool>,
 
-    /// Duration [sec] (dynamic mode max: {}, static mode max: {})
+    /// How -T moves the ephemerides: `shift` keeps the sky of the original
+    /// day (default), `propagate` moves each orbit to the new date so that
+    /// the sky matches the real one at the chosen time of day
+    #[arg(long)]
+    re_epoch: Option<ReEpoch>,
+
+    /// Duration [sec]
     #[arg(short = 'd', long)]
     duration: Option<f64>,
 
@@ -91,6 +132,12 @@ pub struct Args {
     #[arg(short = 's', long, default_value_t = 2600000)]
     frequency: usize,
 
+    /// Time step between position and Doppler updates, in seconds or with a
+    /// unit, e.g. 0.01, 0.01s or 10ms, from 1 ms to 100 ms (default: 0.1);
+    /// user motion is resampled onto these epochs
+    #[arg(long, value_parser = parse_interval)]
+    interval: Option<f64>,
+
     /// I/Q data format [1/8/16] (default: 16)
     #[arg(short = 'b', long, default_value_t = 16)]
     bits: usize,
@@ -106,6 +153,53 @@ pub struct Args {
     /// Show details about simulated channels
     #[arg(short = 'v', long,default_value_t = false, action = ArgAction::SetTrue)]
     verbose: bool,
+
+    /// Signals to generate e.g. L1CA,L2C,L5,E1,B1I,L1OF,SBAS,QZSS (default:
+    /// L1CA); bands other than the first signal's are written to
+    /// <output>_<band>.<ext>
+    #[arg(long, value_delimiter = ',')]
+    signals: Option<Vec<Signal>>,
+
+    /// Galileo E1-B/E1-C memory code file (required for E1)
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    galileo_codes: Option<PathBuf>,
+
+    /// Centre frequency of the GLONASS G1 output [Hz] (default: 1602000000)
+    #[arg(long)]
+    glonass_center: Option<f64>,
+
+    /// SBAS GEO satellite and message configuration file (required for SBAS)
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    sbas_file: Option<PathBuf>,
+
+    /// SP3 precise orbit and clock files giving the true satellite positions
+    /// and clocks, while the navigation messages still carry the broadcast
+    /// ephemerides; several files are separated by commas and merged
+    #[arg(
+        long = "sp3",
+        value_delimiter = ',',
+        value_hint = clap::ValueHint::FilePath
+    )]
+    precise_orbits: Option<Vec<PathBuf>>,
+
+    /// YUMA or SEM almanac giving the GPS ephemerides when no navigation
+    /// file is given; the orbits are propagated to the scenario, which may
+    /// be weeks away from the almanac
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    almanac: Option<PathBuf>,
+
+    /// Number of satellites of a nominal GPS constellation, e.g. 24 or 31,
+    /// placed at the scenario start time and used when no navigation file
+    /// or almanac is given
+    #[arg(long)]
+    synthetic: Option<usize>,
+
+    /// Ephemeris handover rule: `closest` switches all satellites to the
+    /// data set with the closest TOE (default), `tom` switches each one at
+    /// the transmission time of its data sets, and a number of seconds, e.g.
+    /// 7200, switches each one that long before the TOE of its data sets
+    #[arg(long)]
+    handover: Option<Handover>,
 }
 
 impl Args {
@@ -119,21 +213,36 @@ impl Args {
     /// * `Err(Error)` - If an error occurs during simulation
     pub fn run(&self) -> Result<(), Error> {
         let builder = SignalGeneratorBuilder::default()
-            .navigation_file(Some(self.ephemerides.clone()))?
+            .navigation_files(
+                (!self.ephemerides.is_empty())
+                    .then(|| self.ephemerides.clone()),
+            )?
+            .almanac_file(self.almanac.clone())?
+            .synthetic_constellation(self.synthetic)?
             .user_motion_file(self.user_motion_ecef.clone())?
             .user_motion_llh_file(self.user_motion_llh.clone())?
+            .trajectory_interpolation(self.interpolation)
             .user_motion_nmea_gga_file(self.nmea_gga.clone())?
+            .motion_profile_file(self.profile.clone())?
             .location_ecef(self.location_ecef.clone())?
             .location(self.location.clone())?
             .leap(self.leap.clone())
             .time(self.time.clone())?
             .time_override(self.time_override)
+            .re_epoch(self.re_epoch)
             .duration(self.duration)
             .output_file(self.output.clone())
             .frequency(Some(self.frequency))?
+            .update_interval(self.interval)?
             .data_format(Some(self.bits))?
             .ionospheric_disable(Some(self.ionospheric_disable))
             .path_loss(self.path_loss)
+            .signals(self.signals.clone())?
+            .galileo_codes(self.galileo_codes.clone())?
+            .glonass_center_frequency(self.glonass_center)?
+            .sbas_file(self.sbas_file.clone())?
+            .precise_orbit_files(self.precise_orbits.clone())?
+            .ephemeris_handover(self.handover)
             .verbose(Some(self.verbose));
         let mut generator = builder.build()?;
         generator.initialize()?;
@@ -141,3 +250,25 @@ impl Args {
         Ok(())
     }
 }
+
+/// Parses an update interval given in seconds, with an optional `s` or `ms`
+/// unit, into seconds.
+fn parse_interval(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+    let (number, scale) = if let Some(ms) = value.strip_suffix("ms") {
+        (ms, 1e-3)
+    } else if let Some(s) = value.strip_suffix('s') {
+        (s, 1.0)
+    } else {
+        (value, 1.0)
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .map(|n| n * scale)
+        .map_err(|_| {
+            format!(
+                "invalid interval `{value}`, expected e.g. 0.01, 0.01s or 10ms"
+            )
+        })
+}
@@ -13,8 +13,6 @@
 //! - Configuration limits and defaults
 
 #![allow(unused)]
-/// Maximum number of user motion records that can be processed
-pub const USER_MOTION_SIZE: usize = 3000;
 
 /// Mathematical constant π (pi) with high precision
 #[allow(clippy::approx_constant)]
@@ -23,6 +21,27 @@ pub const PI: f64 = 3.141_592_653_589_8;
 /// Maximum number of satellites in RINEX file
 pub const MAX_SAT: usize = 32;
 
+/// Maximum number of Galileo satellites (PRN E01-E36)
+pub const MAX_SAT_GALILEO: usize = 36;
+
+/// Maximum number of BeiDou satellites (PRN C01-C63)
+pub const MAX_SAT_BEIDOU: usize = 63;
+
+/// Maximum number of GLONASS satellites (slots R01-R24)
+pub const MAX_SAT_GLONASS: usize = 24;
+
+/// Lowest SBAS PRN number (GEO satellites S120-S158)
+pub const MIN_PRN_SBAS: usize = 120;
+
+/// Highest SBAS PRN number
+pub const MAX_PRN_SBAS: usize = 158;
+
+/// Lowest QZSS L1 C/A PRN number (satellites J01-J10)
+pub const MIN_PRN_QZSS: usize = 193;
+
+/// Highest QZSS L1 C/A PRN number
+pub const MAX_PRN_QZSS: usize = 202;
+
 /// Maximum number of satellite channels that can be simulated simultaneously
 pub const MAX_CHAN: usize = 16;
 
@@ -110,6 +129,39 @@ pub const POW2_M24: f64 = 5.960_464_477_539_063e-8;
 /// Standard value from GPS Interface Control Document (ICD-GPS-200)
 pub const GM_EARTH: f64 = 3.986_005e14;
 
+/// Earth's gravitational constant (μ) in m³/s² used by Galileo
+/// Standard value from the Galileo OS Signal-In-Space ICD
+pub const GM_EARTH_GALILEO: f64 = 3.986_004_418e14;
+
+/// Earth's gravitational constant (μ) in m³/s² used by BeiDou (CGCS2000)
+/// Standard value from the BeiDou B1I Signal-In-Space ICD
+pub const GM_EARTH_BEIDOU: f64 = 3.986_004_418e14;
+
+/// Earth's gravitational constant (μ) in m³/s² of the PZ-90 frame
+/// Standard value from the GLONASS ICD (edition 5.1)
+pub const GM_EARTH_GLONASS: f64 = 3.986_004_4e14;
+
+/// Equatorial radius of the PZ-90 ellipsoid in meters
+pub const RE_GLONASS: f64 = 6_378_136.0;
+
+/// Second zonal harmonic coefficient (J2) of the PZ-90 geopotential
+pub const J2_GLONASS: f64 = 1.082_625_75e-3;
+
+/// Earth's rotation rate (ω) in rad/s of the PZ-90 frame
+pub const OMEGA_EARTH_GLONASS: f64 = 7.292_115e-5;
+
+/// Number of weeks after which the 10-bit GPS week number wraps to zero
+pub const GPS_WEEK_ROLLOVER: i32 = 1024;
+
+/// Offset of BeiDou Time (BDT) behind GPS time in seconds
+pub const BDT_GPST_OFFSET: f64 = 14.0;
+
+/// GPS week number of the BDT start epoch (2006-01-01 00:00:00 UTC)
+pub const BDT_WEEK_OFFSET: i32 = 1356;
+
+/// Offset of International Atomic Time (TAI) ahead of GPS time in seconds
+pub const TAI_GPST_OFFSET: f64 = 19.0;
+
 /// Earth's rotation rate (ω) in rad/s
 /// Standard value from GPS Interface Control Document (ICD-GPS-200)
 pub const OMEGA_EARTH: f64 = 7.292_115_146_7e-5;
@@ -147,6 +199,58 @@ pub const CARR_FREQ: f64 = 1575.42e6;
 /// C/A code chipping rate in Hz (1.023 MHz)
 pub const CODE_FREQ: f64 = 1.023e6;
 
+/// GPS L2 carrier frequency in Hz (1227.60 MHz)
+pub const CARR_FREQ_L2: f64 = 1227.60e6;
+
+/// GPS L2 signal wavelength in meters
+/// Calculated as c/f where f is the L2 carrier frequency (1227.60 MHz)
+pub const LAMBDA_L2: f64 = SPEED_OF_LIGHT / CARR_FREQ_L2;
+
+/// Length of the GPS L2 CM (civil-moderate) code in chips
+/// One period lasts 20 ms at the 511.5 kHz CM chipping rate
+pub const L2CM_SEQ_LEN: usize = 10230;
+
+/// Length of the GPS L2 CL (civil-long) code in chips
+/// One period lasts 1.5 s at the 511.5 kHz CL chipping rate
+pub const L2CL_SEQ_LEN: usize = 767_250;
+
+/// GPS L5 carrier frequency in Hz (1176.45 MHz)
+pub const CARR_FREQ_L5: f64 = 1176.45e6;
+
+/// GPS L5 signal wavelength in meters
+/// Calculated as c/f where f is the L5 carrier frequency (1176.45 MHz)
+pub const LAMBDA_L5: f64 = SPEED_OF_LIGHT / CARR_FREQ_L5;
+
+/// GPS L5 I5/Q5 code chipping rate in Hz (10.23 MHz)
+pub const CODE_FREQ_L5: f64 = 10.23e6;
+
+/// Length of the GPS L5 I5/Q5 codes in chips (1 ms at 10.23 MHz)
+pub const L5_SEQ_LEN: usize = 10230;
+
+/// Length of the Galileo E1-B/E1-C primary codes in chips (4 ms)
+pub const E1_SEQ_LEN: usize = 4092;
+
+/// BeiDou B1I carrier frequency in Hz (1561.098 MHz)
+pub const CARR_FREQ_B1I: f64 = 1561.098e6;
+
+/// BeiDou B1I code chipping rate in Hz (2.046 MHz)
+pub const CODE_FREQ_B1I: f64 = 2.046e6;
+
+/// Length of the BeiDou B1I ranging codes in chips (1 ms at 2.046 MHz)
+pub const B1I_SEQ_LEN: usize = 2046;
+
+/// GLONASS L1OF carrier frequency of frequency channel 0 in Hz (1602 MHz)
+pub const CARR_FREQ_G1: f64 = 1602.0e6;
+
+/// Spacing of the GLONASS L1OF frequency channels in Hz (562.5 kHz)
+pub const CARR_FREQ_G1_STEP: f64 = 562.5e3;
+
+/// GLONASS L1OF code chipping rate in Hz (0.511 MHz)
+pub const CODE_FREQ_G1: f64 = 0.511e6;
+
+/// Length of the GLONASS L1OF ranging code in chips (1 ms)
+pub const G1_SEQ_LEN: usize = 511;
+
 /// Ratio between carrier frequency and code frequency
 /// Equal to 1/1540, as the L1 carrier (1575.42 MHz) is 1540 times the C/A code
 /// rate (1.023 MHz)
@@ -165,10 +269,15 @@ pub const SC08: i32 = 8;
 #[allow(dead_code)]
 pub const SC16: i32 = 16;
 
-/// Size of the ephemeris array for storing satellite orbit data
-/// Set to 15 to accommodate a full day of GPS broadcast ephemeris data
-/// Each element represents approximately 1.6 hours of data
-pub const EPHEM_ARRAY_SIZE: usize = 15;
+/// Default time step between simulation updates in seconds (10 Hz)
+pub const UPDATE_INTERVAL: f64 = 0.1;
+
+/// Shortest time step between simulation updates in seconds (1 kHz)
+pub const MIN_UPDATE_INTERVAL: f64 = 0.001;
+
+/// Longest time step between simulation updates in seconds (10 Hz)
+pub const MAX_UPDATE_INTERVAL: f64 = 0.1;
 
-/// Default sample rate for simulation updates in seconds (10 Hz)
-pub const SAMPLE_RATE: f64 = 0.1;
+/// Interval between the sentences of NMEA GGA streams in seconds, which are
+/// read at 10 Hz as by gps-sdr-sim
+pub const NMEA_GGA_INTERVAL: f64 = 0.1;
@@ -1,8 +1,8 @@
 use constants::{
-    CA_SEQ_LEN, CA_SEQ_LEN_FLOAT, CARR_TO_CODE, CODE_FREQ, LAMBDA_L1_INV,
-    N_DWRD, N_DWRD_SBF, N_SBF, PI, POW2_M5, POW2_M19, POW2_M24, POW2_M27,
-    POW2_M29, POW2_M30, POW2_M31, POW2_M33, POW2_M43, POW2_M50, POW2_M55,
-    SPEED_OF_LIGHT_INV,
+    CA_SEQ_LEN, CA_SEQ_LEN_FLOAT, CARR_TO_CODE, CODE_FREQ, GPS_WEEK_ROLLOVER,
+    LAMBDA_L1_INV, MIN_PRN_QZSS, N_DWRD, N_DWRD_SBF, N_SBF, PI, POW2_M5,
+    POW2_M19, POW2_M24, POW2_M27, POW2_M29, POW2_M30, POW2_M31, POW2_M33,
+    POW2_M43, POW2_M50, POW2_M55, SPEED_OF_LIGHT_INV,
 };
 use geometry::{Azel, Ecef};
 
@@ -11,14 +11,26 @@ use crate::{
     ephemeris::Ephemeris,
     ionoutc::IonoUtc,
     propagation::compute_range,
+    signal::{Constellation, SatelliteId},
     table::*,
 };
+
+/// G2 delay in chips of the GPS PRNs 1-32
+const GPS_G2_DELAY: [usize; 32] = [
+    5, 6, 7, 8, 17, 18, 139, 140, 141, 251, 252, 254, 255, 256, 257, 258, 469,
+    470, 471, 472, 473, 474, 509, 512, 513, 514, 515, 516, 859, 860, 861, 862,
+];
+
+/// G2 delay in chips of the QZSS PRNs 193-202 (IS-QZSS-PNT table 3.2.2-2)
+const QZSS_G2_DELAY: [usize; 10] =
+    [339, 208, 711, 189, 263, 537, 663, 942, 173, 900];
+
 /// Represents a single GPS satellite channel being tracked by the receiver.
 ///
 /// This structure maintains the complete state of a satellite signal channel,
 /// including signal generation parameters, navigation message data, and
-/// tracking information. Each Channel instance corresponds to one satellite
-/// (identified by PRN) that is being simulated.
+/// tracking information. Each Channel instance corresponds to one GPS or QZSS
+/// satellite that is being simulated.
 ///
 /// The Channel is responsible for:
 /// - Generating the satellite-specific C/A code sequence (1023 chips)
@@ -31,14 +43,18 @@ use crate::{
 /// accurately model the changing satellite-receiver geometry and signal
 /// characteristics.
 pub struct Channel {
-    /// Satellite PRN (Pseudorandom Noise) number (1-32)
-    pub prn: usize,
+    /// Satellite transmitting on the channel, `None` for a free channel
+    pub satellite: Option<SatelliteId>,
     /// C/A code sequence chips for this satellite (1023 chips)
     ca_sequence: [i32; CA_SEQ_LEN],
     /// Current carrier frequency with Doppler shift (Hz)
     carrier_frequency: f64,
     /// Current code frequency with Doppler effect (Hz)
     code_frequency: f64,
+    /// Carrier Doppler at the start of the step, from the range rate (Hz)
+    carrier_frequency_start: f64,
+    /// Change of the carrier Doppler during the step (Hz/s)
+    carrier_frequency_rate: f64,
     /// Current carrier phase accumulator (fixed-point representation)
     carrier_phase: u32,
     /// Carrier phase step per sample (fixed-point representation)
@@ -69,10 +85,12 @@ pub struct Channel {
 impl Default for Channel {
     fn default() -> Self {
         Self {
-            prn: 0,
+            satellite: None,
             ca_sequence: [0; CA_SEQ_LEN],
             carrier_frequency: 0.0,
             code_frequency: 0.0,
+            carrier_frequency_start: 0.0,
+            carrier_frequency_rate: 0.0,
             carrier_phase: 0,
             carrier_phase_step: 0,
             code_phase: 0.0,
@@ -100,26 +118,44 @@ impl Channel {
         &self.azel
     }
 
+    /// Returns the current Doppler shift of the carrier (Hz).
+    pub fn carrier_frequency(&self) -> f64 {
+        self.carrier_frequency
+    }
+
+    /// Returns a reference to the navigation subframes, without parity.
+    pub fn subframes(&self) -> &[[u32; N_DWRD_SBF]; 5] {
+        &self.subframes
+    }
+
+    /// Returns a reference to the navigation message words as transmitted,
+    /// with the TOW, week number and parity of the current frame.
+    pub fn data_words(&self) -> &[u32; N_DWRD] {
+        &self.data_words
+    }
+
     /// Initializes or updates the channel state for a specific satellite.
     ///
-    /// This involves setting the PRN, generating C/A code and navigation
-    /// subframes, initializing pseudorange, and setting the initial carrier
-    /// phase.
+    /// This involves setting the satellite, generating C/A code and
+    /// navigation subframes, initializing pseudorange, and setting the initial
+    /// carrier phase.
     ///
     /// # Arguments
-    /// * `prn` - The PRN number of the satellite.
+    /// * `satellite` - The GPS or QZSS satellite.
     /// * `eph` - The ephemeris data for the satellite.
     /// * `ionoutc` - Ionospheric and UTC parameters.
     /// * `receiver_gps_time` - The current GPS time at the receiver.
     /// * `xyz` - The receiver's position in ECEF coordinates.
+    /// * `velocity` - The receiver's velocity in ECEF coordinates.
     /// * `azel` - The satellite's azimuth and elevation as seen from the
     ///   receiver.
+    #[allow(clippy::too_many_arguments)]
     pub fn update_for_satellite(
-        &mut self, prn: usize, eph: &Ephemeris, ionoutc: &IonoUtc,
-        receiver_gps_time: &GpsTime, xyz: &Ecef, azel: Azel,
+        &mut self, satellite: SatelliteId, eph: &Ephemeris, ionoutc: &IonoUtc,
+        receiver_gps_time: &GpsTime, xyz: &Ecef, velocity: &Ecef, azel: Azel,
     ) {
         // Initialize channel
-        self.prn = prn;
+        self.satellite = Some(satellite);
         self.azel = azel;
         // C/A code generation
         self.codegen();
@@ -130,7 +166,7 @@ impl Channel {
         // subframes)
         self.generate_nav_msg(receiver_gps_time, true);
         // Initialize pseudorange
-        let rho = compute_range(eph, ionoutc, receiver_gps_time, xyz);
+        let rho = compute_range(eph, ionoutc, receiver_gps_time, xyz, velocity);
         self.rho0 = rho;
         // Initialize carrier phase
         // r_xyz = rho.range;
@@ -179,6 +215,48 @@ impl Channel {
             .round() as i32;
     }
 
+    /// Updates the channel's state from the range rates of the receiver and
+    /// satellite velocities.
+    ///
+    /// Unlike [`Self::update_state`], the Doppler does not follow the change
+    /// of pseudorange over the step: it sweeps linearly from the range rate
+    /// of the previous measurement to the one of `rho1`, through
+    /// [`Self::sweep_doppler`] within the step. The code phase is set from the
+    /// previous pseudorange as in [`Self::update_state`].
+    ///
+    /// # Arguments
+    /// * `rho1` - The new pseudorange measurement, with its range rate.
+    /// * `dt` - The time difference since the last pseudorange measurement
+    ///   (`rho0`).
+    /// * `sampling_period` - The receiver's sampling period in seconds.
+    pub fn update_state_with_rate(
+        &mut self, rho1: &TimeRange, dt: f64, sampling_period: f64,
+    ) {
+        self.azel = rho1.azel;
+        self.carrier_frequency_start = -self.rho0.rate * LAMBDA_L1_INV;
+        self.carrier_frequency_rate =
+            -(rho1.rate - self.rho0.rate) / dt * LAMBDA_L1_INV;
+        self.sweep_doppler(0.0, sampling_period);
+        self.set_code_phase(rho1);
+    }
+
+    /// Sets the carrier and code Doppler at a time into the current step,
+    /// after [`Self::update_state_with_rate`].
+    ///
+    /// # Arguments
+    /// * `elapsed` - Time since the start of the step in seconds.
+    /// * `sampling_period` - The receiver's sampling period in seconds.
+    pub fn sweep_doppler(&mut self, elapsed: f64, sampling_period: f64) {
+        self.carrier_frequency = self.carrier_frequency_start
+            + self.carrier_frequency_rate * elapsed;
+        self.code_frequency = CODE_FREQ + self.carrier_frequency * CARR_TO_CODE;
+        self.carrier_phase_step = (512.0
+            * 65536.0
+            * self.carrier_frequency
+            * sampling_period)
+            .round() as i32;
+    }
+
     ///  \brief Compute the code phase for a given channel (satellite)
     ///  \param chan Channel on which we operate (is updated)
     ///  \param[in] rho1 Current range, after \a dt has expired
@@ -199,6 +277,15 @@ impl Channel {
         // Carrier and code frequency.
         self.carrier_frequency = -rhorate * LAMBDA_L1_INV;
         self.code_frequency = CODE_FREQ + self.carrier_frequency * CARR_TO_CODE;
+        self.set_code_phase(rho1);
+    }
+
+    /// Sets the code phase and data bit counters from the transmit time of
+    /// the previous pseudorange, then saves the new one.
+    ///
+    /// # Arguments
+    /// * `rho1` - Current range information.
+    fn set_code_phase(&mut self, rho1: &TimeRange) {
         // Initial code phase and data bit counters.
         let ms = (self.rho0.time.diff_secs(&self.nav_message_start_time) + 6.0
             - self.rho0.range * SPEED_OF_LIGHT_INV)
@@ -231,7 +318,8 @@ impl Channel {
     ///
     /// The algorithm uses:
     /// 1. Two 10-bit Linear Feedback Shift Registers (LFSRs), G1 and G2
-    /// 2. A satellite-specific delay value for the G2 register
+    /// 2. A satellite-specific delay value for the G2 register, from the
+    ///    tables of IS-GPS-200 for GPS and IS-QZSS-PNT for QZSS
     /// 3. A modulo-2 addition (XOR) of specific taps from each register
     ///
     /// The resulting sequence has the following properties:
@@ -245,21 +333,26 @@ impl Channel {
     /// generation.
     #[inline]
     pub fn codegen(&mut self) {
-        let delay: [usize; 32] = [
-            5, 6, 7, 8, 17, 18, 139, 140, 141, 251, 252, 254, 255, 256, 257,
-            258, 469, 470, 471, 472, 473, 474, 509, 512, 513, 514, 515, 516,
-            859, 860, 861, 862,
-        ];
+        let delay = match self.satellite {
+            Some(SatelliteId {
+                constellation: Constellation::Gps,
+                prn,
+            }) => prn.checked_sub(1).and_then(|sv| GPS_G2_DELAY.get(sv)),
+            Some(SatelliteId {
+                constellation: Constellation::Qzss,
+                prn,
+            }) => prn
+                .checked_sub(MIN_PRN_QZSS)
+                .and_then(|sv| QZSS_G2_DELAY.get(sv)),
+            _ => None,
+        };
+        let Some(&delay) = delay else {
+            return;
+        };
         let mut g1: [i32; CA_SEQ_LEN] = [0; CA_SEQ_LEN];
         let mut g2: [i32; CA_SEQ_LEN] = [0; CA_SEQ_LEN];
         let mut r1: [i32; N_DWRD_SBF] = [-1; N_DWRD_SBF];
         let mut r2: [i32; N_DWRD_SBF] = [-1; N_DWRD_SBF];
-        // if !(self.prn <= 32 || self.prn >= 1) {
-        //     return;
-        // }
-        if !(1..=32).contains(&self.prn) {
-            return;
-        }
         for i in 0..CA_SEQ_LEN {
             g1[i] = r1[9];
             g2[i] = r2[9];
@@ -273,10 +366,10 @@ impl Channel {
             r2[0] = c2;
         }
 
-        let mut j = CA_SEQ_LEN - delay[self.prn - 1];
-        for (ica, ig1) in self.ca_sequence.iter_mut().zip(g1) {
+        for (j, (ica, ig1)) in
+            (CA_SEQ_LEN - delay..).zip(self.ca_sequence.iter_mut().zip(g1))
+        {
             *ica = (1 - ig1 * g2[j % CA_SEQ_LEN]) / 2;
-            j += 1;
         }
     }
 
@@ -292,15 +385,19 @@ impl Channel {
     /// * `init` - Flag indicating if this is the initial generation (handles
     ///   subframe 5 differently).]
     pub fn generate_nav_msg(&mut self, time: &GpsTime, init: bool) {
-        let mut time_init = GpsTime::default();
         let mut sbfwrd: u32;
         let mut prevwrd: u32 = 0;
         let mut nib: i32;
-        time_init.week = time.week;
-        time_init.sec =
-            f64::from(((time.sec + 0.5) as u32).wrapping_div(30)) * 30.0; // Align with the full frame length = 30 sec
+        // Align with the full frame length = 30 sec; a time within half a
+        // second of the next frame, possibly in the next week, starts it
+        let time_init = GpsTime {
+            week: time.week,
+            sec: ((time.sec + 0.5) / 30.0).floor() * 30.0,
+        }
+        .add_secs(0.0);
 
-        let wn = (time_init.week % 1024) as u32;
+        // Transmission week number, modulo 1024
+        let wn = time_init.week.rem_euclid(GPS_WEEK_ROLLOVER) as u32;
         let mut tow = (time_init.sec as u32).wrapping_div(6);
         self.nav_message_start_time = time_init; // Data bit reference time
 
@@ -606,6 +703,8 @@ impl Channel {
     ///   - UTC parameters (`A0`, `A1`, `ΔtLS`)
     ///   - Leap second transition parameters
     /// - Subframe 5 page 25 is reserved (zero-filled in this implementation)
+    /// - QZSS satellites follow IS-QZSS-PNT: subframes 4 and 5 carry the
+    ///   QZSS data ID (`11`) and the L2 P data flag of subframe 1 is set
     /// - All value conversions follow GPS-ICD-defined scaling factors and
     ///   bit-field layouts
     /// - The constructed subframes are stored in the channel's `subframes`
@@ -615,7 +714,11 @@ impl Channel {
         &mut self, eph: &Ephemeris, ionoutc: &IonoUtc,
     ) {
         let ura = 0;
-        let data_id = 1;
+        let qzss = self
+            .satellite
+            .is_some_and(|sat| sat.constellation == Constellation::Qzss);
+        let data_id = if qzss { 3 } else { 1 };
+        let l2p_flag = u32::from(qzss);
         let sbf4_page25_sv_id = 63;
         let sbf5_page25_sv_id = 51;
         let wnlsf;
@@ -692,7 +795,7 @@ impl Channel {
                 | (ura & 0xf) << 14
                 | (svhlth as u32 & 0x3f) << 8
                 | (iodc >> 8 & 0x3) << 6,
-            0,
+            l2p_flag << 29,
             0,
             0,
             (tgd as u32 & 0xff) << 6,
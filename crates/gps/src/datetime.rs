@@ -1,5 +1,6 @@
 use constants::{
-    SECONDS_IN_DAY, SECONDS_IN_HOUR, SECONDS_IN_MINUTE, SECONDS_IN_WEEK,
+    GPS_WEEK_ROLLOVER, SECONDS_IN_DAY, SECONDS_IN_HOUR, SECONDS_IN_MINUTE,
+    SECONDS_IN_WEEK,
 };
 use geometry::Azel;
 
@@ -10,8 +11,10 @@ use geometry::Azel;
 /// expressed as a week number and seconds within the week.
 ///
 /// The GPS week number rolls over every 1024 weeks (approximately 19.7 years),
-/// with the first rollover occurring on August 21, 1999, and the second on
-/// April 6, 2019.
+/// with the first rollover occurring on August 21, 1999, the second on
+/// April 6, 2019 and the third due on November 20, 2038. `week` holds the
+/// full week number; only the navigation messages reduce it to their field
+/// widths.
 #[derive(Clone, Default)]
 pub struct GpsTime {
     /// GPS week number (since January 6, 1980)
@@ -53,7 +56,8 @@ impl GpsTime {
         let mut new_time: GpsTime = GpsTime { week: 0, sec: 0. };
         new_time.week = self.week;
         new_time.sec = self.sec + dt;
-        new_time.sec = (new_time.sec * 1000.0).round() / 1000.0; // Avoid rounding error
+        // Avoid the accumulation of rounding errors, down to the nanosecond
+        new_time.sec = (new_time.sec * 1e9).round() / 1e9;
 
         // Handle week rollovers
         while new_time.sec >= SECONDS_IN_WEEK {
@@ -71,13 +75,13 @@ impl From<&DateTime> for GpsTime {
     /// Converts a UTC date and time to GPS time.
     ///
     /// This implementation converts a calendar date in UTC to the corresponding
-    /// GPS time (week number and seconds). It accounts for leap years and the
-    /// offset between UTC and GPS time origins.
+    /// GPS time (week number and seconds). It accounts for the leap years of
+    /// the Gregorian calendar and the offset between UTC and GPS time origins;
+    /// the week number is the full one, not reduced modulo 1024.
     ///
     /// # Algorithm
     /// 1. Calculate days since GPS epoch (January 6, 1980)
-    /// 2. Account for leap years
-    /// 3. Convert to weeks and seconds
+    /// 2. Convert to weeks and seconds
     ///
     /// # Arguments
     /// * `time` - UTC date and time to convert
@@ -85,25 +89,12 @@ impl From<&DateTime> for GpsTime {
     /// # Returns
     /// The equivalent GPS time
     fn from(time: &DateTime) -> Self {
-        // Day of year for the 1st of each month (0-based)
-        const DOY: [i32; 12] =
-            [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
-
-        // Years since 1980
-        let ye = time.y - 1980;
-
-        // Compute the number of leap days since Jan 5/Jan 6, 1980.
-        let mut lpdays = ye / 4 + 1;
-        if ye % 4 == 0 && time.m <= 2 {
-            lpdays -= 1;
-        }
-
-        // Compute the number of days elapsed since Jan 5/Jan 6, 1980.
-        let de = ye * 365 + DOY[(time.m - 1) as usize] + time.d + lpdays - 6;
+        // Days elapsed since Jan 6, 1980
+        let de = days_from_civil(time.y, time.m, time.d) - GPS_EPOCH_DAYS;
 
         // Convert time to GPS weeks and seconds.
-        let week = de / 7;
-        let sec = f64::from(de % 7) * SECONDS_IN_DAY
+        let week = de.div_euclid(7);
+        let sec = f64::from(de.rem_euclid(7)) * SECONDS_IN_DAY
             + f64::from(time.hh) * SECONDS_IN_HOUR
             + f64::from(time.mm) * SECONDS_IN_MINUTE
             + time.sec;
@@ -111,6 +102,78 @@ impl From<&DateTime> for GpsTime {
     }
 }
 
+/// Resolves a week number that may be given modulo 1024.
+///
+/// Navigation messages, almanacs and some navigation files carry the week
+/// number modulo 1024; it is taken in the rollover period nearest to a full
+/// week number known to be close, such as the week of a date.
+///
+/// # Arguments
+/// * `week` - Week number, modulo 1024 or full
+/// * `reference` - Full week number near the expected week
+///
+/// # Returns
+/// The full week number nearest to `reference`
+pub fn full_week(week: i32, reference: i32) -> i32 {
+    if week >= GPS_WEEK_ROLLOVER {
+        return week;
+    }
+    let rollovers = f64::from(reference - week) / f64::from(GPS_WEEK_ROLLOVER);
+    week + GPS_WEEK_ROLLOVER * rollovers.round() as i32
+}
+
+/// Days from January 1, 1970 to the GPS epoch, January 6, 1980
+const GPS_EPOCH_DAYS: i32 = 3_657;
+
+/// Counts the days from January 1, 1970 to a date of the proleptic Gregorian
+/// calendar.
+///
+/// # Arguments
+/// * `y` - Year
+/// * `m` - Month (1-12)
+/// * `d` - Day of month, possibly beyond the end of the month
+///
+/// # Returns
+/// The number of days, negative before 1970
+fn days_from_civil(y: i32, m: i32, d: i32) -> i32 {
+    // Years start in March, so that the leap day ends them
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * (m + 9).rem_euclid(12) + 2) / 5 + d - 1;
+    let day_of_era =
+        year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Converts a count of days from January 1, 1970 to a date of the proleptic
+/// Gregorian calendar.
+///
+/// # Arguments
+/// * `days` - Days since January 1, 1970, negative before
+///
+/// # Returns
+/// The year, month (1-12) and day of month
+fn civil_from_days(days: i32) -> (i32, i32, i32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_of_year = (5 * day_of_year + 2) / 153;
+    let d = day_of_year - (153 * month_of_year + 2) / 5 + 1;
+    let m = if month_of_year < 10 {
+        month_of_year + 3
+    } else {
+        month_of_year - 9
+    };
+    let y = year_of_era + era * 400 + i32::from(m <= 2);
+    (y, m, d)
+}
+
 /// Represents a date and time in the Gregorian calendar (UTC).
 ///
 /// This structure stores a calendar date and time with components for year,
@@ -141,12 +204,12 @@ impl From<&GpsTime> for DateTime {
     /// Converts GPS time to UTC date and time.
     ///
     /// This implementation converts GPS time (week number and seconds) to a
-    /// calendar date in UTC. It uses an algorithm based on Julian day numbers
-    /// to perform the conversion.
+    /// calendar date in UTC. The week number is the full one, so dates on
+    /// either side of a 1024-week rollover are distinct.
     ///
     /// # Algorithm
-    /// 1. Convert GPS time to Julian day number
-    /// 2. Apply the Julian day to Gregorian date conversion algorithm
+    /// 1. Count the days since the GPS epoch
+    /// 2. Convert the day count to a Gregorian date
     /// 3. Extract hours, minutes, and seconds from the seconds-of-week
     ///
     /// # Arguments
@@ -155,19 +218,9 @@ impl From<&GpsTime> for DateTime {
     /// # Returns
     /// The equivalent UTC date and time
     fn from(time: &GpsTime) -> Self {
-        // Convert GPS time to Julian day number
-        let c = (f64::from(7 * time.week)
-            + (time.sec / 86400.0).floor()
-            + 2_444_245.0) as i32
-            + 1537;
-
-        // Convert Julian day number to calendar date using the algorithm
-        let d = ((f64::from(c) - 122.1) / 365.25) as i32;
-        let e = 365 * d + d / 4;
-        let f = (f64::from(c - e) / 30.6001) as i32;
-        let d = c - e - (30.6001 * f64::from(f)) as i32;
-        let m = f - 1 - 12 * (f / 14);
-        let y = d - 4715 - (7 + m) / 10;
+        // Days since the GPS epoch, then the calendar date
+        let days = 7 * time.week + (time.sec / SECONDS_IN_DAY).floor() as i32;
+        let (y, m, d) = civil_from_days(days + GPS_EPOCH_DAYS);
 
         // Extract time components from seconds-of-week
         let hh = (time.sec / 3600.0) as i32 % 24;
@@ -202,7 +255,8 @@ impl From<jiff::Zoned> for DateTime {
             d: i32::from(value.day()),
             hh: i32::from(value.hour()),
             mm: i32::from(value.minute()),
-            sec: f64::from(value.second()),
+            sec: f64::from(value.second())
+                + f64::from(value.subsec_nanosecond()) * 1e-9,
         }
     }
 }
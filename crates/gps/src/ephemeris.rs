@@ -1,7 +1,16 @@
-use constants::{OMEGA_EARTH, R2D, SECONDS_IN_HALF_WEEK, SECONDS_IN_WEEK};
+use std::sync::Arc;
+
+use constants::{
+    OMEGA_EARTH, R2D, SECONDS_IN_HALF_WEEK, SECONDS_IN_WEEK, SPEED_OF_LIGHT,
+};
 use geometry::{Azel, Ecef, Location, Neu};
 
-use crate::datetime::{DateTime, GpsTime};
+use crate::{
+    datetime::{DateTime, GpsTime},
+    orbit::StateVector,
+    precise::PreciseOrbit,
+    signal::MessageStream,
+};
 
 /// Represents the broadcast ephemeris data for a single GPS satellite.
 ///
@@ -33,6 +42,9 @@ pub struct Ephemeris {
     /// Time of Ephemeris (TOE) - reference time for ephemeris parameters
     pub toe: GpsTime,
 
+    /// Transmission time of the message, when given by the navigation file
+    pub transmission_time: Option<GpsTime>,
+
     /// Issue of Data, Clock - identifies the clock data set
     pub iodc: i32,
 
@@ -104,6 +116,31 @@ pub struct Ephemeris {
     /// Code on L2 channel
     pub codeL2: i32,
 
+    /// Offset of the broadcasting system's time scale from GPS time
+    /// (seconds): -14 for BeiDou Time, 0 for GPS and Galileo. `toc` and `toe`
+    /// are always stored in GPS time.
+    pub time_offset: f64,
+
+    /// Whether this is a BeiDou geostationary (GEO) satellite, whose orbit is
+    /// computed in a frame inclined by 5 degrees
+    pub geostationary: bool,
+
+    /// Broadcast state vector of a GLONASS or SBAS satellite at `toe`; when
+    /// present it replaces the Keplerian elements. The clock model of
+    /// GLONASS uses `af0` = -τn and `af1` = γn, the one of SBAS `af0` = aGf0
+    /// and `af1` = aGf1.
+    pub state_vector: Option<StateVector>,
+
+    /// GLONASS frequency channel number k (-7 to +6)
+    pub frequency_channel: i32,
+
+    /// Messages broadcast by an SBAS GEO satellite
+    pub sbas_messages: Option<MessageStream>,
+
+    /// Precise orbit and clock of the satellite; when present they give the
+    /// true state of the satellite instead of the broadcast parameters
+    pub precise: Option<Arc<PreciseOrbit>>,
+
     /// --- Derived working variables ---
 
     /// Mean motion - average angular velocity (radians/second)
@@ -166,6 +203,13 @@ impl Ephemeris {
             }
             time_diff
         };
+        // State vectors are integrated instead of using Keplerian elements
+        if let Some(state) = &self.state_vector {
+            let (pos, vel) =
+                state.position_velocity(normalize_time(time.sec, self.toe.sec));
+            let tk = normalize_time(time.sec, self.toc.sec);
+            return (pos, vel, [self.af0 + tk * self.af1, self.af1]);
+        }
         // Calculate normalized time relative to ephemeris reference time
         let tk = normalize_time(time.sec, self.toe.sec);
 
@@ -205,19 +249,30 @@ impl Ephemeris {
         let ypk = rk * suk;
         let xpkdot = rkdot * cuk - ypk * ukdot;
         let ypkdot = rkdot * suk + xpk * ukdot;
-        let ok = self.omg0 + tk * self.omgkdot - OMEGA_EARTH * self.toe.sec;
+        // The node longitude refers to the week of the system's own time
+        // scale; GEO orbits are computed in inertial axes first
+        let toe = (self.toe.sec + self.time_offset).rem_euclid(SECONDS_IN_WEEK);
+        let omgkdot = if self.geostationary {
+            self.omgdot
+        } else {
+            self.omgkdot
+        };
+        let ok = self.omg0 + tk * omgkdot - OMEGA_EARTH * toe;
         let (sok, cok) = ok.sin_cos();
-        let pos = [
+        let mut pos = [
             xpk * cok - ypk * cik * sok,
             xpk * sok + ypk * cik * cok,
             ypk * sik,
         ];
         let tmp = ypkdot * cik - ypk * sik * ikdot;
-        let vel = [
-            -self.omgkdot * pos[1] + xpkdot * cok - tmp * sok,
-            self.omgkdot * pos[0] + xpkdot * sok + tmp * cok,
+        let mut vel = [
+            -omgkdot * pos[1] + xpkdot * cok - tmp * sok,
+            omgkdot * pos[0] + xpkdot * sok + tmp * cok,
             ypk * cik * ikdot + ypkdot * sik,
         ];
+        if self.geostationary {
+            (pos, vel) = geostationary_to_ecef(&pos, &vel, tk);
+        }
         // 5. Calculate satellite clock corrections
         let tk = normalize_time(time.sec, self.toc.sec);
         let clk = [
@@ -230,6 +285,40 @@ impl Ephemeris {
         // eph.tgd; clk[1] = eph.af1 + 2.0 * tk * eph.af2;
     }
 
+    /// Computes the true satellite position, velocity, and clock correction
+    /// at a given time.
+    ///
+    /// The precise orbit and clock of the satellite are used when they cover
+    /// `time`, with the relativistic clock correction -2 r·v/c² and the group
+    /// delay added as for the broadcast clock. Otherwise, and for the clock
+    /// alone when the precise clock is missing, the broadcast ephemeris is
+    /// used as in [`Ephemeris::compute_satellite_state`].
+    ///
+    /// # Arguments
+    /// * `time` - GPS time at which to compute the satellite state
+    ///
+    /// # Returns
+    /// The position (meters), velocity (meters/second) and clock correction
+    /// terms (seconds, seconds/second) in the same form as
+    /// [`Ephemeris::compute_satellite_state`]
+    #[inline]
+    pub fn true_state(&self, time: &GpsTime) -> ([f64; 3], [f64; 3], [f64; 2]) {
+        let Some((pos, vel, clock)) =
+            self.precise.as_ref().and_then(|orbit| orbit.state(time))
+        else {
+            return self.compute_satellite_state(time);
+        };
+        let clk = if let Some([bias, drift]) = clock {
+            let relativistic = -2.0
+                * (pos[0] * vel[0] + pos[1] * vel[1] + pos[2] * vel[2])
+                / (SPEED_OF_LIGHT * SPEED_OF_LIGHT);
+            [bias + relativistic - self.tgd, drift]
+        } else {
+            self.compute_satellite_state(time).2
+        };
+        (pos, vel, clk)
+    }
+
     /// Checks if a satellite is visible from a given receiver position.
     ///
     /// This method determines whether a satellite is visible to a receiver at a
@@ -285,3 +374,36 @@ impl Ephemeris {
         Some((azel, true)) // Visible
     }
 }
+
+/// Rotates the position and velocity of a BeiDou GEO satellite from the
+/// inertial frame of its broadcast orbit into ECEF coordinates.
+///
+/// The frame is first rotated by -5 degrees about the X axis, then by the
+/// Earth rotation since the time of ephemeris about the Z axis
+/// (BDS-SIS-ICD-B1I section 5.2.4.12).
+///
+/// # Arguments
+/// * `pos` - Position in the inclined inertial frame (meters)
+/// * `vel` - Velocity in the inclined inertial frame (meters/second)
+/// * `tk` - Time since the time of ephemeris (seconds)
+///
+/// # Returns
+/// The ECEF position (meters) and velocity (meters/second)
+fn geostationary_to_ecef(
+    pos: &[f64; 3], vel: &[f64; 3], tk: f64,
+) -> ([f64; 3], [f64; 3]) {
+    let (s5, c5) = (-5f64).to_radians().sin_cos();
+    let tilt =
+        |v: &[f64; 3]| [v[0], c5 * v[1] + s5 * v[2], -s5 * v[1] + c5 * v[2]];
+    let (p, v) = (tilt(pos), tilt(vel));
+    let (s, c) = (OMEGA_EARTH * tk).sin_cos();
+    let ecef = [c * p[0] + s * p[1], -s * p[0] + c * p[1], p[2]];
+    (
+        ecef,
+        [
+            c * v[0] + s * v[1] + OMEGA_EARTH * ecef[1],
+            -s * v[0] + c * v[1] - OMEGA_EARTH * ecef[0],
+            v[2],
+        ],
+    )
+}
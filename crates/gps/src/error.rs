@@ -37,6 +37,14 @@ pub enum Error {
     #[error("Invalid duration")]
     InvalidDuration,
 
+    /// Error when the time step between simulation updates is out of range
+    /// or does not hold a whole number of samples
+    #[error(
+        "Invalid update interval: {0} s, expected 0.001 to 0.1 s and a whole \
+         number of sampling periods"
+    )]
+    InvalidUpdateInterval(f64),
+
     /// Error when an invalid start time is specified
     #[error("Invalid start time")]
     InvalidStartTime,
@@ -53,6 +61,39 @@ pub enum Error {
     #[error("Invalid delta leap second")]
     InvalidDeltaLeapSecond,
 
+    /// Error when an unknown or unsupported signal is requested
+    #[error("Invalid signal: {0}")]
+    InvalidSignal(String),
+
+    /// Error when a spreading code file cannot be used
+    #[error("Invalid spreading code file: {0}")]
+    CodeFile(String),
+
+    /// Error when an SBAS configuration or message file cannot be used
+    #[error("Invalid SBAS file: {0}")]
+    SbasFile(String),
+
+    /// Error when a precise orbit file cannot be used
+    #[error("Invalid precise orbit file: {0}")]
+    PreciseOrbitFile(String),
+
+    /// Error when a synthetic constellation has no satellite or more than
+    /// the GPS PRNs
+    #[error("Invalid synthetic constellation size")]
+    InvalidConstellationSize,
+
+    /// Error when an ephemeris handover rule cannot be parsed
+    #[error("Invalid ephemeris handover rule: {0}")]
+    InvalidHandover(String),
+
+    /// Error when a re-epoch mode cannot be parsed
+    #[error("Invalid re-epoch mode: {0}")]
+    InvalidReEpoch(String),
+
+    /// Error when a time scale name cannot be parsed
+    #[error("Invalid time scale: {0}")]
+    InvalidTimeScale(String),
+
     /// Error when incorrect position data is provided
     #[error("Wrong positions")]
     WrongPositions,
@@ -139,6 +180,12 @@ impl Error {
         Error::InvalidDuration
     }
 
+    /// Create a new error for an out of range update interval
+    #[inline]
+    pub fn invalid_update_interval(interval: f64) -> Self {
+        Error::InvalidUpdateInterval(interval)
+    }
+
     /// Create a new error for invalid start time
     #[inline]
     pub fn invalid_start_time() -> Self {
@@ -163,6 +210,54 @@ impl Error {
         Error::InvalidDeltaLeapSecond
     }
 
+    /// Create a new error for an invalid signal selection
+    #[inline]
+    pub fn invalid_signal(message: impl Into<String>) -> Self {
+        Error::InvalidSignal(message.into())
+    }
+
+    /// Create a new error for an invalid spreading code file
+    #[inline]
+    pub fn invalid_code_file(message: impl Into<String>) -> Self {
+        Error::CodeFile(message.into())
+    }
+
+    /// Create a new error for an invalid SBAS configuration or message file
+    #[inline]
+    pub fn invalid_sbas_file(message: impl Into<String>) -> Self {
+        Error::SbasFile(message.into())
+    }
+
+    /// Create a new error for an invalid precise orbit file
+    #[inline]
+    pub fn invalid_precise_orbit_file(message: impl Into<String>) -> Self {
+        Error::PreciseOrbitFile(message.into())
+    }
+
+    /// Create a new error for an invalid synthetic constellation size
+    #[inline]
+    pub fn invalid_constellation_size() -> Self {
+        Error::InvalidConstellationSize
+    }
+
+    /// Create a new error for an invalid ephemeris handover rule
+    #[inline]
+    pub fn invalid_handover(message: impl Into<String>) -> Self {
+        Error::InvalidHandover(message.into())
+    }
+
+    /// Create a new error for an invalid re-epoch mode
+    #[inline]
+    pub fn invalid_re_epoch(message: impl Into<String>) -> Self {
+        Error::InvalidReEpoch(message.into())
+    }
+
+    /// Create a new error for an invalid time scale
+    #[inline]
+    pub fn invalid_time_scale(message: impl Into<String>) -> Self {
+        Error::InvalidTimeScale(message.into())
+    }
+
     /// Create a new error for wrong positions
     #[inline]
     pub fn wrong_positions() -> Self {
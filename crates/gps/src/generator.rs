@@ -4,13 +4,21 @@
 //! signals. It includes the builder pattern for configuring the signal
 //! generator and the main signal generation implementation.
 
+/// GPS ephemeris sets built from YUMA and SEM almanacs
+mod almanac;
 /// Builder pattern implementation for signal generator configuration
 mod builder;
+/// Nominal GPS constellation built without navigation data
+mod constellation;
+/// SBAS GEO satellite configuration
+mod sbas;
 /// Core signal generation implementation
 mod signal_generator;
+#[cfg(test)]
+mod tests;
 /// Utility functions and types for signal generation
 mod utils;
 
 pub use builder::SignalGeneratorBuilder;
 pub use signal_generator::SignalGenerator;
-pub use utils::MotionMode;
+pub use utils::{Handover, MotionMode, ReEpoch};
@@ -0,0 +1,286 @@
+//! GPS ephemeris sets built from a YUMA or SEM almanac.
+//!
+//! An almanac entry is a Keplerian orbit without harmonic corrections, so it
+//! is carried by an [`Ephemeris`] with zero correction terms. Such an orbit
+//! propagates linearly in mean anomaly and node longitude, which lets it be
+//! moved to any reference time without changing the positions it gives: the
+//! data sets are re-issued every two hours around the scenario, however far
+//! it is from the time of applicability, and their navigation messages stay
+//! consistent with the simulated orbits.
+//!
+//! The parameters are rounded to the resolution of their LNAV fields, so
+//! that a receiver decoding the navigation message computes exactly the
+//! orbits and clocks that are simulated.
+
+use constants::{
+    GM_EARTH, MAX_SAT, OMEGA_EARTH, PI, POW2_M5, POW2_M19, POW2_M29, POW2_M31,
+    POW2_M33, POW2_M43, POW2_M55, SECONDS_IN_WEEK,
+};
+use rinex::almanac::{Almanac, AlmanacEntry};
+
+use crate::{
+    datetime::{DateTime, GpsTime, full_week},
+    ephemeris::Ephemeris,
+    generator::utils::SatelliteEphemerides,
+    signal::Constellation,
+};
+
+/// Interval between the reference times of the generated data sets
+/// (seconds), the usual upload cadence of broadcast ephemerides
+const SET_INTERVAL: f64 = 7200.0;
+
+/// Number of units of a 32-bit angle field of the LNAV message
+const ANGLE_UNITS: f64 = 4_294_967_296.0;
+
+/// Returns the multiple of a scale factor nearest to a value, as read back
+/// from a navigation message.
+///
+/// The navigation message encoder truncates the scaled values, so the
+/// result is moved away from zero by the last bits of rounding that would
+/// make it encode as the next smaller unit.
+///
+/// # Arguments
+/// * `units` - Value in units of the scale factor, an integer
+/// * `scale` - Scale factor of the field
+///
+/// # Returns
+/// The value that encodes as `units` exactly
+fn lnav_units(units: f64, scale: f64) -> f64 {
+    let mut value = units * scale;
+    while (value / scale) as i64 != units as i64 {
+        value = if units > 0.0 {
+            value.next_up()
+        } else {
+            value.next_down()
+        };
+    }
+    value
+}
+
+/// Rounds a value to the resolution of its LNAV field.
+///
+/// # Arguments
+/// * `value` - Value to round
+/// * `scale` - Scale factor of the field
+///
+/// # Returns
+/// The rounded value
+fn lnav_value(value: f64, scale: f64) -> f64 {
+    lnav_units((value / scale).round(), scale)
+}
+
+/// Rounds an angle to the resolution of a 32-bit LNAV field, wrapping it
+/// into the range of the field.
+///
+/// # Arguments
+/// * `angle` - Angle (radians)
+///
+/// # Returns
+/// The rounded angle, within [-pi, pi)
+fn lnav_angle(angle: f64) -> f64 {
+    let scale = POW2_M31 * PI;
+    let units = ((angle / scale).round() + ANGLE_UNITS / 2.0)
+        .rem_euclid(ANGLE_UNITS)
+        - ANGLE_UNITS / 2.0;
+    lnav_units(units, scale)
+}
+
+/// Rounds the orbit and clock parameters of an ephemeris to the resolution
+/// of the LNAV message.
+///
+/// # Arguments
+/// * `eph` - Ephemeris to round; its derived quantities are not updated
+pub fn lnav_quantize(eph: &mut Ephemeris) {
+    eph.ecc = lnav_value(eph.ecc, POW2_M33);
+    eph.sqrta = lnav_value(eph.sqrta, POW2_M19);
+    eph.m0 = lnav_angle(eph.m0);
+    eph.omg0 = lnav_angle(eph.omg0);
+    eph.inc0 = lnav_angle(eph.inc0);
+    eph.aop = lnav_angle(eph.aop);
+    eph.deltan = lnav_value(eph.deltan, POW2_M43 * PI);
+    eph.omgdot = lnav_value(eph.omgdot, POW2_M43 * PI);
+    eph.idot = lnav_value(eph.idot, POW2_M43 * PI);
+    for harmonic in [&mut eph.cuc, &mut eph.cus, &mut eph.cic, &mut eph.cis] {
+        *harmonic = lnav_value(*harmonic, POW2_M29);
+    }
+    eph.crc = lnav_value(eph.crc, POW2_M5);
+    eph.crs = lnav_value(eph.crs, POW2_M5);
+    eph.af0 = lnav_value(eph.af0, POW2_M31);
+    eph.af1 = lnav_value(eph.af1, POW2_M43);
+    eph.af2 = lnav_value(eph.af2, POW2_M55);
+    eph.tgd = lnav_value(eph.tgd, POW2_M31);
+}
+
+/// Converts an almanac entry into an ephemeris at its time of
+/// applicability.
+///
+/// # Arguments
+/// * `entry` - Almanac data of the satellite
+/// * `reference_week` - Full week number near the almanac week
+///
+/// # Returns
+/// The ephemeris, with zero correction terms and clock drift rate, and its
+/// parameters rounded to the resolution of the LNAV message
+pub fn almanac_ephemeris(
+    entry: &AlmanacEntry, reference_week: i32,
+) -> Ephemeris {
+    let toa = GpsTime {
+        week: full_week(entry.week, reference_week),
+        sec: entry.toa,
+    };
+    let mut eph = Ephemeris {
+        vflg: true,
+        t: DateTime::from(&toa),
+        toc: toa.clone(),
+        toe: toa,
+        ecc: entry.eccentricity,
+        sqrta: entry.sqrt_a,
+        m0: entry.mean_anomaly,
+        omg0: entry.omega0,
+        inc0: entry.inclination,
+        aop: entry.perigee,
+        omgdot: entry.omega_dot,
+        af0: entry.af0,
+        af1: entry.af1,
+        svhlth: entry.health as i32,
+        codeL2: 1,
+        ..Default::default()
+    };
+    lnav_quantize(&mut eph);
+    eph.A = eph.sqrta * eph.sqrta;
+    eph.n = (GM_EARTH / (eph.A * eph.A * eph.A)).sqrt();
+    eph.sq1e2 = (1.0 - eph.ecc * eph.ecc).sqrt();
+    eph.omgkdot = eph.omgdot - OMEGA_EARTH;
+    eph
+}
+
+/// Returns the time of applicability of an almanac.
+///
+/// # Arguments
+/// * `almanac` - Parsed almanac
+/// * `reference_week` - Full week number near the almanac week
+///
+/// # Returns
+/// The time of applicability of the first satellite, or `None` for an empty
+/// almanac
+pub fn almanac_start_time(
+    almanac: &Almanac, reference_week: i32,
+) -> Option<GpsTime> {
+    almanac.entries.first().map(|entry| GpsTime {
+        week: full_week(entry.week, reference_week),
+        sec: entry.toa,
+    })
+}
+
+/// Moves an ephemeris to a new reference time by secular propagation.
+///
+/// The mean anomaly and the node longitude, which refers to the start of
+/// the week of the reference time, are propagated with their secular rates,
+/// and the clock bias is extrapolated with the drift. No element is
+/// refitted: the harmonic corrections are functions of the argument of
+/// latitude and are kept, and the rate of inclination is a fit parameter
+/// that would drift away over days, so the inclination is not moved. An
+/// ephemeris without rate of inclination, such as one built from an
+/// almanac, therefore gives the same positions and clock offsets at any
+/// time; a broadcast ephemeris drifts from the later broadcast orbits by up
+/// to about 100 m per hour of propagation.
+///
+/// # Arguments
+/// * `eph` - Ephemeris to move
+/// * `toe` - New time of ephemeris and of clock
+/// * `iode` - Issue of data of the new ephemeris
+///
+/// # Returns
+/// The ephemeris at the new reference time
+pub fn propagate_secular(
+    eph: &Ephemeris, toe: &GpsTime, iode: i32,
+) -> Ephemeris {
+    let dt = toe.diff_secs(&eph.toe);
+    let dtc = toe.diff_secs(&eph.toc);
+    let weeks = f64::from(toe.week - eph.toe.week);
+    let wrap = |angle: f64| (angle + PI).rem_euclid(2.0 * PI) - PI;
+    Ephemeris {
+        vflg: eph.vflg,
+        t: DateTime::from(toe),
+        toc: toe.clone(),
+        toe: toe.clone(),
+        iodc: iode,
+        iode,
+        deltan: eph.deltan,
+        cuc: eph.cuc,
+        cus: eph.cus,
+        cic: eph.cic,
+        cis: eph.cis,
+        crc: eph.crc,
+        crs: eph.crs,
+        ecc: eph.ecc,
+        sqrta: eph.sqrta,
+        m0: wrap(eph.m0 + eph.n * dt),
+        omg0: wrap(
+            eph.omg0 + eph.omgdot * dt - OMEGA_EARTH * weeks * SECONDS_IN_WEEK,
+        ),
+        inc0: eph.inc0,
+        aop: eph.aop,
+        omgdot: eph.omgdot,
+        idot: eph.idot,
+        af0: eph.af0 + eph.af1 * dtc + eph.af2 * dtc * dtc,
+        af1: eph.af1 + 2.0 * eph.af2 * dtc,
+        af2: eph.af2,
+        tgd: eph.tgd,
+        svhlth: eph.svhlth,
+        codeL2: eph.codeL2,
+        time_offset: eph.time_offset,
+        geostationary: eph.geostationary,
+        n: eph.n,
+        sq1e2: eph.sq1e2,
+        A: eph.A,
+        omgkdot: eph.omgkdot,
+        ..Default::default()
+    }
+}
+
+/// Builds GPS ephemerides covering a time span from an almanac.
+///
+/// Data sets are issued every two hours, from the last multiple of two
+/// hours before `start` to the first one after `end`; each holds the almanac
+/// orbit of its satellite moved to its reference time, rounded to the
+/// resolution of the LNAV message.
+///
+/// # Arguments
+/// * `almanac` - Parsed almanac
+/// * `start` - Start of the span, also used to resolve 10-bit week numbers
+/// * `end` - End of the span
+///
+/// # Returns
+/// The ephemerides of each satellite; satellites missing from the almanac
+/// or with a PRN above 32 have none
+pub fn almanac_ephemerides(
+    almanac: &Almanac, start: &GpsTime, end: &GpsTime,
+) -> SatelliteEphemerides {
+    let mut entries: [Option<Ephemeris>; MAX_SAT] = Default::default();
+    for entry in &almanac.entries {
+        if let Some(slot) =
+            entry.prn.checked_sub(1).and_then(|sv| entries.get_mut(sv))
+        {
+            *slot = Some(almanac_ephemeris(entry, start.week));
+        }
+    }
+    let first = GpsTime {
+        week: start.week,
+        sec: (start.sec / SET_INTERVAL).floor() * SET_INTERVAL,
+    };
+    let count = (end.diff_secs(&first) / SET_INTERVAL).floor() as usize + 2;
+    let mut ephemerides = SatelliteEphemerides::new();
+    for index in 0..count {
+        let toe = first.add_secs(index as f64 * SET_INTERVAL);
+        let iode = (index % 256) as i32;
+        for (sv, eph) in entries.iter().enumerate() {
+            if let Some(eph) = eph {
+                let mut eph = propagate_secular(eph, &toe, iode);
+                lnav_quantize(&mut eph);
+                ephemerides.insert(Constellation::Gps, sv + 1, eph);
+            }
+        }
+    }
+    ephemerides
+}
@@ -1,34 +1,51 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
-use constants::{EPHEM_ARRAY_SIZE, MAX_CHAN, MAX_SAT, R2D, SECONDS_IN_HOUR};
+use constants::{
+    CARR_FREQ_G1, MAX_CHAN, MAX_SAT, MAX_UPDATE_INTERVAL, MIN_UPDATE_INTERVAL,
+    NMEA_GGA_INTERVAL, R2D, UPDATE_INTERVAL,
+};
 use geometry::{Ecef, Location};
-use parsing::{read_nmea_gga, read_user_motion, read_user_motion_llh};
+use parsing::{
+    Interpolation, MotionProfile, Trajectory, read_motion_profile,
+    read_nmea_gga, read_user_motion, read_user_motion_llh,
+};
+use rinex::almanac::Almanac;
 
 use crate::{
     Error,
-    datetime::{DateTime, GpsTime},
+    datetime::{DateTime, GpsTime, full_week},
     ephemeris::Ephemeris,
     generator::{
+        almanac::{
+            almanac_ephemerides, almanac_start_time, lnav_quantize,
+            propagate_secular,
+        },
+        constellation::nominal_constellation,
+        sbas::{GeoSatellite, read_sbas_file},
         signal_generator::SignalGenerator,
-        utils::{MotionMode, read_navigation_data},
+        utils::{
+            Handover, MotionMode, ReEpoch, SatelliteEphemerides,
+            read_navigation_data,
+        },
     },
     io::DataFormat,
     ionoutc::IonoUtc,
+    precise::PreciseOrbits,
+    signal::{
+        Constellation, MAX_PRN_B1I, MemoryCodes, Signal, fdma_sample_frequency,
+    },
+    timescale::TimeScale,
 };
 /// Type alias for ephemeris-related data used in the builder.
 ///
 /// This tuple contains:
-/// - The number of valid ephemeris sets
 /// - Ionospheric and UTC parameters
-/// - A 2D array of ephemeris data organized by time set and satellite PRN
+/// - The GPS, Galileo, BeiDou, GLONASS and QZSS ephemerides of each
+///   satellite
 ///
 /// This is the same structure as the `Data` type in the utils module,
 /// but defined here for use within the builder.
-type EphemerisRelatedData = (
-    usize,
-    IonoUtc,
-    Box<[[Ephemeris; MAX_SAT]; EPHEM_ARRAY_SIZE]>,
-);
+type EphemerisRelatedData = (IonoUtc, SatelliteEphemerides);
 /// Builder for creating and configuring a `SignalGenerator`.
 ///
 /// This struct implements the builder pattern for creating a `SignalGenerator`
@@ -65,8 +82,14 @@ pub struct SignalGeneratorBuilder {
     leap: Option<Vec<i32>>,
     /// Receiver positions (static or dynamic)
     positions: Option<Vec<Ecef>>,
+    /// Timestamped receiver trajectory, resampled onto the simulation epochs
+    trajectory: Option<Trajectory>,
+    /// Kinematic scenario integrated at the simulation epochs
+    profile: Option<MotionProfile>,
+    /// Interpolation of the trajectory between its records
+    interpolation: Option<Interpolation>,
     /// Sample rate for position updates in seconds
-    sample_rate: Option<f64>,
+    update_interval: Option<f64>,
     /// Motion mode (static or dynamic)
     mode: Option<MotionMode>,
     /// Simulation duration in seconds
@@ -75,6 +98,8 @@ pub struct SignalGeneratorBuilder {
     frequency: Option<f64>,
     /// Whether to override ephemeris time with simulation start time
     time_override: Option<bool>,
+    /// How the ephemerides are moved when their time is overridden
+    re_epoch: Option<ReEpoch>,
     /// GPS time at which the simulation starts
     receiver_gps_time: Option<GpsTime>,
     /// I/Q sample data format (1, 8, or 16 bits)
@@ -85,33 +110,67 @@ pub struct SignalGeneratorBuilder {
     ionospheric_disable: Option<bool>,
     /// Whether to enable verbose output
     verbose: Option<bool>,
+    /// Signals to generate
+    signals: Option<Vec<Signal>>,
+    /// Centre frequency of the GLONASS G1 output in Hz
+    glonass_center_frequency: Option<f64>,
+    /// SBAS GEO satellites and their messages
+    sbas: Option<Vec<GeoSatellite>>,
+    /// Precise orbits and clocks giving the true satellite states
+    precise_orbits: Option<PreciseOrbits>,
+    /// GPS almanac from which ephemerides are built without navigation files
+    almanac: Option<Almanac>,
+    /// Number of satellites of a nominal constellation used without
+    /// navigation files or almanac
+    synthetic_satellites: Option<usize>,
+    /// Rule deciding when a new ephemeris replaces the broadcast one
+    handover: Option<Handover>,
+    /// Galileo E1 memory codes
+    memory_codes: Option<MemoryCodes>,
 }
 impl SignalGeneratorBuilder {
-    /// Parses a datetime string into a timestamp.
+    /// Returns whether a location, a trajectory or a motion profile has
+    /// already been set.
+    fn has_position(&self) -> bool {
+        self.positions.is_some()
+            || self.trajectory.is_some()
+            || self.profile.is_some()
+    }
+
+    /// Parses a datetime string into a calendar date and time.
     ///
     /// Used internally to convert user-provided date/time strings into a format
-    /// that can be used for simulation timing.
+    /// that can be used for simulation timing. Dates with a UTC offset are
+    /// converted to UTC, dates without one are taken as they are.
     ///
     /// # Arguments
     /// * `value` - A string representing a date and time in the format
-    ///   "YYYY-MM-DD HH:MM:SS"
+    ///   "YYYY-MM-DDTHH:MM:SSZ", "YYYY-MM-DD HH:MM:SS" or
+    ///   "YYYY/MM/DD,hh:mm:ss"
     ///
     /// # Returns
-    /// A Result containing either the parsed timestamp or a parsing error
-    fn parse_datetime(value: &str) -> Result<jiff::Timestamp, jiff::Error> {
-        let time: jiff::Timestamp = value.parse()?;
-        Ok(time)
+    /// A Result containing either the parsed date and time or a parsing error
+    fn parse_datetime(value: &str) -> Result<jiff::Zoned, jiff::Error> {
+        let value = value.replace('/', "-").replace(',', "T");
+        match value.parse::<jiff::Timestamp>() {
+            Ok(time) => time.in_tz("UTC"),
+            Err(_) => value.parse::<jiff::civil::DateTime>()?.in_tz("UTC"),
+        }
     }
 
     /// Sets the RINEX navigation file for GPS ephemerides.
     ///
     /// This file contains satellite orbit and clock parameters needed for the
     /// simulation. The function reads and processes the navigation data,
-    /// extracting ephemeris sets and ionospheric/UTC parameters.
+    /// extracting ephemeris sets and ionospheric/UTC parameters. RINEX 3 and
+    /// RINEX 4 files may also carry the Galileo, BeiDou, GLONASS and QZSS
+    /// ephemerides used by the E1, B1I, L1OF and QZSS L1 C/A signals; GPS
+    /// ephemerides are required in either case.
     ///
     /// # Arguments
     /// * `navigation_file` - Optional path to a RINEX navigation file
-    ///   (typically with .nav or .n extension)
+    ///   (typically with .nav, .n or .rnx extension), which may be gzip or
+    ///   Unix compress compressed
     ///
     /// # Returns
     /// * `Ok(Self)` - Builder with navigation data loaded
@@ -123,22 +182,132 @@ impl SignalGeneratorBuilder {
     ///   file
     /// * Other errors if the file cannot be read or parsed
     pub fn navigation_file(
-        mut self, navigation_file: Option<PathBuf>,
+        self, navigation_file: Option<PathBuf>,
+    ) -> Result<Self, Error> {
+        self.navigation_files(navigation_file.map(|file| vec![file]))
+    }
+
+    /// Sets several RINEX navigation files to be merged, e.g. consecutive
+    /// daily files for scenarios that cross a day boundary.
+    ///
+    /// The records of all files are merged in time order. A data set found in
+    /// more than one file, identified by satellite, TOE and IODE, is used
+    /// once. The ionospheric and UTC parameters come from the first file.
+    ///
+    /// # Arguments
+    /// * `navigation_files` - Optional paths to RINEX navigation files, which
+    ///   may be gzip or Unix compress compressed
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - Builder with navigation data loaded
+    /// * `Err(Error)` - If a file cannot be read or no valid ephemeris data
+    ///   was found
+    ///
+    /// # Errors
+    /// * `Error::NoEphemeris` - If no valid ephemeris data was found in the
+    ///   files
+    /// * `Error::Rinex` - If a file cannot be read or its header is
+    ///   malformed, with the line and column at fault
+    pub fn navigation_files(
+        mut self, navigation_files: Option<Vec<PathBuf>>,
     ) -> Result<Self, Error> {
         // Read ephemeris
-        if let Some(file) = navigation_file {
-            let (count, iono_utc, ephemerides) = read_navigation_data(&file)
-                .map_err(|_| {
-                    Error::msg("ERROR: ephemeris file not found or error.")
-                })?;
-            if count == 0 {
+        if let Some(files) = navigation_files {
+            let (iono_utc, satellites) = read_navigation_data(&files)?;
+            if satellites.is_empty(Constellation::Gps) {
                 return Err(Error::NoEphemeris);
             }
-            self.ephemerides_data = Some((count, iono_utc, ephemerides));
+            self.ephemerides_data = Some((iono_utc, satellites));
         }
         Ok(self)
     }
 
+    /// Sets a YUMA or SEM almanac as the source of GPS ephemerides.
+    ///
+    /// Almanac orbits are propagated to the scenario: ephemeris sets are
+    /// issued every two hours from the start time to the end of the
+    /// scenario, so scenarios may be days or weeks away from the time of
+    /// applicability of the almanac, and from any broadcast data. Without a
+    /// start time, the scenario starts at the time of applicability. The
+    /// almanac is only used when no navigation file is given; the ionospheric
+    /// and UTC parameters are then not broadcast.
+    ///
+    /// # Arguments
+    /// * `file` - Optional path to a YUMA or SEM almanac file, possibly
+    ///   compressed
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - Builder with the almanac loaded
+    /// * `Err(Error)` - If the file cannot be read or holds no satellite
+    ///
+    /// # Errors
+    /// * `Error::Rinex` - If the file cannot be read or parsed
+    /// * `Error::NoEphemeris` - If the almanac holds no satellite
+    pub fn almanac_file(
+        mut self, file: Option<PathBuf>,
+    ) -> Result<Self, Error> {
+        if let Some(file) = file {
+            let almanac = Almanac::read_file(&file)?;
+            if almanac.entries.is_empty() {
+                return Err(Error::NoEphemeris);
+            }
+            self.almanac = Some(almanac);
+        }
+        Ok(self)
+    }
+
+    /// Sets a nominal GPS constellation as the source of GPS ephemerides.
+    ///
+    /// The satellites are placed in the slots of the GPS SPS Performance
+    /// Standard, the baseline 24 slots and up to three expanded ones, at the
+    /// start time of the scenario, or at the current time without one, and
+    /// their ephemerides are issued every two hours like those of an
+    /// almanac. This allows scenarios, tests and demonstrations without any
+    /// navigation file. The constellation is only used when neither a
+    /// navigation file nor an almanac is given; the ionospheric and UTC
+    /// parameters are then not broadcast.
+    ///
+    /// # Arguments
+    /// * `satellites` - Optional number of satellites, e.g. 24 for the
+    ///   baseline constellation or 31, given PRNs from 1
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - Builder with the constellation size set
+    /// * `Err(Error)` - If the number of satellites is invalid
+    ///
+    /// # Errors
+    /// * `Error::InvalidConstellationSize` - If the number is zero or above
+    ///   the 32 GPS PRNs
+    pub fn synthetic_constellation(
+        mut self, satellites: Option<usize>,
+    ) -> Result<Self, Error> {
+        if let Some(satellites) = satellites {
+            if !(1..=MAX_SAT).contains(&satellites) {
+                return Err(Error::invalid_constellation_size());
+            }
+            self.synthetic_satellites = Some(satellites);
+        }
+        Ok(self)
+    }
+
+    /// Sets when a newly uploaded ephemeris replaces the broadcast one.
+    ///
+    /// By default all satellites switch to the data set with the closest
+    /// time of ephemeris, as gps-sdr-sim does. The other rules make each
+    /// satellite switch when its own data set is uploaded, about two hours
+    /// before its time of ephemeris, so that receivers see staggered issue
+    /// of data transitions as in live signals.
+    ///
+    /// # Arguments
+    /// * `handover` - Optional handover rule (default: `Handover::Closest`)
+    ///
+    /// # Returns
+    /// * `Self` - Builder with the handover rule set
+    pub fn ephemeris_handover(mut self, handover: Option<Handover>) -> Self {
+        self.handover = handover;
+        self
+    }
+
     /// Sets whether to override ephemeris time with the simulation start time.
     ///
     /// When enabled, this option adjusts the ephemeris data to match the
@@ -157,12 +326,40 @@ impl SignalGeneratorBuilder {
         self
     }
 
+    /// Sets how the ephemerides are moved to the start time when their time
+    /// is overridden.
+    ///
+    /// By default the times of clock and of ephemeris are shifted, as
+    /// gps-sdr-sim does: the satellites then keep their positions of the
+    /// original day. When propagated, the mean anomaly, node and clock of
+    /// each orbit are moved to its new reference time with their secular
+    /// rates, so that the satellites are near where their orbits bring them
+    /// at the chosen date, with navigation messages consistent with the
+    /// simulated orbits. The elements are not refitted, and the positions
+    /// drift from the real ones by up to about 100 m per hour of
+    /// propagation.
+    ///
+    /// # Arguments
+    /// * `re_epoch` - Optional re-epoch mode (default: `ReEpoch::Shift`)
+    ///
+    /// # Returns
+    /// * `Self` - Builder with the re-epoch mode set
+    pub fn re_epoch(mut self, re_epoch: Option<ReEpoch>) -> Self {
+        self.re_epoch = re_epoch;
+        self
+    }
+
     /// Sets the simulation start time.
     ///
     /// This method sets the GPS time at which the simulation will start.
-    /// The time can be specified as a string in the format "YYYY-MM-DD
-    /// HH:MM:SS" or as the special value "now" to use the current system
-    /// time.
+    /// The time can be specified as a string in the format
+    /// "YYYY-MM-DDTHH:MM:SSZ", "YYYY-MM-DD HH:MM:SS" or
+    /// "YYYY/MM/DD,hh:mm:ss", or as the special value "now" to use the
+    /// current system time. Dates are in UTC, converted to GPS time with the
+    /// leap seconds of the date, unless followed by the name of another time
+    /// scale, e.g. "2022-01-01 11:45:14 GPST"; `TAI`, `GST` and `BDT` are
+    /// also recognised. Seconds may have a fraction, down to the nanosecond,
+    /// e.g. "2022-01-01T11:45:14.2504Z".
     ///
     /// # Arguments
     /// * `time` - Optional string representing the start time or "now"
@@ -175,19 +372,17 @@ impl SignalGeneratorBuilder {
     /// * Returns an error if the time string format is invalid
     pub fn time(mut self, time: Option<String>) -> Result<Self, Error> {
         if let Some(time) = time {
-            let time_parsed = match time.to_lowercase().as_str() {
-                "now" => jiff::Timestamp::now().in_tz("UTC"),
-                time => Self::parse_datetime(time)?.in_tz("UTC"),
-            }?;
-            let time = DateTime {
-                y: i32::from(time_parsed.year()),
-                m: i32::from(time_parsed.month()),
-                d: i32::from(time_parsed.day()),
-                hh: i32::from(time_parsed.hour()),
-                mm: i32::from(time_parsed.minute()),
-                sec: f64::from(time_parsed.second()), // TODO: add floor?
+            let (date, scale) = time
+                .trim()
+                .rsplit_once(' ')
+                .and_then(|(date, scale)| Some((date, scale.parse().ok()?)))
+                .unwrap_or((time.trim(), TimeScale::Utc));
+            let (time_parsed, scale) = match date.to_lowercase().as_str() {
+                "now" => (jiff::Timestamp::now().in_tz("UTC")?, TimeScale::Utc),
+                date => (Self::parse_datetime(date)?, scale),
             };
-            self.receiver_gps_time = Some(GpsTime::from(&time));
+            let time = DateTime::from(time_parsed);
+            self.receiver_gps_time = Some(scale.to_gps_time(&time));
         }
         Ok(self)
     }
@@ -231,14 +426,18 @@ impl SignalGeneratorBuilder {
     ///
     /// GPS time and UTC time differ by a number of leap seconds. This method
     /// allows setting the leap second parameters for accurate time conversion.
+    /// The event is announced in the navigation messages, and once the
+    /// scenario crosses it the broadcast leap seconds take the future value.
     ///
     /// # Arguments
     /// * `leap` - Optional vector containing [week number, day number, delta
     ///   time in seconds]
-    ///   - week number: GPS week number when the leap second becomes effective
-    ///   - day number: Day of week (1-7, where 1 is Sunday) when the leap
-    ///     second becomes effective
-    ///   - delta time: Current difference between GPS time and UTC in seconds
+    ///   - week number: GPS week number when the leap second becomes
+    ///     effective, full or modulo 1024 around the scenario start
+    ///   - day number: Day of week (1-7, where 1 is Sunday) at the end of
+    ///     which the leap second becomes effective
+    ///   - delta time: Difference between GPS time and UTC in seconds after
+    ///     the event
     ///
     /// # Returns
     /// * `Self` - Builder with leap second parameters set
@@ -324,6 +523,13 @@ impl SignalGeneratorBuilder {
     /// signal. Higher sampling rates provide more detail but result in
     /// larger output files. The default is 2.6 MHz (2,600,000 Hz).
     ///
+    /// Wideband signals need more than the 1 MHz floor checked here: L5, for
+    /// example, requires at least 10.23 MHz, Galileo E1 at least 12.276 MHz
+    /// and BeiDou B1I at least 2.046 MHz. GLONASS L1OF needs to hold every
+    /// frequency channel around the G1 centre frequency, i.e. about 8.9 MHz
+    /// at the default centre. The rate is checked against every selected
+    /// signal when the generator is built.
+    ///
     /// # Arguments
     /// * `frequency` - Optional sampling frequency in Hz (must be at least 1
     ///   MHz)
@@ -368,7 +574,7 @@ impl SignalGeneratorBuilder {
     pub fn location_ecef(
         mut self, location: Option<Vec<f64>>,
     ) -> Result<Self, Error> {
-        if self.positions.is_some() && location.is_some() {
+        if self.has_position() && location.is_some() {
             return Err(Error::duplicate_position());
         }
         if let Some(location) = location {
@@ -400,7 +606,7 @@ impl SignalGeneratorBuilder {
     pub fn location(
         mut self, location: Option<Vec<f64>>,
     ) -> Result<Self, Error> {
-        if self.positions.is_some() && location.is_some() {
+        if self.has_position() && location.is_some() {
             return Err(Error::duplicate_position());
         }
         if let Some(location) = location {
@@ -454,9 +660,12 @@ impl SignalGeneratorBuilder {
     /// Sets a user motion file in ECEF coordinates for dynamic positioning.
     ///
     /// This method loads a file containing user motion data in Earth-Centered,
-    /// Earth-Fixed (ECEF) coordinate format. The file should contain
-    /// position data for each time step of the simulation. When this option
-    /// is used, the simulation will use dynamic positioning mode.
+    /// Earth-Fixed (ECEF) coordinate format. The file contains timestamped
+    /// positions, optionally with velocities and accelerations, at any rate;
+    /// they are interpolated onto the epochs of the simulation, see
+    /// [`Self::trajectory_interpolation`]. When the records give their
+    /// velocities, the Doppler shifts follow the receiver velocity. When this
+    /// option is used, the simulation will use dynamic positioning mode.
     ///
     /// # Arguments
     /// * `file` - Optional path to a user motion file in ECEF format
@@ -474,12 +683,12 @@ impl SignalGeneratorBuilder {
     pub fn user_motion_file(
         mut self, file: Option<PathBuf>,
     ) -> Result<Self, Error> {
-        if self.positions.is_some() && file.is_some() {
+        if self.has_position() && file.is_some() {
             return Err(Error::duplicate_position());
         }
         if let Some(file) = file {
             self.mode = Some(MotionMode::Dynamic);
-            self.positions = Some(read_user_motion(&file).map_err(|e| {
+            self.trajectory = Some(read_user_motion(&file).map_err(|e| {
                 Error::ParsingError(format!("User motion file error: {e}"))
             })?);
         }
@@ -489,8 +698,9 @@ impl SignalGeneratorBuilder {
     /// Sets a user motion file in LLH coordinates for dynamic positioning.
     ///
     /// This method loads a file containing user motion data in Latitude,
-    /// Longitude, Height (LLH) coordinate format. The file should contain
-    /// position data for each time step of the simulation.
+    /// Longitude, Height (LLH) coordinate format. The file contains
+    /// timestamped positions at any rate, interpolated onto the epochs of the
+    /// simulation.
     /// The LLH coordinates will be automatically converted to ECEF coordinates
     /// for internal use. When this option is used, the simulation will use
     /// dynamic positioning mode.
@@ -512,12 +722,12 @@ impl SignalGeneratorBuilder {
     pub fn user_motion_llh_file(
         mut self, file: Option<PathBuf>,
     ) -> Result<Self, Error> {
-        if self.positions.is_some() && file.is_some() {
+        if self.has_position() && file.is_some() {
             return Err(Error::duplicate_position());
         }
         if let Some(file) = file {
             self.mode = Some(MotionMode::Dynamic);
-            self.positions =
+            self.trajectory =
                 Some(read_user_motion_llh(&file).map_err(|e| {
                     Error::ParsingError(format!(
                         "User motion LLH file error: {e}"
@@ -527,6 +737,28 @@ impl SignalGeneratorBuilder {
         Ok(self)
     }
 
+    /// Sets how the positions of a user motion file are interpolated.
+    ///
+    /// The records of a user motion file or NMEA GGA stream are resampled
+    /// onto the epochs of the simulation, every [`Self::update_interval`] from
+    /// the first record. Records on an epoch are used as they are, so a file
+    /// already at the rate of the simulation is unchanged.
+    ///
+    /// # Arguments
+    /// * `interpolation` - Optional interpolation method (default:
+    ///   `Interpolation::Linear`); `Interpolation::Hermite` follows cubic
+    ///   curves through the positions with the velocities of the records,
+    ///   and the Doppler shifts then follow the derivatives of the curves
+    ///
+    /// # Returns
+    /// * `Self` - Builder with the interpolation method set
+    pub fn trajectory_interpolation(
+        mut self, interpolation: Option<Interpolation>,
+    ) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
     /// Sets a NMEA GGA format file for dynamic positioning.
     ///
     /// This method loads a file containing position data in NMEA GGA sentence
@@ -552,33 +784,247 @@ impl SignalGeneratorBuilder {
     pub fn user_motion_nmea_gga_file(
         mut self, file: Option<PathBuf>,
     ) -> Result<Self, Error> {
-        if self.positions.is_some() && file.is_some() {
+        if self.has_position() && file.is_some() {
             return Err(Error::duplicate_position());
         }
         if let Some(file) = file {
             self.mode = Some(MotionMode::Dynamic);
-            self.positions = Some(read_nmea_gga(&file).map_err(|e| {
-                Error::ParsingError(format!("NMEA GGA file error: {e}"))
+            let error =
+                |e| Error::ParsingError(format!("NMEA GGA file error: {e}"));
+            let positions = read_nmea_gga(&file).map_err(error)?;
+            // Sentences follow each other at 10 Hz
+            let times = (0..positions.len())
+                .map(|k| k as f64 * NMEA_GGA_INTERVAL)
+                .collect();
+            self.trajectory = Some(
+                Trajectory::new(times, positions, None, None).map_err(error)?,
+            );
+        }
+        Ok(self)
+    }
+
+    /// Sets a motion profile for dynamic positioning.
+    ///
+    /// This method loads a kinematic scenario in TOML format: a start
+    /// position, heading and speed, motion limits, and segments holding,
+    /// going straight, accelerating, turning, climbing, circling or going to
+    /// waypoints, with jerk-limited transitions. The positions and velocities
+    /// are integrated at the epochs of the simulation, so the Doppler shifts
+    /// follow the receiver velocity. When this option is used, the
+    /// simulation will use dynamic positioning mode.
+    ///
+    /// # Arguments
+    /// * `file` - Optional path to a motion profile in TOML format
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - Builder with the motion profile loaded
+    /// * `Err(Error)` - If the file cannot be read or if another positioning
+    ///   method was already set
+    ///
+    /// # Errors
+    /// * Returns an error if another positioning method was already set
+    ///   (duplicate position)
+    /// * Returns parsing errors if the file cannot be read or the profile is
+    ///   invalid
+    pub fn motion_profile_file(
+        mut self, file: Option<PathBuf>,
+    ) -> Result<Self, Error> {
+        if self.has_position() && file.is_some() {
+            return Err(Error::duplicate_position());
+        }
+        if let Some(file) = file {
+            self.mode = Some(MotionMode::Dynamic);
+            self.profile = Some(read_motion_profile(&file).map_err(|e| {
+                Error::ParsingError(format!("Motion profile error: {e}"))
             })?);
         }
         Ok(self)
     }
 
+    /// Selects the signals to generate.
+    ///
+    /// By default only GPS L1 C/A is generated. Signals on the same band are
+    /// summed into one baseband stream. The band of the first signal is
+    /// written to the output file; every other band is written to a file
+    /// with the band name appended to the stem, e.g. `gpssim_L2.bin`.
+    ///
+    /// # Arguments
+    /// * `signals` - Optional list of signals, e.g. `[GpsL1Ca, GpsL2C]`
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - Builder with signals set
+    /// * `Err(Error)` - If the list is empty or contains duplicates
+    ///
+    /// # Errors
+    /// * Returns an error if no signal or the same signal twice is given
+    pub fn signals(
+        mut self, signals: Option<Vec<Signal>>,
+    ) -> Result<Self, Error> {
+        if let Some(signals) = signals {
+            if signals.is_empty() {
+                return Err(Error::invalid_signal("no signal selected"));
+            }
+            for (i, signal) in signals.iter().enumerate() {
+                if signals[..i].contains(signal) {
+                    return Err(Error::invalid_signal(format!(
+                        "{signal} selected more than once"
+                    )));
+                }
+            }
+            self.signals = Some(signals);
+        }
+        Ok(self)
+    }
+
+    /// Sets the centre frequency of the GLONASS G1 output.
+    ///
+    /// GLONASS L1OF satellites transmit on separate carriers, 562.5 kHz apart
+    /// from 1598.0625 MHz to 1605.375 MHz. All of them are summed into one
+    /// baseband stream mixed down from this frequency, which defaults to
+    /// 1602 MHz (frequency channel 0). The sampling frequency must cover
+    /// every channel around the centre.
+    ///
+    /// # Arguments
+    /// * `frequency` - Optional centre frequency in Hz
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - Builder with the centre frequency set
+    /// * `Err(Error)` - If the frequency is not a positive number
+    ///
+    /// # Errors
+    /// * Returns an error if the frequency is not finite and positive
+    pub fn glonass_center_frequency(
+        mut self, frequency: Option<f64>,
+    ) -> Result<Self, Error> {
+        if let Some(frequency) = frequency {
+            if !frequency.is_finite() || frequency <= 0.0 {
+                return Err(Error::invalid_signal(format!(
+                    "invalid GLONASS centre frequency {frequency}"
+                )));
+            }
+            self.glonass_center_frequency = Some(frequency);
+        }
+        Ok(self)
+    }
+
+    /// Loads the Galileo E1 OS memory codes.
+    ///
+    /// The E1-B and E1-C primary codes are tabulated in the Galileo OS SIS
+    /// ICD rather than generated, and are not distributed with this crate.
+    /// They must be loaded before the E1 signal can be generated. The file
+    /// holds one code per line as the signal name, the PRN and the code in
+    /// hexadecimal, e.g. `E1B 1 F5D7...`.
+    ///
+    /// # Arguments
+    /// * `file` - Optional path to the code file
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - Builder with the codes loaded
+    /// * `Err(Error)` - If the file cannot be read or is malformed
+    ///
+    /// # Errors
+    /// * `Error::Io` - If the file cannot be read
+    /// * `Error::CodeFile` - If the file is malformed
+    pub fn galileo_codes(
+        mut self, file: Option<PathBuf>,
+    ) -> Result<Self, Error> {
+        if let Some(file) = file {
+            self.memory_codes = Some(MemoryCodes::read_file(&file)?);
+        }
+        Ok(self)
+    }
+
+    /// Loads the SBAS GEO satellites to simulate.
+    ///
+    /// SBAS satellites are not part of the navigation file. Their PRN,
+    /// position (fixed, or a message type 9 state vector) and the messages
+    /// they broadcast are read from a configuration file, which may schedule
+    /// periodic messages, replay messages recorded in EGNOS Message Server
+    /// files and raise alarms (message type 0) at given times.
+    ///
+    /// # Arguments
+    /// * `file` - Optional path to the SBAS configuration file
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - Builder with the SBAS satellites loaded
+    /// * `Err(Error)` - If a file cannot be read or is malformed
+    ///
+    /// # Errors
+    /// * `Error::Io` - If the configuration or a message file cannot be read
+    /// * `Error::SbasFile` - If the configuration or a message file is
+    ///   malformed
+    pub fn sbas_file(mut self, file: Option<PathBuf>) -> Result<Self, Error> {
+        if let Some(file) = file {
+            self.sbas = Some(read_sbas_file(&file)?);
+        }
+        Ok(self)
+    }
+
+    /// Loads precise orbits and clocks from SP3 files.
+    ///
+    /// The precise orbits and clocks become the true states of the
+    /// satellites from which ranges and Doppler are computed, while the
+    /// navigation messages still carry the broadcast ephemerides, so that the
+    /// broadcast orbit and clock errors of the day are reproduced. Satellites
+    /// missing from the files, and times outside them, fall back to the
+    /// broadcast ephemerides. Precise orbits are ignored when the ephemeris
+    /// time is overridden.
+    ///
+    /// # Arguments
+    /// * `files` - Optional paths to SP3-c or SP3-d files, possibly
+    ///   compressed, e.g. consecutive daily files
+    ///
+    /// # Returns
+    /// * `Ok(Self)` - Builder with the precise orbits loaded
+    /// * `Err(Error)` - If a file cannot be read or is malformed
+    ///
+    /// # Errors
+    /// * `Error::Rinex` - If a file cannot be read or parsed
+    /// * `Error::PreciseOrbitFile` - If the time system of the files is not
+    ///   supported
+    pub fn precise_orbit_files(
+        mut self, files: Option<Vec<PathBuf>>,
+    ) -> Result<Self, Error> {
+        if let Some(files) = files {
+            self.precise_orbits = Some(PreciseOrbits::read_files(&files)?);
+        }
+        Ok(self)
+    }
+
     /// Sets the time step between simulation updates.
     ///
     /// This method specifies the time interval in seconds between position
-    /// updates in the simulation. The default is 0.1 seconds (10 Hz update
-    /// rate). Smaller values provide more frequent updates but increase
-    /// computation time.
+    /// updates in the simulation, from 1 ms to 100 ms. The default is 0.1
+    /// seconds (10 Hz update rate). Smaller values follow faster dynamics,
+    /// such as vibrations, but increase computation time. Trajectories are
+    /// resampled onto the updates and the duration is counted in them.
+    ///
+    /// The number of samples per update is the sampling frequency times the
+    /// step, so the step must be a whole number of sampling periods; this is
+    /// checked when the generator is built.
     ///
     /// # Arguments
-    /// * `rate` - Optional time step in seconds
+    /// * `interval` - Optional time step in seconds
     ///
     /// # Returns
-    /// * `Self` - Builder with sample rate set
-    pub fn sample_rate(mut self, rate: Option<f64>) -> Self {
-        self.sample_rate = rate;
-        self
+    /// * `Ok(Self)` - Builder with update interval set
+    /// * `Err(Error)` - If the time step is out of range
+    ///
+    /// # Errors
+    /// * Returns an error if the time step is not between 1 ms and 100 ms
+    pub fn update_interval(
+        mut self, interval: Option<f64>,
+    ) -> Result<Self, Error> {
+        if let Some(interval) = interval {
+            // Bounds within a nanosecond, for steps such as 3 * 0.001
+            if !(MIN_UPDATE_INTERVAL - 1e-9..=MAX_UPDATE_INTERVAL + 1e-9)
+                .contains(&interval)
+            {
+                return Err(Error::invalid_update_interval(interval));
+            }
+            self.update_interval = Some(interval);
+        }
+        Ok(self)
     }
 
     /// Builds the `SignalGenerator` with the configured settings.
@@ -594,7 +1040,8 @@ impl SignalGeneratorBuilder {
     /// * `Err(Error)` - If the configuration is invalid or incomplete
     ///
     /// # Errors
-    /// * `Error::navigation_not_set()` - If no navigation file was provided
+    /// * `Error::navigation_not_set()` - If neither a navigation file, an
+    ///   almanac nor a synthetic constellation was provided
     /// * `Error::invalid_gps_day()` - If an invalid GPS day was specified
     /// * `Error::invalid_gps_week()` - If an invalid GPS week was specified
     /// * `Error::invalid_delta_leap_second()` - If an invalid leap second delta
@@ -606,31 +1053,50 @@ impl SignalGeneratorBuilder {
     /// * `Error::no_current_ephemerides()` - If no valid ephemeris is available
     ///   for the start time
     /// * `Error::data_format_not_set()` - If no data format was specified
+    /// * `Error::invalid_sampling_frequency()` - If the sampling frequency is
+    ///   too low for one of the selected signals
+    /// * `Error::invalid_signal()` - If Galileo E1 is selected without its
+    ///   memory codes, or SBAS without an SBAS configuration
+    /// * `Error::invalid_update_interval()` - If the update interval is not a
+    ///   whole number of sampling periods
     #[allow(clippy::too_many_lines)]
     pub fn build(mut self) -> Result<SignalGenerator, Error> {
         // ensure navigation data is read
-        let Some((count, mut ionoutc, mut ephemerides)) = self.ephemerides_data
-        else {
+        if self.ephemerides_data.is_none()
+            && self.almanac.is_none()
+            && self.synthetic_satellites.is_none()
+        {
             return Err(Error::navigation_not_set());
-        };
+        }
         // check and set defaults
-        // leap setting
-        if let Some(leap) = self.leap {
-            ionoutc.leapen = 1;
-            ionoutc.wnlsf = leap[0];
-            ionoutc.day_number = leap[1];
-            ionoutc.dtlsf = leap[2];
-            if !(1..=7).contains(&ionoutc.day_number) {
-                return Err(Error::invalid_gps_day());
-            }
-            if ionoutc.wnlsf < 0 {
-                return Err(Error::invalid_gps_week());
-            }
-            if !(-128..=127).contains(&ionoutc.dtlsf) {
-                return Err(Error::invalid_delta_leap_second());
+        // update_interval, default is 0.1 s/10 Hz
+        let update_interval = self.update_interval.unwrap_or(UPDATE_INTERVAL);
+        // positions, a trajectory resampled onto the simulation epochs or a
+        // motion profile integrated at them
+        let mut velocities = Vec::new();
+        if let Some(trajectory) = &self.trajectory {
+            let interpolation = self.interpolation.unwrap_or_default();
+            let states =
+                trajectory.resample_states(update_interval, interpolation);
+            self.positions =
+                Some(states.iter().map(|state| state.position).collect());
+            // The range rates follow the receiver velocities when these are
+            // given, or are the derivatives of the interpolated positions
+            if trajectory.has_velocities()
+                || interpolation == Interpolation::Hermite
+            {
+                velocities =
+                    states.iter().map(|state| state.velocity).collect();
             }
         }
-        // positions
+        if let Some(profile) = &self.profile {
+            let states = profile.states(update_interval).map_err(|e| {
+                Error::ParsingError(format!("Motion profile error: {e}"))
+            })?;
+            self.positions =
+                Some(states.iter().map(|state| state.position).collect());
+            velocities = states.iter().map(|state| state.velocity).collect();
+        }
         let positions = if let Some(positions) = self.positions {
             if positions.len() == 1 {
                 self.mode = Some(MotionMode::Static);
@@ -647,8 +1113,6 @@ impl SignalGeneratorBuilder {
             // llh2xyz(&llh, &mut xyz);
             vec![xyz]
         };
-        // sample_rate, default is 0.1/10HZ
-        let sample_rate = self.sample_rate.unwrap_or(0.1);
         // mode
         let mode = self.mode.unwrap_or(MotionMode::Static);
         // check duration
@@ -656,7 +1120,7 @@ impl SignalGeneratorBuilder {
             return Err(Error::invalid_duration());
         }
         let user_motion_count = if let Some(duration) = self.duration {
-            let duration_count = (duration * 10.0 + 0.5) as usize;
+            let duration_count = (duration / update_interval + 0.5) as usize;
             if matches!(mode, MotionMode::Static) {
                 // if is static mode just return it
                 duration_count
@@ -668,28 +1132,159 @@ impl SignalGeneratorBuilder {
             // not set, it is positions' len
             positions.len()
         };
+        // The nominal constellation is placed at the start time
+        let almanac = match (self.almanac, self.synthetic_satellites) {
+            (Some(almanac), synthetic) => {
+                if synthetic.is_some() {
+                    eprintln!(
+                        "Warning: The synthetic constellation is ignored as \
+                         an almanac is given."
+                    );
+                }
+                Some(almanac)
+            }
+            (None, Some(satellites)) => {
+                let epoch = if let Some(time) = &self.receiver_gps_time {
+                    time.clone()
+                } else {
+                    let now = jiff::Timestamp::now().in_tz("UTC")?;
+                    TimeScale::Utc.to_gps_time(&DateTime::from(now))
+                };
+                Some(nominal_constellation(satellites, &epoch))
+            }
+            (None, None) => None,
+        };
+        let (mut ionoutc, mut satellites) =
+            match (self.ephemerides_data, &almanac) {
+                (Some(data), almanac) => {
+                    if almanac.is_some() {
+                        eprintln!(
+                            "Warning: The almanac or synthetic constellation \
+                             is ignored as navigation files are given."
+                        );
+                    }
+                    data
+                }
+                (None, Some(almanac)) => {
+                    // Almanac data sets are issued over the whole scenario
+                    let start = if let Some(time) = &self.receiver_gps_time {
+                        time.clone()
+                    } else {
+                        let now = jiff::Timestamp::now().in_tz("UTC")?;
+                        let now =
+                            TimeScale::Utc.to_gps_time(&DateTime::from(now));
+                        almanac_start_time(almanac, now.week).unwrap_or(now)
+                    };
+                    let end = start
+                        .add_secs(user_motion_count as f64 * update_interval);
+                    (
+                        IonoUtc::default(),
+                        almanac_ephemerides(almanac, &start, &end),
+                    )
+                }
+                (None, None) => return Err(Error::navigation_not_set()),
+            };
+        // leap setting
+        if let Some(leap) = self.leap {
+            ionoutc.leapen = 1;
+            ionoutc.wnlsf = leap[0];
+            ionoutc.day_number = leap[1];
+            ionoutc.dtlsf = leap[2];
+            if !(1..=7).contains(&ionoutc.day_number) {
+                return Err(Error::invalid_gps_day());
+            }
+            if ionoutc.wnlsf < 0 {
+                return Err(Error::invalid_gps_week());
+            }
+            if !(-128..=127).contains(&ionoutc.dtlsf) {
+                return Err(Error::invalid_delta_leap_second());
+            }
+        }
         // frequency
         let sample_frequency = self.frequency.unwrap_or(2_600_000.0);
+        let signals = self.signals.unwrap_or_else(|| vec![Signal::GpsL1Ca]);
+        let glonass_center_frequency =
+            self.glonass_center_frequency.unwrap_or(CARR_FREQ_G1);
+        if signals
+            .iter()
+            .any(|signal| sample_frequency < signal.min_sample_frequency())
+            || signals.contains(&Signal::GlonassL1Of)
+                && sample_frequency
+                    < fdma_sample_frequency(glonass_center_frequency)
+        {
+            return Err(Error::invalid_sampling_frequency());
+        }
+        // Each update holds a whole number of samples
+        let samples = sample_frequency * update_interval;
+        if (samples - samples.round()).abs() > 1e-6 {
+            return Err(Error::invalid_update_interval(update_interval));
+        }
+        if signals.contains(&Signal::GalileoE1) && self.memory_codes.is_none() {
+            return Err(Error::invalid_signal(
+                "E1 requires the Galileo memory codes",
+            ));
+        }
+        if signals.contains(&Signal::SbasL1) && self.sbas.is_none() {
+            return Err(Error::invalid_signal(
+                "SBAS requires an SBAS configuration file",
+            ));
+        }
+        for constellation in [
+            Constellation::Galileo,
+            Constellation::BeiDou,
+            Constellation::Glonass,
+            Constellation::Qzss,
+        ] {
+            if signals
+                .iter()
+                .any(|signal| signal.constellation() == constellation)
+                && satellites.is_empty(constellation)
+            {
+                eprintln!(
+                    "Warning: The navigation file has no {constellation:?} \
+                     ephemerides."
+                );
+            }
+        }
+        if signals.contains(&Signal::BeidouB1I) {
+            let uncoded: Vec<String> = Constellation::BeiDou
+                .prns()
+                .filter(|&prn| {
+                    prn > MAX_PRN_B1I
+                        && !satellites
+                            .get(Constellation::BeiDou, prn)
+                            .is_empty()
+                })
+                .map(|prn| format!("C{prn:02}"))
+                .collect();
+            if !uncoded.is_empty() {
+                eprintln!(
+                    "Warning: No B1I ranging code is known above C{MAX_PRN_B1I}; \
+                     {} are not simulated.",
+                    uncoded.join(", ")
+                );
+            }
+        }
         // is override time?
 
         let antenna_gains: [i32; MAX_CHAN] = [0; MAX_CHAN];
         let antenna_pattern: [f64; 37] = [0.; 37];
-        let mut gpstime_min = GpsTime::default();
-        let mut gpstime_max = GpsTime::default();
-        // get min time of ephemerides
-        for sv in 0..MAX_SAT {
-            if ephemerides[0][sv].vflg {
-                gpstime_min = ephemerides[0][sv].toc.clone();
-                break;
-            }
-        }
-        // get max time of ephemerides
-        for sv in 0..MAX_SAT {
-            if ephemerides[count - 1][sv].vflg {
-                gpstime_max = ephemerides[count - 1][sv].toc.clone();
-                break;
+        // The ephemerides of every enabled constellation bound the scenario;
+        // SBAS satellites are defined relative to its start
+        let mut constellations: Vec<Constellation> = Vec::new();
+        for constellation in signals.iter().map(|signal| signal.constellation())
+        {
+            if constellation != Constellation::Sbas
+                && !constellations.contains(&constellation)
+            {
+                constellations.push(constellation);
             }
         }
+        let Some((gpstime_min, gpstime_max)) =
+            satellites.toc_span(&constellations)
+        else {
+            return Err(Error::no_current_ephemerides());
+        };
         let time_override = self.time_override.unwrap_or(false);
         let receiver_gps_time = if let Some(gps_time_0) = self.receiver_gps_time
         {
@@ -698,7 +1293,7 @@ impl SignalGeneratorBuilder {
                 // Round to nearest 2-hour boundary (7200 seconds)
                 // This matches the C version's behavior exactly: gtmp.sec =
                 // (double)(((int)(g0.sec)) / 7200) * 7200.0;
-                let mut gtmp = GpsTime {
+                let gtmp = GpsTime {
                     week: gps_time_0.week,
                     sec: f64::from((gps_time_0.sec as i32) / 7200) * 7200.0,
                 };
@@ -710,16 +1305,31 @@ impl SignalGeneratorBuilder {
                 ionoutc.tot = gtmp.sec as i32;
                 // Iono/UTC parameters may no longer valid
                 //ionoutc.vflg = FALSE;
-                for sv in 0..MAX_SAT {
-                    for i_eph in ephemerides.iter_mut().take(count) {
-                        if i_eph[sv].vflg {
-                            gtmp = i_eph[sv].toc.add_secs(dsec);
-                            let ttmp = DateTime::from(&gtmp);
-                            i_eph[sv].toc = gtmp;
-                            i_eph[sv].t = ttmp;
-                            gtmp = i_eph[sv].toe.add_secs(dsec);
-                            i_eph[sv].toe = gtmp;
-                        }
+                let re_epoch_mode = self.re_epoch.unwrap_or_default();
+                for (satellite, eph) in satellites.iter_mut_with_id() {
+                    eph.transmission_time = eph
+                        .transmission_time
+                        .as_ref()
+                        .map(|time| time.add_secs(dsec));
+                    if re_epoch_mode == ReEpoch::Shift
+                        || eph.state_vector.is_some()
+                    {
+                        eph.toc = eph.toc.add_secs(dsec);
+                        eph.t = DateTime::from(&eph.toc);
+                        eph.toe = eph.toe.add_secs(dsec);
+                        continue;
+                    }
+                    let toe = eph.toe.add_secs(dsec);
+                    let transmission_time = eph.transmission_time.take();
+                    *eph = Ephemeris {
+                        transmission_time,
+                        ..propagate_secular(eph, &toe, eph.iode)
+                    };
+                    if matches!(
+                        satellite.constellation,
+                        Constellation::Gps | Constellation::Qzss
+                    ) {
+                        lnav_quantize(eph);
                     }
                 }
             } else if gps_time_0.diff_secs(&gpstime_min) < 0.0
@@ -731,37 +1341,61 @@ impl SignalGeneratorBuilder {
         } else {
             gpstime_min
         };
-        let mut valid_ephemerides_index = None;
-
-        // Select the current set of ephemerides
-        for (i, eph_item) in ephemerides.iter().enumerate().take(count) {
-            for e in eph_item.iter().take(MAX_SAT) {
-                if e.vflg {
-                    let dt = receiver_gps_time.diff_secs(&e.toc);
-                    // Relax the time constraint when time_override is true
-                    if time_override
-                        || (-SECONDS_IN_HOUR..SECONDS_IN_HOUR).contains(&dt)
-                    {
-                        valid_ephemerides_index = Some(i);
-                        break;
-                    }
+        // The week of the leap second event may be given modulo 1024
+        if ionoutc.leapen == 1 {
+            ionoutc.wnlsf = full_week(ionoutc.wnlsf, receiver_gps_time.week);
+        }
+        // A leap second event before the start is already in effect
+        if ionoutc
+            .leap_second_event()
+            .is_some_and(|event| receiver_gps_time.diff_secs(&event) >= 0.0)
+        {
+            ionoutc.dtls = ionoutc.dtlsf;
+        }
+        // Select the current ephemeris of each GPS satellite
+        let handover = self.handover.unwrap_or_default();
+        let gps_ephemeris_indices: [Option<usize>; MAX_SAT] =
+            std::array::from_fn(|sv| {
+                satellites.current(
+                    Constellation::Gps,
+                    sv + 1,
+                    &receiver_gps_time,
+                    handover,
+                )
+            });
+        if !constellations.iter().any(|&constellation| {
+            constellation.prns().any(|prn| {
+                satellites
+                    .current(constellation, prn, &receiver_gps_time, handover)
+                    .is_some()
+            })
+        }) {
+            return Err(Error::no_current_ephemerides());
+        }
+        // Precise orbits are dated, so they no longer match shifted
+        // ephemerides
+        if let Some(orbits) = &self.precise_orbits {
+            if time_override {
+                eprintln!(
+                    "Warning: Precise orbits are ignored when the ephemeris \
+                     time is overridden."
+                );
+            } else {
+                for (satellite, eph) in satellites.iter_mut_with_id() {
+                    eph.precise = orbits.get(satellite);
                 }
             }
-            if valid_ephemerides_index.is_some() {
-                // ieph has been set
-                break;
-            }
         }
-
-        // If no valid ephemerides found and time_override is true, use the
-        // first set
-        if valid_ephemerides_index.is_none() && time_override && count > 0 {
-            valid_ephemerides_index = Some(0);
+        // SBAS satellites are defined relative to the start of the scenario
+        if signals.contains(&Signal::SbasL1) {
+            for geo in self.sbas.iter().flatten() {
+                satellites.insert(
+                    Constellation::Sbas,
+                    geo.prn,
+                    geo.ephemeris(&receiver_gps_time),
+                );
+            }
         }
-
-        let Some(valid_ephemerides_index) = valid_ephemerides_index else {
-            return Err(Error::no_current_ephemerides());
-        };
         // Set ionospheric correction based on the disable flag
         // In gpssim.c, when -i flag is used, ionoutc.enable is set to FALSE
         // So when ionospheric_disable is true, ionoutc.enable should be false
@@ -771,10 +1405,12 @@ impl SignalGeneratorBuilder {
         };
 
         let generator = SignalGenerator {
-            ephemerides,
-            valid_ephemerides_index,
+            satellite_ephemerides: satellites,
+            gps_ephemeris_indices,
+            handover,
             ionoutc,
             positions,
+            velocities,
             simulation_step_count: user_motion_count,
             receiver_gps_time,
             antenna_gains,
@@ -782,11 +1418,14 @@ impl SignalGeneratorBuilder {
             mode,
             elevation_mask: 0.0, // Default elevation mask
             sample_frequency,
-            sample_rate,
+            update_interval,
             data_format,
             fixed_gain: self.path_loss,
             output_file: self.output_file,
-            verbose: false,
+            signals,
+            glonass_center_frequency,
+            memory_codes: self.memory_codes.map(Arc::new),
+            verbose: self.verbose.unwrap_or(false),
             ..Default::default()
         };
         Ok(generator)
@@ -0,0 +1,141 @@
+//! Nominal GPS constellation built without navigation data.
+//!
+//! The satellites fly circular orbits of the nominal GPS semi-major axis,
+//! inclined by 55 degrees, in the slots of the GPS SPS Performance Standard
+//! (section 3.1, baseline and expandable 24-slot constellations): six planes
+//! A to F, 60 degrees apart, of four baseline slots each. Up to 24
+//! satellites fill the baseline slots, taking the planes in turn, so that
+//! PRN 1 is in slot A1, PRN 2 in B1 and so on. The 25th to 27th satellites expand slots B1, D2 and F2 in
+//! that order, each into a fore and an aft slot with consecutive PRNs, so
+//! that the following satellites move up by one PRN. Satellites beyond the 27
+//! slots, such as the surplus ones of the 31 flown in recent years, have no
+//! published position; they are put in turn in planes A, C and E, halfway
+//! across the widest gap of their plane.
+//!
+//! The slot positions are given at the epoch of the standard, at which the
+//! Greenwich hour angle is taken as zero; here they apply at the time of
+//! applicability of the constellation.
+//!
+//! The constellation is described as an almanac, so that it gives ephemeris
+//! sets over any scenario in the same way as a YUMA or SEM file, with
+//! parameters that the navigation message encodes exactly.
+
+use constants::{OMEGA_EARTH, PI};
+use rinex::almanac::{Almanac, AlmanacEntry};
+
+use crate::datetime::GpsTime;
+
+/// Nominal orbit inclination (radians)
+const INCLINATION: f64 = 55.0 * PI / 180.0;
+
+/// Square root of the nominal semi-major axis, about 26 560 km (sqrt(meters))
+const SQRT_A: f64 = 5_153.6;
+
+/// Regression of the ascending nodes caused by the Earth oblateness at the
+/// nominal orbit (radians/second)
+const OMEGA_DOT: f64 = -7.83e-9;
+
+/// Longitude of the ascending node of planes A to F at the epoch (degrees)
+const PLANE_NODES: [f64; 6] =
+    [272.847, 332.847, 32.847, 92.847, 152.847, 212.847];
+
+/// Argument of latitude of baseline slots 1 to 4 of planes A to F at the
+/// epoch (degrees)
+const BASELINE_SLOTS: [[f64; 4]; 6] = [
+    [268.126, 161.786, 11.676, 41.806],
+    [80.956, 173.336, 309.976, 204.376],
+    [111.876, 11.796, 339.666, 241.556],
+    [135.226, 265.446, 35.156, 167.356],
+    [197.046, 302.596, 66.066, 333.686],
+    [238.886, 345.226, 105.206, 135.346],
+];
+
+/// Expandable slots in order of expansion, as plane index, slot index and
+/// the arguments of latitude of the fore and aft slots replacing them
+/// (degrees)
+const EXPANDABLE_SLOTS: [(usize, usize, [f64; 2]); 3] = [
+    (1, 0, [94.916, 66.356]),
+    (3, 1, [282.676, 257.976]),
+    (5, 1, [0.456, 334.016]),
+];
+
+/// Planes given the satellites beyond the published slots, in turn
+const SURPLUS_PLANES: [usize; 3] = [0, 2, 4];
+
+/// Places a number of satellites in the nominal slots.
+///
+/// # Arguments
+/// * `satellites` - Number of satellites
+///
+/// # Returns
+/// The plane index and the argument of latitude in degrees of each
+/// satellite, in PRN order
+fn slots(satellites: usize) -> Vec<(usize, f64)> {
+    let expanded = &EXPANDABLE_SLOTS[..satellites.saturating_sub(24).min(3)];
+    let mut slots: Vec<(usize, f64)> = (0..4)
+        .flat_map(|slot| (0..6).map(move |plane| (plane, slot)))
+        .flat_map(|(plane, slot)| {
+            match expanded.iter().find(|&&(p, s, _)| (p, s) == (plane, slot)) {
+                Some((_, _, pair)) => {
+                    pair.map(|latitude| (plane, latitude)).to_vec()
+                }
+                None => vec![(plane, BASELINE_SLOTS[plane][slot])],
+            }
+        })
+        .collect();
+    for index in 0..satellites.saturating_sub(slots.len()) {
+        let plane = SURPLUS_PLANES[index % SURPLUS_PLANES.len()];
+        let mut latitudes: Vec<f64> = slots
+            .iter()
+            .filter(|&&(p, _)| p == plane)
+            .map(|&(_, latitude)| latitude)
+            .collect();
+        latitudes.sort_by(f64::total_cmp);
+        let (start, gap) = latitudes
+            .iter()
+            .zip(latitudes.iter().cycle().skip(1))
+            .map(|(from, to)| (*from, (to - from).rem_euclid(360.0)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0.0, 360.0));
+        slots.push((plane, (start + gap / 2.0).rem_euclid(360.0)));
+    }
+    slots.truncate(satellites);
+    slots
+}
+
+/// Builds the almanac of a nominal GPS constellation.
+///
+/// # Arguments
+/// * `satellites` - Number of satellites, given PRNs 1 to `satellites`
+/// * `epoch` - Time of applicability, at which the satellites are in their
+///   slots
+///
+/// # Returns
+/// The almanac of the healthy satellites, with zero clock offsets
+pub fn nominal_constellation(satellites: usize, epoch: &GpsTime) -> Almanac {
+    let wrap = |angle: f64| (angle + PI).rem_euclid(2.0 * PI) - PI;
+    let entries = slots(satellites)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (plane, latitude))| {
+            // Node longitude at the epoch, referred to the start of the week
+            let node = PLANE_NODES[plane].to_radians();
+            AlmanacEntry {
+                prn: index + 1,
+                health: 0,
+                eccentricity: 0.0,
+                toa: epoch.sec,
+                inclination: INCLINATION,
+                omega_dot: OMEGA_DOT,
+                sqrt_a: SQRT_A,
+                omega0: wrap(node + OMEGA_EARTH * epoch.sec),
+                perigee: 0.0,
+                mean_anomaly: wrap(latitude.to_radians()),
+                af0: 0.0,
+                af1: 0.0,
+                week: epoch.week,
+            }
+        })
+        .collect();
+    Almanac { entries }
+}
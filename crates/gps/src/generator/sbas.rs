@@ -0,0 +1,403 @@
+//! SBAS GEO satellite configuration.
+//!
+//! SBAS satellites are not described by the navigation file. They are
+//! defined in a text file holding one block per satellite, each starting
+//! with the PRN of the satellite:
+//!
+//! ```text
+//! # EGNOS test configuration
+//! prn 123
+//! position 35876000 21800000 0
+//! message 1 60 0 FFFFFFFF000000000000000000000000000000000000000000000
+//! message 9 120 1
+//! message 2 6 2 0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF01234
+//! ems egnos.ems
+//! alarm 300 10
+//! ```
+//!
+//! The following lines are recognised; fields may be separated by
+//! whitespace or commas, and `#` starts a comment:
+//! - `prn N` starts the block of satellite N (120-158).
+//! - `position X Y Z` places the satellite at a fixed WGS-84 position
+//!   (meters).
+//! - `ephemeris T0 X Y Z VX VY VZ AX AY AZ [AGF0 AGF1]` gives a message type
+//!   9 state vector at the GPS time of day T0 (seconds), extrapolated with
+//!   the second-order polynomial of DO-229: position in meters, velocity in
+//!   meters/second, acceleration in meters/second² and the optional clock
+//!   bias (seconds) and drift (seconds/second).
+//! - `message TYPE INTERVAL OFFSET [DATA]` broadcasts message TYPE (0-63)
+//!   every INTERVAL seconds from second OFFSET of the scenario. DATA holds
+//!   the 212 data bits as 53 hexadecimal digits; without it, message type 9
+//!   is encoded from the orbit of the satellite and the other types carry
+//!   zeros. Earlier lines take precedence when several messages are due in
+//!   the same second.
+//! - `alarm START DURATION` broadcasts message type 0 from second START of
+//!   the scenario for DURATION seconds.
+//! - `ems FILE` replays the messages of the satellite recorded in an EGNOS
+//!   Message Server file, relative to the configuration file. Each line of
+//!   such a file holds the PRN, the two-digit year, month, day, hour, minute
+//!   and second of reception, the message type and the 250-bit message in
+//!   hexadecimal. The first recorded message is broadcast in the first
+//!   second of the scenario and the others keep their recorded spacing;
+//!   messages failing their CRC are left out. Recorded messages take
+//!   precedence over the scheduled ones.
+//!
+//! Seconds of the scenario are counted from the first whole second of the
+//! simulation. The preamble and the CRC of every message are regenerated.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use constants::{SECONDS_IN_DAY, WGS84_RADIUS};
+
+use crate::{
+    Error,
+    datetime::{DateTime, GpsTime},
+    ephemeris::Ephemeris,
+    orbit::{Frame, Motion, StateVector},
+    signal::{
+        Constellation, DATA_BITS, MESSAGE_BITS, Message, MessageStream,
+        Scheduled,
+    },
+};
+
+/// Number of hexadecimal digits of the data field of a message
+const DATA_DIGITS: usize = DATA_BITS.div_ceil(4);
+
+/// Largest number of hexadecimal digits of a recorded message, padded to
+/// whole bytes
+const MESSAGE_DIGITS: usize = 64;
+
+/// An SBAS GEO satellite defined by a configuration file.
+#[derive(Clone)]
+pub struct GeoSatellite {
+    /// Satellite PRN number (120-158)
+    pub prn: usize,
+    /// WGS-84 state vector of the satellite, propagated as a polynomial
+    pub state: StateVector,
+    /// GPS time of day of the state vector (seconds), or `None` for a fixed
+    /// position
+    pub t0: Option<f64>,
+    /// Clock bias aGf0 (seconds)
+    pub af0: f64,
+    /// Clock drift aGf1 (seconds/second)
+    pub af1: f64,
+    /// Messages broadcast by the satellite
+    pub messages: MessageStream,
+}
+impl GeoSatellite {
+    /// Creates a satellite without position or messages.
+    fn new(prn: usize) -> Self {
+        Self {
+            prn,
+            state: StateVector {
+                frame: Frame::Wgs84,
+                motion: Motion::Polynomial,
+                ..Default::default()
+            },
+            t0: None,
+            af0: 0.0,
+            af1: 0.0,
+            messages: MessageStream::default(),
+        }
+    }
+
+    /// Builds the ephemeris of the satellite for a scenario.
+    ///
+    /// The reference time of a state vector is the occurrence of its time of
+    /// day closest to the start of the scenario; a fixed position refers to
+    /// the start itself.
+    ///
+    /// # Arguments
+    /// * `start` - GPS time of the start of the scenario
+    pub fn ephemeris(&self, start: &GpsTime) -> Ephemeris {
+        let toe = match self.t0 {
+            Some(t0) => {
+                let day = GpsTime {
+                    week: start.week,
+                    sec: start.sec - start.sec.rem_euclid(SECONDS_IN_DAY),
+                };
+                let toe = day.add_secs(t0);
+                let days = (toe.diff_secs(start) / SECONDS_IN_DAY).round();
+                toe.add_secs(-days * SECONDS_IN_DAY)
+            }
+            None => start.clone(),
+        };
+        Ephemeris {
+            vflg: true,
+            t: DateTime::from(&toe),
+            toc: toe.clone(),
+            toe,
+            af0: self.af0,
+            af1: self.af1,
+            state_vector: Some(self.state.clone()),
+            sbas_messages: Some(MessageStream {
+                start: GpsTime {
+                    week: start.week,
+                    sec: start.sec.floor(),
+                },
+                ..self.messages.clone()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Converts hexadecimal digits into bits, MSB first.
+///
+/// # Returns
+/// The first `count` bits, or `None` if a digit is invalid or there are not
+/// enough digits
+fn hex_bits(hex: &str, count: usize) -> Option<Vec<u8>> {
+    let mut bits = Vec::with_capacity(4 * hex.len());
+    for digit in hex.chars() {
+        let value = digit.to_digit(16)?;
+        bits.extend((0..4).rev().map(|i| (value >> i & 1) as u8));
+    }
+    (bits.len() >= count).then(|| {
+        bits.truncate(count);
+        bits
+    })
+}
+
+/// Converts the reception time of a recorded message, as the two-digit year,
+/// month, day, hour, minute and second, into GPS time.
+fn ems_time(fields: [&str; 6]) -> Option<GpsTime> {
+    let [y, m, d, hh, mm] = [0, 1, 2, 3, 4].map(|i| fields[i].parse::<i32>());
+    let y = match y.ok()? {
+        y @ 0..=79 => 2000 + y,
+        y @ 80..=99 => 1900 + y,
+        y => y,
+    };
+    Some(GpsTime::from(&DateTime {
+        y,
+        m: m.ok()?,
+        d: d.ok()?,
+        hh: hh.ok()?,
+        mm: mm.ok()?,
+        sec: fields[5].parse().ok()?,
+    }))
+}
+
+/// Parses the recorded messages of one satellite from an EGNOS Message
+/// Server file.
+///
+/// # Arguments
+/// * `text` - Contents of the message file
+/// * `prn` - PRN of the satellite whose messages are kept
+///
+/// # Returns
+/// * `Ok(messages)` - One entry per second from the first message, `None`
+///   for seconds without a valid message
+/// * `Err(Error)` - If a line is malformed
+///
+/// # Errors
+/// * `Error::SbasFile` - If a line of the satellite does not hold a time, a
+///   message type and a 250-bit message
+pub fn parse_ems(
+    text: &str, prn: usize,
+) -> Result<Vec<Option<Message>>, Error> {
+    let mut records: Vec<(GpsTime, Message)> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let invalid = |what: &str| {
+            Error::invalid_sbas_file(format!(
+                "message file line {}: {what}",
+                number + 1
+            ))
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.first().and_then(|f| f.parse::<usize>().ok()) != Some(prn) {
+            continue;
+        }
+        let [_, y, m, d, hh, mm, sec, _, hex] = fields[..] else {
+            return Err(invalid("expected PRN, time, type and message"));
+        };
+        let time = ems_time([y, m, d, hh, mm, sec])
+            .ok_or_else(|| invalid("invalid time"))?;
+        let bits = hex_bits(hex, MESSAGE_BITS)
+            .filter(|_| hex.len() <= MESSAGE_DIGITS)
+            .ok_or_else(|| invalid("message must have 250 bits"))?;
+        if let Some(message) = Message::decode(&bits) {
+            records.push((time, message));
+        }
+    }
+    let Some(first) = records
+        .iter()
+        .map(|(t, _)| t.clone())
+        .min_by(|a, b| a.diff_secs(b).total_cmp(&0.0))
+    else {
+        return Ok(Vec::new());
+    };
+    let mut messages = Vec::new();
+    for (time, message) in records {
+        let second = time.diff_secs(&first).round() as usize;
+        if messages.len() <= second {
+            messages.resize(second + 1, None);
+        }
+        messages[second] = Some(message);
+    }
+    Ok(messages)
+}
+
+/// Parses the fields of a `message` line.
+///
+/// # Returns
+/// * `Ok(Scheduled)` - The scheduled message
+/// * `Err(what)` - A description of the invalid field
+fn parse_message(args: &[&str]) -> Result<Scheduled, &'static str> {
+    let [kind, interval, offset, data @ ..] = args else {
+        return Err("expected type, interval and offset");
+    };
+    let kind = kind
+        .parse::<u8>()
+        .ok()
+        .filter(|kind| *kind < 64)
+        .ok_or("message type must be 0-63")?;
+    let interval = interval
+        .parse::<u64>()
+        .ok()
+        .filter(|interval| *interval > 0)
+        .ok_or("interval must be positive")?;
+    let offset = offset.parse::<u64>().map_err(|_| "invalid offset")?;
+    let data = match data {
+        [] => None,
+        [hex] if hex.len() == DATA_DIGITS => {
+            Some(hex_bits(hex, DATA_BITS).ok_or("invalid data")?)
+        }
+        _ => return Err("data must have 53 hexadecimal digits"),
+    };
+    Ok(Scheduled {
+        message: Message { kind, data },
+        interval,
+        offset,
+    })
+}
+
+/// Parses an SBAS configuration in the format described in the module
+/// documentation.
+///
+/// # Arguments
+/// * `text` - Contents of the configuration file
+/// * `dir` - Directory relative to which message files are read
+///
+/// # Returns
+/// * `Ok(satellites)` - The satellites defined by the configuration
+/// * `Err(Error)` - If the configuration is malformed
+///
+/// # Errors
+/// * `Error::SbasFile` - If a line is malformed, a PRN is outside 120-158 or
+///   defined twice, or a satellite has no position above the Earth
+/// * `Error::Io` - If a message file cannot be read
+pub fn parse_sbas_config(
+    text: &str, dir: &Path,
+) -> Result<Vec<GeoSatellite>, Error> {
+    let mut satellites: Vec<GeoSatellite> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let invalid = |what: &str| {
+            Error::invalid_sbas_file(format!("line {}: {what}", number + 1))
+        };
+        let fields: Vec<&str> = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|field| !field.is_empty())
+            .collect();
+        let Some((keyword, args)) = fields.split_first() else {
+            continue;
+        };
+        let keyword = keyword.to_lowercase();
+        let numbers = |count: &[usize]| {
+            args.iter()
+                .map(|arg| arg.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .ok()
+                .filter(|values| count.contains(&values.len()))
+                .ok_or_else(|| invalid(&format!("invalid {keyword} line")))
+        };
+        if keyword == "prn" {
+            let prn = args
+                .first()
+                .and_then(|prn| prn.parse::<usize>().ok())
+                .filter(|prn| Constellation::Sbas.prns().contains(prn))
+                .ok_or_else(|| invalid("PRN must be between 120 and 158"))?;
+            if satellites.iter().any(|geo| geo.prn == prn) {
+                return Err(invalid("PRN defined twice"));
+            }
+            satellites.push(GeoSatellite::new(prn));
+            continue;
+        }
+        let Some(geo) = satellites.last_mut() else {
+            return Err(invalid("expected a prn line first"));
+        };
+        match keyword.as_str() {
+            "position" => {
+                let values = numbers(&[3])?;
+                geo.state.pos.copy_from_slice(&values);
+                geo.t0 = None;
+            }
+            "ephemeris" => {
+                let values = numbers(&[10, 12])?;
+                geo.t0 = Some(values[0]);
+                geo.state.pos.copy_from_slice(&values[1..4]);
+                geo.state.vel.copy_from_slice(&values[4..7]);
+                geo.state.acc.copy_from_slice(&values[7..10]);
+                if let [af0, af1] = values[10..] {
+                    geo.af0 = af0;
+                    geo.af1 = af1;
+                }
+            }
+            "message" => {
+                let scheduled = parse_message(args).map_err(invalid)?;
+                geo.messages.schedule.push(scheduled);
+            }
+            "alarm" => {
+                let [start, duration] = args
+                    .iter()
+                    .map(|arg| arg.parse::<u64>().ok())
+                    .collect::<Option<Vec<_>>>()
+                    .and_then(|values| <[u64; 2]>::try_from(values).ok())
+                    .ok_or_else(|| invalid("expected start and duration"))?;
+                geo.messages.alarms.push((start, duration));
+            }
+            "ems" => {
+                let [file] = args else {
+                    return Err(invalid("expected a file name"));
+                };
+                let text = fs::read_to_string(dir.join(file))?;
+                geo.messages.recorded = parse_ems(&text, geo.prn)?;
+            }
+            _ => return Err(invalid("unknown keyword")),
+        }
+    }
+    // Satellites without a position line are left at the centre of the Earth
+    if let Some(geo) = satellites.iter().find(|geo| {
+        geo.state.pos.iter().map(|p| p * p).sum::<f64>().sqrt() < WGS84_RADIUS
+    }) {
+        return Err(Error::invalid_sbas_file(format!(
+            "PRN {} has no position above the Earth",
+            geo.prn
+        )));
+    }
+    Ok(satellites)
+}
+
+/// Reads an SBAS configuration file.
+///
+/// # Arguments
+/// * `path` - Path to the configuration file
+///
+/// # Returns
+/// * `Ok(satellites)` - The satellites defined by the configuration
+/// * `Err(Error)` - If a file cannot be read or is malformed
+///
+/// # Errors
+/// * `Error::Io` - If the configuration or a message file cannot be read
+/// * `Error::SbasFile` - If the configuration or a message file is
+///   malformed
+pub fn read_sbas_file(path: &Path) -> Result<Vec<GeoSatellite>, Error> {
+    let dir = path.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+    parse_sbas_config(&fs::read_to_string(path)?, &dir)
+}
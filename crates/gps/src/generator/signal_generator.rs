@@ -1,4 +1,7 @@
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use constants::*;
 use geometry::Ecef;
@@ -6,13 +9,17 @@ use geometry::Ecef;
 use crate::{
     Error,
     channel::Channel,
-    datetime::{DateTime, GpsTime},
+    datetime::{GpsTime, TimeRange},
     ephemeris::Ephemeris,
-    generator::utils::MotionMode,
+    generator::utils::{Handover, MotionMode, SatelliteEphemerides},
     io::{DataFormat, IQWriter},
     ionoutc::IonoUtc,
     propagation::compute_range,
+    signal::{
+        Band, Constellation, MemoryCodes, SatelliteId, Signal, SignalChannel,
+    },
     table::ANT_PAT_DB,
+    timescale::TimeScale,
 };
 /// Main class for GPS signal generation and simulation.
 ///
@@ -29,19 +36,24 @@ use crate::{
 /// 3. Call `initialize()` to set up the simulation
 /// 4. Call `run_simulation()` to generate the GPS signals
 pub struct SignalGenerator {
-    /// Satellite ephemeris data organized in hourly sets
-    pub ephemerides: Box<[[Ephemeris; MAX_SAT]; EPHEM_ARRAY_SIZE]>,
-    /// Index of the currently active ephemeris set
-    pub valid_ephemerides_index: usize,
-    /// Array of satellite signal channels being tracked
+    /// GPS, Galileo, BeiDou, GLONASS, SBAS and QZSS ephemerides of each
+    /// satellite
+    pub satellite_ephemerides: SatelliteEphemerides,
+    /// Index of the current ephemeris of each GPS PRN in its list, updated
+    /// at every navigation message refresh
+    pub gps_ephemeris_indices: [Option<usize>; MAX_SAT],
+    /// Rule deciding when a new ephemeris replaces the broadcast one
+    pub handover: Handover,
+    /// GPS and QZSS L1 C/A channels being tracked
     pub channels: [Channel; MAX_CHAN],
     /// Ionospheric and UTC parameters
     pub ionoutc: IonoUtc,
-    /// Tracking which satellites are allocated to which channels (-1 = not
-    /// allocated)
-    pub allocated_satellite: [i32; MAX_SAT],
-    /// Receiver positions in ECEF coordinates (one per 100ms time step)
+    /// Receiver positions in ECEF coordinates (one per time step)
     pub positions: Vec<Ecef>,
+    /// Receiver velocities in ECEF coordinates (one per time step); when
+    /// given, the Doppler shifts follow the range rates instead of the
+    /// change of range between steps
+    pub velocities: Vec<Ecef>,
     /// Total number of motion steps to simulate
     pub simulation_step_count: usize,
     /// Current GPS time at the receiver
@@ -56,8 +68,9 @@ pub struct SignalGenerator {
     pub elevation_mask: f64,
     /// Sampling frequency in Hz (typically 2.6MHz)
     pub sample_frequency: f64,
-    /// Time step between samples in seconds (typically 0.1s)
-    pub sample_rate: f64,
+    /// Time step between updates in seconds, from 1 ms to 100 ms (typically
+    /// 0.1s)
+    pub update_interval: f64,
     /// I/Q data format for output
     pub data_format: DataFormat,
     /// Optional fixed gain value (when Some, path loss is disabled)
@@ -68,6 +81,23 @@ pub struct SignalGenerator {
     pub output_file: Option<PathBuf>,
     /// I/Q sample writer
     pub writer: Option<IQWriter>,
+    /// Signals to generate; the band of the first one is written to
+    /// `output_file`
+    pub signals: Vec<Signal>,
+    /// Channels for signals other than GPS L1 C/A (`MAX_CHAN` per signal)
+    pub signal_channels: Vec<SignalChannel>,
+    /// I/Q sample writers for bands other than the primary one
+    pub band_writers: Vec<(Band, IQWriter)>,
+    /// Accumulator slot of the L1 C/A channels, if L1 is written
+    pub legacy_slot: Option<usize>,
+    /// Accumulator slot of each signal channel: 0 for the primary band,
+    /// followed by the bands of `band_writers`
+    pub band_slots: Vec<usize>,
+    /// Centre frequency of the GLONASS G1 output around which the FDMA
+    /// channels are summed (Hz)
+    pub glonass_center_frequency: f64,
+    /// Galileo E1 memory codes shared by the E1 channels
+    pub memory_codes: Option<Arc<MemoryCodes>>,
     /// Whether the generator has been initialized
     pub initialized: bool,
     /// Whether to show detailed channel status
@@ -76,12 +106,13 @@ pub struct SignalGenerator {
 impl Default for SignalGenerator {
     fn default() -> Self {
         Self {
-            ephemerides: Box::default(),
-            valid_ephemerides_index: usize::default(),
+            satellite_ephemerides: SatelliteEphemerides::default(),
+            gps_ephemeris_indices: [None; MAX_SAT],
+            handover: Handover::default(),
             channels: std::array::from_fn(|_| Channel::default()),
             ionoutc: IonoUtc::default(),
-            allocated_satellite: [0; MAX_SAT],
             positions: Vec::new(),
+            velocities: Vec::new(),
             simulation_step_count: usize::default(),
             receiver_gps_time: GpsTime::default(),
             antenna_gains: [0; MAX_CHAN],
@@ -89,13 +120,20 @@ impl Default for SignalGenerator {
             mode: MotionMode::Static,
             elevation_mask: f64::default(),
             sample_frequency: 0.0,
-            sample_rate: 0.0,
+            update_interval: 0.0,
             data_format: DataFormat::Bits8,
             fixed_gain: None,
             iq_buffer_size: 0,
             // iq_buffer: Vec::new(),
             output_file: None,
             writer: None,
+            signals: vec![Signal::GpsL1Ca],
+            signal_channels: Vec::new(),
+            band_writers: Vec::new(),
+            legacy_slot: None,
+            band_slots: Vec::new(),
+            glonass_center_frequency: CARR_FREQ_G1,
+            memory_codes: None,
             initialized: false,
             verbose: true,
         }
@@ -133,9 +171,16 @@ impl SignalGenerator {
             self.positions[0].x, self.positions[0].y, self.positions[0].z,
         );
         let gps_time_start = self.receiver_gps_time.clone();
-        let date_time_start = DateTime::from(&gps_time_start);
+        let date_time_start = TimeScale::Utc.date_of(&gps_time_start);
+        // Fractions of a second are shown to the 100 ns when given
+        let decimals = if gps_time_start.sec.fract().abs() > 1e-9 {
+            7
+        } else {
+            0
+        };
         eprintln!(
-            "Start time = {:4}/{:02}/{:02},{:02}:{:02}:{:0>2.0} ({}:{:.0})",
+            "Start time = {:4}/{:02}/{:02},{:02}:{:02}:{:0>width$.decimals$} UTC \
+             ({}:{:.decimals$})",
             date_time_start.y,
             date_time_start.m,
             date_time_start.d,
@@ -144,22 +189,38 @@ impl SignalGenerator {
             date_time_start.sec,
             gps_time_start.week,
             gps_time_start.sec,
+            width = if decimals > 0 { decimals + 3 } else { 2 },
         );
         // Clear all channels
         self.channels
             .iter_mut()
             .take(MAX_CHAN)
-            .for_each(|ch| ch.prn = 0);
-        // Clear satellite allocation flag
-        self.allocated_satellite
-            .iter_mut()
-            .take(MAX_SAT)
-            .for_each(|s| *s = -1);
+            .for_each(|ch| ch.satellite = None);
+        // One bank of channels per signal not served by the L1 C/A channels
+        self.signal_channels = self
+            .signals
+            .iter()
+            .filter(|signal| !signal.uses_legacy_channel())
+            .flat_map(|signal| {
+                let center = self.center_frequency(signal.band());
+                let memory_codes = self.memory_codes.clone();
+                std::iter::repeat_with(move || {
+                    SignalChannel::new(*signal)
+                        .center_frequency(center)
+                        .memory_codes(memory_codes.clone())
+                })
+                .take(MAX_CHAN)
+            })
+            .collect();
         // Initial reception time
         self.receiver_gps_time = self.receiver_gps_time.add_secs(0.0);
         // Allocate visible satellites
-        self.allocate_channel(self.positions[0]);
+        self.allocate_channel(
+            self.positions[0],
+            self.velocities.first().copied().unwrap_or_default(),
+        );
         Self::print_channel_status(&self.channels);
+        Self::print_signal_channel_status(&self.signal_channels);
 
         ////////////////////////////////////////////////////////////
         // Receiver antenna gain pattern
@@ -170,7 +231,7 @@ impl SignalGenerator {
         }
 
         self.iq_buffer_size =
-            (self.sample_frequency * self.sample_rate).floor() as usize;
+            (self.sample_frequency * self.update_interval).round() as usize;
         self.writer = match &self.output_file {
             Some(file) => Some(IQWriter::new(
                 file,
@@ -179,6 +240,36 @@ impl SignalGenerator {
             )?),
             None => None,
         };
+        // Every further band gets its own file next to the primary output
+        let primary_band = self.primary_band();
+        self.band_writers.clear();
+        if let Some(file) = &self.output_file {
+            let mut bands: Vec<Band> = Vec::new();
+            for band in self.signals.iter().map(|signal| signal.band()) {
+                if band != primary_band && !bands.contains(&band) {
+                    bands.push(band);
+                }
+            }
+            for band in bands {
+                let writer = IQWriter::new(
+                    &band_output_file(file, band),
+                    self.data_format,
+                    self.iq_buffer_size,
+                )?;
+                self.band_writers.push((band, writer));
+            }
+        }
+        // Accumulator slot of each band: the primary band first, followed by
+        // the additional band writers
+        let mut bands = vec![primary_band];
+        bands.extend(self.band_writers.iter().map(|(band, _)| *band));
+        let slot = |band: Band| bands.iter().position(|b| *b == band);
+        self.legacy_slot = slot(Band::L1);
+        self.band_slots = self
+            .signal_channels
+            .iter()
+            .map(|ch| slot(ch.signal().band()).unwrap_or(0))
+            .collect();
         self.initialized = true;
         Ok(())
     }
@@ -192,65 +283,147 @@ impl SignalGenerator {
     ///
     /// # Arguments
     /// * `xyz` - The current receiver position in ECEF coordinates
+    /// * `velocity` - The current receiver velocity in ECEF coordinates
     ///
     /// # Returns
     /// * The number of visible satellites
-    pub fn allocate_channel(&mut self, xyz: Ecef) -> i32 {
+    pub fn allocate_channel(&mut self, xyz: Ecef, velocity: Ecef) -> i32 {
         let mut visible_satellite_count: i32 = 0;
-        // let ref_0: [f64; 3] = [0., 0., 0.];
-        // #[allow(unused_variables)]
-        // let mut r_ref: f64 = 0.;
-        // #[allow(unused_variables)]
-        // let mut r_xyz: f64;
-        for (sv, eph) in self.ephemerides[self.valid_ephemerides_index]
-            .iter()
-            .enumerate()
-            .take(MAX_SAT)
-        {
-            if let Some((azel, true)) = eph.check_visibility(
-                &self.receiver_gps_time,
-                &xyz,
-                self.elevation_mask,
-            ) {
-                visible_satellite_count += 1; // Number of visible satellites
-                if self.allocated_satellite[sv] == -1 {
-                    // Visible but not allocated
-                    //
-                    // Allocated new satellite
-                    let mut channel_index = 0;
-                    for (i, ichan) in
-                        self.channels.iter_mut().take(MAX_CHAN).enumerate()
-                    {
-                        if ichan.prn == 0 {
-                            // Initialize channel
-                            ichan.update_for_satellite(
-                                sv + 1,
+        // GPS and QZSS L1 C/A share the channels, GPS satellites first
+        for signal in [Signal::GpsL1Ca, Signal::QzssL1Ca] {
+            let enabled = self.signals.contains(&signal);
+            let constellation = signal.constellation();
+            for prn in constellation.prns() {
+                let satellite = SatelliteId::new(constellation, prn);
+                let allocated = self
+                    .channels
+                    .iter()
+                    .position(|ch| ch.satellite == Some(satellite));
+                let eph = select_ephemeris(
+                    &self.satellite_ephemerides,
+                    &self.gps_ephemeris_indices,
+                    satellite,
+                    &self.receiver_gps_time,
+                    self.handover,
+                );
+                let visibility = eph.and_then(|eph| {
+                    eph.check_visibility(
+                        &self.receiver_gps_time,
+                        &xyz,
+                        self.elevation_mask,
+                    )
+                });
+                match (visibility, eph) {
+                    (Some((azel, true)), Some(eph)) => {
+                        visible_satellite_count += 1;
+                        if !enabled || allocated.is_some() {
+                            continue;
+                        }
+                        // Visible but not allocated: take the first free
+                        // channel, if any
+                        if let Some(ch) = self
+                            .channels
+                            .iter_mut()
+                            .find(|ch| ch.satellite.is_none())
+                        {
+                            ch.update_for_satellite(
+                                satellite,
                                 eph,
                                 &self.ionoutc,
                                 &self.receiver_gps_time,
                                 &xyz,
+                                &velocity,
                                 azel,
                             );
-                            break;
                         }
-                        channel_index = i + 1;
                     }
-                    // Set satellite allocation channel
-                    if channel_index < MAX_CHAN {
-                        self.allocated_satellite[sv] = channel_index as i32;
+                    _ => {
+                        // Not visible but allocated: clear the channel
+                        if let Some(i) = allocated {
+                            self.channels[i].satellite = None;
+                        }
                     }
                 }
-            } else if self.allocated_satellite[sv] >= 0 {
-                // Not visible but allocated
-                // Clear channel
-                self.channels[self.allocated_satellite[sv] as usize].prn = 0;
-                // Clear satellite allocation flag
-                self.allocated_satellite[sv] = -1;
             }
         }
+        self.allocate_signal_channels(xyz, velocity);
         visible_satellite_count
     }
 
+    /// Allocates the channels of signals other than GPS L1 C/A.
+    ///
+    /// Every signal has its own bank of channels. A visible satellite without
+    /// a channel in a bank is assigned the first free channel of that bank,
+    /// and channels of satellites that are no longer visible, or without a
+    /// current ephemeris, are released.
+    ///
+    /// # Arguments
+    /// * `xyz` - The current receiver position in ECEF coordinates
+    /// * `velocity` - The current receiver velocity in ECEF coordinates
+    fn allocate_signal_channels(&mut self, xyz: Ecef, velocity: Ecef) {
+        for bank in self.signal_channels.chunks_mut(MAX_CHAN) {
+            let signal = bank[0].signal();
+            let constellation = signal.constellation();
+            for prn in constellation.prns() {
+                let allocated = bank.iter().position(|ch| ch.prn == prn);
+                let eph = select_ephemeris(
+                    &self.satellite_ephemerides,
+                    &self.gps_ephemeris_indices,
+                    SatelliteId::new(constellation, prn),
+                    &self.receiver_gps_time,
+                    self.handover,
+                );
+                match eph.and_then(|eph| {
+                    eph.check_visibility(
+                        &self.receiver_gps_time,
+                        &xyz,
+                        self.elevation_mask,
+                    )
+                }) {
+                    Some((azel, true)) if allocated.is_none() => {
+                        if let (Some(ch), Some(eph)) =
+                            (bank.iter_mut().find(|ch| ch.prn == 0), eph)
+                        {
+                            ch.update_for_satellite(
+                                prn,
+                                eph,
+                                &self.ionoutc,
+                                &self.receiver_gps_time,
+                                &xyz,
+                                &velocity,
+                                azel,
+                            );
+                        }
+                    }
+                    Some((_, true)) => {}
+                    _ => {
+                        if let Some(i) = allocated {
+                            bank[i].prn = 0;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the frequency the I/Q output of a band is mixed down from.
+    ///
+    /// This is the nominal centre of the band, except for the GLONASS G1
+    /// band whose centre can be configured.
+    fn center_frequency(&self, band: Band) -> f64 {
+        match band {
+            Band::G1 => self.glonass_center_frequency,
+            band => band.center_frequency(),
+        }
+    }
+
+    /// Returns the band written to the primary output file.
+    fn primary_band(&self) -> Band {
+        self.signals
+            .first()
+            .map_or(Band::L1, |signal| signal.band())
+    }
+
     /// Generates I/Q samples for all active channels and writes them to the
     /// output file.
     ///
@@ -269,40 +442,83 @@ impl SignalGenerator {
     #[inline]
     fn generate_and_write_samples(&mut self) -> Result<(), Error> {
         let sampling_period = self.sample_frequency.recip();
+        let legacy_slot = self.legacy_slot;
+        let mut acc = vec![(0i32, 0i32); 1 + self.band_writers.len()];
         let writer = self
             .writer
             .as_mut()
             .ok_or_else(|| Error::msg("IQWriter not initialized"))?;
         let buffer_size = writer.buffer_size;
+        // With range rates, the Doppler sweeps through the step and is
+        // updated every millisecond, at the middle of each interval
+        let sweep_interval = (!self.velocities.is_empty())
+            .then(|| ((self.sample_frequency * 1e-3).round() as usize).max(1));
         for isamp in 0..buffer_size {
-            let mut i_acc: i32 = 0;
-            let mut q_acc: i32 = 0;
+            if let Some(interval) = sweep_interval
+                && isamp % interval == 0
+            {
+                let elapsed =
+                    (isamp as f64 + 0.5 * interval as f64) * sampling_period;
+                for ch in &mut self.channels {
+                    if ch.satellite.is_some() {
+                        ch.sweep_doppler(elapsed, sampling_period);
+                    }
+                }
+                for ch in &mut self.signal_channels {
+                    if ch.prn != 0 {
+                        ch.sweep_doppler(elapsed, sampling_period);
+                    }
+                }
+            }
+            acc.fill((0, 0));
             // Step 1: Accumulate signal components from all channels
             for i in 0..MAX_CHAN {
-                if self.channels[i].prn != 0 {
+                if self.channels[i].satellite.is_some() {
                     let (ip, qp) = self.channels[i]
                         .generate_iq_contribution(self.antenna_gains[i]);
                     // Accumulate for all visible satellites
                     // Add to total signal accumulation
-                    i_acc += ip;
-                    q_acc += qp;
+                    if let Some(slot) = legacy_slot {
+                        acc[slot].0 += ip;
+                        acc[slot].1 += qp;
+                    }
                     // Update code phase
                     // Update code phase (C/A code sequence control)
                     self.channels[i].update_navigation_bits(sampling_period);
                 }
             }
+            for (ch, &slot) in
+                self.signal_channels.iter_mut().zip(&self.band_slots)
+            {
+                if ch.prn != 0 {
+                    let (ip, qp) = ch.generate_iq_contribution();
+                    acc[slot].0 += ip;
+                    acc[slot].1 += qp;
+                    ch.advance(sampling_period);
+                }
+            }
 
             // Step 2: Quantize and store I/Q samples
             // Scaled by 2^7
             // i_acc = (i_acc + 64) >> 7;
             // q_acc = (q_acc + 64) >> 7;
             // Store I/Q samples into buffer
+            let (i_acc, q_acc) = acc[0];
             writer.buffer[isamp * 2] = ((i_acc + 64) >> 7) as i16; // 8-bit quantization (with rounding)
             writer.buffer[isamp * 2 + 1] = ((q_acc + 64) >> 7) as i16;
+            for ((_, band_writer), (i_acc, q_acc)) in
+                self.band_writers.iter_mut().zip(&acc[1..])
+            {
+                band_writer.buffer[isamp * 2] = ((i_acc + 64) >> 7) as i16;
+                band_writer.buffer[isamp * 2 + 1] = ((q_acc + 64) >> 7) as i16;
+            }
         }
 
         // Step 3: Write I/Q data to output file (handling different formats)
         writer.write_samples()?;
+        for (_, band_writer) in &mut self.band_writers {
+            band_writer.write_samples()?;
+        }
         Ok(())
     }
 
@@ -312,7 +528,8 @@ impl SignalGenerator {
     /// This method calculates the current signal parameters for each active
     /// satellite channel:
     /// - Computes the current pseudorange (distance) to each satellite
-    /// - Updates the code and carrier phase based on the pseudorange change
+    /// - Updates the code and carrier phase based on the pseudorange change,
+    ///   or on the range rates when the receiver velocities are known
     /// - Calculates the signal gain based on path loss and antenna pattern
     ///
     /// The gain calculation depends on whether fixed gain mode is enabled:
@@ -321,15 +538,25 @@ impl SignalGenerator {
     ///
     /// # Arguments
     /// * `current_location` - The current receiver position in ECEF coordinates
-    fn update_channel_parameters(&mut self, current_location: Ecef) {
-        let ephemeris_set_index = self.valid_ephemerides_index;
+    /// * `current_velocity` - The current receiver velocity in ECEF coordinates
+    fn update_channel_parameters(
+        &mut self, current_location: Ecef, current_velocity: Ecef,
+    ) {
         let sampling_period = self.sample_frequency.recip();
+        let range_rate = !self.velocities.is_empty();
         for i in 0..MAX_CHAN {
             // Only process channels with assigned satellites
-            if self.channels[i].prn != 0 {
-                // Convert satellite PRN to array index
-                let sv = self.channels[i].prn - 1;
-                let eph = &self.ephemerides[ephemeris_set_index][sv];
+            if let Some(eph) =
+                self.channels[i].satellite.and_then(|satellite| {
+                    select_ephemeris(
+                        &self.satellite_ephemerides,
+                        &self.gps_ephemeris_indices,
+                        satellite,
+                        &self.receiver_gps_time,
+                        self.handover,
+                    )
+                })
+            {
                 // Calculate current pseudorange (propagation delay)
                 // Refresh code phase and data bit counters
 
@@ -339,43 +566,133 @@ impl SignalGenerator {
                     &self.ionoutc,
                     &self.receiver_gps_time,
                     &current_location,
+                    &current_velocity,
                 );
-                self.channels[i].update_state(
-                    &rho,
-                    self.sample_rate,
-                    sampling_period,
-                );
+                if range_rate {
+                    self.channels[i].update_state_with_rate(
+                        &rho,
+                        self.update_interval,
+                        sampling_period,
+                    );
+                } else {
+                    self.channels[i].update_state(
+                        &rho,
+                        self.update_interval,
+                        sampling_period,
+                    );
+                }
 
                 // Calculate signal gain (considering path loss and antenna
                 // pattern) Signal gain
-                // Apply gain mode selection
-                let gain = if let Some(fixed_gain) = self.fixed_gain {
-                    // Fixed gain mode
-                    fixed_gain // hold the power level constant
-                } else {
-                    // With path loss compensation
-                    // Path loss
-                    let path_loss = 20_200_000.0 / rho.distance;
-                    // Receiver antenna gain
-                    let boresight_angle_index =
-                        ((90.0 - rho.azel.el * R2D) / 5.0) as usize; // covert elevation to boresight
-                    let ant_gain = self.antenna_pattern[boresight_angle_index];
-                    (path_loss * ant_gain * 128.0) as i32 // scaled by 2^7
-                };
                 // Store gain for IQ generation phase
-                self.antenna_gains[i] = gain; // hold the power level constant
+                self.antenna_gains[i] = self.signal_gain(&rho);
+            }
+        }
+        for i in 0..self.signal_channels.len() {
+            if let Some(satellite) = self.signal_channels[i].satellite() {
+                let Some(eph) = select_ephemeris(
+                    &self.satellite_ephemerides,
+                    &self.gps_ephemeris_indices,
+                    satellite,
+                    &self.receiver_gps_time,
+                    self.handover,
+                ) else {
+                    continue;
+                };
+                let rho = compute_range(
+                    eph,
+                    &self.ionoutc,
+                    &self.receiver_gps_time,
+                    &current_location,
+                    &current_velocity,
+                );
+                let gain = self.signal_gain(&rho);
+                let ch = &mut self.signal_channels[i];
+                if range_rate {
+                    ch.update_state_with_rate(
+                        &rho,
+                        self.update_interval,
+                        sampling_period,
+                    );
+                } else {
+                    ch.update_state(
+                        &rho,
+                        self.update_interval,
+                        sampling_period,
+                    );
+                }
+                ch.gain = gain;
             }
         }
     }
 
+    /// Computes the signal gain of a satellite from its range and elevation.
+    ///
+    /// # Arguments
+    /// * `rho` - Current range information of the satellite
+    ///
+    /// # Returns
+    /// The gain scaled by 2^7, or the fixed gain if path loss is disabled
+    fn signal_gain(&self, rho: &TimeRange) -> i32 {
+        // Apply gain mode selection
+        if let Some(fixed_gain) = self.fixed_gain {
+            // Fixed gain mode
+            fixed_gain // hold the power level constant
+        } else {
+            // With path loss compensation
+            // Path loss
+            let path_loss = 20_200_000.0 / rho.distance;
+            // Receiver antenna gain
+            let boresight_angle_index =
+                ((90.0 - rho.azel.el * R2D) / 5.0) as usize; // covert elevation to boresight
+            let ant_gain = self.antenna_pattern[boresight_angle_index];
+            (path_loss * ant_gain * 128.0) as i32 // scaled by 2^7
+        }
+    }
+
+    /// Broadcasts the new leap seconds once their event is past.
+    ///
+    /// The subframes of the GPS and QZSS channels are regenerated on the
+    /// transition only: once `dtls` takes the value of `dtlsf`, no event is
+    /// pending and later calls do nothing.
+    ///
+    /// # Returns
+    /// Whether the new leap seconds were applied
+    pub(crate) fn apply_leap_second(&mut self) -> bool {
+        if self.ionoutc.dtls == self.ionoutc.dtlsf
+            || self.ionoutc.leap_second_event().is_none_or(|event| {
+                self.receiver_gps_time.diff_secs(&event) < 0.0
+            })
+        {
+            return false;
+        }
+        self.ionoutc.dtls = self.ionoutc.dtlsf;
+        for ichan in &mut self.channels {
+            if let Some(eph) = ichan.satellite.and_then(|satellite| {
+                select_ephemeris(
+                    &self.satellite_ephemerides,
+                    &self.gps_ephemeris_indices,
+                    satellite,
+                    &self.receiver_gps_time,
+                    self.handover,
+                )
+            }) {
+                ichan.generate_navigation_subframes(eph, &self.ionoutc);
+            }
+        }
+        true
+    }
+
     /// Handles periodic tasks that occur at regular intervals during
     /// simulation.
     ///
     /// This method performs tasks that need to happen periodically (every 30
-    /// seconds):
+    /// seconds, at the first step from the start of each frame):
+    /// - Broadcasts the future leap seconds once their event is past
     /// - Updates the navigation message for all active channels
-    /// - Refreshes the ephemeris data set if a newer one is available
-    /// - Updates the navigation subframes if the ephemeris set changed
+    /// - Selects the best ephemeris of every GPS satellite
+    /// - Updates the navigation subframes of the satellites whose ephemeris
+    ///   changed
     /// - Reallocates satellite channels based on current visibility
     ///
     /// These periodic updates ensure that the simulation accurately reflects
@@ -383,80 +700,91 @@ impl SignalGenerator {
     ///
     /// # Arguments
     /// * `current_location` - The current receiver position in ECEF coordinates
-    fn handle_periodic_tasks(&mut self, current_location: Ecef) {
-        let current_step_index =
-            (self.receiver_gps_time.sec * 10.0 + 0.5) as i32;
-        if current_step_index % 300 == 0 {
+    /// * `current_velocity` - The current receiver velocity in ECEF coordinates
+    pub(crate) fn handle_periodic_tasks(
+        &mut self, current_location: Ecef, current_velocity: Ecef,
+    ) {
+        // The step reaching a frame boundary, so that start times off the
+        // grid of steps keep their navigation frames every 30 seconds
+        let frame =
+            |sec: f64| ((sec * 1e9).round() as i64).div_euclid(30_000_000_000);
+        let time = self.receiver_gps_time.sec;
+        if frame(time) != frame(time - self.update_interval) {
             // Every 30 seconds
-            // 1. Update Nav Msg for active channels
-            for ichan in self.channels.iter_mut().take(MAX_CHAN) {
-                if ichan.prn != 0 {
-                    ichan.generate_nav_msg(&self.receiver_gps_time, false);
+            // 0. Broadcast the new leap seconds once their event is past
+            self.apply_leap_second();
+            // 1. Select the current ephemeris of every GPS satellite, and
+            // update the subframes of the channels whose ephemeris changed
+            for prn in Constellation::Gps.prns() {
+                let index = self.satellite_ephemerides.current(
+                    Constellation::Gps,
+                    prn,
+                    &self.receiver_gps_time,
+                    self.handover,
+                );
+                if index == self.gps_ephemeris_indices[prn - 1] {
+                    continue;
                 }
-            }
-            // 2. Refresh ephemeris index and subframes if necessary
-            // Refresh ephemeris and subframes
-            // Quick and dirty fix. Need more elegant way.
-            let mut refreshed_eph = false;
-            let next_ephemeris_set_index = self.valid_ephemerides_index + 1;
-            // Check if next ephemeris set is valid and timely
-            if next_ephemeris_set_index < self.ephemerides.len()
-                && self.ephemerides[next_ephemeris_set_index]
-                    .iter()
-                    .take(MAX_SAT)
-                    .any(|eph| eph.vflg)
-            {
-                // Find the earliest ToC in the next set (or check a specific
-                // SV) Simplified check: Assume SV 0's ToC is
-                // representative
-                if self.ephemerides[next_ephemeris_set_index][0].vflg {
-                    let dt = self.ephemerides[next_ephemeris_set_index][0]
-                        .toc
-                        .diff_secs(&self.receiver_gps_time);
-                    // If the next ephemeris is close (e.g., within an hour),
-                    // switch to it
-                    if dt.abs() < SECONDS_IN_HOUR {
-                        // Use absolute diff
-                        self.valid_ephemerides_index = next_ephemeris_set_index;
-                        refreshed_eph = true;
-                        eprintln!(
-                            "\nSwitched to ephemeris set index \
-                             {next_ephemeris_set_index}"
-                        );
-                    }
+                self.gps_ephemeris_indices[prn - 1] = index;
+                let satellite = SatelliteId::new(Constellation::Gps, prn);
+                if let (Some(ichan), Some(index)) = (
+                    self.channels
+                        .iter_mut()
+                        .find(|ch| ch.satellite == Some(satellite)),
+                    index,
+                ) {
+                    let eph = &self
+                        .satellite_ephemerides
+                        .get(Constellation::Gps, prn)[index];
+                    ichan.generate_navigation_subframes(eph, &self.ionoutc);
                 }
             }
-
-            // If ephemeris refreshed, update subframes for active channels
-            if refreshed_eph {
-                let current_ephemeris_set_index = self.valid_ephemerides_index;
-                for ichan in self
-                    .channels
-                    .iter_mut()
-                    .take(MAX_CHAN)
-                    .filter(|ch| ch.prn != 0)
+            // 2. Update Nav Msg for active channels, with the subframes of the
+            // data sets in use from this frame on
+            for ichan in self.channels.iter_mut().take(MAX_CHAN) {
+                let Some(satellite) = ichan.satellite else {
+                    continue;
+                };
+                // QZSS data sets are selected per refresh interval, so the
+                // subframes follow the ephemeris used for the range
+                if satellite.constellation == Constellation::Qzss
+                    && let Some(eph) = select_ephemeris(
+                        &self.satellite_ephemerides,
+                        &self.gps_ephemeris_indices,
+                        satellite,
+                        &self.receiver_gps_time,
+                        self.handover,
+                    )
                 {
-                    let sv = ichan.prn - 1;
-                    ichan.generate_navigation_subframes(
-                        &self.ephemerides[current_ephemeris_set_index][sv],
+                    ichan.generate_navigation_subframes(eph, &self.ionoutc);
+                }
+                ichan.generate_nav_msg(&self.receiver_gps_time, false);
+            }
+            // Refresh the navigation symbols of the other signals
+            for ch in &mut self.signal_channels {
+                if let Some(eph) = ch.satellite().and_then(|satellite| {
+                    select_ephemeris(
+                        &self.satellite_ephemerides,
+                        &self.gps_ephemeris_indices,
+                        satellite,
+                        &self.receiver_gps_time,
+                        self.handover,
+                    )
+                }) {
+                    ch.refresh_symbols(
+                        eph,
                         &self.ionoutc,
+                        &self.receiver_gps_time,
                     );
-                    // self.ephemerides[current_ephemeris_set_index][sv]
-                    //     .generate_navigation_subframes(
-                    //         &self.ionoutc,
-                    //         &mut ichan.sbf,
-                    //     );
-                    // Maybe need to regenerate nav message bits immediately?
-                    // ichan.generate_nav_msg(&self.receiver_gps_time, false);
-                    // // Already done above, maybe redundant
                 }
             }
             // Update channel allocation
-            self.allocate_channel(current_location);
+            self.allocate_channel(current_location, current_velocity);
 
             // Show details about simulated channels
             if self.verbose {
                 Self::print_channel_status(&self.channels);
+                Self::print_signal_channel_status(&self.signal_channels);
             }
         }
     }
@@ -500,38 +828,65 @@ impl SignalGenerator {
         eprintln!("Starting signal generation for {num_steps} steps...");
         // Generate baseband signals
         self.receiver_gps_time =
-            self.receiver_gps_time.add_secs(self.sample_rate);
+            self.receiver_gps_time.add_secs(self.update_interval);
         let time_start = std::time::Instant::now();
-        // Main loop: Iterate through each time interval (0.1 seconds)
+        // Progress is shown every tenth of a second into the run
+        let update_interval = self.update_interval;
+        let tenths = |steps: usize| {
+            (steps as f64 * update_interval * 10.0 + 1e-6) as u64
+        };
+        // Main loop: Iterate through each time interval (`update_interval`)
         // From 1..num_steps, because step 0 was done in initiallize.
         for step_index in 1..num_steps {
             // Select receiver position based on static/dynamic mode
-            let current_location = match self.mode {
-                MotionMode::Static => self.positions[0],
-                MotionMode::Dynamic => self
-                    .positions
-                    .get(step_index)
-                    .copied()
-                    .unwrap_or(self.positions[0]),
+            let (current_location, current_velocity) = match self.mode {
+                MotionMode::Static => (self.positions[0], Ecef::default()),
+                MotionMode::Dynamic => (
+                    self.positions
+                        .get(step_index)
+                        .copied()
+                        .unwrap_or(self.positions[0]),
+                    self.velocities
+                        .get(step_index)
+                        .copied()
+                        .unwrap_or_default(),
+                ),
             };
             // Step 1: Update satellite parameters (pseudorange, phase, and
             // gain)
-            self.update_channel_parameters(current_location);
+            self.update_channel_parameters(current_location, current_velocity);
 
             // Step 2: Generate baseband I/Q sample data
             self.generate_and_write_samples()?;
             // Update navigation message and channel allocation every 30 seconds
             // Step 3: Periodically update navigation data (every 30 seconds)
-            self.handle_periodic_tasks(current_location);
+            self.handle_periodic_tasks(current_location, current_velocity);
 
             // Step 4: Update simulation time and display progress
             // Update receiver time
             self.receiver_gps_time =
-                self.receiver_gps_time.add_secs(self.sample_rate);
-            eprint!(
-                "\rTime into run = {:4.1}\0",
-                (step_index + 1) as f64 / 10.0
-            );
+                self.receiver_gps_time.add_secs(self.update_interval);
+            if tenths(step_index + 1) != tenths(step_index) {
+                if self.verbose {
+                    let utc = self.ionoutc.utc(&self.receiver_gps_time);
+                    eprint!(
+                        "\rTime into run = {:4.1} (UTC \
+                         {:4}/{:02}/{:02},{:02}:{:02}:{:04.1})\0",
+                        (step_index + 1) as f64 * self.update_interval,
+                        utc.y,
+                        utc.m,
+                        utc.d,
+                        utc.hh,
+                        utc.mm,
+                        utc.sec,
+                    );
+                } else {
+                    eprint!(
+                        "\rTime into run = {:4.1}\0",
+                        (step_index + 1) as f64 * self.update_interval
+                    );
+                }
+            }
         }
 
         eprintln!("\nDone!");
@@ -558,10 +913,12 @@ impl SignalGenerator {
     /// * `channels` - Array of satellite channels
     fn print_channel_status(channels: &[Channel; MAX_CHAN]) {
         eprintln!("PRN Az(deg) El(deg)  Range(m) Iono(m)");
-        for ichan in channels.iter().filter(|ch| ch.prn != 0) {
+        for ichan in channels {
+            let Some(satellite) = ichan.satellite else {
+                continue;
+            };
             eprintln!(
-                "{:02} {:6.1} {:5.1} {:11.1} {:5.1}",
-                ichan.prn,
+                "{satellite} {:6.1} {:5.1} {:11.1} {:5.1}",
                 ichan.azel().az * R2D,
                 ichan.azel().el * R2D,
                 ichan.rho0().distance, /* Using rho0 which is updated in
@@ -570,4 +927,95 @@ impl SignalGenerator {
             );
         }
     }
+
+    /// Prints status information about active channels of signals other than
+    /// GPS L1 C/A.
+    ///
+    /// The table has the same columns as `print_channel_status`, prefixed with
+    /// the signal name. Nothing is printed when no such signal is enabled.
+    ///
+    /// # Arguments
+    /// * `channels` - Channels of the additional signals
+    fn print_signal_channel_status(channels: &[SignalChannel]) {
+        if channels.is_empty() {
+            return;
+        }
+        eprintln!("Signal PRN Az(deg) El(deg)  Range(m) Iono(m)");
+        for ichan in channels.iter().filter(|ch| ch.prn != 0) {
+            eprintln!(
+                "{:<6} {:02} {:6.1} {:5.1} {:11.1} {:5.1}",
+                ichan.signal().to_string(),
+                ichan.prn,
+                ichan.azel().az * R2D,
+                ichan.azel().el * R2D,
+                ichan.rho0().distance,
+                ichan.rho0().iono_delay,
+            );
+        }
+    }
+}
+
+/// Selects the ephemeris used by a channel of a satellite.
+///
+/// GPS satellites use their current ephemeris, selected at every navigation
+/// refresh. Galileo, BeiDou, GLONASS and QZSS satellites use the data set
+/// current at the start of the 30-second navigation refresh interval, so
+/// that the broadcast message and the simulated orbit stay consistent
+/// between refreshes. SBAS GEO satellites have a single data set, used for
+/// the whole scenario.
+///
+/// # Arguments
+/// * `satellites` - Ephemerides of all constellations
+/// * `gps_indices` - Index of the current ephemeris of each GPS PRN
+/// * `satellite` - Satellite of the channel
+/// * `time` - Current receiver time
+/// * `handover` - Rule deciding when a new ephemeris becomes current
+///
+/// # Returns
+/// The ephemeris, or `None` if no valid ephemeris is available
+fn select_ephemeris<'a>(
+    satellites: &'a SatelliteEphemerides, gps_indices: &[Option<usize>],
+    satellite: SatelliteId, time: &GpsTime, handover: Handover,
+) -> Option<&'a Ephemeris> {
+    let SatelliteId { constellation, prn } = satellite;
+    let list = satellites.get(constellation, prn);
+    match constellation {
+        Constellation::Gps => {
+            list.get((*gps_indices.get(prn.checked_sub(1)?)?)?)
+        }
+        Constellation::Sbas => list.first(),
+        constellation => {
+            let refresh = GpsTime {
+                week: time.week,
+                sec: (time.sec / 30.0).floor() * 30.0,
+            };
+            list.get(satellites.current(
+                constellation,
+                prn,
+                &refresh,
+                handover,
+            )?)
+        }
+    }
+}
+
+/// Derives the output file of an additional band from the primary output
+/// file by appending the band name to the file stem, e.g. `gpssim.bin`
+/// becomes `gpssim_L2.bin`.
+///
+/// # Arguments
+/// * `file` - Path of the primary output file
+/// * `band` - Band written to the derived file
+fn band_output_file(file: &Path, band: Band) -> PathBuf {
+    let stem = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match file.extension() {
+        Some(ext) => {
+            format!("{stem}_{}.{}", band.name(), ext.to_string_lossy())
+        }
+        None => format!("{stem}_{}", band.name()),
+    };
+    file.with_file_name(name)
 }
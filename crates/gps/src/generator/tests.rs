@@ -0,0 +1,1278 @@
+use std::{path::Path, sync::Arc};
+
+use geometry::{Ecef, LocationMath};
+use parsing::{Interpolation, read_user_motion};
+use rinex::almanac::{Almanac, AlmanacEntry};
+
+use constants::{LAMBDA_L1, OMEGA_EARTH, PI, POW2_M19, POW2_M31, POW2_M43};
+
+use super::{
+    almanac::{almanac_ephemerides, almanac_ephemeris, propagate_secular},
+    constellation::nominal_constellation,
+    sbas::{parse_ems, parse_sbas_config},
+    utils::{Handover, ReEpoch, SatelliteEphemerides, add_glonass_ephemeris},
+};
+use crate::{
+    Error, SignalGenerator, SignalGeneratorBuilder,
+    channel::Channel,
+    datetime::{DateTime, GpsTime, full_week},
+    ephemeris::Ephemeris,
+    ionoutc::IonoUtc,
+    orbit::{Motion, StateVector},
+    precise::PreciseOrbit,
+    propagation::compute_range,
+    signal::{Constellation, Message, MessageStream, SatelliteId},
+    timescale::TimeScale,
+};
+
+/// Formats message bits as hexadecimal digits, padding the last digit.
+fn hex(bits: &[u8]) -> String {
+    bits.chunks(4)
+        .map(|chunk| {
+            let value = (0..4).fold(0, |acc, i| {
+                acc << 1 | u32::from(chunk.get(i).copied().unwrap_or(0))
+            });
+            char::from_digit(value, 16)
+                .unwrap_or('0')
+                .to_ascii_uppercase()
+        })
+        .collect()
+}
+
+#[test]
+fn test_sbas_config() {
+    let config = "\
+# two GEOs
+prn 123
+position 35876000, 21800000, 0 # EGNOS
+message 9 120 1
+message 2 6 2 0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF01234
+alarm 300 10
+
+PRN 137
+ephemeris 3600 -32300262 27103302 0 0.5 -0.25 1 0.001 0 0 1e-7 1e-12
+";
+    let satellites = match parse_sbas_config(config, Path::new("")) {
+        Ok(satellites) => satellites,
+        Err(e) => panic!("valid configuration rejected: {e}"),
+    };
+    assert_eq!(satellites.len(), 2);
+
+    let egnos = &satellites[0];
+    assert_eq!(egnos.prn, 123);
+    assert_eq!(egnos.t0, None);
+    assert_eq!(egnos.state.motion, Motion::Polynomial);
+    assert!((egnos.state.pos[0] - 35_876_000.0).abs() < 1e-6);
+    assert_eq!(egnos.messages.alarms, vec![(300, 10)]);
+    let schedule = &egnos.messages.schedule;
+    assert_eq!(schedule.len(), 2);
+    assert_eq!(schedule[0].message, Message::derived(9));
+    assert_eq!((schedule[0].interval, schedule[0].offset), (120, 1));
+    let data = schedule[1].message.data.clone().unwrap_or_default();
+    assert_eq!(data.len(), 212);
+    assert_eq!(data[..8], [0, 0, 0, 0, 0, 0, 0, 1]);
+
+    let geo = &satellites[1];
+    assert_eq!(geo.prn, 137);
+    assert_eq!(geo.t0, Some(3600.0));
+    assert!((geo.state.vel[2] - 1.0).abs() < 1e-12);
+    assert!((geo.state.acc[0] - 0.001).abs() < 1e-12);
+    assert!((geo.af0 - 1e-7).abs() < 1e-20);
+    assert!((geo.af1 - 1e-12).abs() < 1e-24);
+}
+
+#[test]
+fn test_sbas_config_errors() {
+    let position = "position 35876000 21800000 0";
+    for config in [
+        "position 35876000 21800000 0",
+        "prn 119\nposition 35876000 21800000 0",
+        &format!("prn 120\n{position}\nprn 120\n{position}"),
+        "prn 120",
+        "prn 120\nposition 1000 0 0",
+        &format!("prn 120\n{position}\nmessage 64 1 0"),
+        &format!("prn 120\n{position}\nmessage 9 0 0"),
+        &format!("prn 120\n{position}\nmessage 2 6 0 0123"),
+        &format!("prn 120\n{position}\nalarm 10"),
+        &format!("prn 120\n{position}\nephemeris 0 1 2 3"),
+        &format!("prn 120\n{position}\norbit 1 2 3"),
+    ] {
+        assert!(
+            parse_sbas_config(config, Path::new("")).is_err(),
+            "accepted: {config}"
+        );
+    }
+}
+
+#[test]
+fn test_sbas_ems() {
+    let eph = Ephemeris::default();
+    let time = GpsTime::default();
+    let line = |prn: usize, sec: u32, kind: u8, flip: Option<usize>| {
+        let mut bits =
+            Message::derived(kind).encode(&eph, &StateVector::default(), &time);
+        if let Some(i) = flip {
+            bits[i] ^= 1;
+        }
+        format!("{prn} 22 01 01 00 00 {sec:02}.000 {kind} {}", hex(&bits))
+    };
+    let text = [
+        line(124, 12, 2, None),
+        line(123, 10, 1, None),
+        line(124, 14, 3, Some(100)),
+        line(124, 15, 4, None),
+        line(124, 10, 1, None),
+    ]
+    .join("\n");
+    let recorded = match parse_ems(&text, 124) {
+        Ok(recorded) => recorded,
+        Err(e) => panic!("valid messages rejected: {e}"),
+    };
+    let kinds: Vec<Option<u8>> = recorded
+        .iter()
+        .map(|message| message.as_ref().map(|m| m.kind))
+        .collect();
+    assert_eq!(kinds, [Some(1), None, Some(2), None, None, Some(4)]);
+
+    // Recorded messages are broadcast from the start of the scenario
+    let stream = MessageStream {
+        recorded,
+        ..Default::default()
+    };
+    assert_eq!(stream.message(2).kind, 2);
+    assert_eq!(stream.message(3).kind, 63);
+
+    assert!(parse_ems("124 22 01 01 00 00 10.000 1", 124).is_err());
+    assert!(
+        parse_ems(&line(124, 10, 1, None).replace("22 01", "22 xx"), 124)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_precise_orbit() {
+    // Circular orbit sampled every 15 minutes for six hours, with a clock
+    // drifting by 1 ns/s
+    let radius = 26_560e3;
+    let rate = std::f64::consts::TAU / 43_082.0;
+    let orbit = |t: f64| {
+        let (sin, cos) = (rate * t).sin_cos();
+        (
+            [radius * cos, radius * sin, 0.0],
+            [-radius * rate * sin, radius * rate * cos, 0.0],
+        )
+    };
+    let start = GpsTime {
+        week: 2190,
+        sec: 518_400.0,
+    };
+    let samples = (0..25)
+        .map(|i| {
+            let t = f64::from(i) * 900.0;
+            let clock = (i != 20).then_some(1e-4 + 1e-9 * t);
+            (start.add_secs(t), orbit(t).0, clock)
+        })
+        .collect();
+    let Some(precise) = PreciseOrbit::new(samples) else {
+        panic!("orbit without samples");
+    };
+    let t = 3.0 * 3600.0 + 450.0;
+    let Some((pos, vel, clock)) = precise.state(&start.add_secs(t)) else {
+        panic!("no state inside the samples");
+    };
+    let (true_pos, true_vel) = orbit(t);
+    for k in 0..3 {
+        assert!((pos[k] - true_pos[k]).abs() < 1e-3);
+        assert!((vel[k] - true_vel[k]).abs() < 1e-6);
+    }
+    assert!(clock.is_some_and(|[bias, drift]| {
+        (bias - (1e-4 + 1e-9 * t)).abs() < 1e-15 && (drift - 1e-9).abs() < 1e-18
+    }));
+    // Samples without clock and times outside the samples
+    assert!(
+        precise
+            .state(&start.add_secs(19.5 * 900.0))
+            .is_some_and(|state| state.2.is_none())
+    );
+    assert!(precise.state(&start.add_secs(-1.0)).is_none());
+    assert!(precise.state(&start.add_secs(24.0 * 900.0 + 1.0)).is_none());
+
+    // The true state adds the relativistic correction and the group delay
+    let eph = Ephemeris {
+        tgd: 5e-9,
+        precise: Some(Arc::new(precise)),
+        ..Default::default()
+    };
+    let (pos, _, clk) = eph.true_state(&start.add_secs(t));
+    assert!((pos[0] - true_pos[0]).abs() < 1e-3);
+    // Circular orbits have no periodic relativistic effect
+    assert!((clk[0] - (1e-4 + 1e-9 * t - 5e-9)).abs() < 1e-14);
+}
+
+#[test]
+fn test_almanac_ephemerides() {
+    let entry = AlmanacEntry {
+        prn: 3,
+        health: 0,
+        eccentricity: 0.010_634_422_3,
+        toa: 589_824.0,
+        inclination: 0.987_602_584_1,
+        omega_dot: -7.828_897_578e-9,
+        sqrt_a: 5_153.677_734,
+        omega0: -2.290_121_722,
+        perigee: 0.660_052_418,
+        mean_anomaly: 1.742_610_574,
+        af0: 1.525_878_906e-5,
+        af1: 1.0e-11,
+        week: 150,
+    };
+    // 10-bit weeks resolve to the era of the reference
+    assert_eq!(full_week(150, 2190), 2198);
+    assert_eq!(full_week(1000, 1030), 1000);
+    assert_eq!(full_week(2198, 0), 2198);
+    let eph = almanac_ephemeris(&entry, 2190);
+    assert_eq!((eph.toe.week, eph.iode), (2198, 0));
+
+    // Moving the ephemeris across the week boundary keeps the orbit
+    let toe = GpsTime {
+        week: 2199,
+        sec: 100_800.0,
+    };
+    let moved = propagate_secular(&eph, &toe, 7);
+    assert_eq!((moved.iode, moved.iodc), (7, 7));
+    for dt in [-3600.0, 0.0, 5400.0] {
+        let time = toe.add_secs(dt);
+        let (pos, vel, clk) = eph.compute_satellite_state(&time);
+        let (moved_pos, moved_vel, moved_clk) =
+            moved.compute_satellite_state(&time);
+        for k in 0..3 {
+            assert!((pos[k] - moved_pos[k]).abs() < 1e-3);
+            assert!((vel[k] - moved_vel[k]).abs() < 1e-6);
+        }
+        assert!((clk[0] - moved_clk[0]).abs() < 1e-15);
+    }
+
+    // Data sets every two hours cover the scenario, weeks after the almanac
+    let almanac = Almanac {
+        entries: vec![entry],
+    };
+    let start = GpsTime {
+        week: 2203,
+        sec: 3_000.0,
+    };
+    let ephemerides =
+        almanac_ephemerides(&almanac, &start, &start.add_secs(9_000.0));
+    let sets = ephemerides.get(Constellation::Gps, 3);
+    assert_eq!(sets.len(), 3);
+    assert!(sets.iter().all(|eph| eph.vflg));
+    assert!(ephemerides.get(Constellation::Gps, 1).is_empty());
+    assert!((sets[0].toe.sec).abs() < 1e-9);
+    assert!((sets[2].toe.sec - 14_400.0).abs() < 1e-9);
+}
+
+/// Reads a field of up to 32 bits split over the data bits of two words.
+fn lnav_field(words: &[u32], first: usize, high_bits: u32) -> u32 {
+    let high = words[first] >> 6 & ((1 << high_bits) - 1);
+    high << 24 | words[first + 1] >> 6 & 0x00ff_ffff
+}
+
+#[test]
+fn test_synthetic_constellation() -> Result<(), Error> {
+    let epoch = GpsTime {
+        week: 2190,
+        sec: 86_400.0,
+    };
+    // Node longitude and argument of latitude at the epoch, in degrees
+    let slot = |almanac: &Almanac, prn: usize| {
+        let entry = &almanac.entries[prn - 1];
+        let node = entry.omega0 - OMEGA_EARTH * entry.toa;
+        (
+            node.to_degrees().rem_euclid(360.0),
+            entry.mean_anomaly.to_degrees().rem_euclid(360.0),
+        )
+    };
+    let near = |(node, latitude): (f64, f64), expected: (f64, f64)| {
+        (node - expected.0).abs() < 1e-9 && (latitude - expected.1).abs() < 1e-9
+    };
+    // Baseline slots A1, B1, B2 and F4 of the SPS performance standard
+    let baseline = nominal_constellation(24, &epoch);
+    assert_eq!(baseline.entries.len(), 24);
+    assert!(near(slot(&baseline, 1), (272.847, 268.126)));
+    assert!(near(slot(&baseline, 2), (332.847, 80.956)));
+    assert!(near(slot(&baseline, 8), (332.847, 173.336)));
+    assert!(near(slot(&baseline, 24), (212.847, 135.346)));
+    // Expanded slots B1F, B1A, D2F, D2A, F2F and F2A
+    let expanded = nominal_constellation(27, &epoch);
+    assert!(near(slot(&expanded, 2), (332.847, 94.916)));
+    assert!(near(slot(&expanded, 3), (332.847, 66.356)));
+    assert!(near(slot(&expanded, 11), (92.847, 282.676)));
+    assert!(near(slot(&expanded, 12), (92.847, 257.976)));
+    assert!(near(slot(&expanded, 14), (212.847, 0.456)));
+    assert!(near(slot(&expanded, 15), (212.847, 334.016)));
+    assert!(near(slot(&expanded, 27), (212.847, 135.346)));
+    // A smaller constellation keeps the first slots of each plane
+    assert!(near(
+        slot(&nominal_constellation(25, &epoch), 4),
+        (32.847, 111.876)
+    ));
+    // Surplus satellites go halfway across the widest gap of planes A, C, E
+    let surplus = nominal_constellation(31, &epoch);
+    assert_eq!(surplus.entries.len(), 31);
+    assert!(near(slot(&surplus, 28), (272.847, 101.796)));
+    assert!(near(slot(&surplus, 29), (32.847, 176.716)));
+    assert!(near(slot(&surplus, 31), (272.847, 214.956)));
+
+    // The navigation message decodes to the simulated orbit and clock
+    let almanac = nominal_constellation(31, &epoch);
+    let ephemerides =
+        almanac_ephemerides(&almanac, &epoch, &epoch.add_secs(3_600.0));
+    assert!(
+        (1..=31).all(|prn| ephemerides.get(Constellation::Gps, prn).len() == 2)
+    );
+    assert!(ephemerides.get(Constellation::Gps, 32).is_empty());
+    let mut channel = Channel::default();
+    for eph in (1..=31).flat_map(|prn| ephemerides.get(Constellation::Gps, prn))
+    {
+        channel.generate_navigation_subframes(eph, &IonoUtc::default());
+        let [sf1, sf2, sf3, _, _] = channel.subframes();
+        let angle = |first| f64::from(lnav_field(sf3, first, 8) as i32);
+        let decoded = [
+            (
+                f64::from(lnav_field(sf2, 3, 8) as i32) * POW2_M31 * PI,
+                eph.m0,
+            ),
+            (f64::from(lnav_field(sf2, 7, 8)) * POW2_M19, eph.sqrta),
+            (angle(2) * POW2_M31 * PI, eph.omg0),
+            (angle(4) * POW2_M31 * PI, eph.inc0),
+            (angle(6) * POW2_M31 * PI, eph.aop),
+            (
+                f64::from((sf3[8] << 2) as i32 >> 8) * POW2_M43 * PI,
+                eph.omgdot,
+            ),
+            (f64::from((sf1[9] << 2) as i32 >> 10) * POW2_M31, eph.af0),
+        ];
+        for (value, expected) in decoded {
+            assert!((value - expected).abs() <= expected.abs() * f64::EPSILON);
+        }
+        assert!(
+            (f64::from(sf2[9] >> 14 & 0xffff) * 16.0 - eph.toe.sec).abs()
+                < 1e-9
+        );
+        assert_eq!(sf2[2] >> 22 & 0xff, eph.iode as u32);
+    }
+
+    // A generator needs no navigation file
+    let generator = SignalGeneratorBuilder::default()
+        .synthetic_constellation(Some(24))?
+        .time(Some(String::from("2022-01-01T00:00:00Z")))?
+        .duration(Some(1.0))
+        .data_format(Some(8))?
+        .build();
+    assert!(generator.is_ok());
+    assert!(matches!(
+        SignalGeneratorBuilder::default().synthetic_constellation(Some(33)),
+        Err(Error::InvalidConstellationSize)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_ephemeris_selection() {
+    let ephemeris = |sec: f64, iode: i32, svhlth: i32| {
+        let toe = GpsTime { week: 2190, sec };
+        Ephemeris {
+            vflg: true,
+            toc: toe.clone(),
+            toe,
+            iode,
+            svhlth,
+            ..Default::default()
+        }
+    };
+    let mut ephemerides = SatelliteEphemerides::new();
+    // Inserted out of order, with a repeated data set and another issue of
+    // data with the same time of ephemeris
+    for eph in [
+        ephemeris(7_200.0, 2, 0),
+        ephemeris(0.0, 1, 0),
+        ephemeris(7_200.0, 2, 0),
+        ephemeris(7_200.0, 3, 0),
+        ephemeris(14_400.0, 4, 63),
+    ] {
+        assert!(ephemerides.insert(Constellation::Gps, 5, eph));
+    }
+    assert!(!ephemerides.insert(Constellation::Gps, 33, ephemeris(0.0, 1, 0)));
+    let list = ephemerides.get(Constellation::Gps, 5);
+    assert_eq!(
+        list.iter().map(|eph| eph.iode).collect::<Vec<_>>(),
+        [1, 2, 3, 4]
+    );
+    let best = |sec| {
+        ephemerides
+            .best(Constellation::Gps, 5, &GpsTime { week: 2190, sec })
+            .map(|index| list[index].iode)
+    };
+    // Closest time of ephemeris, the earlier one halfway between two
+    assert_eq!(best(3_000.0), Some(1));
+    assert_eq!(best(3_600.0), Some(1));
+    // Data set read last for the same time of ephemeris
+    assert_eq!(best(3_630.0), Some(3));
+    // Healthy data sets first, then none beyond four hours
+    assert_eq!(best(14_400.0), Some(3));
+    assert_eq!(best(26_000.0), Some(4));
+    assert_eq!(best(30_000.0), None);
+    assert_eq!(
+        ephemerides.best(Constellation::Gps, 6, &GpsTime::default()),
+        None
+    );
+}
+
+#[test]
+fn test_toc_span_of_enabled_constellations() {
+    let ephemeris = |week: i32, sec: f64| {
+        let toe = GpsTime { week, sec };
+        Ephemeris {
+            vflg: true,
+            toc: toe.clone(),
+            toe,
+            ..Default::default()
+        }
+    };
+    let mut ephemerides = SatelliteEphemerides::new();
+    assert!(ephemerides.insert(
+        Constellation::Gps,
+        3,
+        ephemeris(2190, 7_200.0)
+    ));
+    assert!(ephemerides.insert(
+        Constellation::Gps,
+        3,
+        ephemeris(2190, 14_400.0)
+    ));
+    assert!(ephemerides.insert(
+        Constellation::Galileo,
+        11,
+        ephemeris(2189, 600_000.0)
+    ));
+    assert!(ephemerides.insert(
+        Constellation::BeiDou,
+        6,
+        ephemeris(2190, 86_400.0)
+    ));
+    let span = |constellations: &[Constellation]| {
+        ephemerides
+            .toc_span(constellations)
+            .map(|(first, last)| (first.week, first.sec, last.week, last.sec))
+    };
+    assert_eq!(
+        span(&[Constellation::Gps]),
+        Some((2190, 7_200.0, 2190, 14_400.0))
+    );
+    // Every enabled constellation widens the span, across the week boundary
+    assert_eq!(
+        span(&[
+            Constellation::Gps,
+            Constellation::Galileo,
+            Constellation::BeiDou
+        ]),
+        Some((2189, 600_000.0, 2190, 86_400.0))
+    );
+    // A constellation alone is enough to bound a scenario
+    assert_eq!(
+        span(&[Constellation::BeiDou]),
+        Some((2190, 86_400.0, 2190, 86_400.0))
+    );
+    assert_eq!(span(&[Constellation::Glonass]), None);
+}
+
+#[test]
+fn test_glonass_epoch_uses_leap_second_table() -> Result<(), Error> {
+    let mut ephemerides = SatelliteEphemerides::new();
+    // Records in UTC, without the LEAP SECONDS line of the header
+    for (slot, utc) in
+        [(3, "2022-01-01T00:15:00Z"), (4, "2016-06-01T00:00:00Z")]
+    {
+        let record = rinex::ephemeris::GlonassEphemeris {
+            prn: slot,
+            time_of_clock: utc.parse()?,
+            ..Default::default()
+        };
+        add_glonass_ephemeris(&mut ephemerides, &record)?;
+    }
+    let toc = |slot| {
+        ephemerides
+            .get(Constellation::Glonass, slot)
+            .first()
+            .map(|eph| (eph.toc.week, eph.toc.sec))
+    };
+    // 18 leap seconds since 2017, 17 before
+    assert_eq!(toc(3), Some((2190, 6.0 * 86_400.0 + 900.0 + 18.0)));
+    assert_eq!(toc(4), Some((1899, 3.0 * 86_400.0 + 17.0)));
+    Ok(())
+}
+
+#[test]
+fn test_ephemeris_handover() {
+    let ephemeris = |sec: f64, iode: i32, transmission: Option<f64>| {
+        let toe = GpsTime { week: 2190, sec };
+        Ephemeris {
+            vflg: true,
+            toc: toe.clone(),
+            toe,
+            transmission_time: transmission
+                .map(|sec| GpsTime { week: 2190, sec }),
+            iode,
+            ..Default::default()
+        }
+    };
+    let mut ephemerides = SatelliteEphemerides::new();
+    for eph in [
+        ephemeris(7_200.0, 1, None),
+        ephemeris(14_400.0, 2, Some(5_400.0)),
+        ephemeris(21_600.0, 3, None),
+    ] {
+        assert!(ephemerides.insert(Constellation::Gps, 7, eph));
+    }
+    let list = ephemerides.get(Constellation::Gps, 7);
+    let current = |sec, handover| {
+        ephemerides
+            .current(
+                Constellation::Gps,
+                7,
+                &GpsTime { week: 2190, sec },
+                handover,
+            )
+            .map(|index| list[index].iode)
+    };
+    // All satellites switch halfway between two times of ephemeris
+    assert_eq!(current(10_000.0, Handover::Closest), Some(1));
+    assert_eq!(current(10_900.0, Handover::Closest), Some(2));
+    // Each data set from two hours before its time of ephemeris
+    let upload = Handover::BeforeToe(7_200.0);
+    assert_eq!(current(7_100.0, upload), Some(1));
+    assert_eq!(current(7_200.0, upload), Some(2));
+    assert_eq!(current(14_400.0, upload), Some(3));
+    // Transmission time of the navigation file, two hours before the time
+    // of ephemeris when unknown
+    let transmission = Handover::TransmissionTime;
+    assert_eq!(current(5_300.0, transmission), Some(1));
+    assert_eq!(current(5_400.0, transmission), Some(2));
+    assert_eq!(current(14_399.0, transmission), Some(2));
+    assert_eq!(current(14_400.0, transmission), Some(3));
+    // Before the first cut-over, the closest data set is used
+    assert_eq!(current(0.0, Handover::BeforeToe(0.0)), Some(1));
+    assert_eq!(current(40_000.0, upload), None);
+
+    assert_eq!("closest".parse::<Handover>().ok(), Some(Handover::Closest));
+    assert_eq!(
+        " TOM ".parse::<Handover>().ok(),
+        Some(Handover::TransmissionTime)
+    );
+    assert!(matches!(
+        "7200".parse::<Handover>(),
+        Ok(Handover::BeforeToe(lead)) if (lead - 7_200.0).abs() < 1e-9
+    ));
+    for rule in ["soon", "-60", "20000"] {
+        assert!(matches!(
+            rule.parse::<Handover>(),
+            Err(Error::InvalidHandover(_))
+        ));
+    }
+}
+
+#[test]
+fn test_secular_propagation_against_broadcast() -> Result<(), Error> {
+    let generator = SignalGeneratorBuilder::default()
+        .navigation_file(Some(
+            concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/brdc0010.22n")
+                .into(),
+        ))?
+        .duration(Some(1.0))
+        .data_format(Some(8))?
+        .build()?;
+    // The first data set of the day, propagated to each later one, stays
+    // within 100 m per hour of propagation of the broadcast orbit, i.e.
+    // about 2.4 km after a day, and within 20 ns of the broadcast clock
+    let mut compared = 0;
+    for prn in 1..=32 {
+        let ephemerides =
+            generator.satellite_ephemerides.get(Constellation::Gps, prn);
+        let Some((first, later)) = ephemerides.split_first() else {
+            continue;
+        };
+        for truth in later {
+            let hours = truth.toe.diff_secs(&first.toe) / 3_600.0;
+            let moved = propagate_secular(first, &truth.toe, truth.iode);
+            for dt in [-3_600.0, 0.0, 3_600.0] {
+                let time = truth.toe.add_secs(dt);
+                let (pos, _, clk) = truth.compute_satellite_state(&time);
+                let (moved_pos, _, moved_clk) =
+                    moved.compute_satellite_state(&time);
+                let error = (0..3)
+                    .map(|k| (pos[k] - moved_pos[k]).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                assert!(error < 100.0 * hours, "PRN {prn}: {error} m");
+                assert!((clk[0] - moved_clk[0]).abs() < 20e-9);
+            }
+            compared += 1;
+        }
+    }
+    assert!(compared > 100);
+    Ok(())
+}
+
+#[test]
+fn test_re_epoch_propagation() {
+    let entry = AlmanacEntry {
+        prn: 9,
+        health: 0,
+        eccentricity: 0.004_2,
+        toa: 7_200.0,
+        inclination: 0.96,
+        omega_dot: -8.1e-9,
+        sqrt_a: 5_153.7,
+        omega0: 1.2,
+        perigee: -2.1,
+        mean_anomaly: 0.4,
+        af0: -2.5e-4,
+        af1: -3.0e-12,
+        week: 2190,
+    };
+    // A broadcast orbit with harmonic corrections and a clock drift rate
+    let mut eph = almanac_ephemeris(&entry, 2190);
+    eph.deltan = 4.5e-9;
+    eph.n += eph.deltan;
+    eph.crs = 20.5;
+    eph.crc = 251.0;
+    eph.cuc = 1.1e-6;
+    eph.cus = 5.3e-6;
+    eph.cic = 1.4e-7;
+    eph.cis = -9.0e-8;
+    eph.af2 = 1.0e-19;
+    eph.iodc = 300;
+    eph.iode = 44;
+
+    // Three days later, the propagated elements give the same orbit and
+    // clock
+    let toe = eph.toe.add_secs(3.0 * 86_400.0);
+    let moved = propagate_secular(&eph, &toe, eph.iode);
+    assert_eq!((moved.iode, moved.iodc), (44, 44));
+    assert!((moved.crc - eph.crc).abs() < 1e-12);
+    for dt in [-5_400.0, 0.0, 7_200.0] {
+        let time = toe.add_secs(dt);
+        let (pos, vel, clk) = eph.compute_satellite_state(&time);
+        let (moved_pos, moved_vel, moved_clk) =
+            moved.compute_satellite_state(&time);
+        for k in 0..3 {
+            assert!((pos[k] - moved_pos[k]).abs() < 1e-3);
+            assert!((vel[k] - moved_vel[k]).abs() < 1e-6);
+        }
+        assert!((clk[0] - moved_clk[0]).abs() < 1e-15);
+        assert!((clk[1] - moved_clk[1]).abs() < 1e-18);
+    }
+
+    assert_eq!(
+        "Propagate".parse::<ReEpoch>().ok(),
+        Some(ReEpoch::Propagate)
+    );
+    assert_eq!("shift".parse::<ReEpoch>().ok(), Some(ReEpoch::Shift));
+    assert!(matches!(
+        "refit".parse::<ReEpoch>(),
+        Err(Error::InvalidReEpoch(_))
+    ));
+}
+
+#[test]
+fn test_time_scales() {
+    let date = |y, m, d, hh, mm, sec| DateTime {
+        y,
+        m,
+        d,
+        hh,
+        mm,
+        sec,
+    };
+    let new_year = GpsTime::from(&date(2017, 1, 1, 0, 0, 0.0));
+    assert_eq!(new_year.week, 1930);
+    // UTC dates use the leap seconds of their day, 23:59:60 the old count
+    let utc = |time: &DateTime| TimeScale::Utc.to_gps_time(time);
+    assert!(
+        (utc(&date(2017, 1, 1, 0, 0, 0.0)).diff_secs(&new_year) - 18.0).abs()
+            < 1e-9
+    );
+    assert!(
+        (utc(&date(2016, 12, 31, 23, 59, 60.0)).diff_secs(&new_year) - 17.0)
+            .abs()
+            < 1e-9
+    );
+    assert!(
+        (utc(&date(2016, 12, 31, 23, 59, 59.0)).diff_secs(&new_year) - 16.0)
+            .abs()
+            < 1e-9
+    );
+    assert!(
+        (utc(&date(1980, 1, 6, 0, 0, 0.0)).diff_secs(&GpsTime::default()))
+            .abs()
+            < 1e-9
+    );
+
+    // Back to UTC, with the inserted second
+    for (dt, d, hh, mm, sec) in [
+        (16.5, 31, 23, 59, 59.5),
+        (17.5, 31, 23, 59, 60.5),
+        (18.5, 1, 0, 0, 0.5),
+    ] {
+        let time = TimeScale::Utc.date_of(&new_year.add_secs(dt));
+        assert_eq!((time.d, time.hh, time.mm), (d, hh, mm));
+        assert!((time.sec - sec).abs() < 1e-9);
+    }
+
+    // Other scales have constant offsets from GPS time
+    let time = GpsTime {
+        week: 2190,
+        sec: 561_914.0,
+    };
+    for (scale, hh, mm, sec) in [
+        (TimeScale::Gpst, 12, 5, 14.0),
+        (TimeScale::Gst, 12, 5, 14.0),
+        (TimeScale::Tai, 12, 5, 33.0),
+        (TimeScale::Bdt, 12, 5, 0.0),
+        (TimeScale::Utc, 12, 4, 56.0),
+    ] {
+        let date = scale.date_of(&time);
+        assert_eq!((date.y, date.m, date.d), (2022, 1, 1));
+        assert_eq!((date.hh, date.mm), (hh, mm));
+        assert!((date.sec - sec).abs() < 1e-9);
+        assert!(scale.to_gps_time(&date).diff_secs(&time).abs() < 1e-9);
+    }
+
+    assert_eq!("gpst".parse::<TimeScale>().ok(), Some(TimeScale::Gpst));
+    assert_eq!("BDT".parse::<TimeScale>().ok(), Some(TimeScale::Bdt));
+    assert!(matches!(
+        "GLONASST".parse::<TimeScale>(),
+        Err(Error::InvalidTimeScale(_))
+    ));
+}
+
+#[test]
+fn test_leap_second_event() {
+    // Leap second at the end of 2016/12/31, day 7 of week 1929
+    let mut ionoutc = IonoUtc {
+        vflg: true,
+        dtls: 17,
+        leapen: 1,
+        wnlsf: 1929,
+        day_number: 7,
+        dtlsf: 18,
+        ..Default::default()
+    };
+    let week = |sec| GpsTime { week: 1930, sec };
+    assert!(
+        ionoutc
+            .leap_second_event()
+            .is_some_and(|event| event.diff_secs(&week(18.0)).abs() < 1e-9)
+    );
+    for (sec, d, hh, mm, utc_sec) in [
+        (16.5, 31, 23, 59, 59.5),
+        (17.5, 31, 23, 59, 60.5),
+        (18.5, 1, 0, 0, 0.5),
+    ] {
+        let utc = ionoutc.utc(&week(sec));
+        assert_eq!((utc.d, utc.hh, utc.mm), (d, hh, mm));
+        assert!((utc.sec - utc_sec).abs() < 1e-9);
+        // The same as with the leap-second table
+        let table = TimeScale::Utc.date_of(&week(sec));
+        assert_eq!((table.d, table.hh, table.mm), (d, hh, mm));
+        assert!((table.sec - utc_sec).abs() < 1e-9);
+    }
+
+    // The message announces the event, then carries the new leap seconds
+    let eph = Ephemeris {
+        vflg: true,
+        ..Default::default()
+    };
+    let mut channel = Channel::default();
+    channel.generate_navigation_subframes(&eph, &ionoutc);
+    let page18 = channel.subframes()[3];
+    assert_eq!(page18[8] >> 22 & 0xff, 17);
+    assert_eq!((page18[8] >> 14 & 0xff, page18[8] >> 6 & 0xff), (137, 7));
+    assert_eq!(page18[9] >> 22 & 0xff, 18);
+    ionoutc.dtls = ionoutc.dtlsf;
+    assert!(ionoutc.leap_second_event().is_none());
+    channel.generate_navigation_subframes(&eph, &ionoutc);
+    assert_eq!(channel.subframes()[3][8] >> 22 & 0xff, 18);
+
+    // A removed second skips from 23:59:58 to the next day
+    let ionoutc = IonoUtc {
+        dtls: 18,
+        dtlsf: 17,
+        ..ionoutc
+    };
+    let utc = ionoutc.utc(&week(16.5));
+    assert_eq!((utc.d, utc.hh, utc.mm), (31, 23, 59));
+    assert!((utc.sec - 58.5).abs() < 1e-9);
+    let utc = ionoutc.utc(&week(17.0));
+    assert_eq!((utc.d, utc.hh, utc.mm), (1, 0, 0));
+    assert!(utc.sec.abs() < 1e-9);
+}
+
+#[test]
+fn test_leap_second_applied_once() -> Result<(), Error> {
+    // A leap second at the end of 2022/01/01, day 7 of week 2190
+    let mut generator = SignalGeneratorBuilder::default()
+        .navigation_file(Some(
+            concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/brdc0010.22n")
+                .into(),
+        ))?
+        .location(Some(vec![35.681_298, 139.766_247, 10.0]))?
+        .time(Some(String::from("2022/01/01,20:00:00 GPST")))?
+        .leap(Some(vec![2190, 7, 19]))
+        .duration(Some(1.0))
+        .data_format(Some(8))?
+        .build()?;
+    let position = generator.positions[0];
+    assert!(generator.allocate_channel(position, Ecef::default()) > 0);
+    assert!(!generator.apply_leap_second());
+    assert_eq!(generator.ionoutc.dtls, 18);
+
+    // Regenerated on the first refresh past the event, and not after
+    let Some(event) = generator.ionoutc.leap_second_event() else {
+        panic!("leap second event not set");
+    };
+    generator.receiver_gps_time = event.add_secs(12.0);
+    assert!(generator.apply_leap_second());
+    assert_eq!(generator.ionoutc.dtls, 19);
+    let dtls = |generator: &SignalGenerator| {
+        generator
+            .channels
+            .iter()
+            .filter(|channel| channel.satellite.is_some())
+            .map(|channel| channel.subframes()[3][8] >> 22 & 0xff)
+            .collect::<Vec<_>>()
+    };
+    assert!(!dtls(&generator).is_empty());
+    assert!(dtls(&generator).iter().all(|&dtls| dtls == 19));
+    for refresh in 1..4 {
+        generator.receiver_gps_time =
+            event.add_secs(12.0 + 30.0 * f64::from(refresh));
+        assert!(!generator.apply_leap_second());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ephemeris_switch_reaches_frame() -> Result<(), Error> {
+    // Data sets of 12:00 and 14:00, switched on the first frame past 13:00
+    let mut generator = SignalGeneratorBuilder::default()
+        .navigation_file(Some(
+            concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/brdc0010.22n")
+                .into(),
+        ))?
+        .location(Some(vec![35.681_298, 139.766_247, 10.0]))?
+        .time(Some(String::from("2022/01/01,13:00:00 GPST")))?
+        .duration(Some(1.0))
+        .data_format(Some(8))?
+        .build()?;
+    let position = generator.positions[0];
+    assert!(generator.allocate_channel(position, Ecef::default()) > 0);
+    // IODE of subframe 2 as transmitted in the frame
+    let iodes = |generator: &SignalGenerator| {
+        generator
+            .channels
+            .iter()
+            .filter_map(|channel| {
+                let satellite = channel.satellite?;
+                let words = channel.data_words();
+                let word = if words[21] & 1 == 1 {
+                    words[22] ^ 0x3fff_ffc0
+                } else {
+                    words[22]
+                };
+                let index = generator.gps_ephemeris_indices[satellite.prn - 1]?;
+                let expected = generator
+                    .satellite_ephemerides
+                    .get(Constellation::Gps, satellite.prn)[index]
+                    .iode;
+                Some((word >> 22 & 0xff, expected as u32))
+            })
+            .collect::<Vec<_>>()
+    };
+    let before = iodes(&generator);
+    assert!(before.iter().all(|(sent, expected)| sent == expected));
+
+    generator.receiver_gps_time = generator.receiver_gps_time.add_secs(30.0);
+    generator.handle_periodic_tasks(position, Ecef::default());
+    let after = iodes(&generator);
+    // The new data sets are broadcast from the frame of the switch
+    assert!(after.iter().all(|(sent, expected)| sent == expected));
+    assert!(before.iter().zip(&after).any(|(old, new)| old.1 != new.1));
+    Ok(())
+}
+
+/// Reads the transmission week number and the TOW count of subframe 1 from
+/// the transmitted words, undoing the inversion by the parity of the
+/// previous word.
+fn transmitted_week_and_tow(channel: &Channel) -> (u32, u32) {
+    let words = channel.data_words();
+    let data = |i: usize| {
+        if words[i - 1] & 1 == 1 {
+            words[i] ^ 0x3fff_ffc0
+        } else {
+            words[i]
+        }
+    };
+    (data(12) >> 20 & 0x3ff, data(11) >> 13 & 0x1ffff)
+}
+
+#[test]
+fn test_week_rollover() -> Result<(), Error> {
+    let date = |y, m, d, hh, mm, sec| DateTime {
+        y,
+        m,
+        d,
+        hh,
+        mm,
+        sec,
+    };
+    // The last second before each rollover of the 10-bit week number
+    for (y, m, d, week) in [
+        (1999, 8, 21, 1023),
+        (2019, 4, 6, 2047),
+        (2038, 11, 20, 3071),
+    ] {
+        let before = GpsTime::from(&date(y, m, d, 23, 59, 59.0));
+        assert_eq!(before.week, week);
+        assert!((before.sec - 604_799.0).abs() < 1e-9);
+        let after = before.add_secs(1.0);
+        assert_eq!(after.week, week + 1);
+        let next_day = DateTime::from(&after);
+        assert_eq!((next_day.y, next_day.m, next_day.d), (y, m, d + 1));
+        assert_eq!((next_day.hh, next_day.mm), (0, 0));
+
+        // The message wraps to week 0 and TOW 1 at the rollover
+        let mut channel = Channel::default();
+        channel.generate_nav_msg(&before, true);
+        assert_eq!(transmitted_week_and_tow(&channel), (1023, 100_796));
+        channel.generate_nav_msg(&after, false);
+        assert_eq!(transmitted_week_and_tow(&channel), (0, 1));
+    }
+    // Gregorian leap years beyond 2099
+    let feb28 = GpsTime::from(&date(2100, 2, 28, 12, 0, 0.0));
+    let mar1 = GpsTime::from(&date(2100, 3, 1, 12, 0, 0.0));
+    assert!((mar1.diff_secs(&feb28) - 86_400.0).abs() < 1e-9);
+    let time = DateTime::from(&mar1);
+    assert_eq!((time.y, time.m, time.d, time.hh), (2100, 3, 1, 12));
+    let leap_day =
+        DateTime::from(&GpsTime::from(&date(2400, 2, 29, 0, 0, 0.0)));
+    assert_eq!((leap_day.y, leap_day.m, leap_day.d), (2400, 2, 29));
+
+    // Weeks modulo 1024 resolve around the rollover
+    assert_eq!(full_week(1023, 2048), 2047);
+    assert_eq!(full_week(0, 2047), 2048);
+    assert_eq!(full_week(1023, 3072), 3071);
+
+    // A scenario moved past the third rollover with -T
+    let mut generator = SignalGeneratorBuilder::default()
+        .navigation_file(Some(
+            concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/brdc0010.22n")
+                .into(),
+        ))?
+        .location(Some(vec![35.681_298, 139.766_247, 10.0]))?
+        .time(Some(String::from("2038/11/20,23:59:45 GPST")))?
+        .time_override(Some(true))
+        .leap(Some(vec![1023, 7, 19]))
+        .duration(Some(1.0))
+        .data_format(Some(8))?
+        .build()?;
+    assert_eq!(generator.receiver_gps_time.week, 3071);
+    assert_eq!(generator.ionoutc.wnlsf, 3071);
+    // The data sets in use are those of either side of the rollover
+    for (sv, index) in generator.gps_ephemeris_indices.iter().enumerate() {
+        let Some(index) = index else { continue };
+        let eph = &generator
+            .satellite_ephemerides
+            .get(Constellation::Gps, sv + 1)[*index];
+        assert!(
+            eph.toe.diff_secs(&generator.receiver_gps_time).abs() <= 7_200.0
+        );
+    }
+    let position = generator.positions[0];
+    assert!(generator.allocate_channel(position, Ecef::default()) > 0);
+    for channel in generator
+        .channels
+        .iter()
+        .filter(|channel| channel.satellite.is_some())
+    {
+        assert_eq!(transmitted_week_and_tow(channel), (1023, 100_796));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fractional_start_time() -> Result<(), Error> {
+    // Fractions of a second are kept down to the nanosecond
+    let builder = SignalGeneratorBuilder::default()
+        .time(Some(String::from("2022/01/01,11:45:14.2504 GPST")))?;
+    let generator = builder
+        .navigation_file(Some(
+            concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/brdc0010.22n")
+                .into(),
+        ))?
+        .duration(Some(1.0))
+        .data_format(Some(8))?
+        .build()?;
+    let start = generator.receiver_gps_time.clone();
+    assert_eq!(start.week, 2190);
+    assert!((start.sec - 560_714.250_4).abs() < 1e-9);
+    let utc = DateTime::from(
+        jiff::Timestamp::from_nanosecond(1_641_037_496_000_000_001)?
+            .in_tz("UTC")?,
+    );
+    assert!((utc.sec - 56.000_000_001).abs() < 1e-12);
+
+    // Steps of 100 ms keep the offset from the grid
+    let mut time = start.clone();
+    for _ in 0..3_000 {
+        time = time.add_secs(0.1);
+    }
+    assert!((time.diff_secs(&start) - 300.0).abs() < 1e-9);
+    assert!((time.sec - 561_014.250_4).abs() < 1e-9);
+
+    // A start within half a second of a frame in the next week begins it
+    let mut channel = Channel::default();
+    channel.generate_nav_msg(
+        &GpsTime {
+            week: 2190,
+            sec: 604_799.7,
+        },
+        true,
+    );
+    assert_eq!(transmitted_week_and_tow(&channel), (2191 % 1024, 1));
+    channel.generate_nav_msg(
+        &GpsTime {
+            week: 2190,
+            sec: 604_769.3,
+        },
+        true,
+    );
+    assert_eq!(transmitted_week_and_tow(&channel), (2190 % 1024, 100_791));
+    Ok(())
+}
+
+#[test]
+fn test_receiver_velocity_doppler() -> Result<(), Error> {
+    // Range rates include the receiver velocity
+    let generator = SignalGeneratorBuilder::default()
+        .navigation_file(Some(
+            concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/brdc0010.22n")
+                .into(),
+        ))?
+        .location(Some(vec![35.681_298, 139.766_247, 10.0]))?
+        .duration(Some(1.0))
+        .data_format(Some(8))?
+        .build()?;
+    let time = generator.receiver_gps_time.clone();
+    let position = generator.positions[0];
+    let velocity = Ecef::new(150.0, -250.0, 80.0);
+    let (prn, eph) = (1..=32)
+        .find_map(|prn| {
+            let index = generator.gps_ephemeris_indices[prn - 1]?;
+            let eph = &generator
+                .satellite_ephemerides
+                .get(Constellation::Gps, prn)[index];
+            eph.check_visibility(&time, &position, 0.0)
+                .is_some_and(|(_, visible)| visible)
+                .then_some((prn, eph))
+        })
+        .ok_or_else(|| Error::msg("No visible satellite"))?;
+    let moved = |dt: f64| {
+        let mut xyz = position;
+        xyz.x += velocity.x * dt;
+        xyz.y += velocity.y * dt;
+        xyz.z += velocity.z * dt;
+        xyz
+    };
+    let range = |dt: f64| {
+        compute_range(
+            eph,
+            &generator.ionoutc,
+            &time.add_secs(dt),
+            &moved(dt),
+            &velocity,
+        )
+    };
+    let rho0 = range(0.0);
+    let rho1 = range(0.1);
+    let difference = (range(0.05).range - range(-0.05).range) / 0.1;
+    assert!((rho0.rate - difference).abs() < 0.1);
+    let still = compute_range(
+        eph,
+        &generator.ionoutc,
+        &time,
+        &position,
+        &Ecef::default(),
+    );
+    assert!((rho0.rate - still.rate).abs() > 10.0);
+
+    // The Doppler sweeps from one range rate to the next over the step
+    let mut channel = Channel::default();
+    channel.update_for_satellite(
+        SatelliteId::new(Constellation::Gps, prn),
+        eph,
+        &generator.ionoutc,
+        &time,
+        &position,
+        &velocity,
+        rho0.azel,
+    );
+    let sampling_period = 1.0 / 2.6e6;
+    channel.update_state_with_rate(&rho1, 0.1, sampling_period);
+    assert!((channel.carrier_frequency() + rho0.rate / LAMBDA_L1).abs() < 1e-6);
+    channel.sweep_doppler(0.1, sampling_period);
+    assert!((channel.carrier_frequency() + rho1.rate / LAMBDA_L1).abs() < 1e-6);
+
+    // Hermite trajectories drive the Doppler, linear ones keep the change of
+    // range between steps
+    let circle = |interpolation| {
+        SignalGeneratorBuilder::default()
+            .navigation_file(Some(
+                concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/brdc0010.22n")
+                    .into(),
+            ))?
+            .user_motion_file(Some(
+                concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/circle.csv")
+                    .into(),
+            ))?
+            .trajectory_interpolation(Some(interpolation))
+            .data_format(Some(8))?
+            .build()
+    };
+    assert!(circle(Interpolation::Linear)?.velocities.is_empty());
+    let generator = circle(Interpolation::Hermite)?;
+    assert_eq!(generator.velocities.len(), generator.positions.len());
+    let (p0, p1) = (generator.positions[100], generator.positions[102]);
+    let speed = generator.velocities[101].norm();
+    let distance = (p1 - &p0).norm();
+    assert!((speed - distance / 0.2).abs() < 0.1);
+    Ok(())
+}
+
+#[test]
+fn test_navigation_file_errors() {
+    let navigation = |file: &str| {
+        SignalGeneratorBuilder::default().navigation_file(Some(
+            Path::new(env!("CARGO_WORKSPACE_DIR")).join(file),
+        ))
+    };
+    // Missing files and malformed headers keep the RINEX error
+    assert!(matches!(
+        navigation("resources/missing.22n"),
+        Err(Error::Rinex(_))
+    ));
+    assert!(matches!(
+        navigation("resources/profile.toml"),
+        Err(Error::Rinex(rinex::error::Error::Malformed(diagnostic)))
+            if diagnostic.line == 1
+    ));
+}
+
+#[test]
+fn test_update_interval() -> Result<(), Error> {
+    // From 1 ms to 100 ms
+    for interval in [0.0005, 0.2, 0.0] {
+        assert!(matches!(
+            SignalGeneratorBuilder::default().update_interval(Some(interval)),
+            Err(Error::InvalidUpdateInterval(_))
+        ));
+    }
+    let builder = |interval| {
+        SignalGeneratorBuilder::default()
+            .navigation_file(Some(
+                concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/brdc0010.22n")
+                    .into(),
+            ))?
+            .update_interval(Some(interval))
+    };
+
+    // The duration is counted in steps
+    let generator = builder(0.001)?
+        .duration(Some(1.5))
+        .data_format(Some(8))?
+        .build()?;
+    assert_eq!(generator.simulation_step_count, 1_500);
+    assert!((generator.update_interval - 0.001).abs() < 1e-15);
+
+    // User motion at 10 Hz is resampled onto the steps
+    let circle = read_user_motion(
+        &concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/circle.csv").into(),
+    )
+    .map_err(|err| Error::msg(err.to_string()))?
+    .resample(0.1, Interpolation::Linear);
+    let generator = builder(0.01)?
+        .user_motion_file(Some(
+            concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/circle.csv")
+                .into(),
+        ))?
+        .duration(Some(5.0))
+        .data_format(Some(8))?
+        .build()?;
+    assert_eq!(generator.positions.len(), 29_991);
+    assert_eq!(generator.simulation_step_count, 500);
+    assert_eq!(generator.positions[10].x.to_bits(), circle[1].x.to_bits());
+    let middle = f64::midpoint(circle[1].y, circle[2].y);
+    assert!((generator.positions[15].y - middle).abs() < 1e-6);
+
+    // NMEA GGA sentences are 100 ms apart
+    let nmea = |rate| -> Result<usize, Error> {
+        Ok(builder(rate)?
+            .user_motion_nmea_gga_file(Some(
+                concat!(
+                    env!("CARGO_WORKSPACE_DIR"),
+                    "/resources/triumphv3.txt"
+                )
+                .into(),
+            ))?
+            .data_format(Some(8))?
+            .build()?
+            .positions
+            .len())
+    };
+    assert_eq!(nmea(0.05)?, 2 * nmea(0.1)? - 1);
+
+    // Every step holds a whole number of samples
+    let steps = |frequency| {
+        builder(0.001)?
+            .frequency(Some(frequency))?
+            .duration(Some(1.0))
+            .data_format(Some(8))?
+            .build()
+    };
+    assert!(matches!(
+        steps(1_000_500),
+        Err(Error::InvalidUpdateInterval(_))
+    ));
+    assert!(steps(1_001_000).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_motion_profile_file() -> Result<(), Error> {
+    // The builder integrates a profile file at the update interval
+    let generator = SignalGeneratorBuilder::default()
+        .navigation_file(Some(
+            concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/brdc0010.22n")
+                .into(),
+        ))?
+        .motion_profile_file(Some(
+            concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/profile.toml")
+                .into(),
+        ))?
+        .update_interval(Some(0.05))?
+        .data_format(Some(8))?
+        .build()?;
+    assert!(generator.positions.len() > 1_000);
+    assert_eq!(generator.velocities.len(), generator.positions.len());
+    assert_eq!(generator.simulation_step_count, generator.positions.len());
+    Ok(())
+}
@@ -1,13 +1,20 @@
-use std::path::Path;
+use std::{cmp::Ordering, path::Path, str::FromStr};
 
 use constants::{
-    EPHEM_ARRAY_SIZE, GM_EARTH, MAX_SAT, OMEGA_EARTH, SECONDS_IN_HOUR,
+    BDT_GPST_OFFSET, BDT_WEEK_OFFSET, GM_EARTH, GM_EARTH_BEIDOU,
+    GM_EARTH_GALILEO, MAX_SAT, MAX_SAT_BEIDOU, MAX_SAT_GALILEO,
+    MAX_SAT_GLONASS, MIN_PRN_QZSS, OMEGA_EARTH, SECONDS_IN_HOUR,
+    SECONDS_IN_WEEK,
 };
+use rinex::ephemeris::{GlonassEphemeris, System};
 
 use crate::{
-    datetime::{DateTime, GpsTime},
+    datetime::{DateTime, GpsTime, full_week},
     ephemeris::Ephemeris,
     ionoutc::IonoUtc,
+    orbit::{Frame, Motion, StateVector},
+    signal::{Constellation, SatelliteId, is_geostationary},
+    timescale::gps_utc_offset,
 };
 /// Defines the motion mode for the GPS signal simulation.
 ///
@@ -22,170 +29,745 @@ pub enum MotionMode {
     Dynamic,
     // UserControl - Future feature for real-time user-controlled motion
 }
+/// Defines when a newly uploaded ephemeris replaces the previous one.
+///
+/// Real satellites start broadcasting a data set about two hours before its
+/// time of ephemeris, each on its own upload schedule, so that receivers see
+/// the issues of data of the satellites change at different times. The
+/// reference C implementation instead switches all satellites at once to the
+/// data set with the closest time of ephemeris.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Handover {
+    /// The data set with the closest time of ephemeris is broadcast
+    #[default]
+    Closest,
+    /// A data set is broadcast from the given number of seconds before its
+    /// time of ephemeris
+    BeforeToe(f64),
+    /// A data set is broadcast from its transmission time in the navigation
+    /// file, or two hours before its time of ephemeris when unknown
+    TransmissionTime,
+}
+impl FromStr for Handover {
+    type Err = crate::Error;
+
+    /// Parses a handover rule: `closest`, `tom` for the transmission time of
+    /// the messages, or a number of seconds before the time of ephemeris.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "closest" => Ok(Handover::Closest),
+            "tom" | "transmission" => Ok(Handover::TransmissionTime),
+            lead => match lead.parse::<f64>() {
+                Ok(lead) if (0.0..=MAX_EPHEMERIS_AGE).contains(&lead) => {
+                    Ok(Handover::BeforeToe(lead))
+                }
+                _ => Err(crate::Error::invalid_handover(s)),
+            },
+        }
+    }
+}
+/// Defines how the ephemerides are moved to the scenario start time when
+/// their time is overridden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReEpoch {
+    /// The times of clock and of ephemeris are shifted, so that the
+    /// satellites fly over the sky of the original day, as gps-sdr-sim does
+    #[default]
+    Shift,
+    /// Each orbit is propagated to the new reference time with its secular
+    /// rates, without refitting its elements, so that the sky follows the
+    /// real one at the chosen time of day to within a few kilometers a day;
+    /// GLONASS and SBAS state vectors are shifted
+    Propagate,
+}
+impl FromStr for ReEpoch {
+    type Err = crate::Error;
+
+    /// Parses a re-epoch mode: `shift` or `propagate`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "shift" => Ok(ReEpoch::Shift),
+            "propagate" => Ok(ReEpoch::Propagate),
+            _ => Err(crate::Error::invalid_re_epoch(s)),
+        }
+    }
+}
 /// Type alias for the data returned by the `read_navigation_data` function.
 ///
 /// This tuple contains:
-/// - The number of valid ephemeris sets
 /// - Ionospheric and UTC parameters
-/// - A 2D array of ephemeris data organized by time set and satellite PRN
-type Data = (
-    usize,
-    IonoUtc,
-    Box<[[Ephemeris; MAX_SAT]; EPHEM_ARRAY_SIZE]>,
-);
+/// - The GPS, Galileo, BeiDou, GLONASS and QZSS ephemerides of each
+///   satellite
+type Data = (IonoUtc, SatelliteEphemerides);
+
+/// Largest distance from the time of ephemeris at which a data set is used
+/// (seconds), the usual fit interval of broadcast ephemerides
+const MAX_EPHEMERIS_AGE: f64 = 4.0 * SECONDS_IN_HOUR;
+
+/// Time before its time of ephemeris from which a data set of unknown
+/// transmission time is broadcast (seconds), the usual GPS upload cadence
+const HANDOVER_LEAD: f64 = 2.0 * SECONDS_IN_HOUR;
+
+/// Ephemerides of every satellite, indexed by time.
+///
+/// Each satellite has its own list of data sets, ordered by time of
+/// ephemeris, from which the best one is selected at any time
+/// independently of the other satellites, so that scenarios may span as many
+/// data sets as given, whatever the update cycle of each constellation. SBAS
+/// GEO satellites are stored alongside them.
+#[derive(Default)]
+pub struct SatelliteEphemerides {
+    /// GPS ephemerides indexed by PRN - 1
+    gps: Vec<Vec<Ephemeris>>,
+    /// Galileo ephemerides indexed by PRN - 1
+    galileo: Vec<Vec<Ephemeris>>,
+    /// BeiDou ephemerides indexed by PRN - 1
+    beidou: Vec<Vec<Ephemeris>>,
+    /// GLONASS ephemerides indexed by slot number - 1
+    glonass: Vec<Vec<Ephemeris>>,
+    /// SBAS ephemerides indexed by PRN - 120
+    sbas: Vec<Vec<Ephemeris>>,
+    /// QZSS ephemerides indexed by PRN - 193
+    qzss: Vec<Vec<Ephemeris>>,
+}
+impl SatelliteEphemerides {
+    /// Creates an empty store with a list for every GPS, Galileo, BeiDou,
+    /// GLONASS, SBAS and QZSS PRN.
+    pub fn new() -> Self {
+        let lists = |count| std::iter::repeat_with(Vec::new).take(count);
+        Self {
+            gps: lists(MAX_SAT).collect(),
+            galileo: lists(MAX_SAT_GALILEO).collect(),
+            beidou: lists(MAX_SAT_BEIDOU).collect(),
+            glonass: lists(MAX_SAT_GLONASS).collect(),
+            sbas: lists(Constellation::Sbas.prns().count()).collect(),
+            qzss: lists(Constellation::Qzss.prns().count()).collect(),
+        }
+    }
+
+    /// Returns the ephemerides of a satellite, ordered by time of ephemeris.
+    ///
+    /// # Arguments
+    /// * `constellation` - Constellation of the satellite
+    /// * `prn` - Satellite PRN number
+    ///
+    /// # Returns
+    /// The ephemerides, empty for unknown PRNs
+    pub fn get(
+        &self, constellation: Constellation, prn: usize,
+    ) -> &[Ephemeris] {
+        let lists = match constellation {
+            Constellation::Gps => &self.gps,
+            Constellation::Galileo => &self.galileo,
+            Constellation::BeiDou => &self.beidou,
+            Constellation::Glonass => &self.glonass,
+            Constellation::Sbas => &self.sbas,
+            Constellation::Qzss => &self.qzss,
+        };
+        prn.checked_sub(*constellation.prns().start())
+            .and_then(|sv| lists.get(sv))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns whether no ephemeris of a constellation is stored.
+    pub fn is_empty(&self, constellation: Constellation) -> bool {
+        constellation
+            .prns()
+            .all(|prn| self.get(constellation, prn).is_empty())
+    }
+
+    /// Returns the earliest and latest times of clock of the ephemerides of
+    /// some constellations.
+    ///
+    /// # Arguments
+    /// * `constellations` - Constellations whose ephemerides are spanned
+    ///
+    /// # Returns
+    /// The first and last times of clock, or `None` if none of the
+    /// constellations has an ephemeris
+    pub fn toc_span(
+        &self, constellations: &[Constellation],
+    ) -> Option<(GpsTime, GpsTime)> {
+        let tocs = constellations
+            .iter()
+            .flat_map(|&constellation| {
+                constellation
+                    .prns()
+                    .flat_map(move |prn| self.get(constellation, prn))
+            })
+            .map(|eph| &eph.toc);
+        let first = tocs.clone().min_by(|a, b| a.diff_secs(b).total_cmp(&0.0));
+        let last = tocs.max_by(|a, b| a.diff_secs(b).total_cmp(&0.0));
+        Some((first?.clone(), last?.clone()))
+    }
+
+    /// Selects the best ephemeris of a satellite at a given time.
+    ///
+    /// Data sets whose time of ephemeris is more than four hours away are
+    /// not used. Among the others, healthy data sets are preferred, then the
+    /// closest time of ephemeris; of data sets with the same time of
+    /// ephemeris, the data set read last is selected, whatever its issue of
+    /// data, while between two times of ephemeris equally far away the
+    /// earlier one is kept.
+    ///
+    /// # Arguments
+    /// * `constellation` - Constellation of the satellite
+    /// * `prn` - Satellite PRN number
+    /// * `time` - Time at which the ephemeris is used
+    ///
+    /// # Returns
+    /// The index of the ephemeris in [`SatelliteEphemerides::get`], or
+    /// `None` if no data set is close enough
+    pub fn best(
+        &self, constellation: Constellation, prn: usize, time: &GpsTime,
+    ) -> Option<usize> {
+        let list = self.get(constellation, prn);
+        let mut best: Option<(usize, bool, f64)> = None;
+        for (index, eph) in list.iter().enumerate() {
+            let dt = eph.toe.diff_secs(time).abs();
+            if !eph.vflg || dt > MAX_EPHEMERIS_AGE {
+                continue;
+            }
+            let unhealthy = eph.svhlth != 0;
+            let better =
+                best.is_none_or(|(current, current_unhealthy, current_dt)| {
+                    match unhealthy.cmp(&current_unhealthy) {
+                        Ordering::Less => true,
+                        Ordering::Greater => false,
+                        Ordering::Equal => {
+                            dt < current_dt
+                                || list[current].toe.diff_secs(&eph.toe) == 0.0
+                        }
+                    }
+                });
+            if better {
+                best = Some((index, unhealthy, dt));
+            }
+        }
+        best.map(|(index, _, _)| index)
+    }
+
+    /// Selects the ephemeris of a satellite broadcast at a given time.
+    ///
+    /// With [`Handover::Closest`] this is [`SatelliteEphemerides::best`].
+    /// Otherwise each data set is broadcast from its cut-over time, before
+    /// its time of ephemeris; among the data sets whose cut-over time has
+    /// passed and whose time of ephemeris is at most four hours away,
+    /// healthy ones are preferred, then the latest cut-over, then the data
+    /// set read last. Before the first cut-over, the best data set is used.
+    ///
+    /// # Arguments
+    /// * `constellation` - Constellation of the satellite
+    /// * `prn` - Satellite PRN number
+    /// * `time` - Time at which the ephemeris is broadcast
+    /// * `handover` - Rule giving the cut-over time of the data sets
+    ///
+    /// # Returns
+    /// The index of the ephemeris in [`SatelliteEphemerides::get`], or
+    /// `None` if no data set is close enough
+    pub fn current(
+        &self, constellation: Constellation, prn: usize, time: &GpsTime,
+        handover: Handover,
+    ) -> Option<usize> {
+        let lead = match handover {
+            Handover::Closest => return self.best(constellation, prn, time),
+            Handover::BeforeToe(lead) => lead,
+            Handover::TransmissionTime => HANDOVER_LEAD,
+        };
+        let list = self.get(constellation, prn);
+        let mut current: Option<(usize, bool, f64)> = None;
+        for (index, eph) in list.iter().enumerate() {
+            let cut_over = match (handover, &eph.transmission_time) {
+                (Handover::TransmissionTime, Some(transmission)) => {
+                    transmission.diff_secs(time)
+                }
+                _ => eph.toe.diff_secs(time) - lead,
+            };
+            if !eph.vflg
+                || cut_over > 0.0
+                || eph.toe.diff_secs(time).abs() > MAX_EPHEMERIS_AGE
+            {
+                continue;
+            }
+            let unhealthy = eph.svhlth != 0;
+            let better = current.is_none_or(
+                |(_, current_unhealthy, current_cut_over)| match unhealthy
+                    .cmp(&current_unhealthy)
+                {
+                    Ordering::Less => true,
+                    Ordering::Greater => false,
+                    Ordering::Equal => cut_over >= current_cut_over,
+                },
+            );
+            if better {
+                current = Some((index, unhealthy, cut_over));
+            }
+        }
+        current
+            .map(|(index, _, _)| index)
+            .or_else(|| self.best(constellation, prn, time))
+    }
+
+    /// Returns an iterator over all stored ephemerides.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Ephemeris> {
+        self.gps
+            .iter_mut()
+            .chain(self.galileo.iter_mut())
+            .chain(self.beidou.iter_mut())
+            .chain(self.glonass.iter_mut())
+            .chain(self.sbas.iter_mut())
+            .chain(self.qzss.iter_mut())
+            .flatten()
+    }
+
+    /// Returns an iterator over all stored ephemerides with the identity of
+    /// their satellite.
+    pub fn iter_mut_with_id(
+        &mut self,
+    ) -> impl Iterator<Item = (SatelliteId, &mut Ephemeris)> {
+        [
+            (Constellation::Gps, &mut self.gps),
+            (Constellation::Galileo, &mut self.galileo),
+            (Constellation::BeiDou, &mut self.beidou),
+            (Constellation::Glonass, &mut self.glonass),
+            (Constellation::Sbas, &mut self.sbas),
+            (Constellation::Qzss, &mut self.qzss),
+        ]
+        .into_iter()
+        .flat_map(|(constellation, lists)| {
+            let first = *constellation.prns().start();
+            lists.iter_mut().enumerate().flat_map(move |(sv, list)| {
+                let satellite = SatelliteId::new(constellation, first + sv);
+                list.iter_mut().map(move |eph| (satellite, eph))
+            })
+        })
+    }
+
+    /// Inserts an ephemeris in the list of its satellite, keeping the list
+    /// ordered; a data set received several times, with the same time of
+    /// ephemeris and issue of data, is stored once, and another issue of
+    /// data with the time of ephemeris of a stored one is stored after it.
+    ///
+    /// # Returns
+    /// `false` if the PRN is out of range for the constellation
+    pub fn insert(
+        &mut self, constellation: Constellation, prn: usize, eph: Ephemeris,
+    ) -> bool {
+        let lists = match constellation {
+            Constellation::Gps => &mut self.gps,
+            Constellation::Galileo => &mut self.galileo,
+            Constellation::BeiDou => &mut self.beidou,
+            Constellation::Glonass => &mut self.glonass,
+            Constellation::Sbas => &mut self.sbas,
+            Constellation::Qzss => &mut self.qzss,
+        };
+        let Some(list) = prn
+            .checked_sub(*constellation.prns().start())
+            .and_then(|sv| lists.get_mut(sv))
+        else {
+            return false;
+        };
+        if list
+            .iter()
+            .all(|e| e.toe.diff_secs(&eph.toe) != 0.0 || e.iode != eph.iode)
+        {
+            let index =
+                list.partition_point(|e| e.toe.diff_secs(&eph.toe) <= 0.0);
+            list.insert(index, eph);
+        }
+        true
+    }
+}
 
-/// Reads ionospheric/UTC parameters and ephemeris data from a RINEX navigation
-/// file.
+/// Reads ionospheric/UTC parameters and ephemeris data from RINEX navigation
+/// files.
 ///
-/// This function parses a RINEX navigation file to extract:
+/// This function parses RINEX navigation files, possibly compressed, to
+/// extract:
 /// - Satellite ephemeris data (orbit and clock parameters)
 /// - Ionospheric correction parameters
 /// - UTC conversion parameters
 ///
-/// The ephemeris data is organized into hourly sets to allow for
-/// time-appropriate selection during simulation. Each set contains ephemeris
-/// data for all satellites that are valid within approximately the same time
-/// period.
+/// The ephemerides are kept per satellite and ordered by time of ephemeris,
+/// so that the best data set of each satellite can be selected at any time
+/// of the simulation. GPS records and the Galileo, BeiDou, GLONASS and QZSS
+/// records of RINEX 3 and 4 files are read; records of the other systems are
+/// ignored.
+///
+/// Several files, such as consecutive daily files, are merged into one
+/// time-ordered set of records with duplicate data sets removed; the
+/// ionospheric and UTC parameters come from the first file. Malformed records
+/// are skipped with a warning giving their file, line and column.
 ///
 /// # Arguments
-/// * `file` - Reference to a path pointing to a RINEX navigation file
+/// * `files` - Paths to the RINEX navigation files
 ///
 /// # Returns
-/// * `Ok((count, ionoutc, ephemerides))` - A tuple containing:
-///   - `count`: The number of valid ephemeris sets
+/// * `Ok((ionoutc, satellites))` - A tuple containing:
 ///   - `ionoutc`: Ionospheric and UTC parameters
-///   - `ephemerides`: A 2D array of ephemeris data organized by time set and
-///     satellite PRN
+///   - `satellites`: The GPS, Galileo, BeiDou, GLONASS and QZSS ephemerides
+///     of each satellite
 /// * `Err(Error)` - If the file cannot be read or parsed
 ///
 /// # Errors
-/// * Returns an error if the file cannot be opened
+/// * Returns an error if no file is given or a file cannot be opened
 /// * Returns an error if the RINEX format is invalid
-pub fn read_navigation_data(
-    file: &dyn AsRef<Path>,
+pub fn read_navigation_data<P: AsRef<Path>>(
+    files: &[P],
 ) -> Result<Data, crate::Error> {
-    let rinex_data = rinex::Rinex::read_file(file)?;
-    let mut ephemeris_data: Box<[[Ephemeris; MAX_SAT]; EPHEM_ARRAY_SIZE]> =
-        std::array::from_fn(|_| std::array::from_fn(|_| Ephemeris::default()))
-            .into();
+    let mut rinex_data: Option<rinex::Rinex> = None;
+    for file in files {
+        let rinex = rinex::Rinex::read_file(file)?;
+        // Malformed records are skipped by the reader
+        for warning in &rinex.warnings {
+            eprintln!("Warning: {}: {warning}", file.as_ref().display());
+        }
+        match rinex_data.as_mut() {
+            Some(data) => data.merge(rinex),
+            None => rinex_data = Some(rinex),
+        }
+    }
+    let rinex_data = rinex_data.ok_or_else(|| {
+        rinex::Error::rinex_builder("no navigation file given")
+    })?;
+    let mut satellites = SatelliteEphemerides::new();
     let mut iono_utc = IonoUtc::default();
 
     iono_utc.read_from_rinex(&rinex_data);
-    let mut current_set_index = 0;
-    let mut current_set_start_time: Option<GpsTime> = None;
 
     // Iterate through all ephemeris records in RINEX file
     for rinex_record in &rinex_data.ephemerides {
-        // Calculate satellite index (0-based)
-        // PRN (Pseudo-Random Noise) code number, often 1-based in RINEX
-        let Some(sv) = rinex_record.prn.checked_sub(1) else {
-            // Log or handle PRN 0 case if necessary
-            eprintln!(
-                "Warning: Encountered RINEX record with PRN 0, skipping."
-            );
-            continue;
-        };
-        if sv >= MAX_SAT {
-            eprintln!(
-                "Warning: Skipping ephemeris for SV PRN {} (index {}) as it \
-                 exceeds MAX_SAT {}",
-                rinex_record.prn, sv, MAX_SAT
-            );
-            continue;
-        }
-
-        let utc_datetime =
-            DateTime::from(rinex_record.time_of_clock.in_tz("UTC")?);
-        let gps_time = GpsTime::from(&utc_datetime);
-
-        // --- Determine which time set this ephemeris belongs to ---
-        let mut update_set = false;
-        match current_set_start_time {
-            Some(ref start_time) => {
-                // If time difference exceeds 1 hour
-                if gps_time.diff_secs(start_time).abs() > SECONDS_IN_HOUR {
-                    update_set = true;
-                }
+        match rinex_record.system {
+            System::Gps => add_gps_ephemeris(&mut satellites, rinex_record)?,
+            System::Galileo => {
+                add_galileo_ephemeris(&mut satellites, rinex_record)?;
             }
-            None => {
-                // First valid ephemeris record, set current set start time
-                current_set_start_time = Some(gps_time.clone());
-                // No need to update index since we start at index 0
+            System::BeiDou => {
+                add_beidou_ephemeris(&mut satellites, rinex_record)?;
             }
+            System::Qzss => add_qzss_ephemeris(&mut satellites, rinex_record)?,
+            // GLONASS state vectors are read from their own list below
+            System::Glonass => {}
         }
+    }
 
-        if update_set {
-            // Move to next ephemeris set
-            current_set_index += 1;
-            // Check if new set index exceeds bounds
-            if current_set_index >= EPHEM_ARRAY_SIZE {
-                println!(
-                    "Warning: Reached maximum ephemeris sets \
-                     ({EPHEM_ARRAY_SIZE}). Stopping processing."
-                );
-                break; // Stop processing more records
-            }
-            // Update the start time for the new ephemeris set
-            current_set_start_time = Some(gps_time.clone());
-        }
-        // --- End set index logic ---
-
-        // Get a mutable reference to the target Ephemeris structure to populate
-        // current_set_index is guaranteed to be in bounds here
-        let eph = &mut ephemeris_data[current_set_index][sv];
-        eph.t = utc_datetime;
-        eph.toc = gps_time;
-        eph.af0 = rinex_record.sv_clock.bias;
-        eph.af1 = rinex_record.sv_clock.drift;
-        eph.af2 = rinex_record.sv_clock.drift_rate;
-
-        // orbit1
-        eph.iode = rinex_record.orbit1.iode as i32;
-        eph.crs = rinex_record.orbit1.crs;
-        eph.deltan = rinex_record.orbit1.delta_n;
-        eph.m0 = rinex_record.orbit1.m0;
-
-        // orbit2
-        eph.cuc = rinex_record.orbit2.cuc;
-        eph.ecc = rinex_record.orbit2.ecc;
-        eph.cus = rinex_record.orbit2.cus;
-        eph.sqrta = rinex_record.orbit2.sqrta;
-
-        // orbit3
-        eph.toe.sec = rinex_record.orbit3.toe;
-        eph.cic = rinex_record.orbit3.cic;
-        eph.omg0 = rinex_record.orbit3.omega;
-        eph.cis = rinex_record.orbit3.cis;
-
-        // orbit4
-        eph.inc0 = rinex_record.orbit4.i0;
-        eph.crc = rinex_record.orbit4.crc;
-        eph.aop = rinex_record.orbit4.omega;
-        eph.omgdot = rinex_record.orbit4.omega_dot;
-
-        // orbit5
-        eph.idot = rinex_record.orbit5.idot;
-        eph.codeL2 = rinex_record.orbit5.code_l2 as i32;
-        eph.toe.week = rinex_record.orbit5.week as i32;
-
-        // orbit6
-        eph.svhlth = rinex_record.orbit6.sv_health as i32;
-        if eph.svhlth > 0 && eph.svhlth < 32 {
-            eph.svhlth += 32;
+    // The leap seconds of the header, when given, only cross-check the
+    // offset of the date
+    if let Some(rinex_record) = rinex_data.glonass_ephemerides.first() {
+        let utc = DateTime::from(rinex_record.time_of_clock.in_tz("UTC")?);
+        let offset = gps_utc_offset(&utc) as i32;
+        if rinex_data.leap_seconds != 0 && rinex_data.leap_seconds != offset {
+            eprintln!(
+                "Warning: The navigation file gives {} leap seconds instead \
+                 of {offset}; GLONASS times use {offset}.",
+                rinex_data.leap_seconds
+            );
         }
-        eph.tgd = rinex_record.orbit6.tgd;
-        eph.iodc = rinex_record.orbit6.iodc as i32;
+    }
+    for rinex_record in &rinex_data.glonass_ephemerides {
+        add_glonass_ephemeris(&mut satellites, rinex_record)?;
+    }
+
+    Ok((iono_utc, satellites))
+}
+
+/// Adds a GPS RINEX record to the ephemerides of its satellite.
+///
+/// Health flags of the summary form (1-31) are converted to the six-bit form
+/// of the LNAV message. A data set received several times is stored once.
+///
+/// # Arguments
+/// * `satellites` - Ephemerides of all constellations
+/// * `rinex_record` - GPS record of a RINEX navigation file
+///
+/// # Errors
+/// * Returns an error if the time of clock cannot be converted
+fn add_gps_ephemeris(
+    satellites: &mut SatelliteEphemerides,
+    rinex_record: &rinex::ephemeris::Ephemeris,
+) -> Result<(), crate::Error> {
+    let utc_datetime = DateTime::from(rinex_record.time_of_clock.in_tz("UTC")?);
+    let gps_time = GpsTime::from(&utc_datetime);
+    let mut eph = Ephemeris::default();
+    read_record(&mut eph, rinex_record, utc_datetime, gps_time, GM_EARTH);
+    // Some writers give the week modulo 1024; the time of clock is a date
+    eph.toe.week = full_week(eph.toe.week, eph.toc.week);
+    eph.svhlth = rinex_record.orbit6.sv_health as i32;
+    if eph.svhlth > 0 && eph.svhlth < 32 {
+        eph.svhlth += 32;
+    }
+    eph.tgd = rinex_record.orbit6.tgd;
+    eph.iodc = rinex_record.orbit6.iodc as i32;
+    eph.transmission_time =
+        transmission_time(rinex_record.orbit7.tom, &eph.toe);
+    if !satellites.insert(Constellation::Gps, rinex_record.prn, eph) {
+        eprintln!(
+            "Warning: Skipping GPS ephemeris for PRN {} outside of the L1 \
+             C/A codes 1-{MAX_SAT}.",
+            rinex_record.prn
+        );
+    }
+    Ok(())
+}
+
+/// Resolves the transmission time of a GPS or QZSS message.
+///
+/// RINEX gives it in seconds of the week of the time of ephemeris, possibly
+/// negative when the message was sent the week before, and flags unknown
+/// values with 0.9999E9.
+///
+/// # Arguments
+/// * `tom` - Transmission time of message of the record (seconds of week)
+/// * `toe` - Time of ephemeris of the record
+///
+/// # Returns
+/// The transmission time, or `None` if it is unknown
+fn transmission_time(tom: f64, toe: &GpsTime) -> Option<GpsTime> {
+    if !(-SECONDS_IN_WEEK..SECONDS_IN_WEEK).contains(&tom) {
+        return None;
+    }
+    let time = GpsTime {
+        week: toe.week,
+        sec: 0.0,
+    }
+    .add_secs(tom);
+    // Some writers give the week of transmission rather than of the toe
+    if time.diff_secs(toe) > SECONDS_IN_WEEK / 2.0 {
+        Some(time.add_secs(-SECONDS_IN_WEEK))
+    } else {
+        Some(time)
+    }
+}
+
+/// Adds a Galileo RINEX record to the ephemerides of its satellite.
+///
+/// Only I/NAV data sets are kept, as they are the ones broadcast on E1; a
+/// data set received several times is stored once.
+///
+/// # Arguments
+/// * `satellites` - Ephemerides of all constellations
+/// * `rinex_record` - Galileo record of a RINEX 3 navigation file
+///
+/// # Errors
+/// * Returns an error if the time of clock cannot be converted
+fn add_galileo_ephemeris(
+    satellites: &mut SatelliteEphemerides,
+    rinex_record: &rinex::ephemeris::Ephemeris,
+) -> Result<(), crate::Error> {
+    // Data sources: bit 0 I/NAV E1-B, bit 1 F/NAV E5a-I, bit 2 I/NAV E5b-I
+    let source = rinex_record.orbit5.code_l2 as u32;
+    if source & 0b101 == 0 && source & 0b10 != 0 {
+        return Ok(());
+    }
+    let utc_datetime = DateTime::from(rinex_record.time_of_clock.in_tz("UTC")?);
+    let gps_time = GpsTime::from(&utc_datetime);
+    let mut eph = Ephemeris::default();
+    read_record(
+        &mut eph,
+        rinex_record,
+        utc_datetime,
+        gps_time,
+        GM_EARTH_GALILEO,
+    );
+    // The raw health bits are kept for the I/NAV encoder
+    eph.svhlth = rinex_record.orbit6.sv_health as i32;
+    // BGD E5b/E1, the group delay applying to E1 single-frequency users
+    eph.tgd = rinex_record.orbit6.iodc;
+    if !satellites.insert(Constellation::Galileo, rinex_record.prn, eph) {
+        eprintln!(
+            "Warning: Skipping Galileo ephemeris for PRN {}.",
+            rinex_record.prn
+        );
+    }
+    Ok(())
+}
+
+/// Adds a BeiDou RINEX record to the ephemerides of its satellite.
+///
+/// The epoch and the time of ephemeris of the record are in BeiDou Time
+/// (BDT) and are converted to GPS time; the offset is kept in the ephemeris
+/// for the orbit model. A data set received several times is stored once.
+///
+/// # Arguments
+/// * `satellites` - Ephemerides of all constellations
+/// * `rinex_record` - BeiDou record of a RINEX 3 navigation file
+///
+/// # Errors
+/// * Returns an error if the time of clock cannot be converted
+fn add_beidou_ephemeris(
+    satellites: &mut SatelliteEphemerides,
+    rinex_record: &rinex::ephemeris::Ephemeris,
+) -> Result<(), crate::Error> {
+    let utc_datetime = DateTime::from(rinex_record.time_of_clock.in_tz("UTC")?);
+    let gps_time = GpsTime::from(&utc_datetime).add_secs(BDT_GPST_OFFSET);
+    let mut eph = Ephemeris::default();
+    read_record(
+        &mut eph,
+        rinex_record,
+        DateTime::from(&gps_time),
+        gps_time,
+        GM_EARTH_BEIDOU,
+    );
+    eph.toe = GpsTime {
+        week: eph.toe.week + BDT_WEEK_OFFSET,
+        sec: eph.toe.sec,
+    }
+    .add_secs(BDT_GPST_OFFSET);
+    eph.time_offset = -BDT_GPST_OFFSET;
+    eph.geostationary = is_geostationary(rinex_record.prn);
+    eph.svhlth = rinex_record.orbit6.sv_health as i32;
+    // TGD1, the B1I group delay
+    eph.tgd = rinex_record.orbit6.tgd;
+    // The age of data, clock is stored in the first spare field
+    eph.iodc = rinex_record.orbit7.spare1 as i32;
+    if !satellites.insert(Constellation::BeiDou, rinex_record.prn, eph) {
+        eprintln!(
+            "Warning: Skipping BeiDou ephemeris for PRN {}.",
+            rinex_record.prn
+        );
+    }
+    Ok(())
+}
 
-        // Set valid flag
-        eph.vflg = true;
-        eph.A = eph.sqrta * eph.sqrta;
-        eph.n = (GM_EARTH / (eph.A * eph.A * eph.A)).sqrt() + eph.deltan;
-        eph.sq1e2 = (1.0 - eph.ecc * eph.ecc).sqrt();
-        eph.omgkdot = eph.omgdot - OMEGA_EARTH;
+/// Adds a QZSS RINEX record to the ephemerides of its satellite.
+///
+/// QZSS records share the layout and the time scale of GPS records; the
+/// satellite number of the record is converted to the L1 C/A PRN (J01 is
+/// PRN 193). The health bits are kept as broadcast in the LNAV message. A
+/// data set received several times is stored once.
+///
+/// # Arguments
+/// * `satellites` - Ephemerides of all constellations
+/// * `rinex_record` - QZSS record of a RINEX 3 navigation file
+///
+/// # Errors
+/// * Returns an error if the time of clock cannot be converted
+fn add_qzss_ephemeris(
+    satellites: &mut SatelliteEphemerides,
+    rinex_record: &rinex::ephemeris::Ephemeris,
+) -> Result<(), crate::Error> {
+    let utc_datetime = DateTime::from(rinex_record.time_of_clock.in_tz("UTC")?);
+    let gps_time = GpsTime::from(&utc_datetime);
+    let mut eph = Ephemeris::default();
+    read_record(&mut eph, rinex_record, utc_datetime, gps_time, GM_EARTH);
+    // Some writers give the week modulo 1024; the time of clock is a date
+    eph.toe.week = full_week(eph.toe.week, eph.toc.week);
+    eph.svhlth = rinex_record.orbit6.sv_health as i32;
+    eph.tgd = rinex_record.orbit6.tgd;
+    eph.iodc = rinex_record.orbit6.iodc as i32;
+    eph.transmission_time =
+        transmission_time(rinex_record.orbit7.tom, &eph.toe);
+    let prn = rinex_record.prn + MIN_PRN_QZSS - 1;
+    if !satellites.insert(Constellation::Qzss, prn, eph) {
+        eprintln!(
+            "Warning: Skipping QZSS ephemeris for J{:02}.",
+            rinex_record.prn
+        );
     }
+    Ok(())
+}
 
-    if current_set_start_time.is_some() {
-        current_set_index += 1;
+/// Adds a GLONASS RINEX record to the ephemerides of its satellite.
+///
+/// The reference time of the record is in UTC and is converted to GPS time
+/// with the leap seconds in effect at its date. The state vector is kept in
+/// PZ-90 and converted from kilometers to meters; the clock parameters map
+/// to `af0` = -τn and `af1` = γn with `toc` = `toe` = tb.
+///
+/// # Arguments
+/// * `satellites` - Ephemerides of all constellations
+/// * `rinex_record` - GLONASS record of a RINEX 3 navigation file
+///
+/// # Errors
+/// * Returns an error if the reference time cannot be converted
+pub fn add_glonass_ephemeris(
+    satellites: &mut SatelliteEphemerides, rinex_record: &GlonassEphemeris,
+) -> Result<(), crate::Error> {
+    let utc_datetime = DateTime::from(rinex_record.time_of_clock.in_tz("UTC")?);
+    let gps_time =
+        GpsTime::from(&utc_datetime).add_secs(gps_utc_offset(&utc_datetime));
+    let to_meters = |v: [f64; 3]| v.map(|x| x * 1e3);
+    let eph = Ephemeris {
+        vflg: true,
+        t: DateTime::from(&gps_time),
+        toc: gps_time.clone(),
+        toe: gps_time,
+        af0: rinex_record.clock_bias,
+        af1: rinex_record.relative_frequency_bias,
+        svhlth: rinex_record.health as i32,
+        // The age of the immediate data is kept for the navigation message
+        iodc: rinex_record.age as i32,
+        state_vector: Some(StateVector {
+            pos: to_meters(rinex_record.position),
+            vel: to_meters(rinex_record.velocity),
+            acc: to_meters(rinex_record.acceleration),
+            frame: Frame::Pz90,
+            motion: Motion::Integrated,
+        }),
+        frequency_channel: rinex_record.frequency_channel,
+        ..Default::default()
+    };
+    if !satellites.insert(Constellation::Glonass, rinex_record.prn, eph) {
+        eprintln!(
+            "Warning: Skipping GLONASS ephemeris for PRN {}.",
+            rinex_record.prn
+        );
     }
-    Ok((current_set_index, iono_utc, ephemeris_data))
+    Ok(())
+}
+
+/// Copies the orbit and clock parameters of a RINEX record into an ephemeris
+/// and derives its working variables.
+///
+/// # Arguments
+/// * `eph` - Ephemeris to populate
+/// * `rinex_record` - Record of a RINEX navigation file
+/// * `utc_datetime` - Time of clock as a UTC date
+/// * `gps_time` - Time of clock as GPS time
+/// * `gm` - Gravitational constant of the system's reference frame (m³/s²)
+fn read_record(
+    eph: &mut Ephemeris, rinex_record: &rinex::ephemeris::Ephemeris,
+    utc_datetime: DateTime, gps_time: GpsTime, gm: f64,
+) {
+    eph.t = utc_datetime;
+    eph.toc = gps_time;
+    eph.af0 = rinex_record.sv_clock.bias;
+    eph.af1 = rinex_record.sv_clock.drift;
+    eph.af2 = rinex_record.sv_clock.drift_rate;
+
+    // orbit1
+    eph.iode = rinex_record.orbit1.iode as i32;
+    eph.crs = rinex_record.orbit1.crs;
+    eph.deltan = rinex_record.orbit1.delta_n;
+    eph.m0 = rinex_record.orbit1.m0;
+
+    // orbit2
+    eph.cuc = rinex_record.orbit2.cuc;
+    eph.ecc = rinex_record.orbit2.ecc;
+    eph.cus = rinex_record.orbit2.cus;
+    eph.sqrta = rinex_record.orbit2.sqrta;
+
+    // orbit3
+    eph.toe.sec = rinex_record.orbit3.toe;
+    eph.cic = rinex_record.orbit3.cic;
+    eph.omg0 = rinex_record.orbit3.omega;
+    eph.cis = rinex_record.orbit3.cis;
+
+    // orbit4
+    eph.inc0 = rinex_record.orbit4.i0;
+    eph.crc = rinex_record.orbit4.crc;
+    eph.aop = rinex_record.orbit4.omega;
+    eph.omgdot = rinex_record.orbit4.omega_dot;
+
+    // orbit5
+    eph.idot = rinex_record.orbit5.idot;
+    eph.codeL2 = rinex_record.orbit5.code_l2 as i32;
+    eph.toe.week = rinex_record.orbit5.week as i32;
+
+    // Set valid flag
+    eph.vflg = true;
+    eph.A = eph.sqrta * eph.sqrta;
+    eph.n = (gm / (eph.A * eph.A * eph.A)).sqrt() + eph.deltan;
+    eph.sq1e2 = (1.0 - eph.ecc * eph.ecc).sqrt();
+    eph.omgkdot = eph.omgdot - OMEGA_EARTH;
 }
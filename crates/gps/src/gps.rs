@@ -1,10 +1,11 @@
-//! GPS L1 C/A signal generator and simulator for software-defined radio
+//! GPS signal generator and simulator for software-defined radio
 //! applications.
 //!
-//! This crate provides functionality to generate GPS L1 C/A signals that can be
-//! transmitted through software-defined radio devices. It simulates GPS
-//! satellite signals based on ephemeris data from RINEX navigation files and
-//! user-defined receiver positions.
+//! This crate provides functionality to generate GPS L1 C/A signals, and
+//! optionally further signals such as L2C, L5, Galileo E1, BeiDou B1I,
+//! GLONASS L1OF, SBAS L1 or QZSS L1 C/A, that can be transmitted through
+//! software-defined radio devices. It simulates satellite signals based on ephemeris data from
+//! RINEX navigation files and user-defined receiver positions.
 //!
 //! The main entry point is the `SignalGeneratorBuilder` which allows
 //! configuring all aspects of the simulation before generating the signal with
@@ -26,11 +27,23 @@ mod generator;
 mod io;
 /// Ionospheric and UTC parameter handling
 mod ionoutc;
+/// State-vector orbit propagation and PZ-90/WGS-84 frames
+mod orbit;
+/// Precise orbit and clock interpolation from SP3 files
+mod precise;
 /// Satellite position and velocity propagation
 mod propagation;
+/// Multi-band GNSS signal definitions and generic signal channels
+mod signal;
 /// Lookup tables for signal generation
 mod table;
+/// Time scales, leap seconds and their conversions
+mod timescale;
 
 pub use error::Error;
-pub use generator::{MotionMode, SignalGenerator, SignalGeneratorBuilder};
+pub use generator::{
+    Handover, MotionMode, ReEpoch, SignalGenerator, SignalGeneratorBuilder,
+};
 pub use io::DataFormat;
+pub use parsing::Interpolation;
+pub use signal::{Band, Constellation, MemoryCodes, SatelliteId, Signal};
@@ -98,7 +98,7 @@ impl IQWriter {
         match self.format {
             DataFormat::Bits1 => {
                 // For 1-bit format, pack 8 samples into each byte
-                let mut iq8_buff = vec![0; self.buffer_size / 4];
+                let mut iq8_buff = vec![0; self.buffer_size.div_ceil(4)];
                 for isamp in 0..2 * self.buffer_size {
                     if isamp % 8 == 0 {
                         iq8_buff[isamp / 8] = 0;
@@ -122,7 +122,7 @@ impl IQWriter {
                 unsafe {
                     self.writer.write_all(std::slice::from_raw_parts(
                         iq8_buff.as_ptr().cast::<u8>(),
-                        self.buffer_size / 4,
+                        iq8_buff.len(),
                     ))?;
                 }
             }
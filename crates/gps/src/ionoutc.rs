@@ -1,3 +1,7 @@
+use constants::SECONDS_IN_DAY;
+
+use crate::datetime::{DateTime, GpsTime};
+
 /// Ionospheric and UTC parameters from the GPS navigation message.
 ///
 /// This structure contains parameters for the Klobuchar ionospheric model and
@@ -51,6 +55,18 @@ pub struct IonoUtc {
     /// Used in the Klobuchar ionospheric model
     pub beta3: f64,
 
+    /// Galileo effective ionisation level 1st order parameter (sfu)
+    /// Broadcast in the I/NAV message for the `NeQuick` model
+    pub ai0: f64,
+
+    /// Galileo effective ionisation level 2nd order parameter (sfu/degree)
+    /// Broadcast in the I/NAV message for the `NeQuick` model
+    pub ai1: f64,
+
+    /// Galileo effective ionisation level 3rd order parameter (sfu/degree²)
+    /// Broadcast in the I/NAV message for the `NeQuick` model
+    pub ai2: f64,
+
     /// UTC constant term of polynomial (seconds)
     /// Used for GPS to UTC time conversion
     pub A0: f64,
@@ -102,6 +118,11 @@ impl IonoUtc {
         self.beta1 = rinex.ion_beta[1];
         self.beta2 = rinex.ion_beta[2];
         self.beta3 = rinex.ion_beta[3];
+        if let Some([ai0, ai1, ai2]) = rinex.ion_galileo {
+            self.ai0 = ai0;
+            self.ai1 = ai1;
+            self.ai2 = ai2;
+        }
 
         // Extract UTC parameters
         self.A0 = rinex.delta_utc.a0;
@@ -113,4 +134,49 @@ impl IonoUtc {
         // Set validity flag (tot should be a multiple of 4096 seconds)
         self.vflg = self.tot % 4096 == 0;
     }
+
+    /// Returns the GPS time from which the future leap seconds apply.
+    ///
+    /// The leap second is inserted, or removed, at the end of the UTC day
+    /// `day_number` of week `wnlsf`, so the new offset applies from the
+    /// start of the next UTC day.
+    ///
+    /// # Returns
+    /// The GPS time of the event, or `None` if no leap second event is set
+    /// or it has already been applied to `dtls`
+    pub fn leap_second_event(&self) -> Option<GpsTime> {
+        (self.leapen == 1 && self.dtlsf != self.dtls).then(|| {
+            GpsTime {
+                week: self.wnlsf,
+                sec: f64::from(self.day_number) * SECONDS_IN_DAY,
+            }
+            .add_secs(f64::from(self.dtlsf))
+        })
+    }
+
+    /// Converts a GPS time to UTC with the broadcast leap seconds.
+    ///
+    /// The leap seconds of a pending event apply from its time, and the
+    /// seconds inserted before it read 23:59:60. The polynomial terms `A0`
+    /// and `A1`, below a microsecond, are not applied.
+    ///
+    /// # Arguments
+    /// * `time` - GPS time to convert
+    ///
+    /// # Returns
+    /// The UTC date and time
+    pub fn utc(&self, time: &GpsTime) -> DateTime {
+        let Some(event) = self.leap_second_event() else {
+            return DateTime::from(&time.add_secs(-f64::from(self.dtls)));
+        };
+        let inserted = f64::from(self.dtlsf - self.dtls);
+        let dt = time.diff_secs(&event);
+        if dt >= 0.0 || dt < -inserted {
+            let dtls = if dt >= 0.0 { self.dtlsf } else { self.dtls };
+            return DateTime::from(&time.add_secs(-f64::from(dtls)));
+        }
+        let mut utc = DateTime::from(&time.add_secs(-f64::from(self.dtlsf)));
+        utc.sec += inserted;
+        utc
+    }
 }
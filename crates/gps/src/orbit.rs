@@ -0,0 +1,209 @@
+//! State-vector orbits and terrestrial reference frames.
+//!
+//! GLONASS satellites broadcast their position, velocity and lunisolar
+//! acceleration at a reference time instead of Keplerian elements. Their
+//! orbit is obtained by integrating the equations of motion of the GLONASS
+//! ICD (edition 5.1, appendix A.3.1.2) with a fourth-order Runge-Kutta
+//! scheme. The broadcast state vectors are expressed in PZ-90, while the
+//! receiver positions of the simulator are WGS-84 coordinates, so this module
+//! also converts between the two frames.
+//!
+//! SBAS GEO satellites also broadcast a state vector (RTCA DO-229, message
+//! type 9), which is extrapolated with a second-order polynomial in WGS-84.
+
+use constants::{
+    GM_EARTH_GLONASS, J2_GLONASS, OMEGA_EARTH_GLONASS, RE_GLONASS,
+};
+
+/// Maximum integration step in seconds
+const STEP: f64 = 60.0;
+
+/// Arc second in radians
+const ARC_SECOND: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+
+/// Helmert parameters from PZ-90.11 to WGS-84 (G1150): translations (m),
+/// rotations (arc seconds) and scale difference
+const PZ90_TO_WGS84: ([f64; 3], [f64; 3], f64) = (
+    [-0.013, 0.106, 0.022],
+    [-0.002_3, 0.003_54, -0.004_21],
+    -0.008e-6,
+);
+
+/// Terrestrial reference frame of satellite coordinates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Frame {
+    /// World Geodetic System 1984, the frame of GPS and of the receiver
+    #[default]
+    Wgs84,
+    /// Parametry Zemli 1990 (PZ-90.11), the frame of GLONASS ephemerides
+    Pz90,
+}
+impl Frame {
+    /// Converts a position and velocity from this frame to WGS-84.
+    ///
+    /// # Arguments
+    /// * `pos` - Position in this frame (meters)
+    /// * `vel` - Velocity in this frame (meters/second)
+    ///
+    /// # Returns
+    /// The WGS-84 position (meters) and velocity (meters/second)
+    pub fn to_wgs84(
+        self, pos: &[f64; 3], vel: &[f64; 3],
+    ) -> ([f64; 3], [f64; 3]) {
+        match self {
+            Frame::Wgs84 => (*pos, *vel),
+            Frame::Pz90 => helmert(pos, vel, 1.0),
+        }
+    }
+
+    /// Converts a position and velocity from WGS-84 to this frame.
+    ///
+    /// # Arguments
+    /// * `pos` - WGS-84 position (meters)
+    /// * `vel` - WGS-84 velocity (meters/second)
+    ///
+    /// # Returns
+    /// The position (meters) and velocity (meters/second) in this frame
+    pub fn convert_from_wgs84(
+        self, pos: &[f64; 3], vel: &[f64; 3],
+    ) -> ([f64; 3], [f64; 3]) {
+        match self {
+            Frame::Wgs84 => (*pos, *vel),
+            Frame::Pz90 => helmert(pos, vel, -1.0),
+        }
+    }
+}
+
+/// Applies the small-angle Helmert transformation from PZ-90.11 to WGS-84
+/// (`sign` = 1) or its inverse (`sign` = -1).
+///
+/// The rotations follow the coordinate frame convention of GOST 32453-2017.
+/// Velocities are rotated and scaled only.
+fn helmert(pos: &[f64; 3], vel: &[f64; 3], sign: f64) -> ([f64; 3], [f64; 3]) {
+    let (t, r, m) = PZ90_TO_WGS84;
+    let [rx, ry, rz] = r.map(|angle| sign * angle * ARC_SECOND);
+    let scale = 1.0 + sign * m;
+    let transform = |v: &[f64; 3]| {
+        [
+            scale * (v[0] + rz * v[1] - ry * v[2]),
+            scale * (-rz * v[0] + v[1] + rx * v[2]),
+            scale * (ry * v[0] - rx * v[1] + v[2]),
+        ]
+    };
+    let mut pos = transform(pos);
+    for (p, t) in pos.iter_mut().zip(t) {
+        *p += sign * t;
+    }
+    (pos, transform(vel))
+}
+
+/// Model used to propagate a state vector from its reference time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Motion {
+    /// Numerical integration of the GLONASS equations of motion
+    #[default]
+    Integrated,
+    /// Second-order polynomial in time, the SBAS GEO model
+    Polynomial,
+}
+
+/// Broadcast state vector of a satellite.
+#[derive(Debug, Clone, Default)]
+pub struct StateVector {
+    /// Position at the reference time (meters)
+    pub pos: [f64; 3],
+    /// Velocity at the reference time (meters/second)
+    pub vel: [f64; 3],
+    /// Lunisolar acceleration for integrated state vectors, total
+    /// acceleration for polynomial ones, constant over the fit interval
+    /// (meters/second²)
+    pub acc: [f64; 3],
+    /// Frame of the state vector
+    pub frame: Frame,
+    /// Propagation model of the state vector
+    pub motion: Motion,
+}
+impl StateVector {
+    /// Propagates the state vector and converts it to WGS-84.
+    ///
+    /// # Arguments
+    /// * `tk` - Time since the reference time (seconds)
+    ///
+    /// # Returns
+    /// The WGS-84 position (meters) and velocity (meters/second)
+    pub fn position_velocity(&self, tk: f64) -> ([f64; 3], [f64; 3]) {
+        let (pos, vel) = self.propagate(tk);
+        self.frame.to_wgs84(&pos, &vel)
+    }
+
+    /// Propagates the state vector from the reference time.
+    ///
+    /// Integrated state vectors use fourth-order Runge-Kutta steps of at
+    /// most 60 seconds; polynomial ones are extrapolated with their constant
+    /// acceleration.
+    ///
+    /// # Arguments
+    /// * `tk` - Time since the reference time (seconds)
+    ///
+    /// # Returns
+    /// The position (meters) and velocity (meters/second) in the frame of
+    /// the state vector
+    pub fn propagate(&self, tk: f64) -> ([f64; 3], [f64; 3]) {
+        if self.motion == Motion::Polynomial {
+            return (
+                std::array::from_fn(|i| {
+                    self.pos[i] + self.vel[i] * tk + 0.5 * self.acc[i] * tk * tk
+                }),
+                std::array::from_fn(|i| self.vel[i] + self.acc[i] * tk),
+            );
+        }
+        let mut x = [
+            self.pos[0],
+            self.pos[1],
+            self.pos[2],
+            self.vel[0],
+            self.vel[1],
+            self.vel[2],
+        ];
+        let steps = (tk.abs() / STEP).ceil().max(1.0);
+        let h = tk / steps;
+        for _ in 0..steps as usize {
+            let k1 = self.derivative(&x);
+            let k2 = self.derivative(&add(&x, &k1, h / 2.0));
+            let k3 = self.derivative(&add(&x, &k2, h / 2.0));
+            let k4 = self.derivative(&add(&x, &k3, h));
+            for i in 0..6 {
+                x[i] += h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+            }
+        }
+        ([x[0], x[1], x[2]], [x[3], x[4], x[5]])
+    }
+
+    /// Returns the time derivative of a state (position, velocity) in the
+    /// rotating Earth-fixed frame: central gravity with the J2 term,
+    /// centrifugal and Coriolis accelerations, and the broadcast lunisolar
+    /// acceleration.
+    fn derivative(&self, x: &[f64; 6]) -> [f64; 6] {
+        let r2 = x[0] * x[0] + x[1] * x[1] + x[2] * x[2];
+        let r3 = r2 * r2.sqrt();
+        let mu = GM_EARTH_GLONASS / r3;
+        let j2 = 1.5 * J2_GLONASS * GM_EARTH_GLONASS * RE_GLONASS * RE_GLONASS
+            / (r2 * r3);
+        let z2 = 5.0 * x[2] * x[2] / r2;
+        let w2 = OMEGA_EARTH_GLONASS * OMEGA_EARTH_GLONASS;
+        let a = -mu - j2 * (1.0 - z2);
+        [
+            x[3],
+            x[4],
+            x[5],
+            (a + w2) * x[0] + 2.0 * OMEGA_EARTH_GLONASS * x[4] + self.acc[0],
+            (a + w2) * x[1] - 2.0 * OMEGA_EARTH_GLONASS * x[3] + self.acc[1],
+            (-mu - j2 * (3.0 - z2)) * x[2] + self.acc[2],
+        ]
+    }
+}
+
+/// Returns `x + h * dx`.
+fn add(x: &[f64; 6], dx: &[f64; 6], h: f64) -> [f64; 6] {
+    std::array::from_fn(|i| x[i] + h * dx[i])
+}
@@ -0,0 +1,246 @@
+//! Precise satellite orbits and clocks interpolated from SP3 files.
+//!
+//! Broadcast ephemerides are a fit of the orbit over a few hours and are off
+//! by about a meter; the orbits and clocks estimated afterwards by analysis
+//! centres and distributed as SP3 files are accurate to a few centimeters.
+//! When they are loaded, they give the true position and clock of the
+//! satellites while the broadcast ephemerides are still encoded in the
+//! navigation messages, so that a receiver sees the same broadcast errors as
+//! on the real day.
+//!
+//! Positions are interpolated with a Lagrange polynomial over the ten samples
+//! around the requested time, which keeps the interpolation error at the
+//! millimeter level for 15-minute samples, and the velocity is the
+//! derivative of that polynomial. Clock offsets are interpolated linearly
+//! between the two adjacent samples, as their random-walk behaviour does not
+//! suit polynomials. SP3 positions refer to the centre of mass of the
+//! satellite; the antenna phase centre offset is not applied.
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use constants::{BDT_GPST_OFFSET, MIN_PRN_QZSS, TAI_GPST_OFFSET};
+use rinex::{ephemeris::System, sp3::Sp3};
+
+use crate::{
+    Error,
+    datetime::{DateTime, GpsTime},
+    signal::{Constellation, SatelliteId},
+};
+
+/// Number of samples of the Lagrange interpolation of positions
+const LAGRANGE_POINTS: usize = 10;
+
+/// Largest interval between two samples bridged by the interpolation
+/// (seconds); a satellite is missing from the data across larger gaps
+const MAX_SAMPLE_GAP: f64 = 1800.0;
+
+/// Interpolated position (meters), velocity (meters/second) and, when
+/// available, clock bias (seconds) and drift (seconds/second) of a satellite.
+type PreciseState = ([f64; 3], [f64; 3], Option<[f64; 2]>);
+
+/// Precise orbit and clock samples of one satellite.
+pub struct PreciseOrbit {
+    /// GPS time of the first sample
+    start: GpsTime,
+    /// Times of the samples in seconds from `start`, in increasing order
+    times: Vec<f64>,
+    /// Earth-fixed positions of the samples (meters)
+    positions: Vec<[f64; 3]>,
+    /// Clock offsets of the samples (seconds), `None` where missing
+    clocks: Vec<Option<f64>>,
+}
+impl PreciseOrbit {
+    /// Creates an orbit from its samples.
+    ///
+    /// # Arguments
+    /// * `samples` - GPS time, position (meters) and clock offset (seconds)
+    ///   of each sample, in any order
+    ///
+    /// # Returns
+    /// The orbit, or `None` if there are no samples
+    pub fn new(
+        mut samples: Vec<(GpsTime, [f64; 3], Option<f64>)>,
+    ) -> Option<Self> {
+        let start = samples
+            .iter()
+            .map(|(time, _, _)| time)
+            .min_by(|a, b| a.diff_secs(b).total_cmp(&0.0))?
+            .clone();
+        samples.sort_by(|(a, _, _), (b, _, _)| {
+            a.diff_secs(&start).total_cmp(&b.diff_secs(&start))
+        });
+        Some(Self {
+            times: samples
+                .iter()
+                .map(|(time, _, _)| time.diff_secs(&start))
+                .collect(),
+            positions: samples
+                .iter()
+                .map(|(_, position, _)| *position)
+                .collect(),
+            clocks: samples.iter().map(|(_, _, clock)| *clock).collect(),
+            start,
+        })
+    }
+
+    /// Interpolates the position, velocity and clock of the satellite.
+    ///
+    /// # Arguments
+    /// * `time` - GPS time at which to interpolate
+    ///
+    /// # Returns
+    /// A tuple containing:
+    /// * `[f64; 3]` - Position in ECEF coordinates (meters)
+    /// * `[f64; 3]` - Velocity in ECEF coordinates (meters/second)
+    /// * `Option<[f64; 2]>` - Clock bias (seconds) and drift
+    ///   (seconds/second), `None` if a sample around `time` has no clock
+    ///
+    /// or `None` if `time` is outside the samples or the samples around it
+    /// are too far apart
+    pub fn state(&self, time: &GpsTime) -> Option<PreciseState> {
+        let t = time.diff_secs(&self.start);
+        let count = self.times.len();
+        if count < 2 || t < self.times[0] || t > self.times[count - 1] {
+            return None;
+        }
+        // Samples bracketing `time`, and the window of the interpolation
+        let after = self.times.partition_point(|&ti| ti < t).max(1);
+        let before = after - 1;
+        let points = LAGRANGE_POINTS.min(count);
+        let first = (before + 1).saturating_sub(points / 2).min(count - points);
+        let nodes = &self.times[first..first + points];
+        if nodes
+            .windows(2)
+            .any(|pair| pair[1] - pair[0] > MAX_SAMPLE_GAP)
+        {
+            return None;
+        }
+        let mut position = [0.0; 3];
+        let mut velocity = [0.0; 3];
+        let samples = &self.positions[first..first + points];
+        for (i, (&ti, sample)) in nodes.iter().zip(samples).enumerate() {
+            // Lagrange basis polynomial and its derivative (product rule)
+            let mut weight = 1.0;
+            let mut derivative = 0.0;
+            for (j, &tj) in nodes.iter().enumerate() {
+                if j != i {
+                    derivative = (derivative * (t - tj) + weight) / (ti - tj);
+                    weight *= (t - tj) / (ti - tj);
+                }
+            }
+            for k in 0..3 {
+                position[k] += weight * sample[k];
+                velocity[k] += derivative * sample[k];
+            }
+        }
+        let clock = self.clocks[before].zip(self.clocks[after]).map(
+            |(bias0, bias1)| {
+                let drift =
+                    (bias1 - bias0) / (self.times[after] - self.times[before]);
+                [bias0 + drift * (t - self.times[before]), drift]
+            },
+        );
+        Some((position, velocity, clock))
+    }
+}
+
+/// Precise orbits and clocks of a set of satellites.
+#[derive(Default)]
+pub struct PreciseOrbits {
+    /// Orbit of each satellite
+    orbits: HashMap<SatelliteId, Arc<PreciseOrbit>>,
+}
+impl PreciseOrbits {
+    /// Reads and merges SP3 files.
+    ///
+    /// Warnings of the SP3 reader, such as skipped malformed lines, are
+    /// printed to the standard error.
+    ///
+    /// # Arguments
+    /// * `files` - Paths to the SP3 files, possibly compressed
+    ///
+    /// # Returns
+    /// * `Ok(PreciseOrbits)` - The orbits of the satellites in the files
+    /// * `Err(Error)` - If a file cannot be read or is malformed
+    ///
+    /// # Errors
+    /// * `Error::Rinex` - If a file cannot be read or parsed
+    /// * `Error::PreciseOrbitFile` - If the time system of the files is not
+    ///   supported
+    pub fn read_files<P: AsRef<Path>>(files: &[P]) -> Result<Self, Error> {
+        let sp3 = Sp3::read_files(files)?;
+        for warning in &sp3.warnings {
+            eprintln!("Warning: SP3 {warning}");
+        }
+        Self::from_sp3(&sp3)
+    }
+
+    /// Groups the records of SP3 data by satellite.
+    ///
+    /// Epochs in GPS, Galileo, QZSS, BeiDou or TAI time are converted to GPS
+    /// time.
+    ///
+    /// # Arguments
+    /// * `sp3` - Parsed SP3 data
+    ///
+    /// # Returns
+    /// * `Ok(PreciseOrbits)` - The orbits of the satellites of the data
+    /// * `Err(Error)` - If the time system is not supported
+    ///
+    /// # Errors
+    /// * `Error::PreciseOrbitFile` - If the epochs are in UTC, GLONASS time
+    ///   or an unknown time system
+    pub fn from_sp3(sp3: &Sp3) -> Result<Self, Error> {
+        let offset = match sp3.time_system.as_str() {
+            "GPS" | "GAL" | "QZS" => 0.0,
+            "BDT" => BDT_GPST_OFFSET,
+            "TAI" => -TAI_GPST_OFFSET,
+            system => {
+                return Err(Error::invalid_precise_orbit_file(format!(
+                    "unsupported time system {system}"
+                )));
+            }
+        };
+        let mut samples: HashMap<SatelliteId, Vec<_>> = HashMap::new();
+        for record in &sp3.records {
+            let constellation = match record.system {
+                System::Gps => Constellation::Gps,
+                System::Galileo => Constellation::Galileo,
+                System::BeiDou => Constellation::BeiDou,
+                System::Glonass => Constellation::Glonass,
+                System::Qzss => Constellation::Qzss,
+            };
+            let prn = if record.system == System::Qzss {
+                record.prn + MIN_PRN_QZSS - 1
+            } else {
+                record.prn
+            };
+            let time =
+                GpsTime::from(&DateTime::from(record.time.in_tz("UTC")?))
+                    .add_secs(offset);
+            samples
+                .entry(SatelliteId::new(constellation, prn))
+                .or_default()
+                .push((time, record.position, record.clock));
+        }
+        Ok(Self {
+            orbits: samples
+                .into_iter()
+                .filter_map(|(satellite, samples)| {
+                    Some((satellite, Arc::new(PreciseOrbit::new(samples)?)))
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns the orbit of a satellite.
+    ///
+    /// # Arguments
+    /// * `satellite` - Identity of the satellite
+    ///
+    /// # Returns
+    /// The orbit, or `None` if the satellite is not in the data
+    pub fn get(&self, satellite: SatelliteId) -> Option<Arc<PreciseOrbit>> {
+        self.orbits.get(&satellite).cloned()
+    }
+}
@@ -19,6 +19,9 @@ use crate::{
 /// - Satellite clock offset
 /// - Ionospheric delay
 ///
+/// The satellite follows its precise orbit and clock when they are loaded,
+/// and its broadcast ephemeris otherwise (see [`Ephemeris::true_state`]).
+///
 /// The calculation follows these steps:
 /// 1. Compute satellite position at reception time
 /// 2. Calculate initial light time
@@ -26,7 +29,8 @@ use crate::{
 /// 4. Apply Earth rotation correction
 /// 5. Recalculate geometric range
 /// 6. Apply satellite clock correction to get pseudorange
-/// 7. Calculate range rate (Doppler)
+/// 7. Calculate range rate (Doppler) from the satellite and receiver
+///    velocities and the satellite clock drift
 /// 8. Calculate azimuth and elevation angles
 /// 9. Add ionospheric delay
 ///
@@ -35,15 +39,17 @@ use crate::{
 /// * `ionoutc` - Ionospheric and UTC parameters
 /// * `time` - GPS time at the moment of signal reception
 /// * `xyz` - Position of the receiver in ECEF coordinates
+/// * `velocity` - Velocity of the receiver in ECEF coordinates (m/s)
 ///
 /// # Returns
 /// A `TimeRange` structure containing the computed range information
 pub fn compute_range(
     eph: &Ephemeris, ionoutc: &IonoUtc, time: &GpsTime, xyz: &Ecef,
+    velocity: &Ecef,
 ) -> TimeRange {
     let mut rho = TimeRange::default();
     // SV position at time of the pseudorange observation.
-    let (mut pos, vel, clk) = eph.compute_satellite_state(time);
+    let (mut pos, vel, clk) = eph.true_state(time);
     // Receiver to satellite vector and light-time.
     let los = Ecef::from(&pos) - xyz;
 
@@ -64,10 +70,10 @@ pub fn compute_range(
     // Pseudorange.
     rho.range = range - SPEED_OF_LIGHT * clk[0];
     // Relative velocity of SV and receiver.
-    let vel = Ecef::from(&vel);
+    let vel = Ecef::from(&vel) - velocity;
     let rate = vel.dot_prod(&los) / range;
     // Pseudorange rate.
-    rho.rate = rate; // - SPEED_OF_LIGHT*clk[1];
+    rho.rate = rate - SPEED_OF_LIGHT * clk[1];
     // Time of application.
     rho.time = time.clone();
 
@@ -0,0 +1,475 @@
+//! GNSS signal definitions beyond the legacy GPS L1 C/A channel.
+//!
+//! This module describes every signal the generator can synthesise, the RF
+//! band each one is transmitted on, and the per-signal spreading codes and
+//! navigation message encoders. GPS L1 C/A keeps using the dedicated
+//! `Channel` implementation so that its output stays bit-exact with
+//! gps-sdr-sim; all other signals are produced by the generic
+//! `SignalChannel` engine.
+
+/// BeiDou B1I ranging and Neuman-Hofman code generation
+mod b1i;
+/// BeiDou D1/D2 navigation message encoder
+mod bdnav;
+/// GPS civil navigation (CNAV) message encoder
+mod cnav;
+/// Galileo E1 OS memory and secondary codes
+mod e1;
+/// GLONASS L1OF ranging code and frequency plan
+mod glonass;
+/// GLONASS navigation string encoder
+mod glonav;
+/// Galileo I/NAV message encoder
+mod inav;
+/// GPS L2C CM/CL spreading code generation
+mod l2c;
+/// GPS L5 I5/Q5 spreading and Neuman-Hofman code generation
+mod l5;
+/// SBAS L1 ranging code generation
+mod sbas;
+/// SBAS message framing and scheduling
+mod sbasnav;
+/// Generic multi-component signal channel
+mod signal_channel;
+#[cfg(test)]
+mod tests;
+
+use std::{f64::consts::FRAC_1_SQRT_2, fmt, ops::RangeInclusive, str::FromStr};
+
+pub use b1i::{MAX_PRN_B1I, is_geostationary};
+use constants::{
+    B1I_SEQ_LEN, CA_SEQ_LEN, CARR_FREQ, CARR_FREQ_B1I, CARR_FREQ_G1,
+    CARR_FREQ_L2, CARR_FREQ_L5, CODE_FREQ, CODE_FREQ_B1I, CODE_FREQ_G1,
+    CODE_FREQ_L5, E1_SEQ_LEN, G1_SEQ_LEN, L2CM_SEQ_LEN, L5_SEQ_LEN,
+    MAX_PRN_QZSS, MAX_PRN_SBAS, MAX_SAT, MAX_SAT_BEIDOU, MAX_SAT_GALILEO,
+    MAX_SAT_GLONASS, MIN_PRN_QZSS, MIN_PRN_SBAS,
+};
+pub use e1::MemoryCodes;
+pub use glonass::fdma_sample_frequency;
+pub use sbasnav::{DATA_BITS, MESSAGE_BITS, Message, MessageStream, Scheduled};
+pub use signal_channel::{Component, SignalChannel, Subcarrier};
+
+use crate::{Error, datetime::GpsTime, ephemeris::Ephemeris, ionoutc::IonoUtc};
+
+/// RF band on which a signal is transmitted.
+///
+/// Each band is written to its own I/Q output file, centred on the band's
+/// nominal frequency. Signals sharing a band are summed into the same
+/// baseband stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Band {
+    /// L1 band centred at 1575.42 MHz
+    L1,
+    /// L2 band centred at 1227.60 MHz
+    L2,
+    /// L5 band centred at 1176.45 MHz
+    L5,
+    /// BeiDou B1 band centred at 1561.098 MHz
+    B1,
+    /// GLONASS G1 band, centred at 1602 MHz unless another centre is
+    /// configured for the FDMA channels
+    G1,
+}
+impl Band {
+    /// Returns the centre frequency of the band in Hz.
+    ///
+    /// This is the frequency the I/Q output of the band is mixed down from,
+    /// i.e. the frequency an SDR should be tuned to when replaying it.
+    pub fn center_frequency(self) -> f64 {
+        match self {
+            Band::L1 => CARR_FREQ,
+            Band::L2 => CARR_FREQ_L2,
+            Band::L5 => CARR_FREQ_L5,
+            Band::B1 => CARR_FREQ_B1I,
+            Band::G1 => CARR_FREQ_G1,
+        }
+    }
+
+    /// Returns the short name of the band used in output file names.
+    pub fn name(self) -> &'static str {
+        match self {
+            Band::L1 => "L1",
+            Band::L2 => "L2",
+            Band::L5 => "L5",
+            Band::B1 => "B1",
+            Band::G1 => "G1",
+        }
+    }
+}
+
+/// Satellite constellation transmitting a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Constellation {
+    /// GPS, using the ephemerides of RINEX 2 or RINEX 3 `G` records
+    Gps,
+    /// Galileo, using the ephemerides of RINEX 3 `E` records
+    Galileo,
+    /// BeiDou, using the ephemerides of RINEX 3 `C` records
+    BeiDou,
+    /// GLONASS, using the state vectors of RINEX 3 `R` records
+    Glonass,
+    /// SBAS GEO satellites, defined by an SBAS configuration file
+    Sbas,
+    /// QZSS, using the ephemerides of RINEX 3 `J` records
+    Qzss,
+}
+impl Constellation {
+    /// Returns the highest PRN number of the constellation.
+    pub fn max_prn(self) -> usize {
+        *self.prns().end()
+    }
+
+    /// Returns the PRN numbers of the constellation.
+    pub fn prns(self) -> RangeInclusive<usize> {
+        match self {
+            Constellation::Gps => 1..=MAX_SAT,
+            Constellation::Galileo => 1..=MAX_SAT_GALILEO,
+            Constellation::BeiDou => 1..=MAX_SAT_BEIDOU,
+            Constellation::Glonass => 1..=MAX_SAT_GLONASS,
+            Constellation::Sbas => MIN_PRN_SBAS..=MAX_PRN_SBAS,
+            Constellation::Qzss => MIN_PRN_QZSS..=MAX_PRN_QZSS,
+        }
+    }
+}
+
+/// Identity of a satellite: its constellation and PRN number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SatelliteId {
+    /// Constellation of the satellite
+    pub constellation: Constellation,
+    /// PRN number of the satellite (slot number for GLONASS)
+    pub prn: usize,
+}
+impl SatelliteId {
+    /// Creates the identity of a satellite.
+    ///
+    /// # Arguments
+    /// * `constellation` - Constellation of the satellite
+    /// * `prn` - PRN number of the satellite
+    pub fn new(constellation: Constellation, prn: usize) -> Self {
+        Self { constellation, prn }
+    }
+}
+impl fmt::Display for SatelliteId {
+    /// Formats the identity as in RINEX 3, e.g. `G05`, `S23` for PRN 123 or
+    /// `J01` for PRN 193.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (letter, offset) = match self.constellation {
+            Constellation::Gps => ('G', 0),
+            Constellation::Galileo => ('E', 0),
+            Constellation::BeiDou => ('C', 0),
+            Constellation::Glonass => ('R', 0),
+            Constellation::Sbas => ('S', 100),
+            Constellation::Qzss => ('J', MIN_PRN_QZSS - 1),
+        };
+        write!(f, "{letter}{:02}", self.prn.saturating_sub(offset))
+    }
+}
+
+/// A GNSS signal that can be synthesised by the generator.
+///
+/// The first signal passed to the builder determines the band written to the
+/// primary output file; additional bands are written next to it with the band
+/// name appended to the file stem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Signal {
+    /// GPS L1 C/A (1023-chip Gold code, 50 bps LNAV)
+    #[default]
+    GpsL1Ca,
+    /// GPS L2C (time-multiplexed CM/CL codes, 25 bps CNAV)
+    GpsL2C,
+    /// GPS L5 (I5/Q5 codes with Neuman-Hofman overlays, 50 bps CNAV)
+    GpsL5,
+    /// Galileo E1 OS (CBOC E1-B/E1-C memory codes, 250 sps I/NAV)
+    GalileoE1,
+    /// BeiDou B1I (2046-chip ranging codes, 50 bps D1 with NH20 or 500 bps
+    /// D2)
+    BeidouB1I,
+    /// GLONASS L1OF (511-chip code on per-satellite FDMA carriers, 50 bps
+    /// meander-coded strings)
+    GlonassL1Of,
+    /// SBAS L1 (1023-chip Gold codes, 250 bps messages with rate-1/2 FEC)
+    SbasL1,
+    /// QZSS L1 C/A (1023-chip Gold code, 50 bps LNAV), generated by the GPS
+    /// L1 C/A channels
+    QzssL1Ca,
+}
+impl Signal {
+    /// Returns the constellation transmitting the signal.
+    pub fn constellation(self) -> Constellation {
+        match self {
+            Signal::GpsL1Ca | Signal::GpsL2C | Signal::GpsL5 => {
+                Constellation::Gps
+            }
+            Signal::GalileoE1 => Constellation::Galileo,
+            Signal::BeidouB1I => Constellation::BeiDou,
+            Signal::GlonassL1Of => Constellation::Glonass,
+            Signal::SbasL1 => Constellation::Sbas,
+            Signal::QzssL1Ca => Constellation::Qzss,
+        }
+    }
+
+    /// Returns whether the signal is generated by the GPS L1 C/A channels
+    /// rather than by a [`SignalChannel`].
+    pub fn uses_legacy_channel(self) -> bool {
+        matches!(self, Signal::GpsL1Ca | Signal::QzssL1Ca)
+    }
+
+    /// Returns the band the signal is transmitted on.
+    pub fn band(self) -> Band {
+        match self {
+            Signal::GpsL1Ca
+            | Signal::GalileoE1
+            | Signal::SbasL1
+            | Signal::QzssL1Ca => Band::L1,
+            Signal::GpsL2C => Band::L2,
+            Signal::GpsL5 => Band::L5,
+            Signal::BeidouB1I => Band::B1,
+            Signal::GlonassL1Of => Band::G1,
+        }
+    }
+
+    /// Returns the nominal carrier frequency of the signal in Hz.
+    ///
+    /// For GLONASS L1OF this is the carrier of frequency channel 0.
+    pub fn carrier_frequency(self) -> f64 {
+        match self {
+            Signal::GpsL1Ca
+            | Signal::GalileoE1
+            | Signal::SbasL1
+            | Signal::QzssL1Ca => CARR_FREQ,
+            Signal::GpsL2C => CARR_FREQ_L2,
+            Signal::GpsL5 => CARR_FREQ_L5,
+            Signal::BeidouB1I => CARR_FREQ_B1I,
+            Signal::GlonassL1Of => CARR_FREQ_G1,
+        }
+    }
+
+    /// Returns the carrier frequency a satellite transmits the signal on in
+    /// Hz.
+    ///
+    /// GLONASS L1OF satellites transmit on the carrier of their frequency
+    /// channel; all other signals share the nominal carrier.
+    ///
+    /// # Arguments
+    /// * `eph` - Ephemeris of the satellite
+    pub fn satellite_carrier_frequency(self, eph: &Ephemeris) -> f64 {
+        match self {
+            Signal::GlonassL1Of => {
+                glonass::carrier_frequency(eph.frequency_channel)
+            }
+            _ => self.carrier_frequency(),
+        }
+    }
+
+    /// Returns the composite chipping rate of the signal in chips per second.
+    ///
+    /// For time-multiplexed signals such as L2C this is the rate of the
+    /// interleaved chip stream, not the rate of the individual components.
+    pub fn chip_rate(self) -> f64 {
+        match self {
+            Signal::GpsL1Ca
+            | Signal::GpsL2C
+            | Signal::GalileoE1
+            | Signal::SbasL1
+            | Signal::QzssL1Ca => CODE_FREQ,
+            Signal::GpsL5 => CODE_FREQ_L5,
+            Signal::BeidouB1I => CODE_FREQ_B1I,
+            Signal::GlonassL1Of => CODE_FREQ_G1,
+        }
+    }
+
+    /// Returns the lowest sampling frequency accepted for the signal in Hz.
+    ///
+    /// The 1.023 Mchip/s signals keep the historical 1 MHz floor; faster
+    /// signals need at least one complex sample per chip, and subcarrier
+    /// modulated signals one sample per half period of their fastest
+    /// subcarrier, the BOC(6,1) part of CBOC for Galileo E1. GLONASS L1OF
+    /// needs to hold all frequency channels around the default centre of the
+    /// G1 band.
+    pub fn min_sample_frequency(self) -> f64 {
+        match self {
+            Signal::GpsL1Ca
+            | Signal::GpsL2C
+            | Signal::SbasL1
+            | Signal::QzssL1Ca => 1e6,
+            Signal::GpsL5 => CODE_FREQ_L5,
+            Signal::GalileoE1 => 12.0 * CODE_FREQ,
+            Signal::BeidouB1I => CODE_FREQ_B1I,
+            Signal::GlonassL1Of => fdma_sample_frequency(CARR_FREQ_G1),
+        }
+    }
+
+    /// Returns the number of composite chips spanned by one data symbol.
+    pub fn chips_per_symbol(self) -> u64 {
+        match self {
+            Signal::GpsL1Ca | Signal::QzssL1Ca => 20 * 1023,
+            // One FEC-encoded CNAV symbol per CM code period (20 ms)
+            Signal::GpsL2C => 2 * L2CM_SEQ_LEN as u64,
+            // One FEC-encoded CNAV symbol per 10-bit NH code period (10 ms)
+            Signal::GpsL5 => 10 * L5_SEQ_LEN as u64,
+            // One FEC-encoded I/NAV symbol per E1-B code period (4 ms)
+            Signal::GalileoE1 => E1_SEQ_LEN as u64,
+            // One D2 bit per two code periods (2 ms); D1 bits span ten symbols
+            Signal::BeidouB1I => 2 * B1I_SEQ_LEN as u64,
+            // One meander symbol per ten code periods (10 ms)
+            Signal::GlonassL1Of => 10 * G1_SEQ_LEN as u64,
+            // One FEC-encoded SBAS symbol per two code periods (2 ms)
+            Signal::SbasL1 => 2 * CA_SEQ_LEN as u64,
+        }
+    }
+
+    /// Returns the ratio between the L1 ionospheric delay and the delay
+    /// experienced by this signal.
+    ///
+    /// The first-order ionospheric delay scales with the inverse square of
+    /// the carrier frequency, so the Klobuchar delay computed for L1 is
+    /// multiplied by `(f_L1 / f)^2`.
+    pub fn iono_scale(self) -> f64 {
+        (CARR_FREQ / self.carrier_frequency()).powi(2)
+    }
+
+    /// Builds the spreading components of the signal for a satellite.
+    ///
+    /// # Arguments
+    /// * `prn` - Satellite PRN number
+    /// * `memory_codes` - Galileo E1 memory codes, if loaded
+    ///
+    /// # Returns
+    /// * `Some(components)` - The data and pilot components of the signal
+    /// * `None` - If the signal is served by the L1 C/A channels or the PRN has
+    ///   no code assignment, including Galileo PRNs without memory codes,
+    ///   BeiDou PRNs above 37, GLONASS slots above 24 and SBAS PRNs outside
+    ///   120-158
+    pub fn components(
+        self, prn: usize, memory_codes: Option<&MemoryCodes>,
+    ) -> Option<Vec<Component>> {
+        match self {
+            Signal::GpsL1Ca | Signal::QzssL1Ca => None,
+            Signal::GpsL2C => Some(vec![
+                // CM carries the CNAV symbols in even chip slots
+                Component::new(l2c::cm_code(prn)?).multiplexed(2, 0).data(),
+                // CL is a data-less pilot in odd chip slots
+                Component::new(l2c::cl_code(prn)?).multiplexed(2, 1),
+            ]),
+            Signal::GpsL5 => Some(vec![
+                // I5 carries the CNAV symbols under the NH10 overlay
+                Component::new(l5::i5_code(prn)?)
+                    .secondary(l5::NH10.to_vec())
+                    .data(),
+                // Q5 is a data-less pilot under the NH20 overlay
+                Component::new(l5::q5_code(prn)?)
+                    .secondary(l5::NH20.to_vec())
+                    .quadrature(),
+            ]),
+            Signal::GalileoE1 => {
+                let (e1b, e1c) = memory_codes?.codes(prn)?;
+                Some(vec![
+                    // E1-B carries the I/NAV symbols on CBOC(6,1,1/11,'+')
+                    Component::new(e1b)
+                        .subcarrier(Subcarrier::Cboc(1.0))
+                        .amplitude(FRAC_1_SQRT_2)
+                        .data(),
+                    // E1-C is a pilot under CS25 on CBOC(6,1,1/11,'-'),
+                    // subtracted from E1-B on the same arm
+                    Component::new(e1c)
+                        .secondary(e1::CS25.to_vec())
+                        .subcarrier(Subcarrier::Cboc(-1.0))
+                        .amplitude(-FRAC_1_SQRT_2),
+                ])
+            }
+            Signal::BeidouB1I => {
+                let code = b1i::code(prn)?;
+                Some(vec![if b1i::is_geostationary(prn) {
+                    // GEO satellites carry D2 without an overlay
+                    Component::new(code).data()
+                } else {
+                    // MEO/IGSO satellites carry D1 under the NH20 overlay
+                    Component::new(code).secondary(b1i::NH20.to_vec()).data()
+                }])
+            }
+            Signal::GlonassL1Of => (1..=MAX_SAT_GLONASS)
+                .contains(&prn)
+                .then(|| vec![Component::new(glonass::code()).data()]),
+            Signal::SbasL1 => {
+                Some(vec![Component::new(sbas::code(prn)?).data()])
+            }
+        }
+    }
+
+    /// Encodes the navigation symbols transmitted around a given time.
+    ///
+    /// The returned symbol stream starts at a message boundary preceding
+    /// `time` and covers enough messages to bridge the 30-second refresh
+    /// interval of the generator.
+    ///
+    /// # Arguments
+    /// * `prn` - Satellite PRN number
+    /// * `eph` - Ephemeris of the satellite
+    /// * `ionoutc` - Ionospheric and UTC parameters
+    /// * `time` - Current receiver time
+    ///
+    /// # Returns
+    /// A tuple of the transmit time of the first symbol and the symbols
+    /// themselves (+1/-1), or `None` if the ephemeris lacks the state vector
+    /// broadcast by GLONASS and SBAS
+    pub fn symbols(
+        self, prn: usize, eph: &Ephemeris, ionoutc: &IonoUtc, time: &GpsTime,
+    ) -> Option<(GpsTime, Vec<i8>)> {
+        Some(match self {
+            Signal::GpsL1Ca | Signal::QzssL1Ca => (time.clone(), Vec::new()),
+            Signal::GpsL2C => {
+                cnav::symbols(prn, eph, ionoutc, time, cnav::L2C_PERIOD)
+            }
+            Signal::GpsL5 => {
+                cnav::symbols(prn, eph, ionoutc, time, cnav::L5_PERIOD)
+            }
+            Signal::GalileoE1 => inav::symbols(prn, eph, ionoutc, time),
+            Signal::BeidouB1I if b1i::is_geostationary(prn) => {
+                bdnav::d2_symbols(eph, ionoutc, time)
+            }
+            Signal::BeidouB1I => bdnav::d1_symbols(eph, ionoutc, time),
+            Signal::GlonassL1Of => {
+                let state = eph.state_vector.as_ref()?;
+                glonav::symbols(prn, eph, state, ionoutc, time)
+            }
+            Signal::SbasL1 => {
+                sbasnav::symbols(eph, eph.state_vector.as_ref()?, time)
+            }
+        })
+    }
+}
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Signal::GpsL1Ca => "L1CA",
+            Signal::GpsL2C => "L2C",
+            Signal::GpsL5 => "L5",
+            Signal::GalileoE1 => "E1",
+            Signal::BeidouB1I => "B1I",
+            Signal::GlonassL1Of => "L1OF",
+            Signal::SbasL1 => "SBAS",
+            Signal::QzssL1Ca => "QZSS",
+        };
+        f.write_str(name)
+    }
+}
+impl FromStr for Signal {
+    type Err = Error;
+
+    /// Parses a signal name such as `L1CA`, `L2C`, `L5`, `E1`, `B1I`, `L1OF`,
+    /// `SBAS` or `QZSS` (case-insensitive).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "L1CA" | "L1C/A" | "L1" => Ok(Signal::GpsL1Ca),
+            "L2C" | "L2" => Ok(Signal::GpsL2C),
+            "L5" | "L5I" | "L5Q" | "L5IQ" => Ok(Signal::GpsL5),
+            "E1" | "E1OS" | "E1BC" => Ok(Signal::GalileoE1),
+            "B1I" | "B1" => Ok(Signal::BeidouB1I),
+            "L1OF" | "G1" => Ok(Signal::GlonassL1Of),
+            "SBAS" | "SBASL1" | "S1" => Ok(Signal::SbasL1),
+            "QZSS" | "QZSSL1CA" | "J1" => Ok(Signal::QzssL1Ca),
+            _ => Err(Error::invalid_signal(s)),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! BeiDou B1I ranging and Neuman-Hofman codes.
+//!
+//! The B1I ranging code is the modulo-2 sum of two 11-stage shift register
+//! sequences (BDS-SIS-ICD-B1I section 4.2). G1, `1 + x + x^7 + x^8 + x^9 +
+//! x^10 + x^11`, is used directly; G2, `1 + x + x^2 + x^3 + x^4 + x^5 + x^8 +
+//! x^9 + x^11`, is read through a PRN-specific phase selector that sums two of
+//! its stages. Both registers start from 01010101010 at the beginning of
+//! every 1 ms code period, and the 2047-chip sequence is truncated by one chip
+//! to 2046 chips.
+//!
+//! Only the two-stage selectors of PRN 1-37 are tabulated; the satellites
+//! C38-C63 added by later issues of the ICD have no code here and are not
+//! simulated.
+
+use constants::B1I_SEQ_LEN;
+
+/// Feedback taps of G1: stages 1, 7, 8, 9, 10 and 11
+pub(super) const G1_TAPS: u32 = 1 | 1 << 6 | 1 << 7 | 1 << 8 | 1 << 9 | 1 << 10;
+
+/// Feedback taps of G2: stages 1, 2, 3, 4, 5, 8, 9 and 11
+const G2_TAPS: u32 =
+    1 | 1 << 1 | 1 << 2 | 1 << 3 | 1 << 4 | 1 << 7 | 1 << 8 | 1 << 10;
+
+/// Initial state of both registers, 01010101010 from stage 1 to stage 11
+const INIT: u32 = 0b010_1010_1010;
+
+/// Highest PRN with a B1I ranging code
+pub const MAX_PRN_B1I: usize = 37;
+
+/// G2 phase selector stages for PRN 1-37 (BDS-SIS-ICD-B1I table 4-1)
+pub(super) const G2_PHASE: [(u32, u32); MAX_PRN_B1I] = [
+    (1, 3),
+    (1, 4),
+    (1, 5),
+    (1, 6),
+    (1, 8),
+    (1, 9),
+    (1, 10),
+    (1, 11),
+    (2, 7),
+    (3, 4),
+    (3, 5),
+    (3, 6),
+    (3, 8),
+    (3, 9),
+    (3, 10),
+    (3, 11),
+    (4, 5),
+    (4, 6),
+    (4, 8),
+    (4, 9),
+    (4, 10),
+    (4, 11),
+    (5, 6),
+    (5, 8),
+    (5, 9),
+    (5, 10),
+    (5, 11),
+    (6, 8),
+    (6, 9),
+    (6, 10),
+    (6, 11),
+    (8, 9),
+    (8, 10),
+    (8, 11),
+    (9, 10),
+    (9, 11),
+    (10, 11),
+];
+
+/// 20-bit Neuman-Hofman code 00000100110101001110 applied to the D1
+/// navigation message of MEO and IGSO satellites (the same sequence as the
+/// L5 Q5 overlay)
+pub const NH20: [i8; 20] = [
+    1, 1, 1, 1, 1, -1, 1, 1, -1, -1, 1, -1, 1, -1, 1, 1, -1, -1, -1, 1,
+];
+
+/// Runs an 11-stage Fibonacci shift register from the initial state.
+///
+/// Bit `k` of the state holds stage `k + 1`; the feedback enters stage 1 and
+/// the register shifts towards stage 11.
+///
+/// # Arguments
+/// * `taps` - Feedback mask; bit `k` selects stage `k + 1`
+/// * `output` - Output mask; the chip is the parity of the selected stages
+/// * `len` - Number of output chips
+///
+/// # Returns
+/// The output sequence as logic levels (0 or 1)
+pub(super) fn shift_register(taps: u32, output: u32, len: usize) -> Vec<u8> {
+    let mut state = INIT;
+    (0..len)
+        .map(|_| {
+            let out = ((state & output).count_ones() & 1) as u8;
+            let feedback = (state & taps).count_ones() & 1;
+            state = (state << 1 | feedback) & 0x7ff;
+            out
+        })
+        .collect()
+}
+
+/// Returns whether a PRN is a geostationary satellite.
+///
+/// GEO satellites (C01-C05 and C59-C63) broadcast the D2 message at 500 bps
+/// without the NH code and use a dedicated orbit model.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number
+pub fn is_geostationary(prn: usize) -> bool {
+    matches!(prn, 1..=5 | 59..=63)
+}
+
+/// Generates the 2046-chip B1I ranging code of a satellite.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number (1-37)
+///
+/// # Returns
+/// The chips as +1/-1 values, or `None` for an unsupported PRN
+pub fn code(prn: usize) -> Option<Vec<i8>> {
+    let (a, b) = G2_PHASE.get(prn.checked_sub(1)?)?;
+    let g1 = shift_register(G1_TAPS, 1 << 10, B1I_SEQ_LEN);
+    let g2 = shift_register(G2_TAPS, 1 << (a - 1) | 1 << (b - 1), B1I_SEQ_LEN);
+    Some(
+        g1.iter()
+            .zip(&g2)
+            .map(|(x, y)| 1 - 2 * (x ^ y) as i8)
+            .collect(),
+    )
+}
@@ -0,0 +1,464 @@
+//! BeiDou D1 and D2 navigation message encoding for the B1I signal.
+//!
+//! Both messages are made of 300-bit subframes of ten 30-bit words
+//! (BDS-SIS-ICD-B1I section 5). The first word carries the preamble, the
+//! subframe ID and the 8 most significant bits of the seconds of week (SOW);
+//! its last 11 information bits are protected by a BCH(15,11) code. Each of
+//! the following words carries 22 information bits as two interleaved
+//! BCH(15,11) code words.
+//!
+//! D1 is broadcast by MEO and IGSO satellites at 50 bps in 6-second
+//! subframes: subframe 1 holds the clock, ionosphere and group delay
+//! parameters, subframes 2 and 3 the ephemeris, and subframes 4 and 5 the
+//! almanac pages. D2 is broadcast by GEO satellites at 500 bps in
+//! 0.6-second subframes; the basic navigation data is spread over the ten
+//! pages of subframe 1. Almanac, integrity and differential pages are not
+//! simulated and only carry their page number.
+//!
+//! All times in the message are BeiDou Time (BDT).
+
+use constants::{BDT_GPST_OFFSET, BDT_WEEK_OFFSET, PI};
+
+use super::cnav::BitWriter;
+use crate::{datetime::GpsTime, ephemeris::Ephemeris, ionoutc::IonoUtc};
+
+/// Subframe preamble 11100010010
+const PREAMBLE: u64 = 0b111_0001_0010;
+
+/// Number of bits in a subframe
+const SUBFRAME_BITS: usize = 300;
+
+/// Number of information bits in words 2-10 of a subframe
+const INFO_BITS: usize = 9 * 22;
+
+/// BCH(15,11) generator polynomial `x^4 + x + 1`
+const BCH_POLY: u32 = 0b1_0011;
+
+/// D1 subframe duration in seconds
+const D1_SUBFRAME_PERIOD: f64 = 6.0;
+
+/// D2 subframe duration in seconds
+const D2_SUBFRAME_PERIOD: f64 = 0.6;
+
+/// D2 frame (five subframes) duration in seconds
+const D2_FRAME_PERIOD: f64 = 3.0;
+
+/// Number of D1 bits per B1I symbol; symbols are 2 ms long, the D2 bit
+/// duration, so every 20 ms D1 bit spans ten of them
+const D1_REPEAT: usize = 10;
+
+/// Length of the simulated symbol window in seconds, measured from the start
+/// of the subframe following the first one
+const WINDOW: f64 = 60.0;
+
+/// Converts a GPS time into BeiDou Time.
+pub fn to_bdt(time: &GpsTime) -> GpsTime {
+    let time = time.add_secs(-BDT_GPST_OFFSET);
+    GpsTime {
+        week: time.week - BDT_WEEK_OFFSET,
+        sec: time.sec,
+    }
+}
+
+/// Converts a BeiDou Time into GPS time.
+pub fn from_bdt(time: &GpsTime) -> GpsTime {
+    GpsTime {
+        week: time.week + BDT_WEEK_OFFSET,
+        sec: time.sec,
+    }
+    .add_secs(BDT_GPST_OFFSET)
+}
+
+/// Computes the 4 BCH(15,11) parity bits of 11 information bits.
+pub fn bch(info: u32) -> u32 {
+    let mut remainder = info << 4;
+    for i in (4..15).rev() {
+        if remainder >> i & 1 == 1 {
+            remainder ^= BCH_POLY << (i - 4);
+        }
+    }
+    remainder
+}
+
+/// Scales `value` by `2^-exponent` and rounds it to a two's-complement
+/// integer, of which the writer keeps the required low bits.
+fn scaled(value: f64, exponent: i32) -> u64 {
+    (value * 2f64.powi(-exponent)).round() as i64 as u64
+}
+
+/// Assembles a subframe from its ID, SOW and the information bits of words
+/// 2-10.
+///
+/// # Arguments
+/// * `fraid` - Subframe ID (1-5)
+/// * `sow` - Seconds of BDT week of the subframe
+/// * `info` - 198 information bits of words 2-10, starting with the 12 least
+///   significant SOW bits
+///
+/// # Returns
+/// The 300 subframe bits (0 or 1)
+pub fn subframe(fraid: u64, sow: u64, info: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter::new();
+    w.put(PREAMBLE, 11);
+    w.put(0, 4); // reserved
+    let first = (fraid << 8 | sow >> 12 & 0xff) as u32;
+    w.put(u64::from(first), 11);
+    w.put(u64::from(bch(first)), 4);
+    for word in info.chunks(22) {
+        let value = |bits: &[u8]| {
+            bits.iter().fold(0, |acc, &bit| acc << 1 | u32::from(bit))
+        };
+        let (a, b) = (value(&word[..11]), value(&word[11..]));
+        // Information bits, then parity bits, interleaved bit by bit
+        let (a, b) = (a << 4 | bch(a), b << 4 | bch(b));
+        for i in (0..15).rev() {
+            w.put(u64::from(a >> i & 1), 1);
+            w.put(u64::from(b >> i & 1), 1);
+        }
+    }
+    w.bits
+}
+
+/// Starts the information bits of a subframe with the low SOW bits.
+fn info_writer(sow: u64) -> BitWriter {
+    let mut w = BitWriter::new();
+    w.put(sow & 0xfff, 12);
+    w
+}
+
+/// Pads the information bits of a subframe to the length of words 2-10.
+fn finish(mut w: BitWriter) -> Vec<u8> {
+    w.bits.resize(INFO_BITS, 0);
+    w.bits
+}
+
+/// Broadcast parameters of a satellite as integers in message units.
+struct Fields {
+    /// BDT week number
+    week: u64,
+    /// Clock reference time in units of 8 s
+    toc: u64,
+    /// Ephemeris reference time in units of 8 s
+    toe: u64,
+    /// Autonomous satellite health flag
+    sath1: u64,
+    /// Age of data, clock
+    aodc: u64,
+    /// User range accuracy index
+    urai: u64,
+    /// Age of data, ephemeris
+    aode: u64,
+    /// B1I group delay (0.1 ns)
+    tgd1: u64,
+    /// B2I group delay (0.1 ns)
+    tgd2: u64,
+    /// Klobuchar amplitude coefficients
+    alpha: [u64; 4],
+    /// Klobuchar period coefficients
+    beta: [u64; 4],
+    /// Clock bias
+    a0: u64,
+    /// Clock drift
+    a1: u64,
+    /// Clock drift rate
+    a2: u64,
+    /// Mean motion difference
+    deltan: u64,
+    /// Cosine correction to the argument of latitude
+    cuc: u64,
+    /// Mean anomaly at the reference time
+    m0: u64,
+    /// Eccentricity
+    ecc: u64,
+    /// Sine correction to the argument of latitude
+    cus: u64,
+    /// Cosine correction to the orbit radius
+    crc: u64,
+    /// Sine correction to the orbit radius
+    crs: u64,
+    /// Square root of the semi-major axis
+    sqrta: u64,
+    /// Inclination at the reference time
+    inc0: u64,
+    /// Cosine correction to the inclination
+    cic: u64,
+    /// Rate of right ascension
+    omgdot: u64,
+    /// Sine correction to the inclination
+    cis: u64,
+    /// Rate of inclination
+    idot: u64,
+    /// Longitude of the ascending node
+    omg0: u64,
+    /// Argument of perigee
+    aop: u64,
+}
+impl Fields {
+    /// Quantizes the ephemeris and ionospheric parameters.
+    fn new(eph: &Ephemeris, ionoutc: &IonoUtc) -> Self {
+        let toc = to_bdt(&eph.toc);
+        let toe = to_bdt(&eph.toe);
+        Self {
+            week: (toe.week as u64) & 0x1fff,
+            toc: (toc.sec / 8.0).round() as u64,
+            toe: (toe.sec / 8.0).round() as u64,
+            sath1: (eph.svhlth & 1) as u64,
+            aodc: (eph.iodc & 0x1f) as u64,
+            // The URA is not kept in the ephemeris; index 0 is 2.4 m
+            urai: 0,
+            aode: (eph.iode & 0x1f) as u64,
+            // B1I group delay in units of 0.1 ns; the B2I one is not kept
+            tgd1: (eph.tgd * 1e10).round() as i64 as u64,
+            tgd2: 0,
+            // The GPS Klobuchar parameters share the BeiDou scale factors
+            alpha: [
+                scaled(ionoutc.alpha0, -30),
+                scaled(ionoutc.alpha1, -27),
+                scaled(ionoutc.alpha2, -24),
+                scaled(ionoutc.alpha3, -24),
+            ],
+            beta: [
+                scaled(ionoutc.beta0, 11),
+                scaled(ionoutc.beta1, 14),
+                scaled(ionoutc.beta2, 16),
+                scaled(ionoutc.beta3, 16),
+            ],
+            a0: scaled(eph.af0, -33),
+            a1: scaled(eph.af1, -50),
+            a2: scaled(eph.af2, -66),
+            deltan: scaled(eph.deltan / PI, -43),
+            cuc: scaled(eph.cuc, -31),
+            m0: scaled(eph.m0 / PI, -31),
+            ecc: (eph.ecc * 2f64.powi(33)).round() as u64,
+            cus: scaled(eph.cus, -31),
+            crc: scaled(eph.crc, -6),
+            crs: scaled(eph.crs, -6),
+            sqrta: (eph.sqrta * 2f64.powi(19)).round() as u64,
+            inc0: scaled(eph.inc0 / PI, -31),
+            cic: scaled(eph.cic, -31),
+            omgdot: scaled(eph.omgdot / PI, -43),
+            cis: scaled(eph.cis, -31),
+            idot: scaled(eph.idot / PI, -43),
+            omg0: scaled(eph.omg0 / PI, -31),
+            aop: scaled(eph.aop / PI, -31),
+        }
+    }
+}
+
+/// Builds a D1 subframe.
+///
+/// # Arguments
+/// * `fraid` - Subframe ID (1-5)
+/// * `page` - Almanac page number of subframes 4 and 5 (1-24)
+/// * `sow` - Seconds of BDT week of the subframe
+/// * `fields` - Quantized broadcast parameters
+///
+/// # Returns
+/// The 300 subframe bits (0 or 1)
+fn d1_subframe(fraid: u64, page: u64, sow: u64, f: &Fields) -> Vec<u8> {
+    let mut w = info_writer(sow);
+    match fraid {
+        1 => {
+            w.put(f.sath1, 1);
+            w.put(f.aodc, 5);
+            w.put(f.urai, 4);
+            w.put(f.week, 13);
+            w.put(f.toc, 17);
+            w.put(f.tgd1, 10);
+            w.put(f.tgd2, 10);
+            for &alpha in &f.alpha {
+                w.put(alpha, 8);
+            }
+            for &beta in &f.beta {
+                w.put(beta, 8);
+            }
+            w.put(f.a2, 11);
+            w.put(f.a0, 24);
+            w.put(f.a1, 22);
+            w.put(f.aode, 5);
+        }
+        2 => {
+            w.put(f.deltan, 16);
+            w.put(f.cuc, 18);
+            w.put(f.m0, 32);
+            w.put(f.ecc, 32);
+            w.put(f.cus, 18);
+            w.put(f.crc, 18);
+            w.put(f.crs, 18);
+            w.put(f.sqrta, 32);
+            w.put(f.toe >> 15, 2);
+        }
+        3 => {
+            w.put(f.toe, 15);
+            w.put(f.inc0, 32);
+            w.put(f.cic, 18);
+            w.put(f.omgdot, 24);
+            w.put(f.cis, 18);
+            w.put(f.idot, 14);
+            w.put(f.omg0, 32);
+            w.put(f.aop, 32);
+        }
+        _ => {
+            w.put(0, 1); // reserved
+            w.put(page, 7);
+        }
+    }
+    subframe(fraid, sow, &finish(w))
+}
+
+/// Builds page `page` (1-10) of D2 subframe 1.
+///
+/// # Arguments
+/// * `page` - Page number (1-10)
+/// * `sow` - Seconds of BDT week of the frame
+/// * `fields` - Quantized broadcast parameters
+///
+/// # Returns
+/// The 300 subframe bits (0 or 1)
+fn d2_page(page: u64, sow: u64, f: &Fields) -> Vec<u8> {
+    let mut w = info_writer(sow);
+    w.put(page, 4);
+    match page {
+        1 => {
+            w.put(f.sath1, 1);
+            w.put(f.aodc, 5);
+            w.put(f.urai, 4);
+            w.put(f.week, 13);
+            w.put(f.toc, 17);
+            w.put(f.tgd1, 10);
+            w.put(f.tgd2, 10);
+        }
+        2 => {
+            for &alpha in &f.alpha {
+                w.put(alpha, 8);
+            }
+            for &beta in &f.beta {
+                w.put(beta, 8);
+            }
+        }
+        3 => {
+            w.put(f.a0, 24);
+            w.put(f.a1 >> 18, 4);
+        }
+        4 => {
+            w.put(f.a1, 18);
+            w.put(f.a2, 11);
+            w.put(f.aode, 5);
+            w.put(f.deltan, 16);
+            w.put(f.cuc >> 14, 4);
+        }
+        5 => {
+            w.put(f.cuc, 14);
+            w.put(f.m0, 32);
+            w.put(f.cus, 18);
+            w.put(f.ecc >> 22, 10);
+        }
+        6 => {
+            w.put(f.ecc, 22);
+            w.put(f.sqrta, 32);
+            w.put(f.cic >> 8, 10);
+        }
+        7 => {
+            w.put(f.cic, 8);
+            w.put(f.cis, 18);
+            w.put(f.toe, 17);
+            w.put(f.inc0 >> 21, 11);
+        }
+        8 => {
+            w.put(f.inc0, 21);
+            w.put(f.crc, 18);
+            w.put(f.crs, 18);
+            w.put(f.omgdot >> 19, 5);
+        }
+        9 => {
+            w.put(f.omgdot, 19);
+            w.put(f.omg0, 32);
+            w.put(f.aop >> 30, 2);
+        }
+        _ => {
+            w.put(f.aop, 30);
+            w.put(f.idot, 14);
+        }
+    }
+    subframe(1, sow, &finish(w))
+}
+
+/// Maps message bits to symbols, repeating each bit `repeat` times.
+fn push_bits(symbols: &mut Vec<i8>, bits: &[u8], repeat: usize) {
+    for &bit in bits {
+        let symbol = 1 - 2 * bit as i8;
+        symbols.extend(std::iter::repeat_n(symbol, repeat));
+    }
+}
+
+/// Encodes the D1 symbol stream around a given time.
+///
+/// # Arguments
+/// * `eph` - Ephemeris of the satellite
+/// * `ionoutc` - Ionospheric and UTC parameters
+/// * `time` - Current receiver time
+///
+/// # Returns
+/// A tuple of the transmit time (GPS time) of the first symbol and the 2 ms
+/// symbols (+1/-1)
+pub fn d1_symbols(
+    eph: &Ephemeris, ionoutc: &IonoUtc, time: &GpsTime,
+) -> (GpsTime, Vec<i8>) {
+    let fields = Fields::new(eph, ionoutc);
+    let now = to_bdt(time);
+    let current = GpsTime {
+        week: now.week,
+        sec: (now.sec / D1_SUBFRAME_PERIOD).floor() * D1_SUBFRAME_PERIOD,
+    };
+    let start = current.add_secs(-D1_SUBFRAME_PERIOD);
+    let count = (WINDOW / D1_SUBFRAME_PERIOD).ceil() as usize + 1;
+    let mut symbols = Vec::with_capacity(count * SUBFRAME_BITS * D1_REPEAT);
+    for k in 0..count {
+        let sf_start = start.add_secs(k as f64 * D1_SUBFRAME_PERIOD);
+        let sow = sf_start.sec.round() as u64;
+        let fraid = sow / 6 % 5 + 1;
+        let page = sow / 30 % 24 + 1;
+        let bits = d1_subframe(fraid, page, sow, &fields);
+        push_bits(&mut symbols, &bits, D1_REPEAT);
+    }
+    (from_bdt(&start), symbols)
+}
+
+/// Encodes the D2 symbol stream around a given time.
+///
+/// # Arguments
+/// * `eph` - Ephemeris of the satellite
+/// * `ionoutc` - Ionospheric and UTC parameters
+/// * `time` - Current receiver time
+///
+/// # Returns
+/// A tuple of the transmit time (GPS time) of the first symbol and the 2 ms
+/// symbols (+1/-1)
+pub fn d2_symbols(
+    eph: &Ephemeris, ionoutc: &IonoUtc, time: &GpsTime,
+) -> (GpsTime, Vec<i8>) {
+    let fields = Fields::new(eph, ionoutc);
+    let now = to_bdt(time);
+    let current = GpsTime {
+        week: now.week,
+        sec: (now.sec / D2_FRAME_PERIOD).floor() * D2_FRAME_PERIOD,
+    };
+    let start = current.add_secs(-D2_FRAME_PERIOD);
+    let count = (WINDOW / D2_FRAME_PERIOD).ceil() as usize + 1;
+    let subframes = (D2_FRAME_PERIOD / D2_SUBFRAME_PERIOD).round() as u64;
+    let mut symbols =
+        Vec::with_capacity(count * subframes as usize * SUBFRAME_BITS);
+    for k in 0..count {
+        let frame_start = start.add_secs(k as f64 * D2_FRAME_PERIOD);
+        let sow = frame_start.sec.round() as u64;
+        // Subframe 1 cycles through its ten pages, one per frame
+        let page = sow / 3 % 10 + 1;
+        push_bits(&mut symbols, &d2_page(page, sow, &fields), 1);
+        for fraid in 2..=subframes {
+            let mut w = info_writer(sow);
+            w.put(0, 4); // page number of the unsimulated subframes
+            push_bits(&mut symbols, &subframe(fraid, sow, &finish(w)), 1);
+        }
+    }
+    (from_bdt(&start), symbols)
+}
@@ -0,0 +1,287 @@
+//! GPS civil navigation (CNAV) message encoding.
+//!
+//! CNAV messages are 300 bits long and share a common 38-bit header
+//! (preamble, PRN, message type, TOW count, alert flag) and a trailing CRC-24Q
+//! parity field (IS-GPS-200 section 30.3.3). The message stream is protected
+//! by a continuous rate-1/2, constraint-length-7 convolutional code, so one
+//! message lasts 12 seconds on L2C (50 symbols/s) and 6 seconds on L5 (100
+//! symbols/s).
+//!
+//! The generator broadcasts the minimum message set a receiver needs to
+//! compute a fix: ephemeris types 10 and 11, clock/ionosphere type 30 and
+//! clock/UTC type 33.
+
+use constants::PI;
+
+use crate::{datetime::GpsTime, ephemeris::Ephemeris, ionoutc::IonoUtc};
+
+/// Message duration on L2C in seconds
+pub const L2C_PERIOD: f64 = 12.0;
+
+/// Message duration on L5 in seconds
+pub const L5_PERIOD: f64 = 6.0;
+
+/// Number of bits in a CNAV message
+pub const MESSAGE_BITS: usize = 300;
+
+/// Number of message bits covered by the CRC
+const CRC_BITS: usize = 276;
+
+/// CNAV preamble (10001011)
+const PREAMBLE: u64 = 0b1000_1011;
+
+/// Message types broadcast in turn by every satellite
+const MESSAGE_CYCLE: [u64; 4] = [10, 11, 30, 33];
+
+/// Semi-major axis reference of the CNAV ephemeris (meters)
+const A_REF: f64 = 26_559_710.0;
+
+/// Rate of right ascension reference of the CNAV ephemeris (semi-circles/s)
+const OMEGA_DOT_REF: f64 = -2.6e-9;
+
+/// CRC-24Q generator polynomial without the leading x^24 term
+const CRC24Q_POLY: u32 = 0x0086_4cfb;
+
+/// Length of the simulated symbol window in seconds, measured from the start
+/// of the message following the first one
+const WINDOW: f64 = 60.0;
+
+/// Accumulates a message MSB-first as individual bits.
+pub(super) struct BitWriter {
+    /// Message bits (0 or 1)
+    pub(super) bits: Vec<u8>,
+}
+impl BitWriter {
+    /// Creates an empty writer with room for one message.
+    pub(super) fn new() -> Self {
+        Self {
+            bits: Vec::with_capacity(MESSAGE_BITS),
+        }
+    }
+
+    /// Appends the `n` least significant bits of `value`, MSB first.
+    pub(super) fn put(&mut self, value: u64, n: usize) {
+        for i in (0..n).rev() {
+            self.bits.push((value >> i & 1) as u8);
+        }
+    }
+
+    /// Appends a two's-complement field of `n` bits.
+    pub(super) fn put_signed(&mut self, value: i64, n: usize) {
+        self.put(value as u64, n);
+    }
+
+    /// Scales `value` by `2^-exponent`, rounds it and appends it as a signed
+    /// field of `n` bits.
+    pub(super) fn put_scaled(&mut self, value: f64, exponent: i32, n: usize) {
+        self.put_signed((value * 2f64.powi(-exponent)).round() as i64, n);
+    }
+}
+
+/// Computes the CRC-24Q parity of a bit sequence.
+///
+/// # Arguments
+/// * `bits` - Message bits (0 or 1), MSB first
+///
+/// # Returns
+/// The 24-bit parity value
+pub fn crc24q(bits: &[u8]) -> u32 {
+    bits.iter().fold(0, |crc, &bit| {
+        let feedback = (crc >> 23 & 1) ^ u32::from(bit);
+        let crc = (crc << 1) & 0x00ff_ffff;
+        if feedback == 1 {
+            crc ^ CRC24Q_POLY
+        } else {
+            crc
+        }
+    })
+}
+
+/// Rate-1/2, K=7 convolutional encoder used by CNAV and SBAS.
+///
+/// The encoder uses the generator polynomials G1 = 171 and G2 = 133 (octal)
+/// and emits the G1 symbol first. Its state carries over between messages.
+#[derive(Default)]
+pub struct ConvEncoder {
+    /// Last six input bits, most recent in the most significant position
+    state: u32,
+}
+impl ConvEncoder {
+    /// Encodes message bits into +1/-1 channel symbols.
+    ///
+    /// # Arguments
+    /// * `bits` - Input bits (0 or 1)
+    ///
+    /// # Returns
+    /// Two symbols per input bit, mapped as 0 → +1 and 1 → -1
+    pub fn encode(&mut self, bits: &[u8]) -> Vec<i8> {
+        let mut symbols = Vec::with_capacity(2 * bits.len());
+        for &bit in bits {
+            let register = u32::from(bit) << 6 | self.state;
+            for generator in [0o171, 0o133] {
+                let parity = (register & generator).count_ones() & 1;
+                symbols.push(1 - 2 * parity as i8);
+            }
+            self.state = register >> 1;
+        }
+        symbols
+    }
+}
+
+/// Appends the clock correction block shared by message types 30-37.
+fn put_clock(w: &mut BitWriter, eph: &Ephemeris) {
+    w.put((eph.toe.sec / 300.0) as u64, 11); // t_op
+    w.put(0, 5); // URA_NED0
+    w.put(0, 3); // URA_NED1
+    w.put(0, 3); // URA_NED2
+    w.put((eph.toc.sec / 300.0) as u64, 11);
+    w.put_scaled(eph.af0, -35, 26);
+    w.put_scaled(eph.af1, -48, 20);
+    w.put_scaled(eph.af2, -60, 10);
+}
+
+/// Builds one 300-bit CNAV message.
+///
+/// The message type is chosen from the broadcast cycle by the message index
+/// within the week, so every satellite repeats types 10, 11, 30 and 33.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number
+/// * `eph` - Ephemeris of the satellite
+/// * `ionoutc` - Ionospheric and UTC parameters
+/// * `start` - Transmit time of the first bit of the message
+/// * `period` - Message duration in seconds
+///
+/// # Returns
+/// The message bits (0 or 1) including the CRC
+pub fn message(
+    prn: usize, eph: &Ephemeris, ionoutc: &IonoUtc, start: &GpsTime,
+    period: f64,
+) -> Vec<u8> {
+    let index = (start.sec / period).round() as usize;
+    let message_type = MESSAGE_CYCLE[index % MESSAGE_CYCLE.len()];
+    // TOW count of the start of the next message, in 6-second units
+    let tow = ((start.sec + period) / 6.0).round() as u64 % 100_800;
+    let unhealthy = u64::from(eph.svhlth != 0);
+
+    let mut w = BitWriter::new();
+    w.put(PREAMBLE, 8);
+    w.put(prn as u64, 6);
+    w.put(message_type, 6);
+    w.put(tow, 17);
+    w.put(0, 1); // alert flag
+    match message_type {
+        10 => {
+            w.put(start.week as u64, 13);
+            w.put(unhealthy, 1); // L1 health
+            w.put(unhealthy, 1); // L2 health
+            w.put(unhealthy, 1); // L5 health
+            w.put((eph.toe.sec / 300.0) as u64, 11); // t_op
+            w.put(0, 5); // URA_ED
+            w.put((eph.toe.sec / 300.0) as u64, 11);
+            w.put_scaled(eph.sqrta * eph.sqrta - A_REF, -9, 26);
+            w.put(0, 25); // A dot
+            w.put_scaled(eph.deltan / PI, -44, 17);
+            w.put(0, 23); // delta n0 dot
+            w.put_scaled(eph.m0 / PI, -32, 33);
+            w.put((eph.ecc * 2f64.powi(34)).round() as u64, 33);
+            w.put_scaled(eph.aop / PI, -32, 33);
+            w.put(0, 1); // integrity status flag
+            w.put(0, 1); // L2C phasing
+        }
+        11 => {
+            w.put((eph.toe.sec / 300.0) as u64, 11);
+            w.put_scaled(eph.omg0 / PI, -32, 33);
+            w.put_scaled(eph.inc0 / PI, -32, 33);
+            w.put_scaled(eph.omgdot / PI - OMEGA_DOT_REF, -44, 17);
+            w.put_scaled(eph.idot / PI, -44, 15);
+            w.put_scaled(eph.cis, -30, 16);
+            w.put_scaled(eph.cic, -30, 16);
+            w.put_scaled(eph.crs, -8, 24);
+            w.put_scaled(eph.crc, -8, 24);
+            w.put_scaled(eph.cus, -30, 21);
+            w.put_scaled(eph.cuc, -30, 21);
+        }
+        30 => {
+            put_clock(&mut w, eph);
+            w.put_scaled(eph.tgd, -35, 13);
+            // Inter-signal corrections L1C/A, L2C, L5I5, L5Q5
+            w.put(0, 4 * 13);
+            w.put_scaled(ionoutc.alpha0, -30, 8);
+            w.put_scaled(ionoutc.alpha1, -27, 8);
+            w.put_scaled(ionoutc.alpha2, -24, 8);
+            w.put_scaled(ionoutc.alpha3, -24, 8);
+            w.put_scaled(ionoutc.beta0, 11, 8);
+            w.put_scaled(ionoutc.beta1, 14, 8);
+            w.put_scaled(ionoutc.beta2, 16, 8);
+            w.put_scaled(ionoutc.beta3, 16, 8);
+            w.put(start.week as u64, 8); // WN_OP
+        }
+        _ => {
+            // Message type 33, UTC parameters. The leap second event
+            // defaults mirror the LNAV subframe 4 page 18 encoding.
+            let (wnlsf, dn, dtlsf) = if ionoutc.leapen == 1 {
+                (ionoutc.wnlsf, ionoutc.day_number, ionoutc.dtlsf)
+            } else {
+                (1929, 7, 18)
+            };
+            put_clock(&mut w, eph);
+            w.put_scaled(ionoutc.A0, -35, 16);
+            w.put_scaled(ionoutc.A1, -51, 13);
+            w.put(0, 7); // A2
+            w.put_signed(i64::from(ionoutc.dtls), 8);
+            w.put((ionoutc.tot / 16) as u64, 16);
+            w.put(ionoutc.week_number as u64, 13);
+            w.put(wnlsf as u64, 13);
+            w.put(dn as u64, 4);
+            w.put_signed(i64::from(dtlsf), 8);
+        }
+    }
+    // Reserved bits up to the CRC
+    let reserved = CRC_BITS - w.bits.len();
+    w.put(0, reserved);
+    let crc = crc24q(&w.bits);
+    w.put(u64::from(crc), 24);
+    w.bits
+}
+
+/// Encodes the CNAV symbol stream around a given time.
+///
+/// The stream starts one message before the message containing `time`, so
+/// that signals still in flight from the satellite are covered, and runs for
+/// at least [`WINDOW`] seconds beyond that. The convolutional encoder is
+/// primed with the message preceding the stream.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number
+/// * `eph` - Ephemeris of the satellite
+/// * `ionoutc` - Ionospheric and UTC parameters
+/// * `time` - Current receiver time
+/// * `period` - Message duration in seconds
+///
+/// # Returns
+/// A tuple of the transmit time of the first symbol and the symbols
+pub fn symbols(
+    prn: usize, eph: &Ephemeris, ionoutc: &IonoUtc, time: &GpsTime, period: f64,
+) -> (GpsTime, Vec<i8>) {
+    let current = GpsTime {
+        week: time.week,
+        sec: (time.sec / period).floor() * period,
+    };
+    let start = current.add_secs(-period);
+    let mut encoder = ConvEncoder::default();
+    encoder.encode(&message(
+        prn,
+        eph,
+        ionoutc,
+        &start.add_secs(-period),
+        period,
+    ));
+    let count = (WINDOW / period).ceil() as usize + 1;
+    let mut symbols = Vec::with_capacity(count * 2 * MESSAGE_BITS);
+    for k in 0..count {
+        let t = start.add_secs(k as f64 * period);
+        symbols.extend(encoder.encode(&message(prn, eph, ionoutc, &t, period)));
+    }
+    (start, symbols)
+}
@@ -0,0 +1,149 @@
+//! Galileo E1 OS primary and secondary codes.
+//!
+//! The E1-B and E1-C primary codes are memory codes: 4092-chip sequences
+//! tabulated in annex C of the Galileo OS SIS ICD instead of being generated
+//! by shift registers. They are not distributed with this crate; they are
+//! loaded at run time from a transcription of the ICD tables holding one code
+//! per line, as the signal name, the PRN and the 1023 hexadecimal digits of
+//! the code (most significant bit first):
+//!
+//! ```text
+//! # Galileo OS SIS ICD annex C
+//! E1B 1 F5D71013...
+//! E1C 1 ...
+//! ```
+//!
+//! Fields may be separated by whitespace, commas or semicolons, and lines
+//! starting with `#` are ignored. The loaded codes are kept by the generator
+//! that loaded them and handed to its E1 signal channels.
+
+use std::{fs, path::Path};
+
+use constants::{E1_SEQ_LEN, MAX_SAT_GALILEO};
+
+use crate::Error;
+
+/// Number of hexadecimal digits of one memory code
+const HEX_DIGITS: usize = E1_SEQ_LEN / 4;
+
+/// E1-C secondary code CS25 (Galileo OS SIS ICD table 16), logic levels
+/// 0011100000001010110110010 mapped as 0 → +1 and 1 → -1
+pub const CS25: [i8; 25] = [
+    1, 1, -1, -1, -1, 1, 1, 1, 1, 1, 1, 1, -1, 1, -1, 1, -1, -1, 1, -1, -1, 1,
+    1, -1, 1,
+];
+
+/// Galileo E1 OS memory codes of all satellites.
+#[derive(Debug, PartialEq)]
+pub struct MemoryCodes {
+    /// E1-B codes (+1/-1) indexed by PRN - 1
+    e1b: Vec<Option<Vec<i8>>>,
+    /// E1-C codes (+1/-1) indexed by PRN - 1
+    e1c: Vec<Option<Vec<i8>>>,
+}
+impl MemoryCodes {
+    /// Parses memory codes from the text format described in the module
+    /// documentation.
+    ///
+    /// # Arguments
+    /// * `text` - Contents of a code file
+    ///
+    /// # Returns
+    /// * `Ok(MemoryCodes)` - The codes found in the file
+    /// * `Err(Error)` - If a line is malformed
+    ///
+    /// # Errors
+    /// * `Error::CodeFile` - If a line has an unknown signal name, a PRN
+    ///   outside 1-36 or a code that is not 1023 hexadecimal digits long
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut codes = Self {
+            e1b: vec![None; MAX_SAT_GALILEO],
+            e1c: vec![None; MAX_SAT_GALILEO],
+        };
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |what: &str| {
+                Error::invalid_code_file(format!("line {}: {what}", number + 1))
+            };
+            let fields: Vec<&str> = line
+                .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                .filter(|field| !field.is_empty())
+                .collect();
+            let [name, prn, hex] = fields[..] else {
+                return Err(invalid("expected signal, PRN and code"));
+            };
+            let table = match name.to_uppercase().as_str() {
+                "E1B" | "E1-B" => &mut codes.e1b,
+                "E1C" | "E1-C" => &mut codes.e1c,
+                _ => return Err(invalid("unknown signal")),
+            };
+            let slot = prn
+                .parse::<usize>()
+                .ok()
+                .and_then(|prn| prn.checked_sub(1))
+                .and_then(|sv| table.get_mut(sv))
+                .ok_or_else(|| invalid("invalid PRN"))?;
+            if hex.len() != HEX_DIGITS {
+                return Err(invalid("code must have 1023 hexadecimal digits"));
+            }
+            let mut chips = Vec::with_capacity(E1_SEQ_LEN);
+            for digit in hex.chars() {
+                let value = digit
+                    .to_digit(16)
+                    .ok_or_else(|| invalid("invalid hexadecimal digit"))?;
+                chips.extend(
+                    (0..4).rev().map(|i| 1 - 2 * (value >> i & 1) as i8),
+                );
+            }
+            *slot = Some(chips);
+        }
+        Ok(codes)
+    }
+
+    /// Reads memory codes from a file.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the code file
+    ///
+    /// # Returns
+    /// * `Ok(MemoryCodes)` - The codes found in the file
+    /// * `Err(Error)` - If the file cannot be read or is malformed
+    ///
+    /// # Errors
+    /// * `Error::Io` - If the file cannot be read
+    /// * `Error::CodeFile` - If a line is malformed
+    pub fn read_file(path: &Path) -> Result<Self, Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the E1-B code of a satellite.
+    ///
+    /// # Arguments
+    /// * `prn` - Satellite PRN number (1-36)
+    pub fn e1b(&self, prn: usize) -> Option<&[i8]> {
+        self.e1b.get(prn.checked_sub(1)?)?.as_deref()
+    }
+
+    /// Returns the E1-C code of a satellite.
+    ///
+    /// # Arguments
+    /// * `prn` - Satellite PRN number (1-36)
+    pub fn e1c(&self, prn: usize) -> Option<&[i8]> {
+        self.e1c.get(prn.checked_sub(1)?)?.as_deref()
+    }
+
+    /// Returns the E1-B and E1-C codes of a satellite.
+    ///
+    /// # Arguments
+    /// * `prn` - Satellite PRN number (1-36)
+    ///
+    /// # Returns
+    /// The E1-B and E1-C chips, or `None` if either code is missing for the
+    /// PRN
+    pub fn codes(&self, prn: usize) -> Option<(Vec<i8>, Vec<i8>)> {
+        Some((self.e1b(prn)?.to_vec(), self.e1c(prn)?.to_vec()))
+    }
+}
@@ -0,0 +1,52 @@
+//! GLONASS L1OF ranging code and frequency plan.
+//!
+//! All GLONASS satellites transmit the same 511-chip maximal-length sequence
+//! of the 9-stage register `1 + x^5 + x^9` (GLONASS ICD edition 5.1,
+//! section 3.3.1.2), read from stage 7 after starting from all ones. The
+//! satellites are separated in frequency instead: each one is assigned a
+//! frequency channel k from -7 to +6 and transmits on `1602 MHz + k * 562.5
+//! kHz`. Antipodal satellites share a channel.
+
+use std::ops::RangeInclusive;
+
+use constants::{CARR_FREQ_G1, CARR_FREQ_G1_STEP, CODE_FREQ_G1, G1_SEQ_LEN};
+
+/// Frequency channel numbers in use
+pub const CHANNELS: RangeInclusive<i32> = -7..=6;
+
+/// Returns the carrier frequency of a frequency channel in Hz.
+///
+/// # Arguments
+/// * `channel` - Frequency channel number k
+pub fn carrier_frequency(channel: i32) -> f64 {
+    CARR_FREQ_G1 + f64::from(channel) * CARR_FREQ_G1_STEP
+}
+
+/// Returns the lowest complex sampling frequency that keeps the main lobes
+/// of all frequency channels inside the band around a centre frequency.
+///
+/// # Arguments
+/// * `center` - Centre frequency of the output (Hz)
+pub fn fdma_sample_frequency(center: f64) -> f64 {
+    let offset = CHANNELS
+        .map(|k| (carrier_frequency(k) - center).abs())
+        .fold(0.0, f64::max);
+    2.0 * (offset + CODE_FREQ_G1)
+}
+
+/// Generates the 511-chip L1OF ranging code.
+///
+/// # Returns
+/// The chips as +1/-1 values
+pub fn code() -> Vec<i8> {
+    // Bit k of the state holds stage k + 1; stages 5 and 9 feed stage 1
+    let mut state: u32 = 0x1ff;
+    (0..G1_SEQ_LEN)
+        .map(|_| {
+            let out = (state >> 6 & 1) as i8;
+            let feedback = (state >> 4 ^ state >> 8) & 1;
+            state = (state << 1 | feedback) & 0x1ff;
+            1 - 2 * out
+        })
+        .collect()
+}
@@ -0,0 +1,296 @@
+//! GLONASS L1OF navigation message encoding.
+//!
+//! The message is made of 2-second strings (GLONASS ICD edition 5.1, section
+//! 3.3.2). Each string holds 85 bits, numbered from 85 down to 1 in
+//! transmission order: an idle bit set to 0, the string number m in bits
+//! 84-81, data in bits 80-9 and the Hamming check bits β8-β1 in bits 8-1. The
+//! bits are transmitted in relative (differential) code, combined with a
+//! 100 Hz meander, during the first 1.7 s of the string; the last 0.3 s carry
+//! the 30-symbol time mark. Fifteen strings form a 30-second frame starting
+//! on a 30-second boundary of Moscow time (UTC + 3 h).
+//!
+//! Strings 1-4 carry the immediate data of the satellite (state vector in
+//! PZ-90, clock, health and slot) and string 5 the time references. Strings
+//! 6-15 hold the almanac, which is not simulated; they only carry their
+//! string number.
+
+use constants::{SECONDS_IN_DAY, SECONDS_IN_HOUR};
+
+use super::cnav::BitWriter;
+use crate::{
+    datetime::{DateTime, GpsTime},
+    ephemeris::Ephemeris,
+    ionoutc::IonoUtc,
+    orbit::{Frame, StateVector},
+};
+
+/// Number of bits in a string
+pub const STRING_BITS: usize = 85;
+
+/// Time mark 111110001101110101000010010110, one bit per 10 ms symbol
+pub const TIME_MARK: [u8; 30] = [
+    1, 1, 1, 1, 1, 0, 0, 0, 1, 1, 0, 1, 1, 1, 0, 1, 0, 1, 0, 0, 0, 0, 1, 0, 0,
+    1, 0, 1, 1, 0,
+];
+
+/// String duration in seconds
+const STRING_PERIOD: f64 = 2.0;
+
+/// Number of strings in a frame
+const FRAME_STRINGS: u64 = 15;
+
+/// Offset of Moscow time from UTC in seconds
+const MOSCOW_OFFSET: f64 = 3.0 * SECONDS_IN_HOUR;
+
+/// First year of the four-year interval numbering (N4 = 1)
+const N4_EPOCH: i32 = 1996;
+
+/// Length of the simulated symbol window in seconds, measured from the start
+/// of the string following the first one
+const WINDOW: f64 = 60.0;
+
+/// Computes the Hamming check bits of a string.
+///
+/// The 76 data bits b9-b84 occupy the positions of a Hamming code word that
+/// are not powers of two; β1-β7 are the parities of the positions having the
+/// corresponding bit set, and β8 makes the parity of the whole string even.
+///
+/// # Arguments
+/// * `string` - The 85 string bits in transmission order (bit 85 first); the
+///   check bits are ignored
+///
+/// # Returns
+/// β1-β8 (0 or 1)
+pub fn hamming(string: &[u8]) -> [u8; 8] {
+    let bit = |i: usize| string[STRING_BITS - i];
+    let mut beta = [0u8; 8];
+    let mut position: u32 = 0;
+    for i in 9..STRING_BITS {
+        position += 1;
+        while position.is_power_of_two() {
+            position += 1;
+        }
+        if bit(i) == 1 {
+            for (k, beta) in beta.iter_mut().take(7).enumerate() {
+                *beta ^= (position >> k & 1) as u8;
+            }
+        }
+    }
+    beta[7] = beta[..7]
+        .iter()
+        .chain((9..=STRING_BITS).map(|i| &string[STRING_BITS - i]))
+        .fold(0, |acc, bit| acc ^ bit);
+    beta
+}
+
+/// Scales `value` by `2^-exponent` and rounds it to a sign-magnitude field
+/// of `n` bits, the sign in the most significant bit.
+fn sign_magnitude(value: f64, exponent: i32, n: usize) -> u64 {
+    let magnitude = (value.abs() * 2f64.powi(-exponent)).round() as u64;
+    u64::from(value < 0.0) << (n - 1) | magnitude & ((1 << (n - 1)) - 1)
+}
+
+/// Broadcast parameters of a satellite as integers in message units.
+struct Fields {
+    /// Position components x, y, z in units of 2^-11 km (PZ-90)
+    pos: [u64; 3],
+    /// Velocity components in units of 2^-20 km/s
+    vel: [u64; 3],
+    /// Lunisolar acceleration components in units of 2^-30 km/s²
+    acc: [u64; 3],
+    /// Index of the 15-minute interval of the Moscow day holding tb
+    tb: u64,
+    /// Relative frequency bias γn in units of 2^-40
+    gamma: u64,
+    /// Clock bias τn in units of 2^-30 s
+    tau: u64,
+    /// Health flag Bn, the most significant bit set for an unhealthy
+    /// satellite
+    health: u64,
+    /// Age of the immediate data En in days
+    age: u64,
+    /// Slot number n
+    slot: u64,
+}
+impl Fields {
+    /// Quantizes the broadcast parameters of a satellite.
+    ///
+    /// # Arguments
+    /// * `prn` - Slot number of the satellite
+    /// * `eph` - Ephemeris of the satellite
+    /// * `state` - State vector of the ephemeris
+    /// * `ionoutc` - Ionospheric and UTC parameters (for the leap seconds)
+    fn new(
+        prn: usize, eph: &Ephemeris, state: &StateVector, ionoutc: &IonoUtc,
+    ) -> Self {
+        let (pos, vel) = match state.frame {
+            Frame::Pz90 => (state.pos, state.vel),
+            Frame::Wgs84 => {
+                Frame::Pz90.convert_from_wgs84(&state.pos, &state.vel)
+            }
+        };
+        let tb = moscow_time_of_day(&eph.toe, ionoutc);
+        Self {
+            pos: pos.map(|p| sign_magnitude(p / 1e3, -11, 27)),
+            vel: vel.map(|v| sign_magnitude(v / 1e3, -20, 24)),
+            acc: state.acc.map(|a| sign_magnitude(a / 1e3, -30, 5)),
+            tb: (tb / 900.0).round() as u64,
+            gamma: sign_magnitude(eph.af1, -40, 11),
+            tau: sign_magnitude(-eph.af0, -30, 22),
+            health: if eph.svhlth == 0 { 0 } else { 0b100 },
+            age: eph.iodc.clamp(0, 31) as u64,
+            slot: prn as u64,
+        }
+    }
+}
+
+/// Returns the time of day in Moscow time of a GPS time, in seconds.
+fn moscow_time_of_day(time: &GpsTime, ionoutc: &IonoUtc) -> f64 {
+    (time.sec - f64::from(ionoutc.dtls) + MOSCOW_OFFSET)
+        .rem_euclid(SECONDS_IN_DAY)
+}
+
+/// Returns the four-year interval number N4 and the day number NT within
+/// that interval of the Moscow date of a UTC time.
+///
+/// # Arguments
+/// * `utc` - UTC time, expressed on the GPS week scale
+fn moscow_day(utc: &GpsTime) -> (u64, u64) {
+    let moscow = utc.add_secs(MOSCOW_OFFSET);
+    let date = DateTime::from(&moscow);
+    let n4 = (date.y - N4_EPOCH).div_euclid(4);
+    let first = GpsTime::from(&DateTime {
+        y: N4_EPOCH + 4 * n4,
+        m: 1,
+        d: 1,
+        ..Default::default()
+    });
+    let nt = (moscow.diff_secs(&first) / SECONDS_IN_DAY).floor() as u64 + 1;
+    ((n4 + 1) as u64, nt)
+}
+
+/// Builds string `number` (1-15) of a frame.
+///
+/// # Arguments
+/// * `number` - String number m
+/// * `tk` - Moscow time of day of the frame start (seconds)
+/// * `day` - Four-year interval number N4 and day number NT
+/// * `fields` - Quantized broadcast parameters
+///
+/// # Returns
+/// The 85 string bits in transmission order
+fn string(number: u64, tk: f64, day: (u64, u64), f: &Fields) -> Vec<u8> {
+    let mut w = BitWriter::new();
+    w.put(0, 1); // idle bit
+    w.put(number, 4);
+    match number {
+        1 => {
+            let tk = tk as u64;
+            w.put(0, 2); // spare
+            w.put(0, 2); // P1: tb updated every 30 minutes
+            w.put(tk / 3600, 5);
+            w.put(tk % 3600 / 60, 6);
+            w.put(tk % 60 / 30, 1);
+            w.put(f.vel[0], 24);
+            w.put(f.acc[0], 5);
+            w.put(f.pos[0], 27);
+        }
+        2 => {
+            w.put(f.health, 3);
+            w.put(f.tb & 1, 1); // P2: parity of tb
+            w.put(f.tb, 7);
+            w.put(0, 5); // spare
+            w.put(f.vel[1], 24);
+            w.put(f.acc[1], 5);
+            w.put(f.pos[1], 27);
+        }
+        3 => {
+            w.put(1, 1); // P3: five almanacs in the frame
+            w.put(f.gamma, 11);
+            w.put(0, 1); // spare
+            w.put(0, 2); // P: time references from the control segment
+            w.put(f.health >> 2, 1); // ln
+            w.put(f.vel[2], 24);
+            w.put(f.acc[2], 5);
+            w.put(f.pos[2], 27);
+        }
+        4 => {
+            w.put(f.tau, 22);
+            w.put(0, 5); // Δτn
+            w.put(f.age, 5);
+            w.put(0, 14); // spare
+            w.put(0, 1); // P4
+            w.put(0, 4); // FT
+            w.put(0, 3); // spare
+            w.put(day.1, 11); // NT
+            w.put(f.slot, 5);
+            w.put(0b01, 2); // M: GLONASS-M
+        }
+        5 => {
+            w.put(day.1, 11); // NA
+            w.put(0, 32); // τc: GLONASS time aligned with UTC(SU)
+            w.put(0, 1); // spare
+            w.put(day.0, 5); // N4
+            w.put(0, 22); // τGPS
+            w.put(f.health >> 2, 1); // ln
+        }
+        _ => {}
+    }
+    w.bits.resize(STRING_BITS - 8, 0);
+    let beta = hamming(&[w.bits.as_slice(), &[0; 8]].concat());
+    w.bits.extend(beta.iter().rev());
+    w.bits
+}
+
+/// Appends a string to the symbol stream: the bits in relative code, each
+/// combined with the meander into two 10 ms symbols, followed by the time
+/// mark.
+fn push_string(symbols: &mut Vec<i8>, bits: &[u8]) {
+    let level = |bit: u8| 1 - 2 * bit as i8;
+    let mut previous = 0;
+    for &bit in bits {
+        let relative = bit ^ previous;
+        previous = relative;
+        symbols.push(level(relative));
+        symbols.push(level(relative ^ 1));
+    }
+    symbols.extend(TIME_MARK.iter().map(|&bit| level(bit)));
+}
+
+/// Encodes the navigation symbol stream around a given time.
+///
+/// # Arguments
+/// * `prn` - Slot number of the satellite
+/// * `eph` - Ephemeris of the satellite
+/// * `state` - State vector of the ephemeris
+/// * `ionoutc` - Ionospheric and UTC parameters
+/// * `time` - Current receiver time
+///
+/// # Returns
+/// A tuple of the transmit time (GPS time) of the first symbol and the 10 ms
+/// symbols (+1/-1)
+pub fn symbols(
+    prn: usize, eph: &Ephemeris, state: &StateVector, ionoutc: &IonoUtc,
+    time: &GpsTime,
+) -> (GpsTime, Vec<i8>) {
+    let fields = Fields::new(prn, eph, state, ionoutc);
+    let dtls = f64::from(ionoutc.dtls);
+    let now = time.add_secs(-dtls);
+    let current = GpsTime {
+        week: now.week,
+        sec: (now.sec / STRING_PERIOD).floor() * STRING_PERIOD,
+    };
+    let start = current.add_secs(-STRING_PERIOD);
+    let count = (WINDOW / STRING_PERIOD).ceil() as usize + 1;
+    let mut symbols =
+        Vec::with_capacity(count * (2 * STRING_BITS + TIME_MARK.len()));
+    for k in 0..count {
+        let string_start = start.add_secs(k as f64 * STRING_PERIOD);
+        let tod = (string_start.sec + MOSCOW_OFFSET).rem_euclid(SECONDS_IN_DAY);
+        let number = (tod / STRING_PERIOD).round() as u64 % FRAME_STRINGS + 1;
+        let tk = tod - tod % 30.0;
+        let day = moscow_day(&string_start);
+        push_string(&mut symbols, &string(number, tk, day, &fields));
+    }
+    (start.add_secs(dtls), symbols)
+}
@@ -0,0 +1,253 @@
+//! Galileo I/NAV message encoding for the E1-B data component.
+//!
+//! I/NAV is organised in 2-second pages, each made of an even and an odd
+//! page part of one second (Galileo OS SIS ICD section 4.3.2). A page part
+//! holds 120 bits that are FEC encoded with the rate-1/2, K=7 code of CNAV
+//! with its second generator inverted, block interleaved over 8 rows and 30
+//! columns and preceded by a 10-symbol synchronisation pattern, giving 250
+//! symbols per second. A 128-bit word is split into 112 bits in the even part
+//! and 16 bits in the odd part, protected by a CRC-24Q over both parts.
+//!
+//! The generator broadcasts the nominal 30-second subframe of 15 pages with
+//! the ephemeris words 1-4, the ionosphere and GST word 5 and the GST-UTC
+//! word 6. Pages reserved for almanacs carry spare words (type 0) holding the
+//! GST instead.
+
+use constants::PI;
+
+use super::cnav::{BitWriter, ConvEncoder, crc24q};
+use crate::{datetime::GpsTime, ephemeris::Ephemeris, ionoutc::IonoUtc};
+
+/// Page duration in seconds
+pub const PAGE_PERIOD: f64 = 2.0;
+
+/// Number of data bits of a word carried by the even page part
+const EVEN_DATA_BITS: usize = 112;
+
+/// Number of bits in a word
+const WORD_BITS: usize = 128;
+
+/// Subframe duration in seconds
+const SUBFRAME_PERIOD: f64 = 30.0;
+
+/// Word types of the 15 pages of a nominal E1-B subframe
+const SUBFRAME_WORDS: [u64; 15] = [2, 4, 6, 0, 0, 0, 0, 0, 0, 0, 1, 3, 5, 0, 0];
+
+/// Page part synchronisation pattern 0101100000
+pub const SYNC: [i8; 10] = [1, -1, 1, -1, -1, 1, 1, 1, 1, 1];
+
+/// Rows of the block interleaver
+const ROWS: usize = 8;
+
+/// Columns of the block interleaver
+const COLUMNS: usize = 30;
+
+/// Length of the simulated symbol window in seconds, measured from the start
+/// of the page following the first one
+const WINDOW: f64 = 60.0;
+
+/// Week number of the GST start epoch on the GPS time scale
+const GST_WEEK_OFFSET: i32 = 1024;
+
+/// Signal-in-space accuracy index broadcast in word 3 (3.12 m); the RINEX
+/// SISA value is not kept in the ephemeris
+const SISA_INDEX: u64 = 107;
+
+/// Appends the GST week number and time of week of a page start.
+fn put_gst(w: &mut BitWriter, page_start: &GpsTime) {
+    let week = (page_start.week - GST_WEEK_OFFSET).rem_euclid(4096);
+    w.put(week as u64, 12);
+    w.put(page_start.sec.round() as u64, 20);
+}
+
+/// Builds one 128-bit I/NAV word.
+///
+/// # Arguments
+/// * `word_type` - Word type (0-6)
+/// * `prn` - Satellite PRN number
+/// * `eph` - Ephemeris of the satellite
+/// * `ionoutc` - Ionospheric and UTC parameters
+/// * `page_start` - Transmit time of the start of the page
+///
+/// # Returns
+/// The word bits (0 or 1)
+pub fn word(
+    word_type: u64, prn: usize, eph: &Ephemeris, ionoutc: &IonoUtc,
+    page_start: &GpsTime,
+) -> Vec<u8> {
+    let iodnav = eph.iode as u64 & 0x3ff;
+    let mut w = BitWriter::new();
+    w.put(word_type, 6);
+    match word_type {
+        1 => {
+            w.put(iodnav, 10);
+            w.put((eph.toe.sec / 60.0) as u64, 14);
+            w.put_scaled(eph.m0 / PI, -31, 32);
+            w.put((eph.ecc * 2f64.powi(33)).round() as u64, 32);
+            w.put((eph.sqrta * 2f64.powi(19)).round() as u64, 32);
+            w.put(0, 2);
+        }
+        2 => {
+            w.put(iodnav, 10);
+            w.put_scaled(eph.omg0 / PI, -31, 32);
+            w.put_scaled(eph.inc0 / PI, -31, 32);
+            w.put_scaled(eph.aop / PI, -31, 32);
+            w.put_scaled(eph.idot / PI, -43, 14);
+            w.put(0, 2);
+        }
+        3 => {
+            w.put(iodnav, 10);
+            w.put_scaled(eph.omgdot / PI, -43, 24);
+            w.put_scaled(eph.deltan / PI, -43, 16);
+            w.put_scaled(eph.cuc, -29, 16);
+            w.put_scaled(eph.cus, -29, 16);
+            w.put_scaled(eph.crc, -5, 16);
+            w.put_scaled(eph.crs, -5, 16);
+            w.put(SISA_INDEX, 8);
+        }
+        4 => {
+            w.put(iodnav, 10);
+            w.put(prn as u64, 6);
+            w.put_scaled(eph.cic, -29, 16);
+            w.put_scaled(eph.cis, -29, 16);
+            w.put((eph.toc.sec / 60.0) as u64, 14);
+            w.put_scaled(eph.af0, -34, 31);
+            w.put_scaled(eph.af1, -46, 21);
+            w.put_scaled(eph.af2, -59, 6);
+            w.put(0, 2);
+        }
+        5 => {
+            w.put((ionoutc.ai0 * 4.0).round() as u64, 11);
+            w.put_scaled(ionoutc.ai1, -8, 11);
+            w.put_scaled(ionoutc.ai2, -15, 14);
+            w.put(0, 5); // ionospheric disturbance flags
+            // Only the E1/E5b group delay used by I/NAV users is kept in the
+            // ephemeris, so it is broadcast for E5a as well
+            w.put_scaled(eph.tgd, -32, 10);
+            w.put_scaled(eph.tgd, -32, 10);
+            // RINEX health bits: E1-B DVS, E1-B HS (2), E5a DVS, E5a HS (2),
+            // E5b DVS, E5b HS (2)
+            let health = eph.svhlth as u64;
+            w.put(health >> 7 & 3, 2); // E5b HS
+            w.put(health >> 1 & 3, 2); // E1-B HS
+            w.put(health >> 6 & 1, 1); // E5b DVS
+            w.put(health & 1, 1); // E1-B DVS
+            put_gst(&mut w, page_start);
+            w.put(0, 23);
+        }
+        6 => {
+            // The leap second event defaults mirror the LNAV encoding
+            let (wnlsf, dn, dtlsf) = if ionoutc.leapen == 1 {
+                (ionoutc.wnlsf, ionoutc.day_number, ionoutc.dtlsf)
+            } else {
+                (1929, 7, 18)
+            };
+            w.put_scaled(ionoutc.A0, -30, 32);
+            w.put_scaled(ionoutc.A1, -50, 24);
+            w.put_signed(i64::from(ionoutc.dtls), 8);
+            w.put((ionoutc.tot / 3600) as u64, 8);
+            w.put(ionoutc.week_number as u64 & 0xff, 8);
+            w.put(wnlsf as u64 & 0xff, 8);
+            w.put(dn as u64, 3);
+            w.put_signed(i64::from(dtlsf), 8);
+            w.put(page_start.sec.round() as u64, 20);
+            w.put(0, 3);
+        }
+        _ => {
+            w.put(0b10, 2); // time field
+            w.bits.resize(w.bits.len() + 88, 0); // spare
+            put_gst(&mut w, page_start);
+        }
+    }
+    w.bits
+}
+
+/// Splits a word into its even and odd page parts.
+///
+/// # Arguments
+/// * `word` - The 128 bits of a word
+///
+/// # Returns
+/// The 120 bits of the even and of the odd page part, including the CRC and
+/// tail bits
+pub fn page_parts(word: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut even = BitWriter::new();
+    even.put(0, 1); // even
+    even.put(0, 1); // nominal page
+    even.bits.extend_from_slice(&word[..EVEN_DATA_BITS]);
+    let mut odd = BitWriter::new();
+    odd.put(1, 1); // odd
+    odd.put(0, 1); // nominal page
+    odd.bits.extend_from_slice(&word[EVEN_DATA_BITS..WORD_BITS]);
+    odd.put(0, 40); // reserved 1
+    odd.put(0, 22); // search and rescue
+    odd.put(0, 2); // spare
+    let protected: Vec<u8> =
+        even.bits.iter().chain(&odd.bits).copied().collect();
+    odd.put(u64::from(crc24q(&protected)), 24);
+    odd.put(0, 8); // reserved 2
+    even.put(0, 6); // tail
+    odd.put(0, 6); // tail
+    (even.bits, odd.bits)
+}
+
+/// Encodes one page part into its 250 channel symbols.
+///
+/// # Arguments
+/// * `bits` - The 120 bits of the page part
+///
+/// # Returns
+/// The synchronisation pattern followed by the encoded and interleaved
+/// symbols (+1/-1)
+pub fn encode_part(bits: &[u8]) -> Vec<i8> {
+    // The tail bits of every page part flush the encoder
+    let encoded: Vec<i8> = ConvEncoder::default()
+        .encode(bits)
+        .iter()
+        .enumerate()
+        .map(|(i, &symbol)| if i % 2 == 1 { -symbol } else { symbol })
+        .collect();
+    // Written column by column, read row by row (ICD section 4.1.4.2), so
+    // a receiver deinterleaves with out[c * 8 + r] = in[r * 30 + c]
+    let mut symbols = SYNC.to_vec();
+    symbols.extend(
+        (0..ROWS)
+            .flat_map(|row| (0..COLUMNS).map(move |column| column * ROWS + row))
+            .map(|i| encoded[i]),
+    );
+    symbols
+}
+
+/// Encodes the I/NAV symbol stream around a given time.
+///
+/// The stream starts one page before the page containing `time` and runs for
+/// at least [`WINDOW`] seconds beyond that.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number
+/// * `eph` - Ephemeris of the satellite
+/// * `ionoutc` - Ionospheric and UTC parameters
+/// * `time` - Current receiver time
+///
+/// # Returns
+/// A tuple of the transmit time of the first symbol and the symbols
+pub fn symbols(
+    prn: usize, eph: &Ephemeris, ionoutc: &IonoUtc, time: &GpsTime,
+) -> (GpsTime, Vec<i8>) {
+    let current = GpsTime {
+        week: time.week,
+        sec: (time.sec / PAGE_PERIOD).floor() * PAGE_PERIOD,
+    };
+    let start = current.add_secs(-PAGE_PERIOD);
+    let count = (WINDOW / PAGE_PERIOD).ceil() as usize + 1;
+    let mut symbols = Vec::with_capacity(count * 500);
+    for k in 0..count {
+        let page_start = start.add_secs(k as f64 * PAGE_PERIOD);
+        let page = (page_start.sec % SUBFRAME_PERIOD / PAGE_PERIOD) as usize;
+        let bits = word(SUBFRAME_WORDS[page], prn, eph, ionoutc, &page_start);
+        let (even, odd) = page_parts(&bits);
+        symbols.extend(encode_part(&even));
+        symbols.extend(encode_part(&odd));
+    }
+    (start, symbols)
+}
@@ -0,0 +1,134 @@
+//! GPS L2 civil (L2C) spreading codes.
+//!
+//! Both L2C codes are truncated outputs of the same 27-stage linear feedback
+//! shift register, `1 + x^3 + x^4 + x^5 + x^6 + x^9 + x^11 + x^13 + x^16 +
+//! x^19 + x^21 + x^24 + x^27` (IS-GPS-200 figure 3-9). The CM code is reset
+//! every 10230 chips and the CL code every 767250 chips, each starting from a
+//! PRN-specific initial state.
+
+use constants::{L2CL_SEQ_LEN, L2CM_SEQ_LEN};
+
+/// Feedback mask of the L2C code generator (octal 445112474)
+const L2C_FEEDBACK: u32 = 0o445_112_474;
+
+/// Initial register states of the CM code for PRN 1-32 (IS-GPS-200 table
+/// 3-IIa)
+pub(super) const CM_INIT: [u32; 32] = [
+    0o742_417_664,
+    0o756_014_035,
+    0o002_747_144,
+    0o066_265_724,
+    0o601_403_471,
+    0o703_232_733,
+    0o124_510_070,
+    0o617_316_361,
+    0o047_541_621,
+    0o733_031_046,
+    0o713_512_145,
+    0o024_437_606,
+    0o021_264_003,
+    0o230_655_351,
+    0o001_314_400,
+    0o222_021_506,
+    0o540_264_026,
+    0o205_521_705,
+    0o064_022_144,
+    0o120_161_274,
+    0o044_023_533,
+    0o724_744_327,
+    0o045_743_577,
+    0o741_201_660,
+    0o700_274_134,
+    0o010_247_261,
+    0o713_433_445,
+    0o737_324_162,
+    0o311_627_434,
+    0o710_452_007,
+    0o722_462_133,
+    0o050_172_213,
+];
+
+/// Initial register states of the CL code for PRN 1-32 (IS-GPS-200 table
+/// 3-IIa)
+pub(super) const CL_INIT: [u32; 32] = [
+    0o624_145_772,
+    0o506_610_362,
+    0o220_360_016,
+    0o710_406_104,
+    0o001_143_345,
+    0o053_023_326,
+    0o652_521_276,
+    0o206_124_777,
+    0o015_563_374,
+    0o561_522_076,
+    0o023_163_525,
+    0o117_776_450,
+    0o606_516_355,
+    0o003_037_343,
+    0o046_515_565,
+    0o671_511_621,
+    0o605_402_220,
+    0o002_576_207,
+    0o525_163_451,
+    0o266_527_765,
+    0o006_760_703,
+    0o501_474_556,
+    0o743_747_443,
+    0o615_534_726,
+    0o763_621_420,
+    0o720_727_474,
+    0o700_521_043,
+    0o222_567_263,
+    0o132_765_304,
+    0o746_332_245,
+    0o102_300_466,
+    0o255_231_716,
+];
+
+/// Advances the L2C shift register by one chip.
+///
+/// The output chip is the least significant bit of the register before the
+/// shift.
+#[inline]
+pub(super) fn step(state: u32) -> u32 {
+    (state >> 1) ^ ((state & 1) * L2C_FEEDBACK)
+}
+
+/// Runs the L2C shift register from `init` and returns `len` chips.
+///
+/// Chips are mapped from logic levels to signal levels as 0 → +1 and
+/// 1 → -1.
+fn generate(init: u32, len: usize) -> Vec<i8> {
+    let mut state = init;
+    (0..len)
+        .map(|_| {
+            let out = state & 1;
+            state = step(state);
+            1 - 2 * out as i8
+        })
+        .collect()
+}
+
+/// Generates the 10230-chip L2 CM code of a satellite.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number (1-32)
+///
+/// # Returns
+/// The CM chips as +1/-1 values, or `None` for an unsupported PRN
+pub fn cm_code(prn: usize) -> Option<Vec<i8>> {
+    let init = CM_INIT.get(prn.checked_sub(1)?)?;
+    Some(generate(*init, L2CM_SEQ_LEN))
+}
+
+/// Generates the 767250-chip L2 CL code of a satellite.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number (1-32)
+///
+/// # Returns
+/// The CL chips as +1/-1 values, or `None` for an unsupported PRN
+pub fn cl_code(prn: usize) -> Option<Vec<i8>> {
+    let init = CL_INIT.get(prn.checked_sub(1)?)?;
+    Some(generate(*init, L2CL_SEQ_LEN))
+}
@@ -0,0 +1,114 @@
+//! GPS L5 spreading and Neuman-Hofman codes.
+//!
+//! The I5 and Q5 codes are the modulo-2 sum of two 13-stage shift register
+//! sequences (IS-GPS-705 section 3.3.2.2). XA, `1 + x^9 + x^10 + x^12 + x^13`,
+//! is short-cycled to 8190 chips; XB, `1 + x + x^3 + x^4 + x^6 + x^7 + x^8 +
+//! x^12 + x^13`, runs through its full 8191-chip period starting from a
+//! PRN-specific advance. Both registers start from the all-ones state at the
+//! beginning of every 1 ms code period of 10230 chips.
+
+use constants::L5_SEQ_LEN;
+
+/// Length of the short-cycled XA sequence in chips
+const XA_LEN: usize = 8190;
+
+/// Length of the XB sequence in chips
+const XB_LEN: usize = 8191;
+
+/// Feedback taps of XA: stages 9, 10, 12 and 13
+const XA_TAPS: u32 = 1 << 8 | 1 << 9 | 1 << 11 | 1 << 12;
+
+/// Feedback taps of XB: stages 1, 3, 4, 6, 7, 8, 12 and 13
+const XB_TAPS: u32 =
+    1 | 1 << 2 | 1 << 3 | 1 << 5 | 1 << 6 | 1 << 7 | 1 << 11 | 1 << 12;
+
+/// XB code advance of the I5 code for PRN 1-32 (IS-GPS-705 table 3-Ia)
+pub(super) const I5_ADVANCE: [usize; 32] = [
+    266, 365, 804, 1138, 1509, 1559, 1756, 2084, 2170, 2303, 2527, 2687, 2930,
+    3471, 3940, 4132, 4332, 4924, 5343, 5443, 5641, 5816, 5898, 5918, 5955,
+    6243, 6345, 6477, 6518, 6875, 7168, 7187,
+];
+
+/// XB code advance of the Q5 code for PRN 1-32 (IS-GPS-705 table 3-Ia)
+pub(super) const Q5_ADVANCE: [usize; 32] = [
+    1701, 323, 5292, 2020, 5429, 7136, 1041, 5947, 4315, 148, 535, 1939, 5206,
+    5910, 3595, 5135, 6082, 6990, 3546, 1523, 4548, 4484, 1893, 3961, 7106,
+    5299, 4660, 276, 4389, 3783, 1591, 1601,
+];
+
+/// 10-bit Neuman-Hofman code applied to the I5 (data) component
+pub const NH10: [i8; 10] = [1, 1, 1, 1, -1, -1, 1, -1, 1, -1];
+
+/// 20-bit Neuman-Hofman code applied to the Q5 (pilot) component
+pub const NH20: [i8; 20] = [
+    1, 1, 1, 1, 1, -1, 1, 1, -1, -1, 1, -1, 1, -1, 1, 1, -1, -1, -1, 1,
+];
+
+/// Runs a 13-stage Fibonacci shift register from the all-ones state.
+///
+/// # Arguments
+/// * `taps` - Feedback mask; bit `k` selects stage `k + 1`
+/// * `len` - Number of output chips (stage 13)
+///
+/// # Returns
+/// The output sequence as logic levels (0 or 1)
+fn shift_register(taps: u32, len: usize) -> Vec<u8> {
+    let mut state: u32 = 0x1fff;
+    (0..len)
+        .map(|_| {
+            let out = (state >> 12 & 1) as u8;
+            let feedback = (state & taps).count_ones() & 1;
+            state = (state << 1 | feedback) & 0x1fff;
+            out
+        })
+        .collect()
+}
+
+/// Returns the XB register state after `advance` shifts from all ones.
+///
+/// The state is returned with stage 1 in the most significant of the 13
+/// bits, matching the "initial XB code state" columns of the ICD.
+#[cfg(test)]
+pub(super) fn xb_state(advance: usize) -> u32 {
+    let xb = shift_register(XB_TAPS, advance + 13);
+    // Stage k holds the chip that will be output 13 - k shifts later
+    xb[advance..advance + 13]
+        .iter()
+        .fold(0, |acc, &bit| acc >> 1 | u32::from(bit) << 12)
+}
+
+/// Generates one code period from a given XB advance.
+fn generate(advance: usize) -> Vec<i8> {
+    let xa = shift_register(XA_TAPS, XA_LEN);
+    let xb = shift_register(XB_TAPS, XB_LEN);
+    (0..L5_SEQ_LEN)
+        .map(|i| {
+            let chip = xa[i % XA_LEN] ^ xb[(i + advance) % XB_LEN];
+            1 - 2 * chip as i8
+        })
+        .collect()
+}
+
+/// Generates the 10230-chip I5 code of a satellite.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number (1-32)
+///
+/// # Returns
+/// The I5 chips as +1/-1 values, or `None` for an unsupported PRN
+pub fn i5_code(prn: usize) -> Option<Vec<i8>> {
+    let advance = I5_ADVANCE.get(prn.checked_sub(1)?)?;
+    Some(generate(*advance))
+}
+
+/// Generates the 10230-chip Q5 code of a satellite.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number (1-32)
+///
+/// # Returns
+/// The Q5 chips as +1/-1 values, or `None` for an unsupported PRN
+pub fn q5_code(prn: usize) -> Option<Vec<i8>> {
+    let advance = Q5_ADVANCE.get(prn.checked_sub(1)?)?;
+    Some(generate(*advance))
+}
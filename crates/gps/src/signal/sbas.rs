@@ -0,0 +1,58 @@
+//! SBAS L1 ranging codes.
+//!
+//! SBAS GEO satellites transmit 1023-chip Gold codes from the same G1/G2
+//! register pair as GPS L1 C/A. The codes of PRNs 120-158 are selected by a
+//! delay of the G2 sequence instead of the phase taps used for PRNs 1-32
+//! (RTCA DO-229, appendix A.2 and IS-GPS-200 table 3-IV).
+
+use constants::{CA_SEQ_LEN, MAX_PRN_SBAS, MIN_PRN_SBAS};
+
+/// G2 delay in chips of PRNs 120-158
+const G2_DELAY: [usize; MAX_PRN_SBAS - MIN_PRN_SBAS + 1] = [
+    145, 175, 52, 21, 237, 235, 886, 657, 634, 762, 355, 1012, 176, 603, 130,
+    359, 595, 68, 386, 797, 456, 499, 883, 307, 127, 211, 121, 118, 163, 628,
+    853, 484, 289, 811, 202, 1021, 463, 568, 904,
+];
+
+/// Generates one period of a 10-stage register sequence starting from all
+/// ones.
+///
+/// # Arguments
+/// * `taps` - Stages (1-10) added into the feedback
+///
+/// # Returns
+/// The output of stage 10 as 0/1 bits
+fn register(taps: &[u32]) -> Vec<u8> {
+    // Bit k of the state holds stage k + 1
+    let mut state: u32 = 0x3ff;
+    (0..CA_SEQ_LEN)
+        .map(|_| {
+            let out = (state >> 9 & 1) as u8;
+            let feedback =
+                taps.iter().fold(0, |acc, &tap| acc ^ (state >> (tap - 1)));
+            state = (state << 1 | feedback & 1) & 0x3ff;
+            out
+        })
+        .collect()
+}
+
+/// Generates the ranging code of an SBAS satellite.
+///
+/// # Arguments
+/// * `prn` - Satellite PRN number (120-158)
+///
+/// # Returns
+/// The 1023 chips as +1/-1 values, or `None` for PRNs outside 120-158
+pub fn code(prn: usize) -> Option<Vec<i8>> {
+    let delay = *G2_DELAY.get(prn.checked_sub(MIN_PRN_SBAS)?)?;
+    let g1 = register(&[3, 10]);
+    let g2 = register(&[2, 3, 6, 8, 9, 10]);
+    Some(
+        (0..CA_SEQ_LEN)
+            .map(|i| {
+                let chip = g1[i] ^ g2[(i + CA_SEQ_LEN - delay) % CA_SEQ_LEN];
+                1 - 2 * chip as i8
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,258 @@
+//! SBAS L1 message framing and scheduling.
+//!
+//! SBAS messages are 250-bit blocks broadcast once per second (RTCA DO-229,
+//! appendix A.4.3): an 8-bit preamble, the 6-bit message type, 212 data bits
+//! and a CRC-24Q parity over the first 226 bits. The preambles 01010011,
+//! 10011010 and 11000110 rotate from one message to the next, the first one
+//! starting on every 6-second GPS epoch. The 250 bps stream is protected by
+//! the same continuous rate-1/2, K=7 convolutional code as CNAV, giving 500
+//! symbols/s, i.e. one symbol per two code periods.
+//!
+//! The message contents are not computed from a correction model: they are
+//! taken from a [`MessageStream`] attached to the GEO ephemeris, which replays
+//! recorded messages and periodic messages configured by the user, and
+//! raises alarms by broadcasting message type 0. Only message type 9 (GEO
+//! navigation) can be derived from the simulated orbit.
+
+use constants::SECONDS_IN_DAY;
+
+use super::cnav::{BitWriter, ConvEncoder, crc24q};
+use crate::{
+    datetime::GpsTime,
+    ephemeris::Ephemeris,
+    orbit::{Motion, StateVector},
+};
+
+/// Number of bits in a message
+pub const MESSAGE_BITS: usize = 250;
+
+/// Number of data bits following the message type
+pub const DATA_BITS: usize = 212;
+
+/// Number of message bits covered by the CRC
+const CRC_BITS: usize = 226;
+
+/// Preambles of three consecutive messages
+const PREAMBLES: [u64; 3] = [0b0101_0011, 0b1001_1010, 0b1100_0110];
+
+/// Message type 0: do not use the GEO for safety applications
+pub const ALARM_MESSAGE: u8 = 0;
+
+/// Message type 9: GEO navigation message
+pub const GEO_NAVIGATION: u8 = 9;
+
+/// Message type 63: null message
+pub const NULL_MESSAGE: u8 = 63;
+
+/// Length of the simulated symbol window in seconds, measured from the start
+/// of the message following the first one
+const WINDOW: f64 = 60.0;
+
+/// Message type and data field of one SBAS message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// Message type (0-63)
+    pub kind: u8,
+    /// The 212 data bits (0 or 1), or `None` for data derived from the
+    /// satellite: the GEO navigation data for message type 9 and zeros for
+    /// the other types
+    pub data: Option<Vec<u8>>,
+}
+impl Message {
+    /// Creates a message whose data is derived from the satellite.
+    ///
+    /// # Arguments
+    /// * `kind` - Message type (0-63)
+    pub fn derived(kind: u8) -> Self {
+        Self { kind, data: None }
+    }
+
+    /// Builds the 250-bit block of a message transmitted at a given time.
+    ///
+    /// # Arguments
+    /// * `eph` - Ephemeris of the satellite, for derived message data
+    /// * `state` - State vector of the ephemeris, for derived message data
+    /// * `time` - Transmit time of the first bit, on a whole second
+    ///
+    /// # Returns
+    /// The message bits, preamble first
+    pub fn encode(
+        &self, eph: &Ephemeris, state: &StateVector, time: &GpsTime,
+    ) -> Vec<u8> {
+        let mut w = BitWriter::new();
+        let index = (time.sec.round() as i64).rem_euclid(3) as usize;
+        w.put(PREAMBLES[index], 8);
+        w.put(u64::from(self.kind), 6);
+        match &self.data {
+            Some(data) => w.bits.extend(data.iter().take(DATA_BITS)),
+            None if self.kind == GEO_NAVIGATION => {
+                w.bits.extend(geo_navigation(eph, state));
+            }
+            None => {}
+        }
+        w.bits.resize(CRC_BITS, 0);
+        let crc = crc24q(&w.bits);
+        w.put(u64::from(crc), 24);
+        w.bits
+    }
+
+    /// Decodes a received 250-bit message block.
+    ///
+    /// # Arguments
+    /// * `bits` - The message bits (0 or 1), preamble first
+    ///
+    /// # Returns
+    /// The message type and data, or `None` if the block is too short or
+    /// fails its CRC
+    pub fn decode(bits: &[u8]) -> Option<Self> {
+        let parity = bits.get(CRC_BITS..MESSAGE_BITS)?;
+        let crc = parity.iter().fold(0, |crc, &bit| crc << 1 | u32::from(bit));
+        (crc24q(&bits[..CRC_BITS]) == crc).then(|| Self {
+            kind: bits[8..14].iter().fold(0, |kind, &bit| kind << 1 | bit),
+            data: Some(bits[14..CRC_BITS].to_vec()),
+        })
+    }
+}
+
+/// A message broadcast at regular intervals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheduled {
+    /// Message to broadcast
+    pub message: Message,
+    /// Interval between two broadcasts (seconds, at least 1)
+    pub interval: u64,
+    /// Second of the scenario of the first broadcast
+    pub offset: u64,
+}
+
+/// Messages broadcast by one SBAS GEO satellite during a scenario.
+///
+/// Seconds are counted from `start`. For every second, the message is the
+/// first applicable one of:
+/// 1. message type 0 while an alarm is raised,
+/// 2. the recorded message of that second,
+/// 3. the first scheduled message due in that second, in order of
+///    precedence (a message whose slot is taken is skipped, not delayed),
+/// 4. the null message (type 63).
+#[derive(Clone, Default)]
+pub struct MessageStream {
+    /// Periodic messages, in order of precedence
+    pub schedule: Vec<Scheduled>,
+    /// Recorded messages, one per second from `start` (`None` for gaps)
+    pub recorded: Vec<Option<Message>>,
+    /// Alarms as the first second and the number of seconds they last
+    pub alarms: Vec<(u64, u64)>,
+    /// GPS time of the first message of the scenario, on a whole second
+    pub start: GpsTime,
+}
+impl MessageStream {
+    /// Returns the message broadcast in a second of the scenario.
+    ///
+    /// # Arguments
+    /// * `second` - Seconds since `start`; messages before the start of the
+    ///   scenario are null messages
+    pub fn message(&self, second: i64) -> Message {
+        let Ok(n) = u64::try_from(second) else {
+            return Message::derived(NULL_MESSAGE);
+        };
+        if self
+            .alarms
+            .iter()
+            .any(|&(first, length)| (first..first + length).contains(&n))
+        {
+            return Message::derived(ALARM_MESSAGE);
+        }
+        if let Some(Some(message)) =
+            usize::try_from(n).ok().and_then(|i| self.recorded.get(i))
+        {
+            return message.clone();
+        }
+        self.schedule
+            .iter()
+            .find(|s| n >= s.offset && (n - s.offset) % s.interval == 0)
+            .map_or_else(
+                || Message::derived(NULL_MESSAGE),
+                |s| s.message.clone(),
+            )
+    }
+}
+
+/// Appends `value` quantized with a resolution of `lsb` as a
+/// two's-complement field of `n` bits, saturating at the field range.
+fn put_quantized(w: &mut BitWriter, value: f64, lsb: f64, n: usize) {
+    let max = (1i64 << (n - 1)) - 1;
+    w.put_signed(((value / lsb).round() as i64).clamp(-max - 1, max), n);
+}
+
+/// Encodes the data of message type 9 (DO-229 table A-18) from the state
+/// vector and clock of a GEO satellite.
+///
+/// # Arguments
+/// * `eph` - Ephemeris of the satellite, for the time and clock
+/// * `state` - State vector of the ephemeris
+///
+/// # Returns
+/// The 212 data bits
+pub fn geo_navigation(eph: &Ephemeris, state: &StateVector) -> Vec<u8> {
+    let mut w = BitWriter::new();
+    w.put(0, 8); // reserved
+    w.put((eph.toe.sec.rem_euclid(SECONDS_IN_DAY) / 16.0) as u64, 13); // t0
+    w.put(0, 4); // URA index
+    put_quantized(&mut w, state.pos[0], 0.08, 30);
+    put_quantized(&mut w, state.pos[1], 0.08, 30);
+    put_quantized(&mut w, state.pos[2], 0.4, 25);
+    put_quantized(&mut w, state.vel[0], 0.000_625, 17);
+    put_quantized(&mut w, state.vel[1], 0.000_625, 17);
+    put_quantized(&mut w, state.vel[2], 0.004, 18);
+    let acc = if state.motion == Motion::Polynomial {
+        state.acc
+    } else {
+        [0.0; 3]
+    };
+    put_quantized(&mut w, acc[0], 0.000_012_5, 10);
+    put_quantized(&mut w, acc[1], 0.000_012_5, 10);
+    put_quantized(&mut w, acc[2], 0.000_062_5, 10);
+    w.put_scaled(eph.af0, -31, 12);
+    w.put_scaled(eph.af1, -40, 8);
+    w.bits
+}
+
+/// Encodes the SBAS symbol stream around a given time.
+///
+/// The stream starts one message before the message containing `time` and
+/// runs for at least [`WINDOW`] seconds beyond that; the convolutional
+/// encoder is primed with the message preceding the stream. A satellite
+/// without a message stream broadcasts null messages.
+///
+/// # Arguments
+/// * `eph` - Ephemeris of the satellite
+/// * `state` - State vector of the ephemeris
+/// * `time` - Current receiver time
+///
+/// # Returns
+/// A tuple of the transmit time of the first symbol and the 2 ms symbols
+/// (+1/-1)
+pub fn symbols(
+    eph: &Ephemeris, state: &StateVector, time: &GpsTime,
+) -> (GpsTime, Vec<i8>) {
+    let current = GpsTime {
+        week: time.week,
+        sec: time.sec.floor(),
+    };
+    let start = current.add_secs(-1.0);
+    let message = |t: &GpsTime| {
+        let message = eph.sbas_messages.as_ref().map_or_else(
+            || Message::derived(NULL_MESSAGE),
+            |stream| stream.message(t.diff_secs(&stream.start).round() as i64),
+        );
+        message.encode(eph, state, t)
+    };
+    let mut encoder = ConvEncoder::default();
+    encoder.encode(&message(&start.add_secs(-1.0)));
+    let count = WINDOW as usize + 1;
+    let mut symbols = Vec::with_capacity(count * 2 * MESSAGE_BITS);
+    for k in 0..count {
+        symbols.extend(encoder.encode(&message(&start.add_secs(k as f64))));
+    }
+    (start, symbols)
+}
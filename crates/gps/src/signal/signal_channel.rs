@@ -0,0 +1,509 @@
+use std::sync::Arc;
+
+use constants::{CARR_FREQ, SPEED_OF_LIGHT, SPEED_OF_LIGHT_INV};
+use geometry::{Azel, Ecef};
+
+use crate::{
+    datetime::{GpsTime, TimeRange},
+    ephemeris::Ephemeris,
+    ionoutc::IonoUtc,
+    propagation::compute_range,
+    signal::{Constellation, MemoryCodes, SatelliteId, Signal},
+    table::{COS_TABLE512, SIN_TABLE512},
+};
+
+/// Square-wave subcarrier applied on top of a component's spreading code.
+///
+/// Subcarriers are sine-phased: each chip starts with the positive half
+/// period. BOC(n,1) subcarriers run at `n` times 1.023 MHz, i.e. `n` periods
+/// per chip of a 1.023 Mchip/s code.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Subcarrier {
+    /// No subcarrier (BPSK)
+    #[default]
+    None,
+    /// BOC(n,1) with the given number of subcarrier periods per chip
+    Boc(u32),
+    /// Composite BOC: `sqrt(10/11)` BOC(1,1) plus `sign * sqrt(1/11)`
+    /// BOC(6,1), the Galileo E1 OS modulation
+    Cboc(f64),
+}
+impl Subcarrier {
+    /// Returns the subcarrier value at a fractional position within a chip.
+    ///
+    /// # Arguments
+    /// * `fraction` - Position within the current chip (`0.0..1.0`)
+    #[inline]
+    pub fn value(self, fraction: f64) -> f64 {
+        let boc = |periods: u32| {
+            if ((fraction * f64::from(2 * periods)) as u32).is_multiple_of(2) {
+                1.0
+            } else {
+                -1.0
+            }
+        };
+        match self {
+            Subcarrier::None => 1.0,
+            Subcarrier::Boc(periods) => boc(periods),
+            Subcarrier::Cboc(sign) => {
+                (10.0f64 / 11.0).sqrt() * boc(1)
+                    + sign * (1.0f64 / 11.0).sqrt() * boc(6)
+            }
+        }
+    }
+}
+
+/// One spreading component of a signal.
+///
+/// A signal is modelled as a sum of components, each with its own primary
+/// code, optional secondary (overlay) code, optional subcarrier, carrier arm
+/// and relative amplitude. Components sharing one chip stream in alternate
+/// chips, such as L2 CM and CL, are described by their time-multiplexing
+/// slot.
+#[derive(Clone, Debug)]
+pub struct Component {
+    /// Primary spreading code chips (+1/-1)
+    code: Vec<i8>,
+    /// Secondary code chips, one per primary code period (+1/-1)
+    secondary: Vec<i8>,
+    /// Subcarrier modulating the spreading code
+    subcarrier: Subcarrier,
+    /// Number of time-multiplexing slots in the composite chip stream
+    slots: u64,
+    /// Slot of the composite chip stream occupied by this component
+    slot: u64,
+    /// Whether the component is carried on the quadrature arm
+    quadrature: bool,
+    /// Whether the component is modulated by navigation data symbols
+    data: bool,
+    /// Relative amplitude of the component
+    amplitude: f64,
+}
+impl Component {
+    /// Creates a unit-amplitude, data-less component on the in-phase arm.
+    ///
+    /// # Arguments
+    /// * `code` - Primary spreading code chips (+1/-1)
+    pub fn new(code: Vec<i8>) -> Self {
+        Self {
+            code,
+            secondary: vec![1],
+            subcarrier: Subcarrier::None,
+            slots: 1,
+            slot: 0,
+            quadrature: false,
+            data: false,
+            amplitude: 1.0,
+        }
+    }
+
+    /// Places the component in one slot of a time-multiplexed chip stream.
+    ///
+    /// # Arguments
+    /// * `slots` - Number of components sharing the chip stream
+    /// * `slot` - Slot index of this component (`0..slots`)
+    pub fn multiplexed(mut self, slots: u64, slot: u64) -> Self {
+        self.slots = slots;
+        self.slot = slot;
+        self
+    }
+
+    /// Applies a secondary (overlay) code, one chip per primary code period.
+    ///
+    /// # Arguments
+    /// * `secondary` - Secondary code chips (+1/-1)
+    pub fn secondary(mut self, secondary: Vec<i8>) -> Self {
+        self.secondary = secondary;
+        self
+    }
+
+    /// Modulates the spreading code with a subcarrier.
+    ///
+    /// # Arguments
+    /// * `subcarrier` - Subcarrier applied to every chip
+    pub fn subcarrier(mut self, subcarrier: Subcarrier) -> Self {
+        self.subcarrier = subcarrier;
+        self
+    }
+
+    /// Sets the relative amplitude of the component.
+    ///
+    /// A negative amplitude inverts the component, as used for the E1-C pilot
+    /// of Galileo E1.
+    ///
+    /// # Arguments
+    /// * `amplitude` - Amplitude relative to a unit BPSK component
+    pub fn amplitude(mut self, amplitude: f64) -> Self {
+        self.amplitude = amplitude;
+        self
+    }
+
+    /// Moves the component to the quadrature arm of the carrier.
+    pub fn quadrature(mut self) -> Self {
+        self.quadrature = true;
+        self
+    }
+
+    /// Marks the component as modulated by the navigation data symbols.
+    pub fn data(mut self) -> Self {
+        self.data = true;
+        self
+    }
+
+    /// Returns the chip value at a composite chip index, including the
+    /// secondary code but excluding data modulation.
+    ///
+    /// Returns 0 when the chip belongs to another time-multiplexed component.
+    #[inline]
+    fn chip(&self, composite: u64) -> i8 {
+        if composite % self.slots != self.slot {
+            return 0;
+        }
+        let k = composite / self.slots;
+        let len = self.code.len() as u64;
+        let period = k / len;
+        self.code[(k % len) as usize]
+            * self.secondary[(period % self.secondary.len() as u64) as usize]
+    }
+}
+
+/// Signal channel for any signal described by [`Signal::components`].
+///
+/// This is the generic counterpart of the L1 C/A `Channel`: it tracks the
+/// code and carrier phase of one satellite signal, holds a window of encoded
+/// navigation symbols, and produces the baseband I/Q contribution of the
+/// signal relative to the centre frequency of its band. The centre can be
+/// moved, e.g. to place the GLONASS FDMA channels within the output band.
+///
+/// The code phase is kept as a chip count since the first symbol of the
+/// current symbol window, which starts on a message boundary. Every spreading
+/// and secondary code period divides the message length, so the chip count
+/// also determines the phase of every code.
+#[derive(Default)]
+pub struct SignalChannel {
+    /// Satellite PRN number (0 when the channel is free)
+    pub prn: usize,
+    /// Signal generated by this channel
+    signal: Signal,
+    /// Spreading components of the signal
+    components: Vec<Component>,
+    /// Composite chipping rate of the signal (chips/s)
+    chip_rate: f64,
+    /// Number of composite chips per data symbol
+    chips_per_symbol: u64,
+    /// Carrier frequency the satellite transmits the signal on (Hz)
+    carrier: f64,
+    /// Frequency the baseband output is mixed down from (Hz)
+    center_frequency: f64,
+    /// Encoded navigation symbols (+1/-1)
+    symbols: Vec<i8>,
+    /// Transmit time of the first symbol in `symbols`
+    symbol_start: GpsTime,
+    /// Current Doppler shift of the carrier (Hz)
+    carrier_frequency: f64,
+    /// Current chipping rate including code Doppler (chips/s)
+    code_frequency: f64,
+    /// Doppler shift at the start of the step (Hz)
+    carrier_frequency_start: f64,
+    /// Change of the Doppler shift during the step (Hz/s)
+    carrier_frequency_rate: f64,
+    /// Current carrier phase accumulator (fixed-point representation)
+    carrier_phase: u32,
+    /// Carrier phase step per sample (fixed-point representation)
+    carrier_phase_step: i32,
+    /// Composite chips elapsed since `symbol_start`
+    chip_phase: f64,
+    /// Signal gain applied to the I/Q contribution
+    pub gain: i32,
+    /// Satellite azimuth and elevation angles
+    azel: Azel,
+    /// Previous pseudorange measurement and associated data
+    rho0: TimeRange,
+    /// Group delay of this signal relative to L1 C/A (meters)
+    group_delay: f64,
+    /// Galileo E1 memory codes, shared by the channels of a generator
+    memory_codes: Option<Arc<MemoryCodes>>,
+}
+impl SignalChannel {
+    /// Creates a free channel for a signal, centred on the signal's band.
+    pub fn new(signal: Signal) -> Self {
+        Self {
+            signal,
+            center_frequency: signal.band().center_frequency(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the frequency the baseband output is mixed down from.
+    ///
+    /// # Arguments
+    /// * `center_frequency` - Centre frequency of the output (Hz)
+    pub fn center_frequency(mut self, center_frequency: f64) -> Self {
+        self.center_frequency = center_frequency;
+        self
+    }
+
+    /// Sets the Galileo E1 memory codes the channel spreads E1 with.
+    ///
+    /// # Arguments
+    /// * `memory_codes` - Memory codes loaded by the generator, if any
+    pub fn memory_codes(
+        mut self, memory_codes: Option<Arc<MemoryCodes>>,
+    ) -> Self {
+        self.memory_codes = memory_codes;
+        self
+    }
+
+    /// Returns the signal generated by this channel.
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// Returns the satellite transmitting on this channel, or `None` when the
+    /// channel is free.
+    pub fn satellite(&self) -> Option<SatelliteId> {
+        (self.prn != 0)
+            .then(|| SatelliteId::new(self.signal.constellation(), self.prn))
+    }
+
+    /// Returns a reference to the latest pseudorange information.
+    pub fn rho0(&self) -> &TimeRange {
+        &self.rho0
+    }
+
+    /// Returns a reference to the satellite's azimuth and elevation.
+    pub fn azel(&self) -> &Azel {
+        &self.azel
+    }
+
+    /// Initializes the channel for a specific satellite.
+    ///
+    /// Generates the spreading codes and the navigation symbol window and
+    /// initializes the pseudorange and carrier phase.
+    ///
+    /// # Arguments
+    /// * `prn` - The PRN number of the satellite.
+    /// * `eph` - The ephemeris data for the satellite.
+    /// * `ionoutc` - Ionospheric and UTC parameters.
+    /// * `receiver_gps_time` - The current GPS time at the receiver.
+    /// * `xyz` - The receiver's position in ECEF coordinates.
+    /// * `velocity` - The receiver's velocity in ECEF coordinates.
+    /// * `azel` - The satellite's azimuth and elevation as seen from the
+    ///   receiver.
+    ///
+    /// # Returns
+    /// `false` if the signal has no code assignment for the PRN, or its
+    /// navigation message cannot be encoded from the ephemeris, in which case
+    /// the channel stays free.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_for_satellite(
+        &mut self, prn: usize, eph: &Ephemeris, ionoutc: &IonoUtc,
+        receiver_gps_time: &GpsTime, xyz: &Ecef, velocity: &Ecef, azel: Azel,
+    ) -> bool {
+        let Some(components) =
+            self.signal.components(prn, self.memory_codes.as_deref())
+        else {
+            return false;
+        };
+        self.prn = prn;
+        self.azel = azel;
+        self.components = components;
+        self.chip_rate = self.signal.chip_rate();
+        self.chips_per_symbol = self.signal.chips_per_symbol();
+        self.carrier = self.signal.satellite_carrier_frequency(eph);
+        // P(Y)-code group delay scaling, IS-GPS-200 section 20.3.3.3.3.2;
+        // the group delays of the other systems already refer to the signal
+        // itself and are applied through the clock correction
+        self.group_delay = if self.signal.constellation() == Constellation::Gps
+        {
+            eph.tgd * (self.signal.iono_scale() - 1.0) * SPEED_OF_LIGHT
+        } else {
+            0.0
+        };
+        if !self.refresh_symbols(eph, ionoutc, receiver_gps_time) {
+            return false;
+        }
+        let rho = compute_range(eph, ionoutc, receiver_gps_time, xyz, velocity);
+        self.rho0 = self.signal_range(&rho);
+        self.carrier_phase = 0;
+        true
+    }
+
+    /// Regenerates the navigation symbol window around the given time.
+    ///
+    /// The channel is released if the navigation message cannot be encoded
+    /// from the ephemeris, such as a GLONASS or SBAS data set without a state
+    /// vector.
+    ///
+    /// # Arguments
+    /// * `eph` - The ephemeris data for the satellite.
+    /// * `ionoutc` - Ionospheric and UTC parameters.
+    /// * `time` - The current GPS time at the receiver.
+    ///
+    /// # Returns
+    /// `false` if the channel was released.
+    pub fn refresh_symbols(
+        &mut self, eph: &Ephemeris, ionoutc: &IonoUtc, time: &GpsTime,
+    ) -> bool {
+        let Some((start, symbols)) =
+            self.signal.symbols(self.prn, eph, ionoutc, time)
+        else {
+            self.prn = 0;
+            return false;
+        };
+        self.symbol_start = start;
+        self.symbols = symbols;
+        true
+    }
+
+    /// Converts an L1 C/A range into the range observed on this signal.
+    ///
+    /// The ionospheric delay is scaled with the carrier frequency of the
+    /// satellite and the inter-signal group delay is added.
+    fn signal_range(&self, rho: &TimeRange) -> TimeRange {
+        let mut rho = rho.clone();
+        let iono_delay = rho.iono_delay * (CARR_FREQ / self.carrier).powi(2);
+        rho.range += iono_delay - rho.iono_delay + self.group_delay;
+        rho.iono_delay = iono_delay;
+        rho
+    }
+
+    /// Updates the channel's state based on new pseudorange information.
+    ///
+    /// Computes the Doppler-shifted carrier and code frequencies from the
+    /// pseudorange rate and the code phase from the signal transmit time.
+    ///
+    /// # Arguments
+    /// * `rho1` - The new L1 C/A pseudorange and associated time/azel data.
+    /// * `dt` - The time difference since the last pseudorange measurement
+    ///   (`rho0`).
+    /// * `sampling_period` - The receiver's sampling period in seconds.
+    pub fn update_state(
+        &mut self, rho1: &TimeRange, dt: f64, sampling_period: f64,
+    ) {
+        let rho1 = self.signal_range(rho1);
+        self.azel = rho1.azel;
+        let rhorate = (rho1.range - self.rho0.range) / dt;
+        self.carrier_frequency_start =
+            -rhorate * self.carrier * SPEED_OF_LIGHT_INV;
+        self.carrier_frequency_rate = 0.0;
+        self.sweep_doppler(0.0, sampling_period);
+        self.set_chip_phase(rho1);
+    }
+
+    /// Updates the channel's state from the range rates of the receiver and
+    /// satellite velocities.
+    ///
+    /// The Doppler sweeps linearly from the range rate of the previous
+    /// measurement to the one of `rho1`, through [`Self::sweep_doppler`]
+    /// within the step, instead of following the change of pseudorange.
+    ///
+    /// # Arguments
+    /// * `rho1` - The new L1 C/A pseudorange, with its range rate.
+    /// * `dt` - The time difference since the last pseudorange measurement
+    ///   (`rho0`).
+    /// * `sampling_period` - The receiver's sampling period in seconds.
+    pub fn update_state_with_rate(
+        &mut self, rho1: &TimeRange, dt: f64, sampling_period: f64,
+    ) {
+        let rho1 = self.signal_range(rho1);
+        self.azel = rho1.azel;
+        let scale = -self.carrier * SPEED_OF_LIGHT_INV;
+        self.carrier_frequency_start = self.rho0.rate * scale;
+        self.carrier_frequency_rate = (rho1.rate - self.rho0.rate) / dt * scale;
+        self.sweep_doppler(0.0, sampling_period);
+        self.set_chip_phase(rho1);
+    }
+
+    /// Sets the Doppler shift and the chipping rate at a time into the
+    /// current step.
+    ///
+    /// # Arguments
+    /// * `elapsed` - Time since the start of the step in seconds.
+    /// * `sampling_period` - The receiver's sampling period in seconds.
+    pub fn sweep_doppler(&mut self, elapsed: f64, sampling_period: f64) {
+        let carrier = self.carrier;
+        self.carrier_frequency = self.carrier_frequency_start
+            + self.carrier_frequency_rate * elapsed;
+        self.code_frequency =
+            self.chip_rate * (1.0 + self.carrier_frequency / carrier);
+        // The carrier is generated relative to the centre of the output
+        let baseband_frequency =
+            self.carrier_frequency + carrier - self.center_frequency;
+        self.carrier_phase_step =
+            (512.0 * 65536.0 * baseband_frequency * sampling_period).round()
+                as i32;
+    }
+
+    /// Sets the chip phase from the transmit time of the previous
+    /// pseudorange, then saves the new one.
+    fn set_chip_phase(&mut self, rho1: TimeRange) {
+        self.chip_phase = (self.rho0.time.diff_secs(&self.symbol_start)
+            - self.rho0.range * SPEED_OF_LIGHT_INV)
+            * self.chip_rate;
+        self.rho0 = rho1;
+    }
+
+    /// Generates the In-phase (I) and Quadrature (Q) signal contributions for
+    /// this channel.
+    ///
+    /// Sums the components of the signal at the current chip, including their
+    /// subcarriers, applies the data symbol to the data components and
+    /// rotates the result by the current carrier phase using the sine/cosine
+    /// lookup tables.
+    ///
+    /// # Returns
+    /// A tuple `(ip, qp)` representing the I and Q components, zero once
+    /// the symbol window is exhausted.
+    pub fn generate_iq_contribution(&self) -> (i32, i32) {
+        let chip_phase = self.chip_phase.max(0.0);
+        let chip = chip_phase as u64;
+        let fraction = chip_phase.fract();
+        // The window is refreshed before it runs out; past its end there is
+        // no data to transmit
+        let index = (chip / self.chips_per_symbol) as usize;
+        debug_assert!(
+            index < self.symbols.len(),
+            "{} PRN {}: symbol {index} past the window of {}",
+            self.signal,
+            self.prn,
+            self.symbols.len()
+        );
+        let Some(&symbol) = self.symbols.get(index) else {
+            return (0, 0);
+        };
+        let (mut si, mut sq) = (0.0, 0.0);
+        for component in &self.components {
+            let mut value = f64::from(component.chip(chip))
+                * component.amplitude
+                * component.subcarrier.value(fraction);
+            if component.data {
+                value *= f64::from(symbol);
+            }
+            if component.quadrature {
+                sq += value;
+            } else {
+                si += value;
+            }
+        }
+        let i_table = (self.carrier_phase >> 16 & 0x1ff) as usize;
+        let cos = f64::from(COS_TABLE512[i_table]);
+        let sin = f64::from(SIN_TABLE512[i_table]);
+        let gain = f64::from(self.gain);
+        (
+            (gain * (si * cos - sq * sin)) as i32,
+            (gain * (si * sin + sq * cos)) as i32,
+        )
+    }
+
+    /// Advances the code and carrier phase by one sample.
+    ///
+    /// # Arguments
+    /// * `sampling_period` - The receiver sampling period in seconds.
+    pub fn advance(&mut self, sampling_period: f64) {
+        self.chip_phase += self.code_frequency * sampling_period;
+        self.carrier_phase = self
+            .carrier_phase
+            .wrapping_add(self.carrier_phase_step as u32);
+    }
+}
@@ -0,0 +1,810 @@
+use super::{
+    Band, Constellation, MemoryCodes, Message, MessageStream, SatelliteId,
+    Scheduled, Signal, Subcarrier, b1i, bdnav,
+    cnav::{self, ConvEncoder, crc24q},
+    e1, glonass, glonav, inav, l2c, l5, sbas, sbasnav,
+};
+use crate::{
+    datetime::GpsTime,
+    ephemeris::Ephemeris,
+    ionoutc::IonoUtc,
+    orbit::{Frame, Motion, StateVector},
+};
+
+/// Interprets `n` message bits starting at `start` as an unsigned integer.
+fn field(bits: &[u8], start: usize, n: usize) -> u64 {
+    bits[start..start + n]
+        .iter()
+        .fold(0, |acc, &bit| acc << 1 | u64::from(bit))
+}
+
+#[test]
+fn test_l2c_code_end_states() {
+    // IS-GPS-200 table 3-IIa lists the register state holding the last chip
+    // of each code: PRN 1 CM 552566002, CL 267724236 (octal)
+    let mut cm = l2c::CM_INIT[0];
+    for _ in 0..constants::L2CM_SEQ_LEN - 1 {
+        cm = l2c::step(cm);
+    }
+    assert_eq!(cm, 0o552_566_002);
+    let mut cl = l2c::CL_INIT[0];
+    for _ in 0..constants::L2CL_SEQ_LEN - 1 {
+        cl = l2c::step(cl);
+    }
+    assert_eq!(cl, 0o267_724_236);
+}
+
+#[test]
+fn test_l2c_code_lengths() {
+    assert_eq!(l2c::cm_code(1).map(|c| c.len()), Some(10230));
+    assert_eq!(l2c::cl_code(32).map(|c| c.len()), Some(767_250));
+    assert!(l2c::cm_code(0).is_none());
+    assert!(l2c::cm_code(33).is_none());
+}
+
+#[test]
+fn test_crc24q_residue_is_zero() {
+    let mut bits: Vec<u8> =
+        (0..276).map(|i| ((i * 7 + 3) % 5 % 2) as u8).collect();
+    let crc = crc24q(&bits);
+    bits.extend((0..24).rev().map(|i| (crc >> i & 1) as u8));
+    assert_eq!(crc24q(&bits), 0);
+}
+
+#[test]
+fn test_conv_encoder_impulse_response() {
+    // A single one followed by zeros reproduces the generator taps
+    let mut encoder = ConvEncoder::default();
+    let mut bits = vec![0u8; 7];
+    bits[0] = 1;
+    let symbols = encoder.encode(&bits);
+    let g1: Vec<i8> = symbols.iter().step_by(2).copied().collect();
+    let g2: Vec<i8> = symbols.iter().skip(1).step_by(2).copied().collect();
+    // G1 = 1111001, G2 = 1011011 (octal 171 and 133)
+    assert_eq!(g1, [-1, -1, -1, -1, 1, 1, -1]);
+    assert_eq!(g2, [-1, 1, -1, -1, 1, -1, -1]);
+}
+
+#[test]
+fn test_cnav_message_header() {
+    let eph = Ephemeris::default();
+    let ionoutc = IonoUtc::default();
+    let start = GpsTime {
+        week: 2190,
+        sec: 12.0 * 5.0,
+    };
+    let bits = cnav::message(7, &eph, &ionoutc, &start, cnav::L2C_PERIOD);
+    assert_eq!(bits.len(), cnav::MESSAGE_BITS);
+    assert_eq!(field(&bits, 0, 8), 0b1000_1011);
+    assert_eq!(field(&bits, 8, 6), 7);
+    // Message index 5 in the 10, 11, 30, 33 cycle
+    assert_eq!(field(&bits, 14, 6), 11);
+    // TOW of the next message in 6-second units
+    assert_eq!(field(&bits, 20, 17), 12);
+    assert_eq!(crc24q(&bits), 0);
+}
+
+#[test]
+fn test_cnav_symbol_window() {
+    let eph = Ephemeris::default();
+    let ionoutc = IonoUtc::default();
+    let time = GpsTime {
+        week: 2190,
+        sec: 100.0,
+    };
+    let (start, symbols) =
+        cnav::symbols(1, &eph, &ionoutc, &time, cnav::L2C_PERIOD);
+    assert!((start.sec - 84.0).abs() < 1e-9);
+    assert!(symbols.len() as f64 / 50.0 >= time.diff_secs(&start) + 30.0);
+}
+
+#[test]
+fn test_signal_parsing_and_bands() {
+    assert_eq!("l2c".parse::<Signal>().ok(), Some(Signal::GpsL2C));
+    assert_eq!("L1CA".parse::<Signal>().ok(), Some(Signal::GpsL1Ca));
+    assert!("E5b".parse::<Signal>().is_err());
+    assert_eq!(Signal::GpsL2C.band(), Band::L2);
+    assert!(
+        (Signal::GpsL2C.iono_scale() - (77.0f64 / 60.0).powi(2)).abs() < 1e-12
+    );
+}
+
+#[test]
+fn test_l5_initial_xb_states() {
+    // IS-GPS-705 table 3-Ia, PRN 1: I5 0101011100100, Q5 1001011001100
+    assert_eq!(l5::xb_state(l5::I5_ADVANCE[0]), 0b0_1010_1110_0100);
+    assert_eq!(l5::xb_state(l5::Q5_ADVANCE[0]), 0b1_0010_1100_1100);
+}
+
+#[test]
+fn test_l5_codes() {
+    assert_eq!(l5::i5_code(1).map(|c| c.len()), Some(10230));
+    assert!(l5::q5_code(33).is_none());
+    assert_ne!(l5::i5_code(1), l5::q5_code(1));
+    // Overlay codes divide the 6-second CNAV message on L5
+    assert_eq!(l5::NH10.len(), 10);
+    assert_eq!(l5::NH20.len(), 20);
+    assert_eq!(Signal::GpsL5.chips_per_symbol() * 600, 6 * 10_230_000);
+}
+
+#[test]
+fn test_l5_signal() {
+    assert_eq!("l5".parse::<Signal>().ok(), Some(Signal::GpsL5));
+    assert_eq!(Signal::GpsL5.band(), Band::L5);
+    assert_eq!(Signal::GpsL5.to_string(), "L5");
+    assert!(Signal::GpsL5.min_sample_frequency() > 10e6);
+    assert_eq!(Signal::GpsL5.components(4, None).map(|c| c.len()), Some(2));
+}
+
+#[test]
+fn test_e1_secondary_code() {
+    // Galileo OS SIS ICD table 16: CS25 = 0011100000001010110110010
+    let expected: Vec<i8> = "0011100000001010110110010"
+        .chars()
+        .map(|c| if c == '1' { -1 } else { 1 })
+        .collect();
+    assert_eq!(e1::CS25.to_vec(), expected);
+}
+
+#[test]
+fn test_e1_memory_code_parsing() {
+    let hex = format!("8{}", "0".repeat(1022));
+    let codes = MemoryCodes::parse(&format!("# comment\n\nE1B, 3, {hex}\n"));
+    let Ok(codes) = codes else {
+        panic!("valid code file rejected");
+    };
+    let e1b = codes.e1b(3).unwrap_or_default();
+    assert_eq!(e1b.len(), 4092);
+    assert_eq!(e1b[..2], [-1, 1]);
+    assert!(codes.e1c(3).is_none());
+    assert!(codes.e1b(37).is_none());
+    assert!(MemoryCodes::parse(&format!("E1B 37 {hex}")).is_err());
+    assert!(MemoryCodes::parse(&format!("E5B 3 {hex}")).is_err());
+    assert!(MemoryCodes::parse("E1C 3 FFFF").is_err());
+}
+
+#[test]
+fn test_e1_components_use_given_codes() {
+    let zeros = "0".repeat(1023);
+    let ones = "F".repeat(1023);
+    let file = |e1b: &str| format!("E1B 3 {e1b}\nE1C 3 {zeros}\n");
+    let (Ok(first), Ok(second)) = (
+        MemoryCodes::parse(&file(&zeros)),
+        MemoryCodes::parse(&file(&ones)),
+    ) else {
+        panic!("valid code file rejected");
+    };
+    assert!(Signal::GalileoE1.components(3, None).is_none());
+    assert!(Signal::GalileoE1.components(4, Some(&first)).is_none());
+    // Each generator spreads with the codes it was given
+    assert_eq!(first.codes(3).map(|(e1b, _)| e1b[0]), Some(1));
+    assert_eq!(second.codes(3).map(|(e1b, _)| e1b[0]), Some(-1));
+    assert!(Signal::GalileoE1.components(3, Some(&first)).is_some());
+    assert!(Signal::GalileoE1.components(3, Some(&second)).is_some());
+}
+
+#[test]
+fn test_inav_page_parts() {
+    let eph = Ephemeris::default();
+    let ionoutc = IonoUtc::default();
+    let start = GpsTime {
+        week: 2190,
+        sec: 20.0,
+    };
+    let word = inav::word(5, 11, &eph, &ionoutc, &start);
+    assert_eq!(word.len(), 128);
+    assert_eq!(field(&word, 0, 6), 5);
+    let (even, odd) = inav::page_parts(&word);
+    assert_eq!((even.len(), odd.len()), (120, 120));
+    assert_eq!((even[0], odd[0]), (0, 1));
+    assert!(even[114..].iter().chain(&odd[114..]).all(|&bit| bit == 0));
+    // The CRC covers the even part and the odd part up to the CRC field
+    let protected: Vec<u8> =
+        even[..114].iter().chain(&odd[..106]).copied().collect();
+    assert_eq!(crc24q(&protected), 0);
+}
+
+#[test]
+fn test_inav_interleaver() {
+    // All-zero bits encode to +1 (G1) and inverted -1 (G2); the 8 x 30
+    // block is read row by row, and the symbols of a row are 8 apart in the
+    // encoded stream, so even rows hold G1 symbols only
+    let symbols = inav::encode_part(&[0; 120]);
+    assert_eq!(symbols.len(), 250);
+    assert_eq!(symbols[..10], inav::SYNC);
+    for (i, &symbol) in symbols[10..].iter().enumerate() {
+        let row = i / 30;
+        assert_eq!(symbol, if row % 2 == 0 { 1 } else { -1 });
+    }
+
+    // Known answer for a single 1 bit: the impulse response of G1 = 171
+    // and inverted G2 = 133 (octal) fills the first 14 encoded symbols,
+    // i.e. the first column and the top of the second one
+    let mut bits = [0; 120];
+    bits[0] = 1;
+    let symbols = inav::encode_part(&bits);
+    let column =
+        |c: usize| (0..8).map(|r| symbols[10 + r * 30 + c]).collect::<Vec<_>>();
+    assert_eq!(column(0), [-1, 1, -1, -1, -1, 1, -1, 1]);
+    assert_eq!(column(1), [1, -1, 1, 1, -1, 1, 1, -1]);
+    for c in 2..30 {
+        assert_eq!(column(c), [1, -1, 1, -1, 1, -1, 1, -1]);
+    }
+    assert_eq!(symbols[10..40].iter().filter(|&&s| s == -1).count(), 1);
+
+    // The receiver deinterleaver gives back the encoder output
+    let mut deinterleaved = [0; 240];
+    for r in 0..8 {
+        for c in 0..30 {
+            deinterleaved[c * 8 + r] = symbols[10 + r * 30 + c];
+        }
+    }
+    let encoded: Vec<i8> = ConvEncoder::default()
+        .encode(&bits)
+        .iter()
+        .enumerate()
+        .map(|(i, &symbol)| if i % 2 == 1 { -symbol } else { symbol })
+        .collect();
+    assert_eq!(deinterleaved[..], encoded[..]);
+}
+
+#[test]
+fn test_inav_symbol_window() {
+    let eph = Ephemeris::default();
+    let ionoutc = IonoUtc::default();
+    let time = GpsTime {
+        week: 2190,
+        sec: 100.5,
+    };
+    let (start, symbols) = inav::symbols(1, &eph, &ionoutc, &time);
+    assert!((start.sec - 98.0).abs() < 1e-9);
+    assert_eq!(symbols.len() % 250, 0);
+    assert!(symbols.len() as f64 / 250.0 >= time.diff_secs(&start) + 30.0);
+    assert!(symbols.chunks(250).all(|part| part[..10] == inav::SYNC));
+}
+
+#[test]
+fn test_subcarriers() {
+    assert!((Subcarrier::Boc(1).value(0.25) - 1.0).abs() < 1e-12);
+    assert!((Subcarrier::Boc(1).value(0.75) + 1.0).abs() < 1e-12);
+    assert!((Subcarrier::Boc(6).value(0.1) + 1.0).abs() < 1e-12);
+    let peak = (10.0f64 / 11.0).sqrt() + (1.0f64 / 11.0).sqrt();
+    assert!((Subcarrier::Cboc(1.0).value(0.01) - peak).abs() < 1e-12);
+    // CBOC is zero-mean over a chip and keeps unit power
+    let n = 1200;
+    let (sum, power) = (0..n)
+        .map(|i| Subcarrier::Cboc(-1.0).value((f64::from(i) + 0.5) / 1200.0))
+        .fold((0.0, 0.0), |(s, p), v| (s + v, p + v * v));
+    assert!(sum.abs() < 1e-9);
+    assert!((power / f64::from(n) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_galileo_e1_signal() {
+    assert_eq!("e1".parse::<Signal>().ok(), Some(Signal::GalileoE1));
+    assert_eq!(Signal::GalileoE1.to_string(), "E1");
+    assert_eq!(Signal::GalileoE1.band(), Band::L1);
+    assert_eq!(Signal::GalileoE1.constellation(), Constellation::Galileo);
+    assert_eq!(Signal::GalileoE1.constellation().max_prn(), 36);
+    // 250 symbols per second
+    assert_eq!(Signal::GalileoE1.chips_per_symbol() * 250, 1_023_000);
+    // Two samples per BOC(6,1) subcarrier period keep the CBOC unaliased
+    assert!(Signal::GalileoE1.min_sample_frequency() >= 12.276e6);
+    assert!((Signal::GalileoE1.iono_scale() - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_b1i_codes() {
+    // G1 is a maximal-length sequence: period 2047 with 1024 ones
+    let g1 = b1i::shift_register(b1i::G1_TAPS, 1 << 10, 2 * 2047);
+    assert_eq!(g1[..2047], g1[2047..]);
+    assert_eq!(
+        g1[..2047].iter().map(|&chip| u32::from(chip)).sum::<u32>(),
+        1024
+    );
+    assert_eq!(b1i::code(1).map(|c| c.len()), Some(2046));
+    assert!(b1i::code(0).is_none());
+    assert!(b1i::code(38).is_none());
+    // Distinct PRNs are nearly orthogonal at zero lag
+    let codes: Vec<Vec<i8>> = (1..=37).filter_map(b1i::code).collect();
+    for (i, a) in codes.iter().enumerate() {
+        for b in &codes[i + 1..] {
+            let dot: i32 = a.iter().zip(b).map(|(x, y)| i32::from(x * y)).sum();
+            assert!(dot.abs() < 200);
+        }
+    }
+}
+
+#[test]
+fn test_bch_parity() {
+    // x^4 mod (x^4 + x + 1) = x + 1
+    assert_eq!(bdnav::bch(1), 0b0011);
+    // Every code word is a multiple of the generator polynomial
+    for info in [0b101_1100_0111, 0b111_1111_1111, 0b000_0100_0000] {
+        let mut word = info << 4 | bdnav::bch(info);
+        for i in (4..15).rev() {
+            if word >> i & 1 == 1 {
+                word ^= 0b1_0011 << (i - 4);
+            }
+        }
+        assert_eq!(word, 0);
+    }
+}
+
+/// Extracts the subframe ID and the SOW of a B1I subframe.
+fn bd_header(bits: &[u8]) -> (u64, u64) {
+    // The first two information code words of word 2 are interleaved
+    let word2: Vec<u8> = bits[30..60]
+        .iter()
+        .step_by(2)
+        .take(11)
+        .chain(bits[31..60].iter().step_by(2).take(11))
+        .copied()
+        .collect();
+    let sow = field(bits, 18, 8) << 12 | field(&word2, 0, 12);
+    (field(bits, 15, 3), sow)
+}
+
+#[test]
+fn test_d1_subframes() {
+    let eph = Ephemeris::default();
+    let ionoutc = IonoUtc::default();
+    let time = GpsTime {
+        week: 2190,
+        sec: 100.5,
+    };
+    let (start, symbols) = bdnav::d1_symbols(&eph, &ionoutc, &time);
+    // Subframes start on 6 s boundaries of BDT, 14 s behind GPS time, one
+    // subframe before the current one
+    assert!((start.sec - 92.0).abs() < 1e-9);
+    assert!(symbols.len() as f64 / 500.0 >= time.diff_secs(&start) + 30.0);
+    let bits: Vec<u8> = symbols
+        .iter()
+        .step_by(10)
+        .map(|&s| u8::from(s < 0))
+        .collect();
+    for (k, subframe) in bits.chunks(300).enumerate() {
+        assert_eq!(field(subframe, 0, 11), 0b111_0001_0010);
+        let sow = 78 + 6 * k as u64;
+        assert_eq!(bd_header(subframe), (sow / 6 % 5 + 1, sow));
+    }
+}
+
+#[test]
+fn test_d2_subframes() {
+    let eph = Ephemeris::default();
+    let ionoutc = IonoUtc::default();
+    let time = GpsTime {
+        week: 2190,
+        sec: 100.5,
+    };
+    let (start, symbols) = bdnav::d2_symbols(&eph, &ionoutc, &time);
+    // Frames start on 3 s boundaries of BDT
+    assert!((start.sec - 95.0).abs() < 1e-9);
+    assert_eq!(symbols.len() % 1500, 0);
+    let bits: Vec<u8> = symbols.iter().map(|&s| u8::from(s < 0)).collect();
+    for (k, subframe) in bits.chunks(300).enumerate() {
+        let sow = 81 + 3 * (k / 5) as u64;
+        assert_eq!(bd_header(subframe), (k as u64 % 5 + 1, sow));
+    }
+}
+
+#[test]
+fn test_beidou_geo_orbit() {
+    // A GEO satellite over the equator: in the frame of the GEO orbit model,
+    // tilted by -5 degrees about the X axis, its orbit is inclined by 5
+    // degrees with the ascending node on the -X axis
+    let toe = GpsTime {
+        week: 2190,
+        sec: 3600.0,
+    };
+    let mut eph = Ephemeris {
+        toe: toe.clone(),
+        toc: toe.clone(),
+        sqrta: 42_164_170f64.sqrt(),
+        inc0: 5f64.to_radians(),
+        time_offset: -14.0,
+        geostationary: true,
+        ..Default::default()
+    };
+    eph.omg0 = constants::PI + constants::OMEGA_EARTH * (toe.sec - 14.0);
+    eph.A = eph.sqrta * eph.sqrta;
+    eph.n = (constants::GM_EARTH_BEIDOU / eph.A.powi(3)).sqrt();
+    eph.sq1e2 = 1.0;
+    for dt in [0.0, 1800.0, 7200.0] {
+        let (pos, vel, _) = eph.compute_satellite_state(&toe.add_secs(dt));
+        assert!(pos[2].abs() < 1.0);
+        let radius = pos.iter().map(|p| p * p).sum::<f64>().sqrt();
+        assert!((radius - eph.A).abs() < 1.0);
+        // Geostationary: nearly fixed in ECEF
+        assert!(vel.iter().map(|v| v * v).sum::<f64>().sqrt() < 1.0);
+    }
+}
+
+#[test]
+fn test_beidou_b1i_signal() {
+    assert_eq!("b1i".parse::<Signal>().ok(), Some(Signal::BeidouB1I));
+    assert_eq!(Signal::BeidouB1I.to_string(), "B1I");
+    assert_eq!(Signal::BeidouB1I.band(), Band::B1);
+    assert_eq!(Signal::BeidouB1I.constellation().max_prn(), 63);
+    // 500 symbols per second, the D2 bit rate
+    assert_eq!(Signal::BeidouB1I.chips_per_symbol() * 500, 2_046_000);
+    assert!(b1i::is_geostationary(3) && !b1i::is_geostationary(6));
+    assert!(Signal::BeidouB1I.components(6, None).is_some());
+    assert!(
+        Signal::BeidouB1I
+            .components(b1i::MAX_PRN_B1I, None)
+            .is_some()
+    );
+    assert!(Signal::BeidouB1I.components(38, None).is_none());
+}
+
+#[test]
+fn test_glonass_code() {
+    // Maximal-length sequence: 256 ones and 255 zeros, starting with the
+    // seven ones of the initial state
+    let code = glonass::code();
+    assert_eq!(code.len(), 511);
+    assert_eq!(code.iter().map(|&chip| i32::from(chip)).sum::<i32>(), -1);
+    assert!(code[..7].iter().all(|&chip| chip == -1));
+    assert_eq!(code[7], 1);
+}
+
+#[test]
+fn test_glonass_hamming() {
+    // β1 as listed in the GLONASS ICD (edition 5.1, section 4.7)
+    const BETA1: [usize; 41] = [
+        9, 10, 12, 13, 15, 17, 19, 20, 22, 24, 26, 28, 30, 32, 34, 35, 37, 39,
+        41, 43, 45, 47, 49, 51, 53, 55, 57, 59, 61, 63, 65, 66, 68, 70, 72, 74,
+        76, 78, 80, 82, 84,
+    ];
+    let mut string = [0u8; 85];
+    for (i, bit) in string.iter_mut().enumerate().skip(1).take(76) {
+        *bit = u8::from(i % 3 == 0 || i % 7 == 1);
+    }
+    let beta = glonav::hamming(&string);
+    let beta1 = BETA1.iter().fold(0, |acc, &i| acc ^ string[85 - i]);
+    assert_eq!(beta[0], beta1);
+    // With the check bits in place the string has even parity
+    for (k, &b) in beta.iter().enumerate() {
+        string[84 - k] = b;
+    }
+    assert_eq!(string.iter().fold(0, |acc, bit| acc ^ bit), 0);
+    // A single data bit error changes the check bits
+    string[40] ^= 1;
+    assert_ne!(glonav::hamming(&string), beta);
+}
+
+#[test]
+fn test_glonass_strings() {
+    let state = StateVector {
+        pos: [-1.0e7, 2.0e7, 5.0e6],
+        frame: Frame::Pz90,
+        ..Default::default()
+    };
+    let eph = Ephemeris::default();
+    let ionoutc = IonoUtc {
+        dtls: 18,
+        ..Default::default()
+    };
+    let time = GpsTime {
+        week: 2190,
+        sec: 100.5,
+    };
+    let (start, symbols) = glonav::symbols(3, &eph, &state, &ionoutc, &time);
+    // No string is encoded without a state vector
+    assert!(
+        Signal::GlonassL1Of
+            .symbols(3, &eph, &ionoutc, &time)
+            .is_none()
+    );
+    // Strings start on even seconds of UTC, one string before the current
+    assert!((start.sec - 98.0).abs() < 1e-9);
+    assert!(symbols.len() as f64 / 100.0 >= time.diff_secs(&start) + 30.0);
+    assert_eq!(symbols.len() % 200, 0);
+    let level = |bit: &u8| 1 - 2 * *bit as i8;
+    for (k, string) in symbols.chunks(200).enumerate() {
+        // Meander: every bit is a symbol followed by its inverse
+        assert!(string[..170].chunks(2).all(|pair| pair[0] == -pair[1]));
+        assert!(
+            string[170..]
+                .iter()
+                .copied()
+                .eq(glonav::TIME_MARK.iter().map(level))
+        );
+        // Undo the relative code
+        let mut previous = 0;
+        let bits: Vec<u8> = string[..170]
+            .iter()
+            .step_by(2)
+            .map(|&s| {
+                let relative = u8::from(s < 0);
+                let bit = relative ^ previous;
+                previous = relative;
+                bit
+            })
+            .collect();
+        assert_eq!(bits[0], 0);
+        // 00:01:20 Moscow time is string 11 of its frame
+        let number = (10 + k as u64) % 15 + 1;
+        assert_eq!(field(&bits, 1, 4), number);
+        let beta = glonav::hamming(&bits);
+        assert!(bits[77..].iter().eq(beta.iter().rev()));
+        match number {
+            // tk = 03:01:30 and 03:02:00 Moscow time, x = -10000 km
+            1 => {
+                let tk = 10_890 + 30 * (k as u64 / 15);
+                assert_eq!(field(&bits, 9, 5), tk / 3600);
+                assert_eq!(field(&bits, 14, 6), tk % 3600 / 60);
+                assert_eq!(field(&bits, 20, 1), tk % 60 / 30);
+                assert_eq!(field(&bits, 50, 27), 1 << 26 | 10_000 << 11);
+            }
+            4 => assert_eq!(field(&bits, 70, 5), 3),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn test_glonass_orbit() {
+    // Circular equatorial orbit at the GLONASS radius, expressed in the
+    // rotating frame
+    let radius = 25_510_000.0;
+    let speed = (constants::GM_EARTH_GLONASS / radius).sqrt();
+    let state = StateVector {
+        pos: [radius, 0.0, 0.0],
+        vel: [0.0, speed - constants::OMEGA_EARTH_GLONASS * radius, 0.0],
+        frame: Frame::Pz90,
+        ..Default::default()
+    };
+    for dt in [-900.0, 300.0, 900.0] {
+        let (pos, _) = state.propagate(dt);
+        let r = pos.iter().map(|p| p * p).sum::<f64>().sqrt();
+        assert!((r - radius).abs() < 10_000.0);
+        assert!(pos[2].abs() < 1e-6);
+    }
+    // Integrating back returns to the initial state
+    let (pos, vel) = state.propagate(900.0);
+    let back = StateVector {
+        pos,
+        vel,
+        ..state.clone()
+    };
+    let (pos, _) = back.propagate(-900.0);
+    for (p, q) in pos.iter().zip(&state.pos) {
+        assert!((p - q).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_pz90_wgs84_round_trip() {
+    let pos = [-1.0e7, 2.0e7, 5.0e6];
+    let vel = [1000.0, -2000.0, 3000.0];
+    let (wgs, wgs_vel) = Frame::Pz90.to_wgs84(&pos, &vel);
+    // The frames differ by decimetres at orbit altitude
+    let shift = wgs
+        .iter()
+        .zip(&pos)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt();
+    assert!(shift > 0.05 && shift < 2.0);
+    let (back, back_vel) = Frame::Pz90.convert_from_wgs84(&wgs, &wgs_vel);
+    for (a, b) in back.iter().zip(&pos).chain(back_vel.iter().zip(&vel)) {
+        assert!((a - b).abs() < 1e-3);
+    }
+    assert_eq!(Frame::Wgs84.to_wgs84(&pos, &vel), (pos, vel));
+}
+
+#[test]
+fn test_glonass_l1of_signal() {
+    assert_eq!("l1of".parse::<Signal>().ok(), Some(Signal::GlonassL1Of));
+    assert_eq!(Signal::GlonassL1Of.to_string(), "L1OF");
+    assert_eq!(Signal::GlonassL1Of.band(), Band::G1);
+    assert_eq!(Signal::GlonassL1Of.constellation().max_prn(), 24);
+    // 100 meander symbols per second
+    assert_eq!(Signal::GlonassL1Of.chips_per_symbol() * 100, 511_000);
+    let eph = Ephemeris {
+        frequency_channel: -7,
+        ..Default::default()
+    };
+    let carrier = Signal::GlonassL1Of.satellite_carrier_frequency(&eph);
+    assert!((carrier - 1_598.062_5e6).abs() < 1e-3);
+    // Channels -7 to +6 around 1602 MHz, plus the main lobe
+    let fs = glonass::fdma_sample_frequency(1602e6);
+    assert!((fs - 2.0 * (3_937_500.0 + 511_000.0)).abs() < 1e-3);
+    assert!(glonass::fdma_sample_frequency(1601.71875e6) < fs);
+    assert!(Signal::GlonassL1Of.components(24, None).is_some());
+    assert!(Signal::GlonassL1Of.components(25, None).is_none());
+}
+
+#[test]
+fn test_sbas_codes() {
+    // DO-229 lists the first 10 chips of each code in octal
+    for (prn, first) in
+        [(120, 0o671), (125, 0o701), (131, 0o1226), (138, 0o1327)]
+    {
+        let Some(code) = sbas::code(prn) else {
+            panic!("no code for PRN {prn}");
+        };
+        let chips = code[..10]
+            .iter()
+            .fold(0, |acc, &chip| acc << 1 | u32::from(chip < 0));
+        assert_eq!(chips, first, "PRN {prn}");
+        assert_eq!(code.len(), 1023);
+    }
+    assert!(sbas::code(119).is_none());
+    assert!(sbas::code(159).is_none());
+    // Gold codes have the three-valued cross-correlation -65, -1 and 63
+    let (Some(a), Some(b)) = (sbas::code(120), sbas::code(158)) else {
+        panic!("missing codes");
+    };
+    for shift in 0..1023 {
+        let correlation: i32 = (0..1023)
+            .map(|i| i32::from(a[i]) * i32::from(b[(i + shift) % 1023]))
+            .sum();
+        assert!([-65, -1, 63].contains(&correlation));
+    }
+}
+
+#[test]
+fn test_sbas_frames() {
+    let eph = Ephemeris::default();
+    let message = Message {
+        kind: 2,
+        data: Some((0..212).map(|i| u8::from(i % 3 == 0)).collect()),
+    };
+    let preambles: Vec<u64> = (6..9)
+        .map(|sec| {
+            let time = GpsTime {
+                week: 2190,
+                sec: f64::from(sec),
+            };
+            let bits = message.encode(&eph, &StateVector::default(), &time);
+            assert_eq!(bits.len(), 250);
+            assert_eq!(Message::decode(&bits).as_ref(), Some(&message));
+            field(&bits, 0, 8)
+        })
+        .collect();
+    assert_eq!(preambles, [0x53, 0x9a, 0xc6]);
+    // A corrupted block fails its CRC
+    let mut bits =
+        message.encode(&eph, &StateVector::default(), &GpsTime::default());
+    bits[100] ^= 1;
+    assert!(Message::decode(&bits).is_none());
+}
+
+#[test]
+fn test_sbas_geo_navigation() {
+    let state = StateVector {
+        pos: [-3.0e7, 2.9e7, 8.0],
+        vel: [1.0, -2.0, 3.0],
+        acc: [1e-4, 0.0, -1e-3],
+        motion: Motion::Polynomial,
+        ..Default::default()
+    };
+    let eph = Ephemeris {
+        toe: GpsTime {
+            week: 2190,
+            sec: 86_400.0 + 3_200.0,
+        },
+        af0: -2.0e-8,
+        ..Default::default()
+    };
+    let bits = sbasnav::geo_navigation(&eph, &state);
+    assert_eq!(bits.len(), 212);
+    let signed = |start, n| {
+        let value = field(&bits, start, n) as i64;
+        value - (value >> (n - 1) << n)
+    };
+    assert_eq!(field(&bits, 8, 13), 200); // t0 in units of 16 s
+    assert_eq!(signed(25, 30), -375_000_000); // X, 0.08 m
+    assert_eq!(signed(85, 25), 20); // Z, 0.4 m
+    assert_eq!(signed(127, 17), -3200); // Y rate, 0.000625 m/s
+    assert_eq!(signed(144, 18), 750); // Z rate, 0.004 m/s
+    assert_eq!(signed(162, 10), 8); // X acceleration, 0.0000125 m/s²
+    assert_eq!(signed(192, 12), -43); // aGf0, 2^-31 s
+    // No message is encoded without a state vector
+    assert!(
+        Signal::SbasL1
+            .symbols(120, &eph, &IonoUtc::default(), &eph.toe)
+            .is_none()
+    );
+    // The polynomial model extrapolates with the constant acceleration
+    let (pos, vel) = state.propagate(100.0);
+    assert!((pos[2] - (8.0 + 300.0 - 5.0)).abs() < 1e-9);
+    assert!((vel[0] - 1.01).abs() < 1e-12);
+}
+
+#[test]
+fn test_sbas_message_schedule() {
+    let stream = MessageStream {
+        schedule: vec![
+            Scheduled {
+                message: Message::derived(1),
+                interval: 6,
+                offset: 0,
+            },
+            Scheduled {
+                message: Message::derived(9),
+                interval: 2,
+                offset: 0,
+            },
+        ],
+        recorded: vec![None, None, Some(Message::derived(25))],
+        alarms: vec![(6, 2)],
+        ..Default::default()
+    };
+    let kinds: Vec<u8> = (-1..10).map(|n| stream.message(n).kind).collect();
+    assert_eq!(kinds, [63, 1, 63, 25, 63, 9, 63, 0, 0, 9, 63]);
+}
+
+#[test]
+fn test_sbas_symbol_window() {
+    let eph = Ephemeris {
+        sbas_messages: Some(MessageStream {
+            schedule: vec![Scheduled {
+                message: Message::derived(9),
+                interval: 3,
+                offset: 1,
+            }],
+            alarms: vec![(40, 5)],
+            start: GpsTime {
+                week: 2190,
+                sec: 90.0,
+            },
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let time = GpsTime {
+        week: 2190,
+        sec: 100.4,
+    };
+    let state = StateVector::default();
+    let (start, symbols) = sbasnav::symbols(&eph, &state, &time);
+    assert!((start.sec - 99.0).abs() < 1e-9);
+    assert!(symbols.len() as f64 / 500.0 >= time.diff_secs(&start) + 30.0);
+    // A later window continues the same encoded stream
+    let (later, again) = sbasnav::symbols(&eph, &state, &time.add_secs(30.0));
+    let offset = (later.diff_secs(&start) * 500.0).round() as usize;
+    assert_eq!(symbols[offset..], again[..symbols.len() - offset]);
+}
+
+#[test]
+fn test_sbas_l1_signal() {
+    assert_eq!("sbas".parse::<Signal>().ok(), Some(Signal::SbasL1));
+    assert_eq!(Signal::SbasL1.to_string(), "SBAS");
+    assert_eq!(Signal::SbasL1.band(), Band::L1);
+    assert_eq!(Signal::SbasL1.constellation(), Constellation::Sbas);
+    assert_eq!(Constellation::Sbas.prns(), 120..=158);
+    // 500 symbols per second
+    assert_eq!(Signal::SbasL1.chips_per_symbol() * 500, 1_023_000);
+    assert!(Signal::SbasL1.components(120, None).is_some());
+    assert!(Signal::SbasL1.components(32, None).is_none());
+}
+
+#[test]
+fn test_qzss_l1ca_signal() {
+    assert_eq!("qzss".parse::<Signal>().ok(), Some(Signal::QzssL1Ca));
+    assert_eq!(Signal::QzssL1Ca.to_string(), "QZSS");
+    assert_eq!(Signal::QzssL1Ca.band(), Band::L1);
+    assert_eq!(Signal::QzssL1Ca.constellation(), Constellation::Qzss);
+    assert_eq!(Constellation::Qzss.prns(), 193..=202);
+    // Both L1 C/A signals are generated by the legacy channels
+    assert!(Signal::QzssL1Ca.uses_legacy_channel());
+    assert!(Signal::GpsL1Ca.uses_legacy_channel());
+    assert!(!Signal::SbasL1.uses_legacy_channel());
+    assert!(Signal::QzssL1Ca.components(193, None).is_none());
+}
+
+#[test]
+fn test_satellite_id_display() {
+    let id = |constellation, prn| SatelliteId::new(constellation, prn);
+    assert_eq!(id(Constellation::Gps, 5).to_string(), "G05");
+    assert_eq!(id(Constellation::Galileo, 11).to_string(), "E11");
+    assert_eq!(id(Constellation::Glonass, 24).to_string(), "R24");
+    assert_eq!(id(Constellation::Sbas, 123).to_string(), "S23");
+    assert_eq!(id(Constellation::Qzss, 193).to_string(), "J01");
+    assert_eq!(id(Constellation::Qzss, 202).to_string(), "J10");
+}
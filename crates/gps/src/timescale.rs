@@ -0,0 +1,183 @@
+//! Time scales and the conversions between them.
+//!
+//! The simulation runs in GPS time (GPST), a continuous scale 19 seconds
+//! behind International Atomic Time (TAI). Galileo System Time (GST) is
+//! aligned with GPST, and BeiDou Time (BDT) is 14 seconds behind it. UTC
+//! follows TAI with an integer number of leap seconds, inserted at the end of
+//! June or December, so a UTC date is converted with the table of leap
+//! seconds below; during an inserted second, UTC reads 23:59:60.
+
+use std::{fmt, str::FromStr};
+
+use constants::{BDT_GPST_OFFSET, TAI_GPST_OFFSET};
+
+use crate::{
+    Error,
+    datetime::{DateTime, GpsTime},
+};
+
+/// Dates from which TAI - UTC takes a new value (year, month, day, seconds),
+/// as announced by the IERS; no leap second has been inserted since the end
+/// of 2016
+const LEAP_SECONDS: [(i32, i32, i32, i32); 28] = [
+    (1972, 1, 1, 10),
+    (1972, 7, 1, 11),
+    (1973, 1, 1, 12),
+    (1974, 1, 1, 13),
+    (1975, 1, 1, 14),
+    (1976, 1, 1, 15),
+    (1977, 1, 1, 16),
+    (1978, 1, 1, 17),
+    (1979, 1, 1, 18),
+    (1980, 1, 1, 19),
+    (1981, 7, 1, 20),
+    (1982, 7, 1, 21),
+    (1983, 7, 1, 22),
+    (1985, 7, 1, 23),
+    (1988, 1, 1, 24),
+    (1990, 1, 1, 25),
+    (1991, 1, 1, 26),
+    (1992, 7, 1, 27),
+    (1993, 7, 1, 28),
+    (1994, 7, 1, 29),
+    (1996, 1, 1, 30),
+    (1997, 7, 1, 31),
+    (1999, 1, 1, 32),
+    (2006, 1, 1, 33),
+    (2009, 1, 1, 34),
+    (2012, 7, 1, 35),
+    (2015, 7, 1, 36),
+    (2017, 1, 1, 37),
+];
+
+/// A time scale in which user dates may be given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeScale {
+    /// Coordinated Universal Time
+    #[default]
+    Utc,
+    /// GPS time
+    Gpst,
+    /// International Atomic Time
+    Tai,
+    /// Galileo System Time
+    Gst,
+    /// BeiDou Time
+    Bdt,
+}
+impl TimeScale {
+    /// Converts a date of this time scale to GPS time.
+    ///
+    /// # Arguments
+    /// * `time` - Calendar date and time in this time scale; a UTC second
+    ///   of 60 or more denotes an inserted leap second
+    ///
+    /// # Returns
+    /// The equivalent GPS time
+    pub fn to_gps_time(self, time: &DateTime) -> GpsTime {
+        let calendar = GpsTime::from(time);
+        match self {
+            TimeScale::Utc => calendar.add_secs(gps_utc_offset(time)),
+            TimeScale::Gpst | TimeScale::Gst => calendar,
+            TimeScale::Tai => calendar.add_secs(-TAI_GPST_OFFSET),
+            TimeScale::Bdt => calendar.add_secs(BDT_GPST_OFFSET),
+        }
+    }
+
+    /// Converts a GPS time to a date of this time scale.
+    ///
+    /// # Arguments
+    /// * `time` - GPS time to convert
+    ///
+    /// # Returns
+    /// The calendar date and time in this time scale, with a second of 60
+    /// during an inserted UTC leap second
+    pub fn date_of(self, time: &GpsTime) -> DateTime {
+        match self {
+            TimeScale::Utc => gps_to_utc(time),
+            TimeScale::Gpst | TimeScale::Gst => DateTime::from(time),
+            TimeScale::Tai => DateTime::from(&time.add_secs(TAI_GPST_OFFSET)),
+            TimeScale::Bdt => DateTime::from(&time.add_secs(-BDT_GPST_OFFSET)),
+        }
+    }
+}
+impl fmt::Display for TimeScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TimeScale::Utc => "UTC",
+            TimeScale::Gpst => "GPST",
+            TimeScale::Tai => "TAI",
+            TimeScale::Gst => "GST",
+            TimeScale::Bdt => "BDT",
+        };
+        f.write_str(name)
+    }
+}
+impl FromStr for TimeScale {
+    type Err = Error;
+
+    /// Parses a time scale name such as `UTC`, `GPST`, `TAI`, `GST` or `BDT`
+    /// (case-insensitive).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "UTC" | "Z" => Ok(TimeScale::Utc),
+            "GPST" | "GPS" => Ok(TimeScale::Gpst),
+            "TAI" => Ok(TimeScale::Tai),
+            "GST" | "GAL" => Ok(TimeScale::Gst),
+            "BDT" | "BDS" => Ok(TimeScale::Bdt),
+            _ => Err(Error::invalid_time_scale(s)),
+        }
+    }
+}
+
+/// Returns the offset of GPS time from UTC at a UTC date.
+///
+/// # Arguments
+/// * `utc` - UTC date; dates before 1972 use the offset of 1972
+///
+/// # Returns
+/// GPST - UTC (seconds), 18 since 2017
+pub fn gps_utc_offset(utc: &DateTime) -> f64 {
+    let date = (utc.y, utc.m, utc.d);
+    let tai_utc = LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|&&(y, m, d, _)| (y, m, d) <= date)
+        .map_or(LEAP_SECONDS[0].3, |&(_, _, _, tai_utc)| tai_utc);
+    f64::from(tai_utc) - TAI_GPST_OFFSET
+}
+
+/// Converts a GPS time to a UTC date, with a second of 60 during an
+/// inserted leap second.
+///
+/// # Arguments
+/// * `time` - GPS time to convert
+///
+/// # Returns
+/// The UTC date and time
+fn gps_to_utc(time: &GpsTime) -> DateTime {
+    for &(y, m, d, tai_utc) in LEAP_SECONDS.iter().rev() {
+        let offset = f64::from(tai_utc) - TAI_GPST_OFFSET;
+        // GPS time at which the offset takes effect, one second after the
+        // inserted 23:59:60
+        let start = GpsTime::from(&DateTime {
+            y,
+            m,
+            d,
+            ..Default::default()
+        })
+        .add_secs(offset);
+        let dt = time.diff_secs(&start);
+        if dt >= 0.0 {
+            return DateTime::from(&time.add_secs(-offset));
+        }
+        if dt >= -1.0 {
+            let mut utc = DateTime::from(&time.add_secs(-offset));
+            utc.sec += 1.0;
+            return utc;
+        }
+    }
+    DateTime::from(
+        &time.add_secs(TAI_GPST_OFFSET - f64::from(LEAP_SECONDS[0].3)),
+    )
+}
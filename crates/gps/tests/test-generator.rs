@@ -48,8 +48,9 @@ fn to_builder(args: &[Vec<String>]) -> Result<SignalGeneratorBuilder, Error> {
                 builder = builder.leap(Some(leap));
             }
             [t, value] if t == "-t" => {
-                // convert YYYY/MM/DD,hh:mm:ss to YYYY-MM-DD hh:mm:ss
-                let value = value.replace('/', "-").replace(',', " ") + "-00";
+                // convert YYYY/MM/DD,hh:mm:ss to YYYY-MM-DD hh:mm:ss, in GPS
+                // time as gps-sdr-sim takes it
+                let value = value.replace('/', "-").replace(',', " ") + " GPST";
                 builder = builder.time(Some(value))?;
             }
             [T, ..] if T == "-T" => {
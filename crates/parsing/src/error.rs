@@ -28,6 +28,32 @@ pub enum Error {
     #[error("Invalid user motion format: {0}")]
     InvalidUserMotionFormat(String),
 
+    /// Error when the times of a trajectory do not increase
+    #[error(
+        "Time {time} s at line {line} is not after the previous time \
+         {previous} s; trajectory records must be in strictly increasing time"
+    )]
+    NonMonotonicTime {
+        /// Line of the record
+        line: u64,
+        /// Time of the record in seconds
+        time: f64,
+        /// Time of the previous record in seconds
+        previous: f64,
+    },
+
+    /// Error when a trajectory interpolation method is unknown
+    #[error("Invalid interpolation: {0} (expected linear or hermite)")]
+    InvalidInterpolation(String),
+
+    /// Error when parsing TOML files
+    #[error("TOML error: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    /// Error when a motion profile is invalid or cannot be flown
+    #[error("Invalid motion profile: {0}")]
+    InvalidMotionProfile(String),
+
     /// Error when coordinates are outside valid ranges
     #[error("Invalid coordinates: latitude={latitude}, longitude={longitude}")]
     InvalidCoordinates {
@@ -65,6 +91,28 @@ impl Error {
         Error::InvalidUserMotionFormat(message.into())
     }
 
+    /// Create a new error for trajectory times out of order
+    #[inline]
+    pub fn non_monotonic_time(line: u64, time: f64, previous: f64) -> Self {
+        Error::NonMonotonicTime {
+            line,
+            time,
+            previous,
+        }
+    }
+
+    /// Create a new error for an unknown interpolation method
+    #[inline]
+    pub fn invalid_interpolation(method: impl Into<String>) -> Self {
+        Error::InvalidInterpolation(method.into())
+    }
+
+    /// Create a new error for an invalid motion profile
+    #[inline]
+    pub fn invalid_motion_profile(message: impl Into<String>) -> Self {
+        Error::InvalidMotionProfile(message.into())
+    }
+
     /// Create a new error for invalid coordinates
     #[inline]
     pub fn invalid_coordinates(latitude: f64, longitude: f64) -> Self {
@@ -0,0 +1,442 @@
+//! Jerk-limited kinematics of motion profiles.
+//!
+//! Speeds, turn rates and climb rates follow schedules planned for each
+//! phase of a profile, and the receiver is moved along them with the
+//! midpoint method in steps of at most [`MAX_SUBSTEP`]. Phases towards a
+//! waypoint also steer the heading onto it, under the turn rate limit and
+//! the jerk limit across the track.
+
+use std::f64::consts::PI;
+
+use geometry::{Ecef, Location, Neu};
+
+use crate::ReceiverState;
+
+/// Longest integration step of a motion profile in seconds
+pub(crate) const MAX_SUBSTEP: f64 = 0.01;
+
+/// Horizontal distance to a steering target (meters) under which the heading
+/// is no longer steered towards it
+const STEERING_CUTOFF: f64 = 10.0;
+
+/// Time constant in seconds with which small heading errors are steered out
+const STEERING_TIME: f64 = 1.0;
+
+/// Wraps an angle to (-π, π] radians.
+pub(crate) fn wrap(angle: f64) -> f64 {
+    PI - (PI - angle).rem_euclid(2.0 * PI)
+}
+
+/// Jerk-limited change of a value, e.g. a speed.
+///
+/// The rate of change rises at the jerk limit, cruises at the rate limit
+/// and falls back to zero at the jerk limit; the cruise is skipped when the
+/// change is too small to reach the rate limit. An infinite jerk limit gives
+/// a linear ramp.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SCurve {
+    /// Value at the start of the change
+    from: f64,
+    /// Value at the end of the change
+    to: f64,
+    /// Largest rate of change reached
+    peak: f64,
+    /// Duration of the rise of the rate of change in seconds
+    ramp: f64,
+    /// Duration of the change at the peak rate in seconds
+    cruise: f64,
+}
+impl SCurve {
+    /// Plans the change of a value under rate and jerk limits.
+    ///
+    /// # Arguments
+    /// * `from` - Value at the start of the change
+    /// * `to` - Value at the end of the change
+    /// * `rate` - Largest rate of change, positive
+    /// * `jerk` - Largest change of the rate of change, positive or infinite
+    pub fn new(from: f64, to: f64, rate: f64, jerk: f64) -> Self {
+        let change = (to - from).abs();
+        let (peak, ramp) = if jerk.is_infinite() {
+            (rate, 0.0)
+        } else if change >= rate * rate / jerk {
+            (rate, rate / jerk)
+        } else {
+            let peak = (change * jerk).sqrt();
+            (peak, peak / jerk)
+        };
+        let cruise = if peak > 0.0 {
+            (change / peak - ramp).max(0.0)
+        } else {
+            0.0
+        };
+        Self {
+            from,
+            to,
+            peak,
+            ramp,
+            cruise,
+        }
+    }
+
+    /// Keeps a value unchanged.
+    pub fn hold(value: f64) -> Self {
+        Self {
+            from: value,
+            to: value,
+            peak: 0.0,
+            ramp: 0.0,
+            cruise: 0.0,
+        }
+    }
+
+    /// Returns the duration of the change in seconds.
+    pub fn duration(&self) -> f64 {
+        2.0 * self.ramp + self.cruise
+    }
+
+    /// Returns the value at a time since the start of the change, held
+    /// before and after it.
+    pub fn value(&self, t: f64) -> f64 {
+        let duration = self.duration();
+        if t <= 0.0 {
+            return self.from;
+        }
+        if t >= duration {
+            return self.to;
+        }
+        let sign = (self.to - self.from).signum();
+        if t < self.ramp {
+            self.from + sign * self.peak / self.ramp * t * t / 2.0
+        } else if t < self.ramp + self.cruise {
+            self.from + sign * self.peak * (self.ramp / 2.0 + t - self.ramp)
+        } else {
+            let left = duration - t;
+            self.to - sign * self.peak / self.ramp * left * left / 2.0
+        }
+    }
+
+    /// Returns the integral of the value from the start of the change to a
+    /// time since then.
+    pub fn integral(&self, t: f64) -> f64 {
+        let duration = self.duration();
+        let sign = (self.to - self.from).signum();
+        let whole = f64::midpoint(self.from, self.to) * duration;
+        if t <= 0.0 {
+            self.from * t
+        } else if t >= duration {
+            whole + self.to * (t - duration)
+        } else if t < self.ramp {
+            self.from * t + sign * self.peak / self.ramp * t.powi(3) / 6.0
+        } else if t < self.ramp + self.cruise {
+            let cruise = t - self.ramp;
+            self.from * t
+                + sign
+                    * self.peak
+                    * (self.ramp * self.ramp / 6.0
+                        + self.ramp * cruise / 2.0
+                        + cruise * cruise / 2.0)
+        } else {
+            let left = duration - t;
+            whole - self.to * left
+                + sign * self.peak / self.ramp * left.powi(3) / 6.0
+        }
+    }
+}
+
+/// Course of a kinematic quantity over a phase of a motion profile.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Schedule {
+    /// Changes at the start of the phase and changes again to end with it
+    Ramps {
+        /// Change from the start of the phase
+        rise: SCurve,
+        /// Change ending with the phase
+        fall: SCurve,
+        /// Duration of the phase in seconds
+        duration: f64,
+    },
+    /// Rate of a quintic smooth step, whose integral over the phase is
+    /// `delta` and whose first two derivatives vanish at both ends
+    Smooth {
+        /// Integral of the rate over the phase
+        delta: f64,
+        /// Duration of the phase in seconds
+        duration: f64,
+    },
+}
+impl Schedule {
+    /// Keeps a value over a phase.
+    pub fn hold(value: f64, duration: f64) -> Self {
+        Self::Ramps {
+            rise: SCurve::hold(value),
+            fall: SCurve::hold(value),
+            duration,
+        }
+    }
+
+    /// Changes a value at the start of a phase and holds it.
+    pub fn rise(rise: SCurve, duration: f64) -> Self {
+        Self::Ramps {
+            rise,
+            fall: SCurve::hold(rise.to),
+            duration,
+        }
+    }
+
+    /// Returns the integral of the value from the start of the phase to a
+    /// time since then.
+    pub fn integral(&self, t: f64) -> f64 {
+        match self {
+            Self::Ramps {
+                rise,
+                fall,
+                duration,
+            } => {
+                let start = duration - fall.duration();
+                if t > start {
+                    rise.integral(start) + fall.integral(t - start)
+                } else {
+                    rise.integral(t)
+                }
+            }
+            Self::Smooth { delta, duration } => {
+                if *duration <= 0.0 {
+                    return 0.0;
+                }
+                let x = (t / duration).clamp(0.0, 1.0);
+                delta * x.powi(3) * (10.0 - 15.0 * x + 6.0 * x * x)
+            }
+        }
+    }
+
+    /// Returns the value at a time since the start of the phase.
+    pub fn value(&self, t: f64) -> f64 {
+        match self {
+            Self::Ramps {
+                rise,
+                fall,
+                duration,
+            } => {
+                let start = duration - fall.duration();
+                if t > start {
+                    fall.value(t - start)
+                } else {
+                    rise.value(t)
+                }
+            }
+            Self::Smooth { delta, duration } => {
+                if *duration <= 0.0 {
+                    return 0.0;
+                }
+                let x = (t / duration).clamp(0.0, 1.0);
+                delta / duration * 30.0 * x * x * (1.0 - x) * (1.0 - x)
+            }
+        }
+    }
+}
+
+/// Steering of the heading towards a location.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Steering {
+    /// Location steered towards, in radians
+    pub target: Location,
+    /// Largest turn rate in radians per second
+    pub rate: f64,
+    /// Largest jerk across the track in meters per second cubed
+    pub jerk: f64,
+}
+
+/// Phase of a motion profile with planned speed, turn and climb.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Phase {
+    /// Duration in seconds
+    pub duration: f64,
+    /// Horizontal speed in meters per second
+    pub speed: Schedule,
+    /// Turn rate in radians per second, positive to the right
+    pub turn_rate: Schedule,
+    /// Vertical speed in meters per second, positive upwards
+    pub climb_rate: Schedule,
+    /// Steering of the heading towards a location, on top of the turn rate
+    pub steering: Option<Steering>,
+}
+impl Phase {
+    /// Plans a phase moving straight with the given speed and climb.
+    pub fn straight(duration: f64, speed: Schedule, climb: Schedule) -> Self {
+        Self {
+            duration,
+            speed,
+            turn_rate: Schedule::hold(0.0, duration),
+            climb_rate: climb,
+            steering: None,
+        }
+    }
+
+    /// Plans a phase moving straight and level, changing the speed first.
+    pub fn level(duration: f64, rise: SCurve) -> Self {
+        Self::straight(
+            duration,
+            Schedule::rise(rise, duration),
+            Schedule::hold(0.0, duration),
+        )
+    }
+}
+
+/// Kinematic state of the receiver moving through a motion profile.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Vehicle {
+    /// ECEF position in meters
+    pub position: Ecef,
+    /// Heading in radians, clockwise from north
+    pub heading: f64,
+    /// Horizontal speed in meters per second
+    pub speed: f64,
+    /// Vertical speed in meters per second
+    pub climb_rate: f64,
+    /// Turn rate of the steering in radians per second, positive to the
+    /// right
+    pub steering_rate: f64,
+}
+impl Vehicle {
+    /// Returns the geodetic location, in radians.
+    pub fn location(&self) -> Location {
+        Location::from(&self.position)
+    }
+
+    /// Returns the position and ECEF velocity.
+    pub fn state(&self) -> ReceiverState {
+        ReceiverState {
+            position: self.position,
+            velocity: velocity(
+                &self.location(),
+                self.speed,
+                self.heading,
+                self.climb_rate,
+            ),
+        }
+    }
+
+    /// Returns the local offset of a location at the height of the receiver,
+    /// as its horizontal distance in meters and its bearing in radians.
+    pub fn course_to(&self, target: &Location) -> (f64, f64) {
+        let location = self.location();
+        let level =
+            Location::new(target.latitude, target.longitude, location.height);
+        let neu = Neu::from_ecef(
+            &(Ecef::from(&level) - &self.position),
+            location.ltcmat(),
+        );
+        (neu.north.hypot(neu.east), neu.east.atan2(neu.north))
+    }
+
+    /// Returns the turn rate of the steering at the end of a step.
+    ///
+    /// The rate heads for the fastest one from which the heading can still
+    /// settle on the bearing of the target without overshooting, within the
+    /// turn rate limit, and changes by at most the jerk limit across the
+    /// track over the step; small errors decay with [`STEERING_TIME`]. Close
+    /// to the target, or without steering, the rate falls back to zero.
+    ///
+    /// # Arguments
+    /// * `steering` - Steering of the phase, if any
+    /// * `h` - Integration step in seconds
+    fn steer(&self, steering: Option<&Steering>, h: f64) -> f64 {
+        let Some(steering) = steering else {
+            return 0.0;
+        };
+        // Change of the turn rate per second that keeps the acceleration
+        // across the track within the jerk limit
+        let slope = if self.speed > 0.0 {
+            steering.jerk / self.speed
+        } else {
+            f64::INFINITY
+        };
+        let (distance, bearing) = self.course_to(&steering.target);
+        let error = wrap(bearing - self.heading);
+        let aim = if distance > STEERING_CUTOFF && error.abs() > 0.0 {
+            (2.0 * slope * error.abs())
+                .sqrt()
+                .min(error.abs() / STEERING_TIME)
+                .min(steering.rate)
+                .copysign(error)
+        } else {
+            0.0
+        };
+        let change = slope * h;
+        self.steering_rate + (aim - self.steering_rate).clamp(-change, change)
+    }
+
+    /// Moves the receiver along a phase with the midpoint method, turning
+    /// by the exact integral of the turn rate and of the steering rate,
+    /// which changes linearly over the step.
+    ///
+    /// # Arguments
+    /// * `phase` - Phase of the motion profile
+    /// * `tau` - Time since the start of the phase in seconds
+    /// * `h` - Integration step in seconds
+    pub fn advance(&mut self, phase: &Phase, tau: f64, h: f64) {
+        let steering_rate = self.steer(phase.steering.as_ref(), h);
+        let middle = tau + h / 2.0;
+        let end = tau + h;
+        let turned = phase.turn_rate.integral(tau);
+        let start = velocity(
+            &self.location(),
+            phase.speed.value(tau),
+            self.heading,
+            phase.climb_rate.value(tau),
+        );
+        let predicted = offset(&self.position, &start, h / 2.0);
+        let midway = velocity(
+            &Location::from(&predicted),
+            phase.speed.value(middle),
+            self.heading + phase.turn_rate.integral(middle) - turned
+                + h * (3.0 * self.steering_rate + steering_rate) / 8.0,
+            phase.climb_rate.value(middle),
+        );
+        self.position = offset(&self.position, &midway, h);
+        self.heading += phase.turn_rate.integral(end) - turned
+            + h * f64::midpoint(self.steering_rate, steering_rate);
+        self.speed = phase.speed.value(end);
+        self.climb_rate = phase.climb_rate.value(end);
+        self.steering_rate = steering_rate;
+    }
+
+    /// Moves the receiver through a whole phase.
+    pub fn run(&mut self, phase: &Phase) {
+        let mut tau = 0.0;
+        while tau < phase.duration {
+            let h = MAX_SUBSTEP.min(phase.duration - tau);
+            self.advance(phase, tau, h);
+            tau += h;
+        }
+    }
+}
+
+/// Converts a horizontal speed, heading and vertical speed into an ECEF
+/// velocity.
+///
+/// # Arguments
+/// * `location` - Geodetic location in radians
+/// * `speed` - Horizontal speed in meters per second
+/// * `heading` - Heading in radians, clockwise from north
+/// * `climb_rate` - Vertical speed in meters per second
+fn velocity(
+    location: &Location, speed: f64, heading: f64, climb_rate: f64,
+) -> Ecef {
+    // Rows of the rotation are the north, east and up axes
+    let [north, east, up] = location.ltcmat();
+    let (sin, cos) = heading.sin_cos();
+    let local = [speed * cos, speed * sin, climb_rate];
+    let component =
+        |i: usize| north[i] * local[0] + east[i] * local[1] + up[i] * local[2];
+    Ecef::new(component(0), component(1), component(2))
+}
+
+/// Moves a position at a velocity for a time.
+fn offset(position: &Ecef, velocity: &Ecef, dt: f64) -> Ecef {
+    Ecef::new(
+        position.x + velocity.x * dt,
+        position.y + velocity.y * dt,
+        position.z + velocity.z * dt,
+    )
+}
@@ -2,18 +2,33 @@
 //!
 //! This crate provides parsers for various file formats used in GPS simulation:
 //! - NMEA GGA sentences for position data
-//! - User motion files in ECEF and LLH formats
+//! - User motion files in ECEF and LLH formats, with timestamped records
+//!   interpolated onto the simulation epochs
+//! - Motion profiles in TOML, kinematic scenarios integrated at the
+//!   simulation epochs
 //!
 //! The parsers convert the input data into appropriate coordinate structures
 //! that can be used by the GPS signal generator.
 
 /// Error types for parsing operations
 mod error;
+/// Jerk-limited kinematics of motion profiles
+mod kinematics;
 /// NMEA sentence parsing implementation
 mod nmea;
+/// Motion profile parsing and integration
+mod profile;
+#[cfg(test)]
+mod tests;
+/// Timestamped receiver trajectories and their interpolation
+mod trajectory;
 /// User motion file parsing implementation
 mod user_motion;
 
 pub use error::Error;
 pub use nmea::read_nmea_gga;
+pub use profile::{
+    Direction, Limits, MotionProfile, Segment, Start, read_motion_profile,
+};
+pub use trajectory::{Interpolation, ReceiverState, Trajectory};
 pub use user_motion::{read_user_motion, read_user_motion_llh};
@@ -0,0 +1,784 @@
+//! Motion profiles: a start and segments in TOML, planned as jerk-limited
+//! phases and integrated into receiver states at the simulation epochs.
+
+use std::{f64::consts::PI, fs, path::PathBuf, str::FromStr};
+
+use geometry::{Ecef, Location};
+use serde::Deserialize;
+
+use crate::{
+    Error, ReceiverState,
+    kinematics::{
+        MAX_SUBSTEP, Phase, SCurve, Schedule, Steering, Vehicle, wrap,
+    },
+};
+
+/// Epochs closer than this to the integrated time (seconds) are sampled
+const TIME_TOLERANCE: f64 = 1e-9;
+
+/// Iterations aiming the turn towards a waypoint from where the turn ends
+const AIM_ITERATIONS: usize = 8;
+
+/// Start of a motion profile.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Start {
+    /// Latitude and longitude in degrees, height above the WGS84 ellipsoid
+    /// in meters
+    pub position: [f64; 3],
+    /// Heading in degrees, clockwise from north
+    #[serde(default)]
+    pub heading: f64,
+    /// Horizontal speed in meters per second
+    #[serde(default)]
+    pub speed: f64,
+}
+
+/// Limits of the motion, applied to every transition.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Largest acceleration along the track and vertically, in meters per
+    /// second squared (default: 2)
+    pub acceleration: f64,
+    /// Largest jerk along the track, vertically and across the track when
+    /// entering or leaving a turn, in meters per second cubed (default: 1)
+    pub jerk: f64,
+    /// Turn rate towards waypoints in degrees per second (default: 3)
+    pub turn_rate: f64,
+}
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            acceleration: 2.0,
+            jerk: 1.0,
+            turn_rate: 3.0,
+        }
+    }
+}
+
+/// Direction of a circle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Clockwise seen from above
+    #[default]
+    Right,
+    /// Counter-clockwise seen from above
+    Left,
+}
+
+/// Segment of a motion profile, starting from where the previous one ended.
+///
+/// Speeds and climb rates change under the [`Limits`]; turns enter and leave
+/// their rate under the jerk limit across the track. Segments other than
+/// `hold`, `straight`, `accelerate` and `waypoint` keep the speed.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Segment {
+    /// Stops, then stands still
+    Hold {
+        /// Time standing still in seconds
+        duration: f64,
+    },
+    /// Moves straight ahead, changing the speed first
+    Straight {
+        /// Speed in meters per second (default: the current speed)
+        speed: Option<f64>,
+        /// Duration in seconds, including the speed change
+        duration: Option<f64>,
+        /// Distance in meters, including the speed change
+        distance: Option<f64>,
+    },
+    /// Changes the speed while moving straight ahead
+    Accelerate {
+        /// Final speed in meters per second
+        speed: f64,
+        /// Largest acceleration in meters per second squared (default: the
+        /// limit)
+        acceleration: Option<f64>,
+    },
+    /// Turns at a rate
+    Turn {
+        /// Turn rate in degrees per second, positive to the right
+        rate: f64,
+        /// Angle turned in degrees
+        angle: Option<f64>,
+        /// Final heading in degrees, reached turning in the direction of the
+        /// rate
+        heading: Option<f64>,
+        /// Duration in seconds, including the rate changes
+        duration: Option<f64>,
+    },
+    /// Climbs or descends at a vertical speed
+    Climb {
+        /// Vertical speed in meters per second; positive climbs, unless a
+        /// height gives the direction
+        rate: f64,
+        /// Final height above the WGS84 ellipsoid in meters
+        height: Option<f64>,
+        /// Duration in seconds, including the rate changes
+        duration: Option<f64>,
+    },
+    /// Flies circles at the current speed
+    Circle {
+        /// Radius in meters
+        radius: f64,
+        /// Number of turns (default: 1)
+        turns: Option<f64>,
+        /// Duration in seconds, instead of a number of turns
+        duration: Option<f64>,
+        /// Direction (default: right)
+        #[serde(default)]
+        direction: Direction,
+    },
+    /// Turns towards a position, then goes to it
+    Waypoint {
+        /// Latitude and longitude in degrees, height above the WGS84
+        /// ellipsoid in meters, reached smoothly over the leg
+        position: [f64; 3],
+        /// Speed of the leg in meters per second (default: the current
+        /// speed)
+        speed: Option<f64>,
+        /// Whether to stop at the position
+        #[serde(default)]
+        stop: bool,
+    },
+}
+
+/// Kinematic scenario generating the receiver trajectory.
+///
+/// The receiver starts at a position, heading and speed, and moves through
+/// segments; its positions and velocities are integrated at the epochs of
+/// the simulation, so the Doppler shifts follow the motion exactly.
+///
+/// # File Format
+/// Profiles are TOML files:
+/// ```toml
+/// [start]
+/// position = [35.681298, 139.766247, 10.0]
+/// heading = 90.0
+///
+/// [limits]
+/// acceleration = 2.0
+/// jerk = 1.0
+///
+/// [[segment]]
+/// type = "accelerate"
+/// speed = 20.0
+///
+/// [[segment]]
+/// type = "turn"
+/// rate = 3.0
+/// angle = 90.0
+///
+/// [[segment]]
+/// type = "waypoint"
+/// position = [35.69, 139.77, 100.0]
+/// stop = true
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MotionProfile {
+    /// Start of the motion
+    pub start: Start,
+    /// Limits of the motion
+    #[serde(default)]
+    pub limits: Limits,
+    /// Segments, in order
+    #[serde(default, rename = "segment")]
+    pub segments: Vec<Segment>,
+}
+impl FromStr for MotionProfile {
+    type Err = Error;
+
+    /// Parses and validates a TOML motion profile.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let profile: Self = toml::from_str(s)?;
+        profile.validate()?;
+        Ok(profile)
+    }
+}
+
+/// Extent of a turn or a climb.
+#[derive(Debug, Clone, Copy)]
+enum Span {
+    /// Turned angle in radians, or climbed height in meters
+    Amount(f64),
+    /// Duration in seconds
+    Duration(f64),
+}
+
+/// Checks that exactly one of the named options is given.
+fn exactly_one(options: &[(&str, Option<f64>)]) -> Result<(), String> {
+    if options.iter().filter(|(_, value)| value.is_some()).count() == 1 {
+        return Ok(());
+    }
+    let names = options.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    Err(format!("expected exactly one of {}", names.join(", ")))
+}
+
+/// Checks that a value is a positive number.
+fn positive(name: &str, value: f64) -> Result<(), String> {
+    if value.is_finite() && value > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{name} must be positive, got {value}"))
+    }
+}
+
+/// Checks that a value is a non-negative number.
+fn non_negative(name: &str, value: f64) -> Result<(), String> {
+    if value.is_finite() && value >= 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{name} must not be negative, got {value}"))
+    }
+}
+
+/// Checks that a value is a finite number.
+fn finite(name: &str, value: f64) -> Result<(), String> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("{name} must be a number, got {value}"))
+    }
+}
+
+/// Checks a latitude, longitude and height.
+fn check_position(position: [f64; 3]) -> Result<(), Error> {
+    let [latitude, longitude, height] = position;
+    if !(-90.0..=90.0).contains(&latitude)
+        || !(-180.0..=180.0).contains(&longitude)
+        || !height.is_finite()
+    {
+        return Err(Error::invalid_coordinates(latitude, longitude));
+    }
+    Ok(())
+}
+
+/// Converts a latitude, longitude and height to a location in radians.
+fn location(position: [f64; 3]) -> Location {
+    Location::new(
+        position[0].to_radians(),
+        position[1].to_radians(),
+        position[2],
+    )
+}
+
+/// Finds where an increasing function of `[0, limit]` reaches a target, by
+/// bisection.
+fn solve(limit: f64, target: f64, f: impl Fn(f64) -> f64) -> f64 {
+    let (mut low, mut high) = (0.0, limit);
+    for _ in 0..64 {
+        let middle = f64::midpoint(low, high);
+        if f(middle) < target {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    f64::midpoint(low, high)
+}
+
+impl Segment {
+    /// Checks the values of the segment.
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            Segment::Hold { duration } => non_negative("duration", duration),
+            Segment::Straight {
+                speed,
+                duration,
+                distance,
+            } => {
+                exactly_one(&[("duration", duration), ("distance", distance)])?;
+                if let Some(speed) = speed {
+                    non_negative("speed", speed)?;
+                }
+                non_negative("duration", duration.unwrap_or(0.0))?;
+                non_negative("distance", distance.unwrap_or(0.0))
+            }
+            Segment::Accelerate {
+                speed,
+                acceleration,
+            } => {
+                non_negative("speed", speed)?;
+                positive("acceleration", acceleration.unwrap_or(1.0))
+            }
+            Segment::Turn {
+                rate,
+                angle,
+                heading,
+                duration,
+            } => {
+                exactly_one(&[
+                    ("angle", angle),
+                    ("heading", heading),
+                    ("duration", duration),
+                ])?;
+                positive("rate", rate.abs())?;
+                non_negative("angle", angle.unwrap_or(0.0))?;
+                finite("heading", heading.unwrap_or(0.0))?;
+                non_negative("duration", duration.unwrap_or(0.0))
+            }
+            Segment::Climb {
+                rate,
+                height,
+                duration,
+            } => {
+                exactly_one(&[("height", height), ("duration", duration)])?;
+                positive("rate", rate.abs())?;
+                finite("height", height.unwrap_or(0.0))?;
+                non_negative("duration", duration.unwrap_or(0.0))
+            }
+            Segment::Circle {
+                radius,
+                turns,
+                duration,
+                ..
+            } => {
+                positive("radius", radius)?;
+                if turns.is_some() && duration.is_some() {
+                    return Err(
+                        "expected at most one of turns, duration".into()
+                    );
+                }
+                positive("turns", turns.unwrap_or(1.0))?;
+                non_negative("duration", duration.unwrap_or(0.0))
+            }
+            Segment::Waypoint {
+                position, speed, ..
+            } => {
+                check_position(position).map_err(|e| e.to_string())?;
+                positive("speed", speed.unwrap_or(1.0))
+            }
+        }
+    }
+}
+
+impl MotionProfile {
+    /// Checks the start, the limits and every segment of the profile.
+    ///
+    /// # Errors
+    /// * `Error::InvalidCoordinates` - If a position is out of range
+    /// * `Error::InvalidMotionProfile` - If a value is out of range, or a
+    ///   segment gives none or several of its alternative extents
+    pub fn validate(&self) -> Result<(), Error> {
+        check_position(self.start.position)?;
+        non_negative("start speed", self.start.speed)
+            .and_then(|()| finite("start heading", self.start.heading))
+            .and_then(|()| {
+                positive("acceleration limit", self.limits.acceleration)
+            })
+            .and_then(|()| positive("jerk limit", self.limits.jerk))
+            .and_then(|()| positive("turn rate limit", self.limits.turn_rate))
+            .map_err(Error::invalid_motion_profile)?;
+        for (index, segment) in self.segments.iter().enumerate() {
+            segment.validate().map_err(|message| {
+                Error::invalid_motion_profile(format!(
+                    "segment {}: {message}",
+                    index + 1
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Integrates the motion at the epochs of the simulation.
+    ///
+    /// # Arguments
+    /// * `step` - Time between epochs in seconds
+    ///
+    /// # Returns
+    /// * `Ok(Vec<ReceiverState>)` - Positions and velocities every `step`
+    ///   from the start until the end of the last segment
+    /// * `Err(Error)` - If the profile is invalid or a segment cannot be
+    ///   flown within the limits
+    ///
+    /// # Errors
+    /// * `Error::InvalidMotionProfile` - If a value is out of range, or a
+    ///   segment is too short for its speed change
+    pub fn states(&self, step: f64) -> Result<Vec<ReceiverState>, Error> {
+        self.validate()?;
+        positive("step", step).map_err(Error::invalid_motion_profile)?;
+        let mut vehicle = Vehicle {
+            position: Ecef::from(&location(self.start.position)),
+            heading: self.start.heading.to_radians(),
+            speed: self.start.speed,
+            climb_rate: 0.0,
+            steering_rate: 0.0,
+        };
+        let mut states = vec![vehicle.state()];
+        let mut time = 0.0;
+        for (index, segment) in self.segments.iter().enumerate() {
+            let phases = self.plan(segment, &vehicle).map_err(|message| {
+                Error::invalid_motion_profile(format!(
+                    "segment {}: {message}",
+                    index + 1
+                ))
+            })?;
+            for phase in &phases {
+                let mut tau = 0.0;
+                while tau < phase.duration {
+                    let epoch = states.len() as f64 * step;
+                    let h =
+                        MAX_SUBSTEP.min(phase.duration - tau).min(epoch - time);
+                    vehicle.advance(phase, tau, h);
+                    tau += h;
+                    time += h;
+                    if epoch - time < TIME_TOLERANCE {
+                        time = epoch;
+                        states.push(vehicle.state());
+                    }
+                }
+            }
+        }
+        Ok(states)
+    }
+
+    /// Plans the phases of a segment from the current state.
+    ///
+    /// # Errors
+    /// * Returns a message if the segment is too short for its speed change
+    ///   or needs a speed the receiver does not have
+    fn plan(
+        &self, segment: &Segment, vehicle: &Vehicle,
+    ) -> Result<Vec<Phase>, String> {
+        let Limits {
+            acceleration, jerk, ..
+        } = self.limits;
+        let speed = vehicle.speed;
+        let phase = match *segment {
+            Segment::Hold { duration } => {
+                let stop = SCurve::new(speed, 0.0, acceleration, jerk);
+                self.straight(
+                    speed,
+                    0.0,
+                    Some(stop.duration() + duration),
+                    None,
+                )?
+            }
+            Segment::Straight {
+                speed: target,
+                duration,
+                distance,
+            } => self.straight(
+                speed,
+                target.unwrap_or(speed),
+                duration,
+                distance,
+            )?,
+            Segment::Accelerate {
+                speed: target,
+                acceleration: limit,
+            } => {
+                let rise = SCurve::new(
+                    speed,
+                    target,
+                    limit.unwrap_or(acceleration),
+                    jerk,
+                );
+                Phase::level(rise.duration(), rise)
+            }
+            Segment::Turn {
+                rate,
+                angle,
+                heading,
+                duration,
+            } => {
+                let rate = rate.to_radians();
+                let span = match (angle, heading, duration) {
+                    (Some(angle), ..) => Span::Amount(angle.to_radians()),
+                    (_, Some(heading), _) => Span::Amount(
+                        ((heading.to_radians() - vehicle.heading)
+                            * rate.signum())
+                        .rem_euclid(2.0 * PI),
+                    ),
+                    _ => Span::Duration(duration.unwrap_or_default()),
+                };
+                self.turn(speed, rate, span)
+            }
+            Segment::Circle {
+                radius,
+                turns,
+                duration,
+                direction,
+            } => {
+                if speed <= 0.0 {
+                    return Err("a circle needs a moving receiver".into());
+                }
+                let span = duration.map_or_else(
+                    || Span::Amount(turns.unwrap_or(1.0) * 2.0 * PI),
+                    Span::Duration,
+                );
+                let rate = speed / radius;
+                match direction {
+                    Direction::Right => self.turn(speed, rate, span),
+                    Direction::Left => self.turn(speed, -rate, span),
+                }
+            }
+            Segment::Climb {
+                rate,
+                height,
+                duration,
+            } => {
+                let span = height.map_or_else(
+                    || Span::Duration(duration.unwrap_or_default()),
+                    |height| Span::Amount(height - vehicle.location().height),
+                );
+                self.climb(speed, rate, span)
+            }
+            Segment::Waypoint {
+                position,
+                speed: cruise,
+                stop,
+            } => {
+                return self.waypoint(
+                    vehicle,
+                    &location(position),
+                    cruise.unwrap_or(speed),
+                    stop,
+                );
+            }
+        };
+        Ok(vec![phase])
+    }
+
+    /// Plans a straight line, changing the speed first.
+    ///
+    /// # Arguments
+    /// * `speed` - Horizontal speed at the start in meters per second
+    /// * `target` - Speed of the line in meters per second
+    /// * `duration` - Duration in seconds, including the speed change
+    /// * `distance` - Distance in meters, used instead of the duration
+    ///
+    /// # Errors
+    /// * Returns a message if the line is too short for the speed change
+    fn straight(
+        &self, speed: f64, target: f64, duration: Option<f64>,
+        distance: Option<f64>,
+    ) -> Result<Phase, String> {
+        let rise = SCurve::new(
+            speed,
+            target,
+            self.limits.acceleration,
+            self.limits.jerk,
+        );
+        let total = if let Some(distance) = distance {
+            let covered = f64::midpoint(speed, target) * rise.duration();
+            if target <= 0.0 || distance < covered {
+                return Err(format!(
+                    "{distance} m is too short to reach {target} m/s, which \
+                     takes {covered:.1} m"
+                ));
+            }
+            rise.duration() + (distance - covered) / target
+        } else {
+            let duration = duration.unwrap_or_default();
+            if duration < rise.duration() {
+                return Err(format!(
+                    "{duration} s is too short to reach {target} m/s, which \
+                     takes {:.1} s",
+                    rise.duration()
+                ));
+            }
+            duration
+        };
+        Ok(Phase::level(total, rise))
+    }
+
+    /// Plans a turn at the current speed.
+    ///
+    /// The turn rate ramps linearly so that the acceleration across the
+    /// track changes at the jerk limit; turns too short for the full rate
+    /// peak below it.
+    ///
+    /// # Arguments
+    /// * `speed` - Horizontal speed in meters per second
+    /// * `rate` - Turn rate in radians per second, positive to the right
+    /// * `span` - Turned angle in radians, or duration
+    fn turn(&self, speed: f64, rate: f64, span: Span) -> Phase {
+        let slope = if speed > 0.0 {
+            self.limits.jerk / speed
+        } else {
+            f64::INFINITY
+        };
+        let ramp = SCurve::new(0.0, rate, slope, f64::INFINITY).duration();
+        let (peak, total) = match span {
+            Span::Amount(angle) if angle >= rate.abs() * ramp => {
+                (rate, angle / rate.abs() + ramp)
+            }
+            Span::Amount(angle) => {
+                let peak = (angle * slope).sqrt();
+                (peak.copysign(rate), 2.0 * peak / slope)
+            }
+            Span::Duration(duration) if duration >= 2.0 * ramp => {
+                (rate, duration)
+            }
+            Span::Duration(duration) => {
+                ((slope * duration / 2.0).copysign(rate), duration)
+            }
+        };
+        Phase {
+            duration: total,
+            speed: Schedule::hold(speed, total),
+            turn_rate: Schedule::Ramps {
+                rise: SCurve::new(0.0, peak, slope, f64::INFINITY),
+                fall: SCurve::new(peak, 0.0, slope, f64::INFINITY),
+                duration: total,
+            },
+            climb_rate: Schedule::hold(0.0, total),
+            steering: None,
+        }
+    }
+
+    /// Plans a climb or descent at the current speed and heading.
+    ///
+    /// The vertical speed changes under the acceleration and jerk limits;
+    /// climbs too short for the full rate peak below it.
+    ///
+    /// # Arguments
+    /// * `speed` - Horizontal speed in meters per second
+    /// * `rate` - Vertical speed in meters per second
+    /// * `span` - Height change in meters, or duration
+    fn climb(&self, speed: f64, rate: f64, span: Span) -> Phase {
+        let Limits {
+            acceleration, jerk, ..
+        } = self.limits;
+        let ramp =
+            |rate: f64| SCurve::new(0.0, rate, acceleration, jerk).duration();
+        let (peak, total) = match span {
+            Span::Amount(change) => {
+                let rate = rate.abs();
+                let peak = if change.abs() >= rate * ramp(rate) {
+                    rate
+                } else {
+                    solve(rate, change.abs(), |rate| rate * ramp(rate))
+                };
+                let total = if peak > 0.0 {
+                    change.abs() / peak + ramp(peak)
+                } else {
+                    0.0
+                };
+                (peak.copysign(change), total)
+            }
+            Span::Duration(duration) => {
+                let peak = if duration >= 2.0 * ramp(rate.abs()) {
+                    rate.abs()
+                } else {
+                    solve(rate.abs(), duration, |rate| 2.0 * ramp(rate))
+                };
+                (peak.copysign(rate), duration)
+            }
+        };
+        Phase::straight(
+            total,
+            Schedule::hold(speed, total),
+            Schedule::Ramps {
+                rise: SCurve::new(0.0, peak, acceleration, jerk),
+                fall: SCurve::new(peak, 0.0, acceleration, jerk),
+                duration: total,
+            },
+        )
+    }
+
+    /// Plans the turn towards a waypoint and the leg to it.
+    ///
+    /// The turn is aimed from where it ends; along the leg the heading is
+    /// steered towards the waypoint under the turn rate and jerk limits, the
+    /// speed changes at the start and, to stop, at the end, and the height
+    /// follows a smooth step to the height of the waypoint.
+    ///
+    /// # Arguments
+    /// * `vehicle` - State at the start of the segment
+    /// * `target` - Waypoint, in radians
+    /// * `cruise` - Speed of the leg in meters per second
+    /// * `stop` - Whether to stop at the waypoint
+    ///
+    /// # Errors
+    /// * Returns a message if the receiver has no speed to go, or the
+    ///   waypoint is too close for the speed changes
+    fn waypoint(
+        &self, vehicle: &Vehicle, target: &Location, cruise: f64, stop: bool,
+    ) -> Result<Vec<Phase>, String> {
+        if cruise <= 0.0 {
+            return Err("a waypoint needs a speed".into());
+        }
+        let rate = self.limits.turn_rate.to_radians();
+        let mut bearing = vehicle.course_to(target).1;
+        let mut turn = self.turn(vehicle.speed, rate, Span::Amount(0.0));
+        let mut after = *vehicle;
+        for _ in 0..AIM_ITERATIONS {
+            let change = wrap(bearing - vehicle.heading);
+            turn = self.turn(
+                vehicle.speed,
+                rate.copysign(change),
+                Span::Amount(change.abs()),
+            );
+            after = *vehicle;
+            after.run(&turn);
+            let aim = after.course_to(target).1;
+            if wrap(aim - bearing).abs() < 1e-9 {
+                break;
+            }
+            bearing = aim;
+        }
+
+        let Limits {
+            acceleration, jerk, ..
+        } = self.limits;
+        let (distance, _) = after.course_to(target);
+        let rise = SCurve::new(after.speed, cruise, acceleration, jerk);
+        let fall = if stop {
+            SCurve::new(cruise, 0.0, acceleration, jerk)
+        } else {
+            SCurve::hold(cruise)
+        };
+        let covered = f64::midpoint(after.speed, cruise) * rise.duration()
+            + cruise / 2.0 * fall.duration();
+        if distance < covered {
+            return Err(format!(
+                "the waypoint is {distance:.1} m away, too close for the \
+                 speed changes, which take {covered:.1} m"
+            ));
+        }
+        let total =
+            rise.duration() + fall.duration() + (distance - covered) / cruise;
+        let leg = Phase {
+            duration: total,
+            speed: Schedule::Ramps {
+                rise,
+                fall,
+                duration: total,
+            },
+            turn_rate: Schedule::hold(0.0, total),
+            climb_rate: Schedule::Smooth {
+                delta: target.height - after.location().height,
+                duration: total,
+            },
+            steering: Some(Steering {
+                target: *target,
+                rate,
+                jerk,
+            }),
+        };
+        Ok(vec![turn, leg])
+    }
+}
+
+/// Reads a motion profile from a TOML file.
+///
+/// # Arguments
+/// * `filename` - Path to the TOML file
+///
+/// # Returns
+/// * `Ok(MotionProfile)` - The validated profile
+/// * `Err(Error)` - If the file cannot be read or the profile is invalid
+///
+/// # Errors
+/// * Returns an error if the file cannot be opened
+/// * Returns an error if the TOML is invalid or has unknown fields
+/// * Returns an error if a value is out of range
+pub fn read_motion_profile(filename: &PathBuf) -> Result<MotionProfile, Error> {
+    fs::read_to_string(filename)?.parse()
+}
@@ -0,0 +1,305 @@
+use geometry::{Ecef, Location, LocationMath, Neu};
+
+use crate::{
+    Error, Interpolation, MotionProfile, ReceiverState, Trajectory,
+    kinematics::{MAX_SUBSTEP, Phase, Schedule, Steering, Vehicle, wrap},
+    read_user_motion,
+    user_motion::parse_user_motion,
+};
+
+#[test]
+fn test_trajectory_interpolation() -> Result<(), Error> {
+    // Irregular records of a cubic motion along x with its velocities
+    let times = vec![0.0, 1.0, 2.5, 3.0];
+    let point = |x: f64| Ecef::new(x, 2.0, -3.0);
+    let trajectory = Trajectory::new(
+        times.clone(),
+        times.iter().map(|&t| point(t * t * t)).collect(),
+        Some(
+            times
+                .iter()
+                .map(|&t| Ecef::new(3.0 * t * t, 0.0, 0.0))
+                .collect(),
+        ),
+        None,
+    )?;
+    // Cubic Hermite curves follow the motion exactly
+    let positions = trajectory.resample(0.1, Interpolation::Hermite);
+    assert_eq!(positions.len(), 31);
+    for (k, position) in positions.iter().enumerate() {
+        let t = k as f64 / 10.0;
+        assert!((position.x - t * t * t).abs() < 1e-9);
+        assert!((position.y - 2.0).abs() < 1e-12);
+    }
+    // Straight lines between records
+    let positions = trajectory.resample(0.5, Interpolation::Linear);
+    assert_eq!(positions.len(), 7);
+    assert!((positions[1].x - 0.5).abs() < 1e-12);
+    assert!((positions[3].x - (1.0 + (15.625 - 1.0) / 3.0)).abs() < 1e-9);
+
+    // Estimated velocities are exact for a quadratic motion inside
+    let trajectory = Trajectory::new(
+        times.clone(),
+        times.iter().map(|&t| point(t * t)).collect(),
+        None,
+        None,
+    )?;
+    let positions = trajectory.resample(0.25, Interpolation::Hermite);
+    assert!((positions[6].x - 1.5 * 1.5).abs() < 1e-9);
+
+    // Times must strictly increase
+    for times in [vec![0.0, 1.0, 1.0], vec![0.0, 2.0, 1.0]] {
+        assert!(matches!(
+            Trajectory::new(times, vec![point(0.0); 3], None, None),
+            Err(Error::NonMonotonicTime { line: 3, .. })
+        ));
+    }
+    assert_eq!(
+        "Cubic".parse::<Interpolation>().ok(),
+        Some(Interpolation::Hermite)
+    );
+    assert!("spline".parse::<Interpolation>().is_err());
+
+    // A 10 Hz file is unchanged by either interpolation
+    let circle = read_user_motion(
+        &concat!(env!("CARGO_WORKSPACE_DIR"), "/resources/circle.csv").into(),
+    )?;
+    assert_eq!(circle.len(), 3_000);
+    let linear = circle.resample(0.1, Interpolation::Linear);
+    let hermite = circle.resample(0.1, Interpolation::Hermite);
+    assert_eq!(linear.len(), 3_000);
+    assert!(linear.iter().zip(&hermite).all(|(a, b)| {
+        a.x.to_bits() == b.x.to_bits()
+            && a.y.to_bits() == b.y.to_bits()
+            && a.z.to_bits() == b.z.to_bits()
+    }));
+    assert_eq!(linear[0].x.to_bits(), (-3_813_477.954_f64).to_bits());
+    assert_eq!(linear[2_999].z.to_bits(), 3_662_773.758_f64.to_bits());
+    Ok(())
+}
+
+#[test]
+fn test_trajectory_velocities() -> Result<(), Error> {
+    // Quartic motion along x with its velocities and accelerations
+    let times = vec![0.0, 1.0, 2.5, 3.0];
+    let trajectory = Trajectory::new(
+        times.clone(),
+        times
+            .iter()
+            .map(|&t| Ecef::new(t.powi(4), 0.0, 0.0))
+            .collect(),
+        Some(
+            times
+                .iter()
+                .map(|&t| Ecef::new(4.0 * t.powi(3), 0.0, 0.0))
+                .collect(),
+        ),
+        Some(
+            times
+                .iter()
+                .map(|&t| Ecef::new(12.0 * t * t, 0.0, 0.0))
+                .collect(),
+        ),
+    )?;
+    assert!(trajectory.has_velocities());
+    // Quintic Hermite curves follow the motion and its velocity exactly
+    let states = trajectory.resample_states(0.1, Interpolation::Hermite);
+    assert_eq!(states.len(), 31);
+    for (k, state) in states.iter().enumerate() {
+        let t = k as f64 / 10.0;
+        assert!((state.position.x - t.powi(4)).abs() < 1e-9);
+        assert!((state.velocity.x - 4.0 * t.powi(3)).abs() < 1e-9);
+    }
+    // Velocities of the records interpolated linearly
+    let states = trajectory.resample_states(0.5, Interpolation::Linear);
+    assert!((states[1].velocity.x - 2.0).abs() < 1e-12);
+    // Velocities of cubic curves are their derivatives
+    let cubic = Trajectory::new(
+        times.clone(),
+        times.iter().map(|&t| Ecef::new(t * t, 0.0, 0.0)).collect(),
+        None,
+        None,
+    )?;
+    assert!(!cubic.has_velocities());
+    let states = cubic.resample_states(0.25, Interpolation::Hermite);
+    assert!((states[6].velocity.x - 3.0).abs() < 1e-9);
+    Ok(())
+}
+
+#[test]
+fn test_user_motion_field_counts() {
+    // Positions, velocities and accelerations
+    for content in [
+        "0,1,2,3\n1,1,2,3\n",
+        "0,1,2,3,0,0,0\n1,1,2,3,0,0,0\n",
+        "0,1,2,3,0,0,0,0,0,0\n1,1,2,3,0,0,0,0,0,0\n",
+    ] {
+        let Ok(trajectory) = parse_user_motion(content) else {
+            panic!("{content:?} should be parsed");
+        };
+        assert_eq!(trajectory.len(), 2);
+    }
+    // Other counts, or counts that change from record to record
+    for content in [
+        "0,1,2,3,0\n1,1,2,3,0\n",
+        "0,1,2\n",
+        "0,1,2,3\n1,1,2,3,0,0,0\n",
+        "0,1,2,3,0,0,0\n1,1,2,3,0,0,0,0,0,0\n",
+    ] {
+        assert!(matches!(
+            parse_user_motion(content),
+            Err(Error::InvalidUserMotionFormat(_))
+        ));
+    }
+}
+
+/// Returns the horizontal speed, heading in degrees and vertical speed of a
+/// receiver state.
+fn local_motion(state: &ReceiverState) -> (f64, f64, f64) {
+    let ltcmat = Location::from(&state.position).ltcmat();
+    let neu = Neu::from_ecef(&state.velocity, ltcmat);
+    (
+        neu.north.hypot(neu.east),
+        neu.east.atan2(neu.north).to_degrees().rem_euclid(360.0),
+        neu.up,
+    )
+}
+
+#[test]
+fn test_motion_profile() -> Result<(), Error> {
+    let states = |profile: &str, step| {
+        profile
+            .parse::<MotionProfile>()
+            .and_then(|profile| profile.states(step))
+    };
+    let start = "[start]\nposition = [35.681298, 139.766247, 10.0]\n\
+                 heading = 90.0\n";
+
+    // Standing still, then 200 m east with a jerk-limited start
+    let line = states(
+        &format!(
+            "{start}[[segment]]\ntype = \"hold\"\nduration = 5.0\n\
+             [[segment]]\ntype = \"straight\"\nspeed = 10.0\n\
+             distance = 200.0\n"
+        ),
+        0.1,
+    )?;
+    // 5 s, then 7 s from 0 to 10 m/s over 35 m, then 16.5 s at 10 m/s
+    assert_eq!(line.len(), 286);
+    assert!((line[50].position - &line[0].position).norm() < 1e-12);
+    assert!((line[285].position - &line[0].position).norm() - 200.0 < 1e-3);
+    let (speed, heading, climb) = local_motion(&line[285]);
+    assert!((speed - 10.0).abs() < 1e-9);
+    assert!((heading - 90.0).abs() < 1e-6);
+    assert!(climb.abs() < 1e-12);
+    let height = |state: &ReceiverState| Location::from(&state.position).height;
+    assert!((height(&line[285]) - 10.0).abs() < 1e-3);
+    // The velocities are the derivatives of the positions
+    for k in 1..line.len() - 1 {
+        let derivative =
+            (line[k + 1].position - &line[k - 1].position) * (1.0 / 0.2);
+        assert!((derivative - &line[k].velocity).norm() < 1e-2);
+    }
+
+    // Turns, climbs and circles keep the speed
+    let manoeuvres = states(
+        &format!(
+            "{start}speed = 10.0\n[[segment]]\ntype = \"turn\"\nrate = 10.0\n\
+             heading = 180.0\n[[segment]]\ntype = \"climb\"\nrate = 2.0\n\
+             height = 60.0\n[[segment]]\ntype = \"circle\"\nradius = 50.0\n\
+             direction = \"left\"\n[[segment]]\ntype = \"straight\"\n\
+             duration = 1.0\n"
+        ),
+        0.01,
+    )?;
+    let last = manoeuvres.last().copied().unwrap_or_default();
+    let (speed, heading, climb) = local_motion(&last);
+    assert!((speed - 10.0).abs() < 1e-9);
+    assert!((heading - 180.0).abs() < 1e-4);
+    assert!(climb.abs() < 1e-12);
+    assert!((height(&last) - 60.0).abs() < 1e-3);
+
+    // A waypoint is reached and the receiver stops there
+    let waypoint = states(
+        &format!(
+            "{start}[[segment]]\ntype = \"waypoint\"\n\
+             position = [35.6760, 139.7710, 20.0]\nspeed = 10.0\nstop = true\n\
+             [[segment]]\ntype = \"hold\"\nduration = 1.0\n"
+        ),
+        0.1,
+    )?;
+    let last = waypoint.last().copied().unwrap_or_default();
+    let target = Ecef::from(&Location::new(
+        35.6760_f64.to_radians(),
+        139.7710_f64.to_radians(),
+        20.0,
+    ));
+    assert!((last.position - &target).norm() < 1e-2);
+    assert!(last.velocity.norm() < 1e-12);
+
+    // Segments that cannot be flown within the limits
+    for profile in [
+        "[[segment]]\ntype = \"straight\"\nspeed = 10.0\ndistance = 10.0\n",
+        "[[segment]]\ntype = \"circle\"\nradius = 50.0\n",
+        "[[segment]]\ntype = \"turn\"\nrate = 3.0\n",
+        "[[segment]]\ntype = \"hold\"\nduration = 1.0\nspeed = 2.0\n",
+    ] {
+        assert!(states(&format!("{start}{profile}"), 0.1).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_steering() {
+    let origin =
+        Location::new(35.0_f64.to_radians(), 139.0_f64.to_radians(), 0.0);
+    let mut vehicle = Vehicle {
+        position: Ecef::from(&origin),
+        heading: 0.0,
+        speed: 10.0,
+        climb_rate: 0.0,
+        steering_rate: 0.0,
+    };
+    // Heading north to a target about 1.8 km to the east
+    let target = Location::new(
+        origin.latitude,
+        origin.longitude + 0.02_f64.to_radians(),
+        0.0,
+    );
+    let (rate, jerk) = (3.0_f64.to_radians(), 1.0);
+    let duration = 100.0;
+    let phase = Phase {
+        steering: Some(Steering { target, rate, jerk }),
+        ..Phase::straight(
+            duration,
+            Schedule::hold(10.0, duration),
+            Schedule::hold(0.0, duration),
+        )
+    };
+    // The turn rate stays within its limit and changes within the jerk
+    // limit across the track
+    let slope = jerk / vehicle.speed;
+    let mut peak: f64 = 0.0;
+    let mut tau = 0.0;
+    while tau < duration {
+        let before = vehicle;
+        vehicle.advance(&phase, tau, MAX_SUBSTEP);
+        tau += MAX_SUBSTEP;
+        peak = peak.max(vehicle.steering_rate.abs());
+        assert!(vehicle.steering_rate.abs() <= rate + 1e-12);
+        assert!(
+            (vehicle.steering_rate - before.steering_rate).abs()
+                <= slope * MAX_SUBSTEP + 1e-12
+        );
+        assert!(
+            wrap(vehicle.heading - before.heading).abs()
+                <= rate * MAX_SUBSTEP + 1e-12
+        );
+    }
+    assert!(peak > rate - 1e-9);
+    // The heading has settled on the target
+    let (distance, bearing) = vehicle.course_to(&target);
+    assert!(distance > 500.0);
+    assert!(wrap(bearing - vehicle.heading).abs() < 1e-5);
+    assert!(vehicle.steering_rate.abs() < 1e-5);
+}
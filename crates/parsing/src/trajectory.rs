@@ -0,0 +1,368 @@
+use std::str::FromStr;
+
+use geometry::Ecef;
+
+use crate::Error;
+
+/// Records closer than this to an epoch of the grid (seconds) are taken as
+/// they are
+const TIME_TOLERANCE: f64 = 1e-6;
+
+/// Method interpolating the receiver positions between trajectory records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Straight lines between consecutive records
+    #[default]
+    Linear,
+    /// Cubic Hermite curves through the positions with the velocities of
+    /// the records, given or estimated from the neighbouring records;
+    /// quintic curves when the records also give their accelerations
+    Hermite,
+}
+impl FromStr for Interpolation {
+    type Err = Error;
+
+    /// Parses `linear`, or `hermite` (also `cubic`), case-insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "linear" => Ok(Interpolation::Linear),
+            "hermite" | "cubic" => Ok(Interpolation::Hermite),
+            _ => Err(Error::invalid_interpolation(s)),
+        }
+    }
+}
+
+/// Receiver position and velocity at an epoch of the simulation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReceiverState {
+    /// ECEF position in meters
+    pub position: Ecef,
+    /// ECEF velocity in meters per second
+    pub velocity: Ecef,
+}
+
+/// Receiver positions at the times of their records.
+///
+/// Records may be irregular or slower than the simulation, e.g. a 1 Hz log;
+/// they are resampled onto the epochs of the simulation, starting at the
+/// first record.
+#[derive(Debug, Clone, Default)]
+pub struct Trajectory {
+    /// Times of the records in seconds, strictly increasing
+    times: Vec<f64>,
+    /// ECEF positions in meters
+    positions: Vec<[f64; 3]>,
+    /// ECEF velocities in meters per second, given or estimated
+    velocities: Vec<[f64; 3]>,
+    /// ECEF accelerations in meters per second squared, given or estimated
+    accelerations: Vec<[f64; 3]>,
+    /// Whether the records give their velocities
+    given_velocities: bool,
+    /// Whether the records give their accelerations
+    given_accelerations: bool,
+}
+impl Trajectory {
+    /// Creates a trajectory from its records.
+    ///
+    /// # Arguments
+    /// * `times` - Times of the records in seconds
+    /// * `positions` - ECEF positions of the records
+    /// * `velocities` - ECEF velocities of the records, estimated from the
+    ///   neighbouring records if `None`
+    /// * `accelerations` - ECEF accelerations of the records, estimated from
+    ///   the velocities if `None`
+    ///
+    /// # Returns
+    /// * `Ok(Trajectory)` - The trajectory
+    /// * `Err(Error)` - If the records are empty, of different lengths, or
+    ///   their times are not strictly increasing
+    ///
+    /// # Errors
+    /// * `Error::NonMonotonicTime` - If a time is not after the previous one;
+    ///   the line is the number of the record, from 1
+    pub fn new(
+        times: Vec<f64>, positions: Vec<Ecef>, velocities: Option<Vec<Ecef>>,
+        accelerations: Option<Vec<Ecef>>,
+    ) -> Result<Self, Error> {
+        let lines = (1..=times.len() as u64).collect::<Vec<_>>();
+        let components =
+            |v: Vec<Ecef>| v.iter().map(|v| [v.x, v.y, v.z]).collect();
+        Self::from_records(
+            &lines,
+            times,
+            components(positions),
+            velocities.map(components),
+            accelerations.map(components),
+        )
+    }
+
+    /// Creates a trajectory from records read on the given lines.
+    ///
+    /// # Arguments
+    /// * `lines` - Line of each record, for diagnostics
+    /// * `times` - Times of the records in seconds
+    /// * `positions` - ECEF positions of the records
+    /// * `velocities` - ECEF velocities of the records, if given
+    /// * `accelerations` - ECEF accelerations of the records, if given
+    ///
+    /// # Errors
+    /// * Returns an error if there are no records, their numbers differ, or
+    ///   a time is not finite or not after the previous one
+    pub(crate) fn from_records(
+        lines: &[u64], times: Vec<f64>, positions: Vec<[f64; 3]>,
+        velocities: Option<Vec<[f64; 3]>>,
+        accelerations: Option<Vec<[f64; 3]>>,
+    ) -> Result<Self, Error> {
+        if times.is_empty() {
+            return Err(Error::invalid_user_motion(
+                "No valid motion records found",
+            ));
+        }
+        if positions.len() != times.len()
+            || velocities.as_ref().is_some_and(|v| v.len() != times.len())
+            || accelerations
+                .as_ref()
+                .is_some_and(|a| a.len() != times.len())
+        {
+            return Err(Error::invalid_user_motion(
+                "Different numbers of times, positions, velocities and \
+                 accelerations",
+            ));
+        }
+        for (k, (line, &time)) in lines.iter().zip(&times).enumerate() {
+            if !time.is_finite() {
+                return Err(Error::invalid_user_motion(format!(
+                    "Invalid time {time} at line {line}"
+                )));
+            }
+            if k > 0 && time <= times[k - 1] {
+                return Err(Error::non_monotonic_time(
+                    *line,
+                    time,
+                    times[k - 1],
+                ));
+            }
+        }
+        let given_velocities = velocities.is_some();
+        let given_accelerations = accelerations.is_some();
+        let velocities = velocities
+            .unwrap_or_else(|| estimate_derivatives(&times, &positions));
+        let accelerations = accelerations
+            .unwrap_or_else(|| estimate_derivatives(&times, &velocities));
+        Ok(Self {
+            times,
+            positions,
+            velocities,
+            accelerations,
+            given_velocities,
+            given_accelerations,
+        })
+    }
+
+    /// Returns the number of records.
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    /// Returns whether the trajectory has no records.
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// Returns the time from the first record to the last one in seconds.
+    pub fn duration(&self) -> f64 {
+        self.times.last().map_or(0.0, |last| last - self.times[0])
+    }
+
+    /// Returns whether the records give their velocities.
+    pub fn has_velocities(&self) -> bool {
+        self.given_velocities
+    }
+
+    /// Resamples the positions of the trajectory onto a grid of epochs.
+    ///
+    /// # Arguments
+    /// * `step` - Interval between the epochs in seconds
+    /// * `interpolation` - Interpolation between the records
+    ///
+    /// # Returns
+    /// The positions at the epochs of the grid
+    pub fn resample(
+        &self, step: f64, interpolation: Interpolation,
+    ) -> Vec<Ecef> {
+        self.resample_states(step, interpolation)
+            .iter()
+            .map(|state| state.position)
+            .collect()
+    }
+
+    /// Resamples the trajectory onto a grid of epochs.
+    ///
+    /// The grid starts at the first record and ends at the last one. Epochs
+    /// within a microsecond of a record take its position and velocity
+    /// unchanged, so a trajectory already on the grid is returned as it is.
+    ///
+    /// The velocities are the derivatives of the Hermite curves, or the
+    /// velocities of the records interpolated linearly.
+    ///
+    /// # Arguments
+    /// * `step` - Interval between the epochs in seconds
+    /// * `interpolation` - Interpolation between the records
+    ///
+    /// # Returns
+    /// The positions and velocities at the epochs of the grid
+    pub fn resample_states(
+        &self, step: f64, interpolation: Interpolation,
+    ) -> Vec<ReceiverState> {
+        let count = (self.duration() / step + TIME_TOLERANCE) as usize + 1;
+        let mut segment = 0;
+        (0..count)
+            .map(|k| {
+                let time = self.times[0] + k as f64 * step;
+                // Segment of records around the epoch
+                while segment + 2 < self.times.len()
+                    && self.times[segment + 1] <= time + TIME_TOLERANCE
+                {
+                    segment += 1;
+                }
+                let (position, velocity) =
+                    self.interpolate(segment, time, interpolation);
+                ReceiverState {
+                    position: Ecef::from(&position),
+                    velocity: Ecef::from(&velocity),
+                }
+            })
+            .collect()
+    }
+
+    /// Interpolates the position and velocity at a time between two records.
+    ///
+    /// # Arguments
+    /// * `segment` - Index of the record starting the segment
+    /// * `time` - Time in seconds, within the segment
+    /// * `interpolation` - Interpolation between the records
+    ///
+    /// # Returns
+    /// The ECEF position and velocity at the time
+    fn interpolate(
+        &self, segment: usize, time: f64, interpolation: Interpolation,
+    ) -> ([f64; 3], [f64; 3]) {
+        let record = |k: usize| (self.positions[k], self.velocities[k]);
+        let t0 = self.times[segment];
+        if (time - t0).abs() < TIME_TOLERANCE || segment + 1 == self.len() {
+            return record(segment);
+        }
+        let t1 = self.times[segment + 1];
+        if (time - t1).abs() < TIME_TOLERANCE {
+            return record(segment + 1);
+        }
+        let (p0, p1) = (self.positions[segment], self.positions[segment + 1]);
+        let (v0, v1) = (self.velocities[segment], self.velocities[segment + 1]);
+        let (a0, a1) =
+            (self.accelerations[segment], self.accelerations[segment + 1]);
+        let h = t1 - t0;
+        let s = (time - t0) / h;
+        match interpolation {
+            Interpolation::Linear => (
+                std::array::from_fn(|i| p0[i] + s * (p1[i] - p0[i])),
+                std::array::from_fn(|i| v0[i] + s * (v1[i] - v0[i])),
+            ),
+            Interpolation::Hermite if self.given_accelerations => {
+                // Quintic basis and its derivative, matching the positions,
+                // velocities and accelerations at both records
+                let (s2, s3, s4, s5) = (s * s, s.powi(3), s.powi(4), s.powi(5));
+                let basis = [
+                    1.0 - 10.0 * s3 + 15.0 * s4 - 6.0 * s5,
+                    s - 6.0 * s3 + 8.0 * s4 - 3.0 * s5,
+                    0.5 * (s2 - 3.0 * s3 + 3.0 * s4 - s5),
+                    0.5 * (s3 - 2.0 * s4 + s5),
+                    -4.0 * s3 + 7.0 * s4 - 3.0 * s5,
+                    10.0 * s3 - 15.0 * s4 + 6.0 * s5,
+                ];
+                let derivative = [
+                    -30.0 * s2 + 60.0 * s3 - 30.0 * s4,
+                    1.0 - 18.0 * s2 + 32.0 * s3 - 15.0 * s4,
+                    s - 4.5 * s2 + 6.0 * s3 - 2.5 * s4,
+                    1.5 * s2 - 4.0 * s3 + 2.5 * s4,
+                    -12.0 * s2 + 28.0 * s3 - 15.0 * s4,
+                    30.0 * s2 - 60.0 * s3 + 30.0 * s4,
+                ];
+                let curve = |b: &[f64; 6], i: usize| {
+                    b[0] * p0[i]
+                        + b[1] * h * v0[i]
+                        + b[2] * h * h * a0[i]
+                        + b[3] * h * h * a1[i]
+                        + b[4] * h * v1[i]
+                        + b[5] * p1[i]
+                };
+                (
+                    std::array::from_fn(|i| curve(&basis, i)),
+                    std::array::from_fn(|i| curve(&derivative, i) / h),
+                )
+            }
+            Interpolation::Hermite => {
+                // Cubic basis and its derivative, matching the positions and
+                // velocities at both records
+                let (s2, s3) = (s * s, s * s * s);
+                let basis = [
+                    2.0 * s3 - 3.0 * s2 + 1.0,
+                    s3 - 2.0 * s2 + s,
+                    3.0 * s2 - 2.0 * s3,
+                    s3 - s2,
+                ];
+                let derivative = [
+                    6.0 * s2 - 6.0 * s,
+                    3.0 * s2 - 4.0 * s + 1.0,
+                    6.0 * s - 6.0 * s2,
+                    3.0 * s2 - 2.0 * s,
+                ];
+                let curve = |b: &[f64; 4], i: usize| {
+                    b[0] * p0[i]
+                        + b[1] * h * v0[i]
+                        + b[2] * p1[i]
+                        + b[3] * h * v1[i]
+                };
+                (
+                    std::array::from_fn(|i| curve(&basis, i)),
+                    std::array::from_fn(|i| curve(&derivative, i) / h),
+                )
+            }
+        }
+    }
+}
+
+/// Estimates the time derivatives of records from their neighbours, the
+/// velocities from the positions or the accelerations from the velocities.
+///
+/// Inner records use the three-point derivative for uneven intervals, the
+/// first and last records the difference with their only neighbour.
+///
+/// # Arguments
+/// * `times` - Times of the records in seconds
+/// * `positions` - ECEF values of the records
+///
+/// # Returns
+/// The derivatives of the values at the records, zero for a single record
+fn estimate_derivatives(
+    times: &[f64], positions: &[[f64; 3]],
+) -> Vec<[f64; 3]> {
+    let n = times.len();
+    (0..n)
+        .map(|k| {
+            if n < 2 {
+                return [0.0; 3];
+            }
+            let (prev, next) = (k.saturating_sub(1), (k + 1).min(n - 1));
+            std::array::from_fn(|i| {
+                if prev == k || next == k {
+                    return (positions[next][i] - positions[prev][i])
+                        / (times[next] - times[prev]);
+                }
+                let (h0, h1) = (times[k] - times[prev], times[next] - times[k]);
+                (h1 * h1 * (positions[k][i] - positions[prev][i])
+                    + h0 * h0 * (positions[next][i] - positions[k][i]))
+                    / (h0 * h1 * (h0 + h1))
+            })
+        })
+        .collect()
+}
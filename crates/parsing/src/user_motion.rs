@@ -3,7 +3,26 @@ use std::{fs, path::PathBuf};
 use constants::R2D;
 use geometry::{Ecef, Location};
 
-use crate::Error;
+use crate::{Error, Trajectory};
+
+/// Parses a field of a motion record.
+///
+/// # Arguments
+/// * `record` - CSV record
+/// * `index` - Index of the field
+/// * `name` - Name of the field, for diagnostics
+///
+/// # Errors
+/// * Returns an error if the field is missing or is not a number
+fn field(
+    record: &csv::StringRecord, index: usize, name: &str,
+) -> Result<f64, Error> {
+    Ok(record
+        .get(index)
+        .ok_or_else(|| Error::missing_field(name))?
+        .trim()
+        .parse()?)
+}
 
 /// Reads user motion data from a CSV file in ECEF coordinate format.
 ///
@@ -14,74 +33,111 @@ use crate::Error;
 /// # File Format
 /// The file should be in CSV format with each line containing:
 /// ```text
-/// time, x, y, z
+/// time, x, y, z[, vx, vy, vz[, ax, ay, az]]
 /// ```
 /// Where:
-/// - `time` is the time in seconds
+/// - `time` is the time in seconds, strictly increasing; records may be
+///   irregular or at any rate, e.g. a 1 Hz log
 /// - `x`, `y`, `z` are ECEF coordinates in meters
+/// - `vx`, `vy`, `vz` are optional ECEF velocities in meters per second,
+///   used by the Hermite interpolation and for the Doppler shifts; they are
+///   otherwise estimated from the neighbouring records
+/// - `ax`, `ay`, `az` are optional ECEF accelerations in meters per second
+///   squared, making the Hermite curves quintic
+///
+/// Every record has the same number of fields: 4, 7 or 10.
 ///
 /// # Arguments
 /// * `filename` - Path to the CSV file containing user motion data
 ///
 /// # Returns
-/// * `Ok(Vec<Ecef>)` - Vector of ECEF coordinates parsed from the file
+/// * `Ok(Trajectory)` - The timestamped positions parsed from the file
 /// * `Err(Error)` - If the file cannot be read or contains invalid data
 ///
 /// # Errors
 /// * Returns an error if the file cannot be opened
 /// * Returns an error if the CSV format is invalid
-/// * Returns an error if any coordinate values cannot be parsed
+/// * Returns an error if a record has other than 4, 7 or 10 fields, or not
+///   as many fields as the first record
+/// * Returns an error if any time or coordinate value cannot be parsed
+/// * Returns an error if the times are not strictly increasing
 /// * Returns an error if the file contains no valid motion records
-pub fn read_user_motion(filename: &PathBuf) -> Result<Vec<Ecef>, Error> {
+pub fn read_user_motion(filename: &PathBuf) -> Result<Trajectory, Error> {
+    parse_user_motion(&fs::read_to_string(filename)?)
+}
+
+/// Parses user motion records in ECEF coordinate format, as read by
+/// [`read_user_motion`].
+///
+/// # Errors
+/// * Returns an error if a record is invalid, as for [`read_user_motion`]
+pub(crate) fn parse_user_motion(content: &str) -> Result<Trajectory, Error> {
+    let mut lines = Vec::new();
+    let mut times = Vec::new();
     let mut xyz = Vec::new();
-    let content = fs::read_to_string(filename)?;
+    let mut velocities = Vec::new();
+    let mut accelerations = Vec::new();
+    let mut field_count = None;
 
     // Create a CSV reader with comma delimiter
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
+        .flexible(true)
         .delimiter(b',')
         .from_reader(content.as_bytes());
 
     for result in rdr.records() {
         let record = result?;
 
-        // Ensure we have enough fields
-        if record.len() < 4 {
+        let line = record.position().map_or(0, csv::Position::line);
+
+        // Positions, with velocities, with accelerations, the same in every
+        // record
+        if ![4, 7, 10].contains(&record.len()) {
             return Err(Error::invalid_user_motion(format!(
-                "Expected at least 4 fields (time,x,y,z), got {}",
+                "Expected 4, 7 or 10 fields (time,x,y,z[,vx,vy,vz[,ax,ay,az]]), \
+                 got {} on line {line}",
+                record.len()
+            )));
+        }
+        let expected = *field_count.get_or_insert(record.len());
+        if record.len() != expected {
+            return Err(Error::invalid_user_motion(format!(
+                "Expected {expected} fields as in the first record, got {} on \
+                 line {line}",
                 record.len()
             )));
         }
 
-        // Extract and parse ECEF coordinates
-        let x = record
-            .get(1)
-            .ok_or_else(|| Error::missing_field("x coordinate"))?
-            .trim()
-            .parse()?;
-
-        let y = record
-            .get(2)
-            .ok_or_else(|| Error::missing_field("y coordinate"))?
-            .trim()
-            .parse()?;
-
-        let z = record
-            .get(3)
-            .ok_or_else(|| Error::missing_field("z coordinate"))?
-            .trim()
-            .parse()?;
-
-        xyz.push(Ecef::from(&[x, y, z]));
-    }
-
-    if xyz.is_empty() {
-        return Err(Error::invalid_user_motion(
-            "No valid motion records found".to_string(),
-        ));
+        // Extract and parse the time and ECEF coordinates
+        lines.push(line);
+        times.push(field(&record, 0, "time")?);
+        xyz.push([
+            field(&record, 1, "x coordinate")?,
+            field(&record, 2, "y coordinate")?,
+            field(&record, 3, "z coordinate")?,
+        ]);
+        if record.len() >= 7 {
+            velocities.push([
+                field(&record, 4, "x velocity")?,
+                field(&record, 5, "y velocity")?,
+                field(&record, 6, "z velocity")?,
+            ]);
+        }
+        if record.len() >= 10 {
+            accelerations.push([
+                field(&record, 7, "x acceleration")?,
+                field(&record, 8, "y acceleration")?,
+                field(&record, 9, "z acceleration")?,
+            ]);
+        }
     }
 
-    Ok(xyz)
+    // Velocities and accelerations are given by every record or by none
+    let given = |values: Vec<[f64; 3]>| (!values.is_empty()).then_some(values);
+    let velocities = given(velocities);
+    let accelerations = given(accelerations);
+    Trajectory::from_records(&lines, times, xyz, velocities, accelerations)
 }
 
 /// Reads user motion data from a CSV file in LLH coordinate format and converts
@@ -97,7 +153,8 @@ pub fn read_user_motion(filename: &PathBuf) -> Result<Vec<Ecef>, Error> {
 /// time, latitude, longitude, height
 /// ```
 /// Where:
-/// - `time` is the time in seconds
+/// - `time` is the time in seconds, strictly increasing; records may be
+///   irregular or at any rate, e.g. a 1 Hz log
 /// - `latitude` is in degrees (-90 to 90)
 /// - `longitude` is in degrees (-180 to 180)
 /// - `height` is in meters above the WGS-84 ellipsoid
@@ -107,19 +164,22 @@ pub fn read_user_motion(filename: &PathBuf) -> Result<Vec<Ecef>, Error> {
 ///   format
 ///
 /// # Returns
-/// * `Ok(Vec<Ecef>)` - Vector of ECEF coordinates converted from the LLH data
+/// * `Ok(Trajectory)` - The timestamped positions converted to ECEF
 /// * `Err(Error)` - If the file cannot be read or contains invalid data
 ///
 /// # Errors
 /// * Returns an error if the file cannot be opened
 /// * Returns an error if the CSV format is invalid
-/// * Returns an error if any coordinate values cannot be parsed
+/// * Returns an error if any time or coordinate value cannot be parsed
 /// * Returns an error if latitude or longitude are outside valid ranges
+/// * Returns an error if the times are not strictly increasing
 /// * Returns an error if the file contains no valid motion records
 ///
 /// # Credit
 /// Originally added by romalvarezllorens@gmail.com
-pub fn read_user_motion_llh(filename: &PathBuf) -> Result<Vec<Ecef>, Error> {
+pub fn read_user_motion_llh(filename: &PathBuf) -> Result<Trajectory, Error> {
+    let mut lines = Vec::new();
+    let mut times = Vec::new();
     let mut xyz = Vec::new();
     let content = fs::read_to_string(filename)?;
 
@@ -140,24 +200,11 @@ pub fn read_user_motion_llh(filename: &PathBuf) -> Result<Vec<Ecef>, Error> {
             )));
         }
 
-        // Extract and parse LLH coordinates
-        let lat = record
-            .get(1)
-            .ok_or_else(|| Error::missing_field("latitude"))?
-            .trim()
-            .parse()?;
-
-        let lon = record
-            .get(2)
-            .ok_or_else(|| Error::missing_field("longitude"))?
-            .trim()
-            .parse()?;
-
-        let height = record
-            .get(3)
-            .ok_or_else(|| Error::missing_field("height"))?
-            .trim()
-            .parse()?;
+        // Extract and parse the time and LLH coordinates
+        let time = field(&record, 0, "time")?;
+        let lat = field(&record, 1, "latitude")?;
+        let lon = field(&record, 2, "longitude")?;
+        let height = field(&record, 3, "height")?;
 
         let mut llh = Location::from(&[lat, lon, height]);
 
@@ -178,14 +225,11 @@ pub fn read_user_motion_llh(filename: &PathBuf) -> Result<Vec<Ecef>, Error> {
         llh.longitude /= R2D;
 
         // Convert to ECEF
-        xyz.push(Ecef::from(&llh));
-    }
-
-    if xyz.is_empty() {
-        return Err(Error::invalid_user_motion(
-            "No valid motion records found".to_string(),
-        ));
+        let position = Ecef::from(&llh);
+        lines.push(record.position().map_or(0, csv::Position::line));
+        times.push(time);
+        xyz.push([position.x, position.y, position.z]);
     }
 
-    Ok(xyz)
+    Trajectory::from_records(&lines, times, xyz, None, None)
 }
@@ -0,0 +1,303 @@
+//! Reader of YUMA and SEM GPS almanac files.
+//!
+//! Almanacs hold a reduced Keplerian orbit and clock model of every GPS
+//! satellite, valid for weeks around their time of applicability. They are
+//! published by the US Coast Guard Navigation Center in two text formats:
+//!
+//! - YUMA, one block of `Key: value` lines per satellite, with angles in
+//!   radians and the absolute orbit inclination.
+//! - SEM, a header with the week and time of applicability followed by
+//!   blocks of whitespace-separated values, with angles in semicircles and
+//!   the inclination as an offset from 0.30 semicircles.
+//!
+//! Both are read into the same [`AlmanacEntry`] form, in radians.
+
+use std::{fs, path::Path};
+
+use crate::{compression, error::Error, reader::Diagnostic};
+
+/// Value of pi used by the GPS interface specification to convert
+/// semicircles to radians
+#[allow(clippy::approx_constant)]
+const GPS_PI: f64 = 3.141_592_653_589_8;
+
+/// Reference inclination of SEM almanacs (semicircles)
+const SEM_INCLINATION: f64 = 0.30;
+
+/// Almanac data of one GPS satellite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlmanacEntry {
+    /// Satellite PRN number
+    pub prn: usize,
+    /// Satellite health (0 for healthy)
+    pub health: u32,
+    /// Eccentricity of the orbit
+    pub eccentricity: f64,
+    /// Time of applicability (seconds of week)
+    pub toa: f64,
+    /// Orbit inclination (radians)
+    pub inclination: f64,
+    /// Rate of right ascension (radians/second)
+    pub omega_dot: f64,
+    /// Square root of the semi-major axis (sqrt(meters))
+    pub sqrt_a: f64,
+    /// Longitude of the ascending node at the start of the week (radians)
+    pub omega0: f64,
+    /// Argument of perigee (radians)
+    pub perigee: f64,
+    /// Mean anomaly at the time of applicability (radians)
+    pub mean_anomaly: f64,
+    /// Clock bias (seconds)
+    pub af0: f64,
+    /// Clock drift (seconds/second)
+    pub af1: f64,
+    /// Week of the time of applicability, as written in the file; YUMA and
+    /// SEM files usually give it modulo 1024
+    pub week: i32,
+}
+
+/// A parsed GPS almanac.
+#[derive(Debug, Clone, Default)]
+pub struct Almanac {
+    /// Almanac data of each satellite, in the order of the file
+    pub entries: Vec<AlmanacEntry>,
+}
+impl Almanac {
+    /// Reads a YUMA or SEM almanac file from the filesystem.
+    ///
+    /// The format is detected from the content: YUMA files are made of
+    /// `Key: value` lines, SEM files of numbers only. Gzip and Unix compress
+    /// files are decompressed transparently.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the almanac file
+    ///
+    /// # Returns
+    /// * `Ok(Almanac)` - Successfully parsed almanac
+    /// * `Err(Error)` - If the file cannot be read or parsed
+    ///
+    /// # Errors
+    /// * Returns an error if the file cannot be read or is malformed
+    pub fn read_file(path: &dyn AsRef<Path>) -> Result<Self, Error> {
+        let data = compression::decompress(fs::read(path)?)?;
+        if data.contains(':') {
+            Self::read_yuma(&data)
+        } else {
+            Self::read_sem(&data)
+        }
+    }
+
+    /// Parses a YUMA almanac.
+    ///
+    /// Each satellite block starts with a line of asterisks, and its fields
+    /// are recognised by the start of their key, in any order.
+    ///
+    /// # Arguments
+    /// * `data` - Text of the YUMA file
+    ///
+    /// # Returns
+    /// * `Ok(Almanac)` - Successfully parsed almanac
+    /// * `Err(Error)` - If a value is malformed or a field is missing
+    ///
+    /// # Errors
+    /// * `Error::Malformed` - If a value is not a number or a block lacks a
+    ///   field
+    pub fn read_yuma(data: &str) -> Result<Self, Error> {
+        let mut entries = Vec::new();
+        let mut block: Option<(usize, [Option<f64>; 13])> = None;
+        for (index, text) in data.lines().enumerate() {
+            let number = index + 1;
+            if text.trim_start().starts_with('*') {
+                if let Some((start, fields)) = block.take() {
+                    entries.push(yuma_entry(start, &fields)?);
+                }
+                block = Some((number, [None; 13]));
+                continue;
+            }
+            let Some((key, value)) = text.split_once(':') else {
+                continue;
+            };
+            let Some((_, fields)) = block.as_mut() else {
+                return Err(Error::Malformed(Diagnostic::new(
+                    number,
+                    1,
+                    "field outside of a satellite block",
+                )));
+            };
+            let key = key.trim().to_lowercase();
+            let Some(field) = YUMA_KEYS.iter().position(|k| key.starts_with(k))
+            else {
+                continue;
+            };
+            let column = text.len() - value.trim_start().len() + 1;
+            fields[field] = Some(value.trim().parse().map_err(|_| {
+                Error::Malformed(Diagnostic::new(
+                    number,
+                    column,
+                    format!("invalid number `{}`", value.trim()),
+                ))
+            })?);
+        }
+        if let Some((start, fields)) = block {
+            entries.push(yuma_entry(start, &fields)?);
+        }
+        Ok(Self { entries })
+    }
+
+    /// Parses a SEM almanac.
+    ///
+    /// The header gives the number of records and a title, then the week
+    /// and the time of applicability of all records. Each record holds the
+    /// PRN, SVN and URA, nine orbit and clock parameters, the health and the
+    /// satellite configuration.
+    ///
+    /// # Arguments
+    /// * `data` - Text of the SEM file
+    ///
+    /// # Returns
+    /// * `Ok(Almanac)` - Successfully parsed almanac
+    /// * `Err(Error)` - If a value is malformed or the file is truncated
+    ///
+    /// # Errors
+    /// * `Error::Malformed` - If a value is not a number or a record is
+    ///   incomplete
+    pub fn read_sem(data: &str) -> Result<Self, Error> {
+        let mut lines = data.lines().enumerate();
+        let count = lines
+            .next()
+            .and_then(|(_, text)| text.split_whitespace().next())
+            .and_then(|count| count.parse::<usize>().ok())
+            .ok_or_else(|| {
+                Error::Malformed(Diagnostic::new(1, 1, "invalid record count"))
+            })?;
+        // Values with their line and column, for diagnostics
+        let mut tokens = lines.flat_map(|(index, text)| {
+            text.split_whitespace().map(move |token| {
+                let column = token.as_ptr() as usize - text.as_ptr() as usize;
+                (index + 1, column + 1, token)
+            })
+        });
+        let mut next = || -> Result<f64, Error> {
+            let (line, column, token) = tokens.next().ok_or_else(|| {
+                Error::Malformed(Diagnostic::new(
+                    data.lines().count(),
+                    1,
+                    "unexpected end of file",
+                ))
+            })?;
+            token.parse().map_err(|_| {
+                Error::Malformed(Diagnostic::new(
+                    line,
+                    column,
+                    format!("invalid number `{token}`"),
+                ))
+            })
+        };
+        let week = next()? as i32;
+        let toa = next()?;
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            let prn = next()? as usize;
+            let _svn = next()?;
+            let _ura = next()?;
+            let eccentricity = next()?;
+            let inclination = (SEM_INCLINATION + next()?) * GPS_PI;
+            let omega_dot = next()? * GPS_PI;
+            let sqrt_a = next()?;
+            let omega0 = next()? * GPS_PI;
+            let perigee = next()? * GPS_PI;
+            let mean_anomaly = next()? * GPS_PI;
+            let af0 = next()?;
+            let af1 = next()?;
+            let health = next()? as u32;
+            let _configuration = next()?;
+            entries.push(AlmanacEntry {
+                prn,
+                health,
+                eccentricity,
+                toa,
+                inclination,
+                omega_dot,
+                sqrt_a,
+                omega0,
+                perigee,
+                mean_anomaly,
+                af0,
+                af1,
+                week,
+            });
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// Start of the keys of the YUMA fields, in the order of the file
+const YUMA_KEYS: [&str; 13] = [
+    "id",
+    "health",
+    "eccentricity",
+    "time of applicability",
+    "orbital inclination",
+    "rate of right ascen",
+    "sqrt(a)",
+    "right ascen at week",
+    "argument of perigee",
+    "mean anom",
+    "af0",
+    "af1",
+    "week",
+];
+
+/// Builds an almanac entry from the fields of a YUMA block.
+///
+/// # Arguments
+/// * `start` - Line number of the block, for diagnostics
+/// * `fields` - Values of the fields, in the order of [`YUMA_KEYS`]
+///
+/// # Returns
+/// * `Ok(AlmanacEntry)` - The almanac entry
+/// * `Err(Error)` - If a field is missing
+fn yuma_entry(
+    start: usize, fields: &[Option<f64>; 13],
+) -> Result<AlmanacEntry, Error> {
+    let mut values = [0.0; 13];
+    for ((value, field), key) in values.iter_mut().zip(fields).zip(YUMA_KEYS) {
+        *value = field.ok_or_else(|| {
+            Error::Malformed(Diagnostic::new(
+                start,
+                1,
+                format!("satellite block without `{key}` field"),
+            ))
+        })?;
+    }
+    let [
+        prn,
+        health,
+        eccentricity,
+        toa,
+        inclination,
+        omega_dot,
+        sqrt_a,
+        omega0,
+        perigee,
+        mean_anomaly,
+        af0,
+        af1,
+        week,
+    ] = values;
+    Ok(AlmanacEntry {
+        prn: prn as usize,
+        health: health as u32,
+        eccentricity,
+        toa,
+        inclination,
+        omega_dot,
+        sqrt_a,
+        omega0,
+        perigee,
+        mean_anomaly,
+        af0,
+        af1,
+        week: week as i32,
+    })
+}
@@ -0,0 +1,170 @@
+//! Decompression of gzip and Unix compress navigation files.
+//!
+//! Navigation files from GNSS data archives are distributed compressed,
+//! either with gzip (`.gz`) or with the LZW-based Unix `compress` (`.Z`).
+//! The format is recognised from the magic bytes at the start of the data,
+//! so plain text files pass through unchanged whatever their name.
+
+use std::io::Read;
+
+use flate2::read::MultiGzDecoder;
+
+use crate::error::Error;
+
+/// Magic bytes at the start of gzip data
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// Magic bytes at the start of Unix compress data
+const COMPRESS_MAGIC: [u8; 2] = [0x1f, 0x9d];
+/// Mask of the maximum code width in the Unix compress flags byte
+const COMPRESS_BITS_MASK: u8 = 0x1f;
+/// Block mode flag of the Unix compress flags byte, enabling the clear code
+const COMPRESS_BLOCK_MODE: u8 = 0x80;
+/// Code width at the start of Unix compress data and after a clear code
+const COMPRESS_INIT_BITS: u32 = 9;
+/// Code that resets the Unix compress string table in block mode
+const COMPRESS_CLEAR: usize = 256;
+
+/// Decodes the content of a navigation file into text.
+///
+/// Gzip data (including concatenated gzip members) and Unix compress data
+/// are decompressed; anything else is taken as text. Hatanaka compression
+/// (CRINEX) is only defined for observation files and is rejected with an
+/// explicit error.
+///
+/// # Arguments
+/// * `data` - Raw content of the file
+///
+/// # Returns
+/// * `Ok(String)` - The decompressed text
+/// * `Err(Error)` - If the data is corrupt or not UTF-8 text
+pub fn decompress(data: Vec<u8>) -> Result<String, Error> {
+    let data = if data.starts_with(&GZIP_MAGIC) {
+        let mut text = Vec::new();
+        MultiGzDecoder::new(data.as_slice()).read_to_end(&mut text)?;
+        text
+    } else if data.starts_with(&COMPRESS_MAGIC) {
+        uncompress(&data)?
+    } else {
+        data
+    };
+    let text = String::from_utf8(data)?;
+    if text
+        .lines()
+        .next()
+        .is_some_and(|line| line.contains("CRINEX VERS"))
+    {
+        return Err(Error::decompress(
+            "Hatanaka compression is only defined for observation files",
+        ));
+    }
+    Ok(text)
+}
+
+/// Decompresses Unix compress (`.Z`) data.
+///
+/// Codes are packed least significant bit first and widen by one bit each
+/// time the string table outgrows them. The encoder writes codes in groups
+/// of eight, so when the width changes or the table is cleared, the rest of
+/// the current group is padding and is skipped.
+///
+/// # Arguments
+/// * `data` - Compressed data, including the three-byte header
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The decompressed bytes
+/// * `Err(Error)` - If the header or a code is invalid
+fn uncompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let [_, _, flags, body @ ..] = data else {
+        return Err(Error::decompress("truncated compress header"));
+    };
+    let max_bits = u32::from(flags & COMPRESS_BITS_MASK);
+    if !(COMPRESS_INIT_BITS..=16).contains(&max_bits) {
+        return Err(Error::decompress(format!(
+            "unsupported compress code width {max_bits}"
+        )));
+    }
+    let block_mode = flags & COMPRESS_BLOCK_MODE != 0;
+    let table_size = 1 << max_bits;
+    let mut prefix = vec![0usize; table_size];
+    let mut suffix: Vec<u8> = (0..table_size).map(|code| code as u8).collect();
+    let first_free = if block_mode { COMPRESS_CLEAR + 1 } else { 256 };
+
+    let mut output = Vec::with_capacity(body.len() * 3);
+    let mut string = Vec::new();
+    let mut n_bits = COMPRESS_INIT_BITS;
+    let mut free = first_free;
+    let mut previous: Option<usize> = None;
+    let mut first_char = 0u8;
+    let mut bit_pos = 0;
+    let mut group_codes = 0;
+    // Skips the padding after the last code read at the current width
+    let skip_group = |bit_pos: &mut usize, group_codes: &mut usize, n_bits| {
+        if !group_codes.is_multiple_of(8) {
+            *bit_pos += (8 - *group_codes % 8) * n_bits as usize;
+        }
+        *group_codes = 0;
+    };
+    loop {
+        // Like the reference implementation, the width grows past 9 bits
+        // even when that is the maximum
+        let max_code = if n_bits == max_bits && n_bits > COMPRESS_INIT_BITS {
+            table_size
+        } else {
+            (1 << n_bits) - 1
+        };
+        if free > max_code {
+            skip_group(&mut bit_pos, &mut group_codes, n_bits);
+            n_bits += 1;
+            continue;
+        }
+        if bit_pos + n_bits as usize > body.len() * 8 {
+            break;
+        }
+        let mut code = 0;
+        for bit in 0..n_bits as usize {
+            let pos = bit_pos + bit;
+            code |= usize::from(body[pos / 8] >> (pos % 8) & 1) << bit;
+        }
+        bit_pos += n_bits as usize;
+        group_codes += 1;
+
+        if block_mode && code == COMPRESS_CLEAR {
+            skip_group(&mut bit_pos, &mut group_codes, n_bits);
+            n_bits = COMPRESS_INIT_BITS;
+            free = first_free;
+            previous = None;
+            continue;
+        }
+        string.clear();
+        let mut entry = match previous {
+            None if code < 256 => code,
+            Some(_) if code < free => code,
+            // The string of the entry being defined by this very code
+            Some(previous) if code == free => {
+                string.push(first_char);
+                previous
+            }
+            _ => {
+                return Err(Error::decompress(format!(
+                    "invalid compress code {code}"
+                )));
+            }
+        };
+        while entry >= 256 {
+            string.push(suffix[entry]);
+            entry = prefix[entry];
+        }
+        first_char = entry as u8;
+        string.push(first_char);
+        output.extend(string.iter().rev());
+        if let Some(previous) = previous
+            && free < table_size
+        {
+            prefix[free] = previous;
+            suffix[free] = first_char;
+            free += 1;
+        }
+        previous = Some(code);
+    }
+    Ok(output)
+}
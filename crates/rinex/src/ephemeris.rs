@@ -5,11 +5,66 @@
 //! complete ephemeris data set and a builder pattern for constructing
 //! ephemeris objects incrementally during parsing.
 
+/// GLONASS state-vector ephemeris
+mod glonass;
 /// Orbital parameter structures for GPS ephemeris
 mod orbit;
-pub use self::orbit::*;
+pub use self::{glonass::*, orbit::*};
 use crate::error::Error;
 
+/// Satellite system of an ephemeris record.
+///
+/// RINEX 2 navigation files carry a single system (GPS for `.n` files), while
+/// RINEX 3 records start with a system letter such as `G` or `E`. GLONASS
+/// (`R`) records hold state vectors and are read into [`GlonassEphemeris`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum System {
+    /// GPS (`G`)
+    #[default]
+    Gps,
+    /// Galileo (`E`)
+    Galileo,
+    /// BeiDou (`C`), whose epochs and times of ephemeris are in BDT
+    BeiDou,
+    /// GLONASS (`R`), whose epochs are in UTC
+    Glonass,
+    /// QZSS (`J`), whose satellite numbers are the PRN minus 192
+    Qzss,
+}
+impl System {
+    /// Returns the system identified by a RINEX 3 satellite system letter.
+    ///
+    /// # Arguments
+    /// * `letter` - Satellite system letter of a record (e.g. `G`)
+    ///
+    /// # Returns
+    /// The system, or `None` for systems that are not supported
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'G' => Some(System::Gps),
+            'E' => Some(System::Galileo),
+            'C' => Some(System::BeiDou),
+            'R' => Some(System::Glonass),
+            'J' => Some(System::Qzss),
+            _ => None,
+        }
+    }
+
+    /// Returns the RINEX 3 system letter of the satellite system.
+    ///
+    /// # Returns
+    /// The letter starting the satellite identifiers, e.g. `G` for GPS
+    pub fn letter(self) -> char {
+        match self {
+            System::Gps => 'G',
+            System::Galileo => 'E',
+            System::BeiDou => 'C',
+            System::Glonass => 'R',
+            System::Qzss => 'J',
+        }
+    }
+}
+
 /// Satellite vehicle clock parameters from the RINEX navigation message.
 ///
 /// This structure contains the clock correction parameters for a GPS satellite.
@@ -68,7 +123,11 @@ impl SvClock {
 /// orbital parameters, following the standard RINEX navigation message format.
 #[derive(Debug, Clone, Default)]
 pub struct Ephemeris {
-    /// Satellite PRN (Pseudo-Random Noise) number (1-32)
+    /// Satellite system of the record
+    pub system: System,
+
+    /// Satellite number of the record: the PRN (1-32) for GPS, and the
+    /// number following the system letter for RINEX 3 records
     pub prn: usize,
 
     /// Reference time for the clock parameters (Time of Clock)
@@ -108,6 +167,9 @@ pub struct Ephemeris {
 /// object is created only when all required components are present.
 #[derive(Debug, Default)]
 pub struct EphemerisBuilder {
+    /// Satellite system, GPS when not set
+    system: Option<System>,
+
     /// Satellite PRN number
     prn: Option<usize>,
 
@@ -148,6 +210,14 @@ impl EphemerisBuilder {
         Self::default()
     }
 
+    /// Sets the satellite system of the record.
+    ///
+    /// # Arguments
+    /// * `system` - Satellite system
+    pub fn set_system(&mut self, system: System) {
+        self.system.replace(system);
+    }
+
     /// Sets the satellite PRN number.
     ///
     /// # Arguments
@@ -253,6 +323,7 @@ impl EphemerisBuilder {
         }
 
         let ephemeris = Ephemeris {
+            system: self.system.take().unwrap_or_default(),
             prn: take(&mut self.prn, "prn is none")?,
             time_of_clock: take(
                 &mut self.time_of_clock,
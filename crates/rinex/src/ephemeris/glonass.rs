@@ -0,0 +1,43 @@
+/// GLONASS broadcast ephemeris of a RINEX 3 `R` record.
+///
+/// Instead of Keplerian elements, GLONASS satellites broadcast their
+/// position, velocity and lunisolar acceleration in the PZ-90 frame at the
+/// reference time `t_b`, which is also the epoch of the record. Receivers
+/// propagate this state vector by numerical integration.
+///
+/// Unlike the other systems, the epoch of a GLONASS record is given in UTC.
+#[derive(Debug, Clone, Default)]
+pub struct GlonassEphemeris {
+    /// Satellite slot number (1-24)
+    pub prn: usize,
+
+    /// Reference time of the state vector and clock parameters (`t_b`), UTC
+    pub time_of_clock: jiff::Timestamp,
+
+    /// SV clock bias (-τn) in seconds
+    pub clock_bias: f64,
+
+    /// SV relative frequency bias (+γn)
+    pub relative_frequency_bias: f64,
+
+    /// Message frame time (`t_k`) in seconds of the UTC week
+    pub frame_time: f64,
+
+    /// Satellite position (X, Y, Z) in PZ-90 in kilometers
+    pub position: [f64; 3],
+
+    /// Satellite velocity in kilometers/second
+    pub velocity: [f64; 3],
+
+    /// Lunisolar acceleration in kilometers/second²
+    pub acceleration: [f64; 3],
+
+    /// Satellite health (0 = healthy)
+    pub health: f64,
+
+    /// Frequency channel number k (-7 to +6)
+    pub frequency_channel: i32,
+
+    /// Age of the operational information in days
+    pub age: f64,
+}
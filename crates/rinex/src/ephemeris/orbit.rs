@@ -249,3 +249,50 @@ impl From<[f64; 4]> for Orbit7 {
         }
     }
 }
+
+/// Converts Orbit1 parameters back to the 4 values of a RINEX orbit line.
+///
+/// This is the inverse of the conversion from an array, used when writing
+/// RINEX data.
+impl From<&Orbit1> for [f64; 4] {
+    /// Creates an array of 4 floating-point values from Orbit1 parameters.
+    ///
+    /// # Arguments
+    /// * `orbit` - The orbit parameters
+    ///
+    /// # Returns
+    /// An array containing [`iode`, `crs`, `delta_n`, `m0`]
+    fn from(orbit: &Orbit1) -> Self {
+        [orbit.iode, orbit.crs, orbit.delta_n, orbit.m0]
+    }
+}
+impl From<&Orbit2> for [f64; 4] {
+    fn from(orbit: &Orbit2) -> Self {
+        [orbit.cuc, orbit.ecc, orbit.cus, orbit.sqrta]
+    }
+}
+impl From<&Orbit3> for [f64; 4] {
+    fn from(orbit: &Orbit3) -> Self {
+        [orbit.toe, orbit.cic, orbit.omega, orbit.cis]
+    }
+}
+impl From<&Orbit4> for [f64; 4] {
+    fn from(orbit: &Orbit4) -> Self {
+        [orbit.i0, orbit.crc, orbit.omega, orbit.omega_dot]
+    }
+}
+impl From<&Orbit5> for [f64; 4] {
+    fn from(orbit: &Orbit5) -> Self {
+        [orbit.idot, orbit.code_l2, orbit.week, orbit.l2_pseudorange]
+    }
+}
+impl From<&Orbit6> for [f64; 4] {
+    fn from(orbit: &Orbit6) -> Self {
+        [orbit.sv_accuracy, orbit.sv_health, orbit.tgd, orbit.iodc]
+    }
+}
+impl From<&Orbit7> for [f64; 4] {
+    fn from(orbit: &Orbit7) -> Self {
+        [orbit.tom, orbit.spare1, orbit.spare2, orbit.spare3]
+    }
+}
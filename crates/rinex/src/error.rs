@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::rule::Rule;
+use crate::reader::Diagnostic;
 
 /// Custom error type for the RINEX crate
 #[derive(Error, Debug)]
@@ -10,6 +10,10 @@ pub enum Error {
     #[error("RINEX file cannot be read")]
     ReadRinex(#[from] std::io::Error),
 
+    /// Error when writing RINEX file
+    #[error("RINEX file cannot be written: {0}")]
+    WriteRinex(#[source] std::io::Error),
+
     /// Error when parsing floating point values from RINEX file
     #[error("Cannot parse to float: {0}")]
     ParseFloat(#[from] std::num::ParseFloatError),
@@ -18,13 +22,17 @@ pub enum Error {
     #[error("Cannot parse to integer: {0}")]
     ParseInt(#[from] std::num::ParseIntError),
 
-    /// Error from the pest parser when parsing RINEX file
-    #[error("Cannot parse RINEX file: {0}")]
-    ParseFile(#[from] Box<pest::error::Error<Rule>>),
+    /// Error when a RINEX file is not valid UTF-8 text
+    #[error("RINEX file is not text: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+
+    /// Error when decompressing a RINEX file
+    #[error("Cannot decompress RINEX file: {0}")]
+    Decompress(String),
 
-    /// Error when processing a specific parsing rule
-    #[error("Cannot parse rule: {0}")]
-    Rule(String),
+    /// Error when a RINEX file cannot be read past a malformed line
+    #[error("Malformed RINEX file at {0}")]
+    Malformed(Diagnostic),
 
     /// Error when building a RINEX object
     #[error("RINEX builder error: {0}")]
@@ -44,10 +52,10 @@ pub enum Error {
 }
 
 impl Error {
-    /// Create a new rule error
+    /// Create a new decompression error
     #[inline]
-    pub fn rule(message: impl Into<String>) -> Self {
-        Error::Rule(message.into())
+    pub fn decompress(message: impl Into<String>) -> Self {
+        Error::Decompress(message.into())
     }
 
     /// Create a new RINEX builder error
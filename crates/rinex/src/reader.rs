@@ -0,0 +1,563 @@
+//! Streaming, line-oriented reader of RINEX navigation files.
+//!
+//! [`RinexReader`] reads a navigation file one record at a time from any
+//! buffered source. Fields are taken from the columns the RINEX formats
+//! assign to them, so values that touch without a separating space, numbers
+//! without a leading zero and blank spare fields are all read correctly. A
+//! malformed record is skipped and reported as a [`Diagnostic`] with its line
+//! and column, and reading resumes at the next record.
+
+/// Parsing of the records of each format version
+mod record;
+
+use std::{
+    fmt,
+    io::{BufRead, Lines},
+};
+
+use jiff::Timestamp;
+
+use crate::{
+    ephemeris::{Ephemeris, GlonassEphemeris},
+    error::Error,
+    rule::{Rinex, RinexBuilder},
+    utc::DeltaUtc,
+};
+
+/// A problem found at a position of a RINEX file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Line number, starting at 1
+    pub line: usize,
+    /// Column number, starting at 1
+    pub column: usize,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic for a position of a file.
+    ///
+    /// # Arguments
+    /// * `line` - Line number, starting at 1
+    /// * `column` - Column number, starting at 1
+    /// * `message` - Description of the problem
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// A record of a navigation file.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Record {
+    /// Keplerian ephemeris (GPS, Galileo, BeiDou, QZSS)
+    Ephemeris(Ephemeris),
+    /// GLONASS state-vector ephemeris
+    Glonass(GlonassEphemeris),
+    /// GPS Klobuchar ionospheric parameters of a RINEX 4 `ION` record
+    Klobuchar {
+        /// Ionosphere parameters A0-A3
+        alpha: [f64; 4],
+        /// Ionosphere parameters B0-B3
+        beta: [f64; 4],
+    },
+    /// Galileo `NeQuick` parameters ai0-ai2 of a RINEX 4 `ION` record
+    NeQuick([f64; 3]),
+    /// GPS-UTC parameters of a RINEX 4 `STO` record
+    GpsUtc(DeltaUtc),
+}
+
+/// A line of a RINEX or SP3 file.
+pub(crate) struct Line {
+    /// Line number, starting at 1
+    pub(crate) number: usize,
+    /// Text of the line without its line ending
+    pub(crate) text: String,
+}
+
+/// Streaming reader of RINEX 2, 3 and 4 navigation files.
+///
+/// The header is read when the reader is created. Records are then read one
+/// at a time through the [`Iterator`] implementation, which only yields
+/// errors for a source that cannot be read, or all at once with
+/// [`RinexReader::into_rinex`]. Records of unsupported satellite systems and
+/// message types are skipped silently; malformed records and header lines
+/// are skipped and collected as warnings.
+///
+/// # Example
+/// ```no_run
+/// use std::{fs::File, io::BufReader};
+///
+/// use rinex::reader::{Record, RinexReader};
+///
+/// let file = File::open("brdc0010.22n")?;
+/// let mut reader = RinexReader::new(BufReader::new(file))?;
+/// for record in &mut reader {
+///     if let Record::Ephemeris(ephemeris) = record? {
+///         println!("{:?} {}", ephemeris.system, ephemeris.prn);
+///     }
+/// }
+/// for warning in reader.warnings() {
+///     eprintln!("Warning: {warning}");
+/// }
+/// # Ok::<(), rinex::Error>(())
+/// ```
+pub struct RinexReader<R> {
+    /// Lines of the source
+    lines: Lines<R>,
+    /// Number of lines read from the source
+    line_count: usize,
+    /// Line read ahead of the current record
+    next_line: Option<Line>,
+    /// Format version
+    version: f64,
+    /// Header data
+    header: RinexBuilder,
+    /// Problems found so far
+    warnings: Vec<Diagnostic>,
+}
+
+impl<R: BufRead> RinexReader<R> {
+    /// Creates a reader and reads the header of the file.
+    ///
+    /// Header lines that RINEX 3 and 4 files may omit default to empty text
+    /// and zero corrections. Header lines holding invalid values are
+    /// reported as warnings and ignored.
+    ///
+    /// # Arguments
+    /// * `reader` - Buffered source of the navigation file
+    ///
+    /// # Returns
+    /// * `Ok(RinexReader)` - A reader positioned at the first record
+    /// * `Err(Error)` - If the header cannot be read
+    ///
+    /// # Errors
+    /// * Returns an error if the source cannot be read, if the first line is
+    ///   not a valid `RINEX VERSION / TYPE` line, or if the header has no
+    ///   `END OF HEADER` line
+    pub fn new(reader: R) -> Result<Self, Error> {
+        let mut rinex_reader = Self {
+            lines: reader.lines(),
+            line_count: 0,
+            next_line: None,
+            version: 0.0,
+            header: RinexBuilder::new(),
+            warnings: Vec::new(),
+        };
+        rinex_reader.read_header()?;
+        Ok(rinex_reader)
+    }
+
+    /// Returns the format version of the file, e.g. 3.04.
+    pub fn version(&self) -> f64 {
+        self.version
+    }
+
+    /// Returns the problems found so far.
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    /// Reads the remaining records and returns the whole file.
+    ///
+    /// The ionospheric and GPS-UTC parameters of RINEX 4 files are taken
+    /// from the first `ION` and `STO` records holding them.
+    ///
+    /// # Returns
+    /// * `Ok(Rinex)` - The header, records and warnings of the file
+    /// * `Err(Error)` - If the source cannot be read
+    ///
+    /// # Errors
+    /// * Returns an error if the source cannot be read
+    pub fn into_rinex(mut self) -> Result<Rinex, Error> {
+        let mut ephemerides = Vec::new();
+        let mut glonass_ephemerides = Vec::new();
+        let (mut klobuchar, mut nequick, mut gps_utc) = (None, None, None);
+        while let Some(record) = self.next_record()? {
+            match record {
+                Record::Ephemeris(ephemeris) => ephemerides.push(ephemeris),
+                Record::Glonass(ephemeris) => {
+                    glonass_ephemerides.push(ephemeris);
+                }
+                Record::Klobuchar { alpha, beta } => {
+                    klobuchar.get_or_insert((alpha, beta));
+                }
+                Record::NeQuick(ion_galileo) => {
+                    nequick.get_or_insert(ion_galileo);
+                }
+                Record::GpsUtc(delta_utc) => {
+                    gps_utc.get_or_insert(delta_utc);
+                }
+            }
+        }
+        if let Some((alpha, beta)) = klobuchar {
+            self.header.set_ion_alpha(alpha);
+            self.header.set_ion_beta(beta);
+        }
+        if let Some(ion_galileo) = nequick {
+            self.header.set_ion_galileo(ion_galileo);
+        }
+        if let Some(delta_utc) = gps_utc {
+            self.header.set_delta_utc(delta_utc);
+        }
+        self.header.set_ephemerides(ephemerides);
+        self.header.set_glonass_ephemerides(glonass_ephemerides);
+        self.header.set_warnings(self.warnings);
+        self.header.build()
+    }
+
+    /// Reads the next line, taking the line read ahead first.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Line))` - The next line
+    /// * `Ok(None)` - At the end of the source
+    /// * `Err(Error)` - If the source cannot be read
+    fn read_line(&mut self) -> Result<Option<Line>, Error> {
+        if let Some(line) = self.next_line.take() {
+            return Ok(Some(line));
+        }
+        let Some(mut text) = self.lines.next().transpose()? else {
+            return Ok(None);
+        };
+        if text.ends_with('\r') {
+            text.pop();
+        }
+        self.line_count += 1;
+        Ok(Some(Line {
+            number: self.line_count,
+            text,
+        }))
+    }
+
+    /// Tells whether the next line continues the current record.
+    ///
+    /// # Returns
+    /// * `Ok(bool)` - False at a blank line, the start of a record or the end
+    ///   of the source
+    /// * `Err(Error)` - If the source cannot be read
+    fn continues_record(&mut self) -> Result<bool, Error> {
+        if self.next_line.is_none() {
+            self.next_line = self.read_line()?;
+        }
+        Ok(self.next_line.as_ref().is_some_and(|line| {
+            !line.text.trim().is_empty()
+                && !is_record_start(self.version, &line.text)
+        }))
+    }
+
+    /// Reads the header lines up to `END OF HEADER`.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the header was read
+    /// * `Err(Error)` - If the header is unusable or cannot be read
+    fn read_header(&mut self) -> Result<(), Error> {
+        let malformed = |line, column, message: &str| {
+            Error::Malformed(Diagnostic::new(line, column, message))
+        };
+        let first = self
+            .read_line()?
+            .ok_or_else(|| malformed(1, 1, "empty file"))?;
+        if column(&first.text, 60, 80) != "RINEX VERSION / TYPE" {
+            return Err(malformed(
+                first.number,
+                61,
+                "expected a RINEX VERSION / TYPE line",
+            ));
+        }
+        let version = column(&first.text, 0, 9);
+        self.version = version.parse().map_err(|_| {
+            malformed(first.number, 1, "invalid format version")
+        })?;
+        self.header.set_version(version.to_string());
+        self.header
+            .set_type(column(&first.text, 20, 60).to_string());
+        self.header.set_program(String::new());
+        self.header.set_agency(String::new());
+        self.header.set_update(String::new());
+        self.header.set_ion_alpha([0.0; 4]);
+        self.header.set_ion_beta([0.0; 4]);
+        self.header.set_delta_utc(DeltaUtc::new(0.0, 0.0, 0, 0));
+        self.header.set_leap_seconds(0);
+        let mut comments = Vec::new();
+        loop {
+            let Some(line) = self.read_line()? else {
+                return Err(malformed(
+                    self.line_count,
+                    1,
+                    "missing END OF HEADER line",
+                ));
+            };
+            match column(&line.text, 60, 80) {
+                "END OF HEADER" => break,
+                "COMMENT" => {
+                    comments.push(column(&line.text, 0, 60).to_string());
+                }
+                label => {
+                    if let Err(diagnostic) = self.read_header_line(label, &line)
+                    {
+                        self.warnings.push(diagnostic);
+                    }
+                }
+            }
+        }
+        self.header.set_comments(comments.join("\n"));
+        Ok(())
+    }
+
+    /// Parses a header line other than the version and comment lines.
+    ///
+    /// Lines are identified by their label in columns 61-80. Lines without
+    /// information needed by the simulator are ignored.
+    ///
+    /// # Arguments
+    /// * `label` - Label of the line
+    /// * `line` - The header line
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the line was read or ignored
+    /// * `Err(Diagnostic)` - If the line holds invalid values
+    fn read_header_line(
+        &mut self, label: &str, line: &Line,
+    ) -> Result<(), Diagnostic> {
+        let header = &mut self.header;
+        match label {
+            "PGM / RUN BY / DATE" => {
+                header.set_program(column(&line.text, 0, 20).to_string());
+                header.set_agency(column(&line.text, 20, 40).to_string());
+                header.set_update(column(&line.text, 40, 60).to_string());
+            }
+            // RINEX 2
+            "ION ALPHA" => header.set_ion_alpha(floats(line, 2, 12)?),
+            "ION BETA" => header.set_ion_beta(floats(line, 2, 12)?),
+            "DELTA-UTC: A0,A1,T,W" => {
+                let [a0, a1] = floats(line, 3, 19)?;
+                let time = int(line, 41, 50)?;
+                let week = int(line, 50, 59)?;
+                header.set_delta_utc(DeltaUtc::new(a0, a1, time, week));
+            }
+            // RINEX 3
+            "IONOSPHERIC CORR" => {
+                let values: [f64; 4] = floats(line, 5, 12)?;
+                match column(&line.text, 0, 4) {
+                    "GPSA" => header.set_ion_alpha(values),
+                    "GPSB" => header.set_ion_beta(values),
+                    "GAL" => {
+                        header
+                            .set_ion_galileo([values[0], values[1], values[2]]);
+                    }
+                    _ => {}
+                }
+            }
+            "TIME SYSTEM CORR" if column(&line.text, 0, 4) == "GPUT" => {
+                let a0 = float(line, 5, 22)?;
+                let a1 = float(line, 22, 38)?;
+                let time = int(line, 38, 45)?;
+                let week = int(line, 45, 50)?;
+                header.set_delta_utc(DeltaUtc::new(a0, a1, time, week));
+            }
+            "LEAP SECONDS" => header.set_leap_seconds(int(line, 0, 6)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Reads the next supported record, skipping malformed ones.
+    ///
+    /// A record is made of its first line and the lines continuing it.
+    /// Lines that neither start nor continue a record are reported and
+    /// skipped.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Record))` - The next record
+    /// * `Ok(None)` - At the end of the source
+    /// * `Err(Error)` - If the source cannot be read
+    fn next_record(&mut self) -> Result<Option<Record>, Error> {
+        while let Some(head) = self.read_line()? {
+            if head.text.trim().is_empty() {
+                continue;
+            }
+            if !is_record_start(self.version, &head.text) {
+                self.warnings.push(Diagnostic::new(
+                    head.number,
+                    1,
+                    "line is not part of a record",
+                ));
+                continue;
+            }
+            let mut lines = vec![head];
+            while self.continues_record()? {
+                lines.extend(self.read_line()?);
+            }
+            let record = if self.version >= 4.0 {
+                record::read_record4(&lines)
+            } else if self.version >= 3.0 {
+                record::read_record3(&lines)
+            } else {
+                record::read_record2(&lines)
+            };
+            match record {
+                Ok(Some(record)) => return Ok(Some(record)),
+                Ok(None) => {}
+                Err(diagnostic) => self.warnings.push(diagnostic),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: BufRead> Iterator for RinexReader<R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// Tells whether a line starts a record.
+///
+/// RINEX 2 records start with the PRN in the first columns, RINEX 3 records
+/// with the satellite system letter and RINEX 4 records with a `>` frame
+/// line; the other lines of a record are indented.
+///
+/// # Arguments
+/// * `version` - Format version of the file
+/// * `text` - Text of the line
+fn is_record_start(version: f64, text: &str) -> bool {
+    if version >= 4.0 {
+        text.starts_with('>')
+    } else if version >= 3.0 {
+        text.starts_with(|c: char| c.is_ascii_uppercase())
+    } else {
+        !text.starts_with("   ")
+    }
+}
+
+/// Returns the trimmed text between two columns of a line.
+///
+/// Columns beyond the end of the line are treated as blank.
+///
+/// # Arguments
+/// * `text` - The line to read from
+/// * `start` - First column (0-based)
+/// * `end` - Column after the last one
+pub(crate) fn column(text: &str, start: usize, end: usize) -> &str {
+    text.get(start..end.min(text.len())).unwrap_or("").trim()
+}
+
+/// Builds the epoch of a record from its fields.
+///
+/// # Arguments
+/// * `line` - Line holding the epoch, for diagnostics
+/// * `year` - Four-digit year
+/// * `[month, day, hour]` - Month, day of month and hour
+/// * `minutes` - Minutes
+/// * `seconds` - Seconds, possibly fractional
+///
+/// # Returns
+/// * `Ok(Timestamp)` - The epoch
+/// * `Err(Diagnostic)` - If the fields do not form a valid date and time
+pub(crate) fn epoch(
+    line: &Line, year: i32, [month, day, hour]: [i32; 3], minutes: i32,
+    seconds: f64,
+) -> Result<Timestamp, Diagnostic> {
+    let datetime =
+        format!("{year}-{month:02}-{day:02}T{hour:02}:{minutes:02}:00Z");
+    datetime
+        .parse::<Timestamp>()
+        .ok()
+        .zip(std::time::Duration::try_from_secs_f64(seconds).ok())
+        .and_then(|(timestamp, seconds)| timestamp.checked_add(seconds).ok())
+        .ok_or_else(|| {
+            Diagnostic::new(line.number, 1, format!("invalid epoch {datetime}"))
+        })
+}
+
+/// Parses a floating-point field, reading a blank field as zero.
+///
+/// RINEX files often use `D` instead of `E` for exponents.
+///
+/// # Arguments
+/// * `line` - The line to read from
+/// * `start` - First column of the field (0-based)
+/// * `end` - Column after the field
+///
+/// # Returns
+/// * `Ok(f64)` - The value of the field
+/// * `Err(Diagnostic)` - If the field is not a number
+pub(crate) fn float(
+    line: &Line, start: usize, end: usize,
+) -> Result<f64, Diagnostic> {
+    let field = column(&line.text, start, end);
+    if field.is_empty() {
+        return Ok(0.0);
+    }
+    field.replace(['D', 'd'], "E").parse().map_err(|_| {
+        Diagnostic::new(
+            line.number,
+            start + 1,
+            format!("invalid number `{field}`"),
+        )
+    })
+}
+
+/// Parses consecutive floating-point fields of the same width.
+///
+/// Blank and missing fields, which RINEX allows for spare and unknown
+/// values, are read as zero.
+///
+/// # Arguments
+/// * `line` - The line to read from
+/// * `start` - First column of the first field (0-based)
+/// * `width` - Width of each field
+///
+/// # Returns
+/// * `Ok([f64; N])` - The values of the fields
+/// * `Err(Diagnostic)` - If a field is not a number
+fn floats<const N: usize>(
+    line: &Line, start: usize, width: usize,
+) -> Result<[f64; N], Diagnostic> {
+    let mut values = [0.0; N];
+    for (i, value) in values.iter_mut().enumerate() {
+        let field_start = start + i * width;
+        *value = float(line, field_start, field_start + width)?;
+    }
+    Ok(values)
+}
+
+/// Parses an integer field.
+///
+/// # Arguments
+/// * `line` - The line to read from
+/// * `start` - First column of the field (0-based)
+/// * `end` - Column after the field
+///
+/// # Returns
+/// * `Ok(i32)` - The value of the field
+/// * `Err(Diagnostic)` - If the field is blank or not an integer
+pub(crate) fn int(
+    line: &Line, start: usize, end: usize,
+) -> Result<i32, Diagnostic> {
+    let field = column(&line.text, start, end);
+    field.parse().map_err(|_| {
+        Diagnostic::new(
+            line.number,
+            start + 1,
+            format!("invalid integer `{field}`"),
+        )
+    })
+}
@@ -0,0 +1,345 @@
+//! Parsing of the records of RINEX 2, 3 and 4 navigation files.
+//!
+//! Records are parsed from the fixed columns of their lines: the epoch
+//! fields, then 19-column floating-point fields (D19.12) after a 3-column
+//! (RINEX 2) or 4-column (RINEX 3 and 4) indent.
+
+use jiff::Timestamp;
+
+use super::{Diagnostic, Line, Record, column, epoch, float, floats, int};
+use crate::{
+    ephemeris::{EphemerisBuilder, GlonassEphemeris, SvClock, System},
+    utc::DeltaUtc,
+};
+
+/// Width of the floating-point fields of the records
+const FIELD_WIDTH: usize = 19;
+/// First column of the clock fields of a RINEX 2 record
+const CLOCK_START2: usize = 22;
+/// First column of the clock fields of a RINEX 3 record
+const CLOCK_START3: usize = 23;
+/// Indent of the continuation lines of a RINEX 2 record
+const INDENT2: usize = 3;
+/// Indent of the continuation lines of RINEX 3 and 4 records
+const INDENT3: usize = 4;
+/// Start of GPS time (1980-01-06T00:00:00Z), from which RINEX 4 `STO`
+/// epochs are counted in weeks
+const GPS_EPOCH: Timestamp = Timestamp::constant(315_964_800, 0);
+/// Number of seconds in a GPS week
+const SECONDS_IN_WEEK: i64 = 604_800;
+
+/// Parses a RINEX 2 GPS navigation record.
+///
+/// # Arguments
+/// * `lines` - Head line and broadcast orbit lines of the record
+///
+/// # Returns
+/// * `Ok(Some(Record))` - The ephemeris of the record
+/// * `Err(Diagnostic)` - If the record is malformed
+pub(super) fn read_record2(
+    lines: &[Line],
+) -> Result<Option<Record>, Diagnostic> {
+    let [head, orbits @ ..] = lines else {
+        return Ok(None);
+    };
+    let prn = prn(head, 0, 2)?;
+    let year = int(head, 3, 5)?;
+    // Two-digit years 80-99 are 1980-1999
+    let year = if year < 80 { 2000 + year } else { 1900 + year };
+    let time_of_clock = epoch(
+        head,
+        year,
+        [int(head, 6, 8)?, int(head, 9, 11)?, int(head, 12, 14)?],
+        int(head, 15, 17)?,
+        float(head, 17, CLOCK_START2)?,
+    )?;
+    let clock = floats(head, CLOCK_START2, FIELD_WIDTH)?;
+    read_keplerian(
+        head,
+        System::Gps,
+        prn,
+        time_of_clock,
+        clock,
+        orbits,
+        INDENT2,
+    )
+}
+
+/// Parses a RINEX 3 navigation record.
+///
+/// The epoch is read as given, without conversion between time systems.
+///
+/// # Arguments
+/// * `lines` - Head line and broadcast orbit lines of the record
+///
+/// # Returns
+/// * `Ok(Some(Record))` - The ephemeris of a supported satellite system
+/// * `Ok(None)` - If the record belongs to an unsupported satellite system
+/// * `Err(Diagnostic)` - If the record is malformed
+pub(super) fn read_record3(
+    lines: &[Line],
+) -> Result<Option<Record>, Diagnostic> {
+    let [head, orbits @ ..] = lines else {
+        return Ok(None);
+    };
+    let Some(system) = head.text.chars().next().and_then(System::from_letter)
+    else {
+        return Ok(None);
+    };
+    let prn = prn(head, 1, 3)?;
+    let time_of_clock = read_epoch3(head)?;
+    let clock = floats(head, CLOCK_START3, FIELD_WIDTH)?;
+    if system == System::Glonass {
+        return read_glonass(head, prn, time_of_clock, clock, orbits)
+            .map(|ephemeris| Some(Record::Glonass(ephemeris)));
+    }
+    read_keplerian(head, system, prn, time_of_clock, clock, orbits, INDENT3)
+}
+
+/// Parses a RINEX 4 navigation record.
+///
+/// Ephemerides are read from `EPH` records of the legacy navigation
+/// messages, whose layout is the one of RINEX 3. Klobuchar and `NeQuick`
+/// parameters are read from `ION` records, and GPS-UTC parameters from
+/// `STO` records. Other records, such as `EOP`, are skipped.
+///
+/// # Arguments
+/// * `lines` - Frame line and body lines of the record
+///
+/// # Returns
+/// * `Ok(Some(Record))` - The content of a supported record
+/// * `Ok(None)` - If the record is not supported
+/// * `Err(Diagnostic)` - If the record is malformed
+pub(super) fn read_record4(
+    lines: &[Line],
+) -> Result<Option<Record>, Diagnostic> {
+    let [frame, body @ ..] = lines else {
+        return Ok(None);
+    };
+    let [">", record_type, sv_id, message] =
+        frame.text.split_whitespace().collect::<Vec<_>>()[..]
+    else {
+        return Err(Diagnostic::new(frame.number, 1, "invalid record frame"));
+    };
+    let system = sv_id.chars().next().and_then(System::from_letter);
+    let too_few_lines = || {
+        Diagnostic::new(
+            frame.number,
+            1,
+            format!("{sv_id}: {record_type} record has too few lines"),
+        )
+    };
+    match (record_type, system) {
+        ("EPH", Some(system)) if is_legacy_message(system, message) => {
+            if body.is_empty() {
+                return Err(too_few_lines());
+            }
+            read_record3(body)
+        }
+        ("STO", _) => {
+            let [epoch_line, parameters, ..] = body else {
+                return Err(too_few_lines());
+            };
+            Ok(read_gps_utc4(epoch_line, parameters)?.map(Record::GpsUtc))
+        }
+        ("ION", Some(System::Gps)) => {
+            let [first, second, third, ..] = body else {
+                return Err(too_few_lines());
+            };
+            let [a0, a1, a2] = floats(first, CLOCK_START3, FIELD_WIDTH)?;
+            let [a3, b0, b1, b2] = floats(second, INDENT3, FIELD_WIDTH)?;
+            let [b3] = floats(third, INDENT3, FIELD_WIDTH)?;
+            Ok(Some(Record::Klobuchar {
+                alpha: [a0, a1, a2, a3],
+                beta: [b0, b1, b2, b3],
+            }))
+        }
+        ("ION", Some(System::Galileo)) => {
+            let [first, ..] = body else {
+                return Err(too_few_lines());
+            };
+            let nequick = floats(first, CLOCK_START3, FIELD_WIDTH)?;
+            Ok(Some(Record::NeQuick(nequick)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Tells whether a RINEX 4 `EPH` record holds a legacy navigation message.
+///
+/// Only the messages whose records have the RINEX 3 layout are read: GPS and
+/// QZSS `LNAV`, Galileo `INAV` and `FNAV`, BeiDou `D1` and `D2`, and GLONASS
+/// `FDMA`. Records of modernized messages such as `CNAV` are skipped.
+///
+/// # Arguments
+/// * `system` - Satellite system of the record
+/// * `message` - Navigation message type of the record frame
+fn is_legacy_message(system: System, message: &str) -> bool {
+    matches!(
+        (system, message),
+        (System::Gps | System::Qzss, "LNAV")
+            | (System::Galileo, "INAV" | "FNAV")
+            | (System::BeiDou, "D1" | "D2")
+            | (System::Glonass, "FDMA")
+    )
+}
+
+/// Parses the satellite number of a record.
+///
+/// # Arguments
+/// * `line` - Head line of the record
+/// * `start` - First column of the number (0-based)
+/// * `end` - Column after the number
+///
+/// # Returns
+/// * `Ok(usize)` - The satellite number
+/// * `Err(Diagnostic)` - If the number is missing or not positive
+fn prn(line: &Line, start: usize, end: usize) -> Result<usize, Diagnostic> {
+    usize::try_from(int(line, start, end)?)
+        .ok()
+        .filter(|&prn| prn > 0)
+        .ok_or_else(|| {
+            Diagnostic::new(line.number, start + 1, "invalid satellite number")
+        })
+}
+
+/// Parses the epoch of a RINEX 3 record or of a RINEX 4 `STO` record.
+///
+/// # Arguments
+/// * `line` - Line starting with the epoch in columns 5-23
+///
+/// # Returns
+/// * `Ok(Timestamp)` - The epoch
+/// * `Err(Diagnostic)` - If the epoch is malformed
+fn read_epoch3(line: &Line) -> Result<Timestamp, Diagnostic> {
+    epoch(
+        line,
+        int(line, 4, 8)?,
+        [int(line, 9, 11)?, int(line, 12, 14)?, int(line, 15, 17)?],
+        int(line, 18, 20)?,
+        f64::from(int(line, 21, 23)?),
+    )
+}
+
+/// Parses the broadcast orbit lines of a Keplerian ephemeris.
+///
+/// # Arguments
+/// * `head` - Head line of the record, for diagnostics
+/// * `system` - Satellite system of the record
+/// * `prn` - Satellite number
+/// * `time_of_clock` - Epoch of the record
+/// * `[bias, drift, drift_rate]` - Clock fields of the head line
+/// * `orbits` - Broadcast orbit lines
+/// * `indent` - First column of the orbit fields
+///
+/// # Returns
+/// * `Ok(Some(Record))` - The ephemeris
+/// * `Err(Diagnostic)` - If the orbit lines are missing or malformed
+fn read_keplerian(
+    head: &Line, system: System, prn: usize, time_of_clock: Timestamp,
+    [bias, drift, drift_rate]: [f64; 3], orbits: &[Line], indent: usize,
+) -> Result<Option<Record>, Diagnostic> {
+    let [o1, o2, o3, o4, o5, o6, o7, ..] = orbits else {
+        return Err(Diagnostic::new(
+            head.number,
+            1,
+            format!("expected 7 broadcast orbit lines, found {}", orbits.len()),
+        ));
+    };
+    let orbit = |line| floats::<4>(line, indent, FIELD_WIDTH);
+    let mut builder = EphemerisBuilder::new();
+    builder.set_system(system);
+    builder.set_prn(prn);
+    builder.set_time_of_clock(time_of_clock);
+    builder.set_sv_clock(SvClock::new(bias, drift, drift_rate));
+    builder.set_orbit1(orbit(o1)?.into());
+    builder.set_orbit2(orbit(o2)?.into());
+    builder.set_orbit3(orbit(o3)?.into());
+    builder.set_orbit4(orbit(o4)?.into());
+    builder.set_orbit5(orbit(o5)?.into());
+    builder.set_orbit6(orbit(o6)?.into());
+    builder.set_orbit7(orbit(o7)?.into());
+    let ephemeris = builder
+        .build()
+        .map_err(|e| Diagnostic::new(head.number, 1, e.to_string()))?;
+    Ok(Some(Record::Ephemeris(ephemeris)))
+}
+
+/// Parses the broadcast orbit lines of a GLONASS ephemeris.
+///
+/// GLONASS records have three broadcast orbit lines holding the X, Y and Z
+/// components of the state vector; the fourth line of RINEX 3.05 is ignored.
+///
+/// # Arguments
+/// * `head` - Head line of the record, for diagnostics
+/// * `prn` - Satellite slot number
+/// * `time_of_clock` - Epoch of the record (UTC)
+/// * `[clock_bias, relative_frequency_bias, frame_time]` - Clock fields of
+///   the head line
+/// * `orbits` - Broadcast orbit lines
+///
+/// # Returns
+/// * `Ok(GlonassEphemeris)` - The ephemeris
+/// * `Err(Diagnostic)` - If the orbit lines are missing or malformed
+fn read_glonass(
+    head: &Line, prn: usize, time_of_clock: Timestamp,
+    [clock_bias, relative_frequency_bias, frame_time]: [f64; 3],
+    orbits: &[Line],
+) -> Result<GlonassEphemeris, Diagnostic> {
+    let [x, y, z, ..] = orbits else {
+        return Err(Diagnostic::new(
+            head.number,
+            1,
+            format!("expected 3 broadcast orbit lines, found {}", orbits.len()),
+        ));
+    };
+    let [x, y, z] = [
+        floats::<4>(x, INDENT3, FIELD_WIDTH)?,
+        floats::<4>(y, INDENT3, FIELD_WIDTH)?,
+        floats::<4>(z, INDENT3, FIELD_WIDTH)?,
+    ];
+    Ok(GlonassEphemeris {
+        prn,
+        time_of_clock,
+        clock_bias,
+        relative_frequency_bias,
+        frame_time,
+        position: [x[0], y[0], z[0]],
+        velocity: [x[1], y[1], z[1]],
+        acceleration: [x[2], y[2], z[2]],
+        health: x[3],
+        frequency_channel: y[3] as i32,
+        age: z[3],
+    })
+}
+
+/// Parses the GPS-UTC parameters of a RINEX 4 `STO` record.
+///
+/// The record epoch is the reference time of the parameters, from which the
+/// reference week and time of week are derived.
+///
+/// # Arguments
+/// * `epoch_line` - First body line, holding the epoch and offset type
+/// * `parameters` - Second body line, holding the polynomial coefficients
+///
+/// # Returns
+/// * `Ok(Some(DeltaUtc))` - The parameters of a `GPUT` record
+/// * `Ok(None)` - If the record relates other time systems
+/// * `Err(Diagnostic)` - If the record is malformed
+fn read_gps_utc4(
+    epoch_line: &Line, parameters: &Line,
+) -> Result<Option<DeltaUtc>, Diagnostic> {
+    if column(&epoch_line.text, CLOCK_START3, CLOCK_START3 + FIELD_WIDTH)
+        != "GPUT"
+    {
+        return Ok(None);
+    }
+    let seconds = read_epoch3(epoch_line)?.as_second() - GPS_EPOCH.as_second();
+    let [_, a0, a1] = floats(parameters, INDENT3, FIELD_WIDTH)?;
+    Ok(Some(DeltaUtc::new(
+        a0,
+        a1,
+        seconds.rem_euclid(SECONDS_IN_WEEK) as i32,
+        seconds.div_euclid(SECONDS_IN_WEEK) as i32,
+    )))
+}
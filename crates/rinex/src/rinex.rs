@@ -3,17 +3,35 @@
 //!
 //! This crate provides functionality to parse RINEX navigation files containing
 //! GPS ephemeris data. It supports the standard RINEX 2.x format commonly used
-//! for distributing GPS satellite orbit information.
+//! for distributing GPS satellite orbit information, as well as the GPS,
+//! Galileo, BeiDou, GLONASS and QZSS records of RINEX 3 (mixed) navigation
+//! files and the legacy navigation message records of RINEX 4 files.
+//! Gzip and Unix compress files are decompressed on reading, and several
+//! files can be merged into one set of records. Files are read record by
+//! record; malformed records are skipped and reported with their location.
+//! Edited or synthesised data can be written back as RINEX 2.11 or 3.04
+//! navigation files. Precise orbits and clocks are read from SP3-c and
+//! SP3-d files, and GPS almanacs from YUMA and SEM files.
 
+/// YUMA and SEM GPS almanac file reader
+pub mod almanac;
+/// Decompression of gzip and Unix compress navigation files
+pub mod compression;
 /// GPS satellite ephemeris data structures and builders
 pub mod ephemeris;
 /// Error types for RINEX parsing operations
 pub mod error;
-/// RINEX file parsing rules and implementation
+/// Streaming line-oriented RINEX navigation file reader
+pub mod reader;
+/// Parsed RINEX navigation data and its builder
 pub mod rule;
+/// SP3 precise orbit and clock file reader
+pub mod sp3;
 /// UTC time conversion utilities
 pub mod utc;
 /// Utility functions for RINEX parsing
 pub mod utils;
+/// RINEX 2.11 and 3.04 navigation file writer
+pub mod writer;
 pub use error::Error;
 pub use rule::Rinex;
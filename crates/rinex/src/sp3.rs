@@ -0,0 +1,276 @@
+//! Reader of SP3-c and SP3-d precise orbit and clock files.
+//!
+//! SP3 files list the positions and clock offsets of satellites at regular
+//! epochs, typically every 5 or 15 minutes, as estimated by analysis centres
+//! from tracking data. Positions are Earth-fixed, in kilometres, and clocks
+//! in microseconds; [`Sp3Record`] holds them in metres and seconds. Velocity
+//! and correlation records are skipped. Like the navigation file reader,
+//! fields are read from their columns and malformed lines are skipped and
+//! reported as [`Diagnostic`]s.
+
+use std::{collections::HashSet, fs, path::Path};
+
+use jiff::Timestamp;
+
+use crate::{
+    compression,
+    ephemeris::System,
+    error::Error,
+    reader::{Diagnostic, Line, column, epoch, float, int},
+};
+
+/// Clock value marking a missing or bad clock offset (microseconds)
+const BAD_CLOCK: f64 = 999_999.0;
+
+/// A position and clock record of an SP3 file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sp3Record {
+    /// Epoch of the record, in the time system of the file
+    pub time: Timestamp,
+    /// Satellite system
+    pub system: System,
+    /// Satellite number (PRN minus 192 for QZSS, slot for GLONASS)
+    pub prn: usize,
+    /// Earth-fixed position (meters)
+    pub position: [f64; 3],
+    /// Clock offset (seconds), `None` when missing or flagged as bad
+    pub clock: Option<f64>,
+}
+
+/// A parsed SP3 precise orbit and clock file.
+#[derive(Debug, Clone)]
+pub struct Sp3 {
+    /// Format version letter (`c` or `d`)
+    pub version: char,
+    /// Coordinate system of the positions, e.g. `IGS20`
+    pub coordinate_system: String,
+    /// Orbit type, e.g. `FIT` or `HLM`
+    pub orbit_type: String,
+    /// Agency that produced the file
+    pub agency: String,
+    /// Time system of the epochs, e.g. `GPS`
+    pub time_system: String,
+    /// Interval between epochs (seconds)
+    pub interval: f64,
+    /// Position and clock records, ordered by epoch
+    pub records: Vec<Sp3Record>,
+    /// Problems found while reading, such as skipped malformed lines
+    pub warnings: Vec<Diagnostic>,
+}
+impl Sp3 {
+    /// Reads an SP3 file from the filesystem.
+    ///
+    /// Gzip (`.gz`) and Unix compress (`.Z`) files are decompressed
+    /// transparently.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the SP3 file
+    ///
+    /// # Returns
+    /// * `Ok(Sp3)` - Successfully parsed SP3 data
+    /// * `Err(Error)` - If the file cannot be read or parsed
+    ///
+    /// # Errors
+    /// * Returns an error if the file cannot be read or if its header is
+    ///   malformed
+    pub fn read_file(path: &dyn AsRef<Path>) -> Result<Self, Error> {
+        let data = compression::decompress(fs::read(path)?)?;
+        Self::read_string(data.as_str())
+    }
+
+    /// Reads several SP3 files, such as consecutive daily files, and merges
+    /// them into one set of records.
+    ///
+    /// # Arguments
+    /// * `paths` - Paths to the SP3 files
+    ///
+    /// # Returns
+    /// * `Ok(Sp3)` - The merged data, with the header of the first file
+    /// * `Err(Error)` - If no file is given or a file cannot be read
+    ///
+    /// # Errors
+    /// * Returns an error if `paths` is empty or if a file cannot be read or
+    ///   parsed
+    pub fn read_files<P: AsRef<Path>>(paths: &[P]) -> Result<Self, Error> {
+        let (first, others) = paths
+            .split_first()
+            .ok_or_else(|| Error::rinex_builder("no SP3 file given"))?;
+        let mut sp3 = Self::read_file(first)?;
+        for path in others {
+            sp3.merge(Self::read_file(path)?);
+        }
+        Ok(sp3)
+    }
+
+    /// Merges the records of another SP3 file into this one.
+    ///
+    /// The header of `self` is kept. Records are sorted by epoch, and a
+    /// record repeating the satellite and epoch of an earlier one is
+    /// dropped, so that overlapping files merge without duplicates.
+    ///
+    /// # Arguments
+    /// * `other` - The SP3 data to merge into `self`
+    pub fn merge(&mut self, other: Sp3) {
+        self.records.extend(other.records);
+        self.records.sort_by_key(|record| record.time);
+        let mut samples = HashSet::new();
+        self.records.retain(|record| {
+            samples.insert((record.time, record.system, record.prn))
+        });
+        self.warnings.extend(other.warnings);
+    }
+
+    /// Parses an SP3 file from a string.
+    ///
+    /// Records of satellite systems that are not supported are skipped
+    /// silently; positions flagged as bad (all zero) are skipped, and
+    /// malformed lines are skipped and listed in [`Sp3::warnings`].
+    ///
+    /// # Arguments
+    /// * `data` - Text of the SP3 file
+    ///
+    /// # Returns
+    /// * `Ok(Sp3)` - Successfully parsed SP3 data
+    /// * `Err(Error)` - If the file is not an SP3-c or SP3-d file
+    ///
+    /// # Errors
+    /// * `Error::Malformed` - If the first two header lines are missing or
+    ///   malformed, or the version is not `c` or `d`
+    pub fn read_string(data: &str) -> Result<Self, Error> {
+        let mut lines = data.lines().enumerate().map(|(i, text)| Line {
+            number: i + 1,
+            text: text.to_string(),
+        });
+        let first = lines.next().unwrap_or(Line {
+            number: 1,
+            text: String::new(),
+        });
+        let version = match first.text.get(..2) {
+            Some("#c") => 'c',
+            Some("#d") => 'd',
+            _ => {
+                return Err(Error::Malformed(Diagnostic::new(
+                    1,
+                    1,
+                    "expected an SP3-c or SP3-d header",
+                )));
+            }
+        };
+        read_epoch(&first).map_err(Error::Malformed)?;
+        let second = lines
+            .next()
+            .filter(|line| line.text.starts_with("##"))
+            .ok_or_else(|| {
+                Error::Malformed(Diagnostic::new(2, 1, "expected `##` line"))
+            })?;
+        let mut sp3 = Sp3 {
+            version,
+            coordinate_system: column(&first.text, 46, 51).to_string(),
+            orbit_type: column(&first.text, 52, 55).to_string(),
+            agency: column(&first.text, 56, 60).to_string(),
+            time_system: String::from("GPS"),
+            interval: float(&second, 24, 38).map_err(Error::Malformed)?,
+            records: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let mut time_system_read = false;
+        let mut current = None;
+        for line in lines {
+            let result = match line.text.get(..1).unwrap_or_default() {
+                "%" if line.text.starts_with("%c") && !time_system_read => {
+                    time_system_read = true;
+                    match column(&line.text, 9, 12) {
+                        // Unspecified in files converted from SP3-a and b
+                        "" | "ccc" => {}
+                        system => system.clone_into(&mut sp3.time_system),
+                    }
+                    Ok(())
+                }
+                "*" => {
+                    let epoch = read_epoch(&line);
+                    current = epoch.as_ref().ok().copied();
+                    epoch.map(|_| ())
+                }
+                "P" => match current {
+                    Some(time) => read_position(&line, time).map(|record| {
+                        sp3.records.extend(record);
+                    }),
+                    None => Err(Diagnostic::new(
+                        line.number,
+                        1,
+                        "position record without a valid epoch",
+                    )),
+                },
+                _ if line.text.starts_with("EOF") => break,
+                _ => Ok(()),
+            };
+            if let Err(warning) = result {
+                sp3.warnings.push(warning);
+            }
+        }
+        Ok(sp3)
+    }
+}
+
+/// Parses the epoch of the first header line or of an epoch line.
+///
+/// # Arguments
+/// * `line` - Line with the epoch in columns 4-31
+///
+/// # Returns
+/// * `Ok(Timestamp)` - The epoch
+/// * `Err(Diagnostic)` - If the epoch is malformed
+fn read_epoch(line: &Line) -> Result<Timestamp, Diagnostic> {
+    epoch(
+        line,
+        int(line, 3, 7)?,
+        [int(line, 8, 10)?, int(line, 11, 13)?, int(line, 14, 16)?],
+        int(line, 17, 19)?,
+        float(line, 20, 31)?,
+    )
+}
+
+/// Parses a position and clock line.
+///
+/// # Arguments
+/// * `line` - Line starting with `P`
+/// * `time` - Epoch of the line
+///
+/// # Returns
+/// * `Ok(Some(Sp3Record))` - The record
+/// * `Ok(None)` - If the satellite system is not supported or the position
+///   is flagged as bad
+/// * `Err(Diagnostic)` - If the line is malformed
+fn read_position(
+    line: &Line, time: Timestamp,
+) -> Result<Option<Sp3Record>, Diagnostic> {
+    // A blank system letter, found in files converted from SP3-a, means GPS
+    let letter = line.text.chars().nth(1).filter(|c| *c != ' ');
+    let Some(system) = System::from_letter(letter.unwrap_or('G')) else {
+        return Ok(None);
+    };
+    let prn = usize::try_from(int(line, 2, 4)?)
+        .ok()
+        .filter(|&prn| prn > 0)
+        .ok_or_else(|| {
+            Diagnostic::new(line.number, 3, "invalid satellite number")
+        })?;
+    let position = [
+        float(line, 4, 18)?,
+        float(line, 18, 32)?,
+        float(line, 32, 46)?,
+    ];
+    if position.iter().all(|&value| value == 0.0) {
+        return Ok(None);
+    }
+    let clock = float(line, 46, 60)?;
+    Ok(Some(Sp3Record {
+        time,
+        system,
+        prn,
+        position: position.map(|km| km * 1e3),
+        clock: (!column(&line.text, 46, 60).is_empty()
+            && clock.abs() < BAD_CLOCK)
+            .then_some(clock * 1e-6),
+    }))
+}
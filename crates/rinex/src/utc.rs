@@ -12,7 +12,7 @@
 ///
 /// These parameters are typically extracted from the navigation message
 /// broadcast by GPS satellites.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DeltaUtc {
     /// Constant term of the UTC offset polynomial (seconds)
     pub a0: f64,
@@ -0,0 +1,389 @@
+//! Writing of RINEX 2.11 and 3.04 navigation files.
+//!
+//! Headers and records are written in the fixed-column layouts of the
+//! format specifications, with floating-point values in the Fortran `D`
+//! exponent notation (e.g. ` 0.266515184194D-03`), so that the output reads
+//! back through [`crate::reader::RinexReader`] and other RINEX tools.
+
+use std::io::{self, Write};
+
+use jiff::{civil::DateTime, tz::TimeZone};
+
+use crate::{
+    ephemeris::{Ephemeris, GlonassEphemeris, System},
+    rule::Rinex,
+};
+
+/// Version of the RINEX format written by [`Rinex::write`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// RINEX 2.11 GPS navigation file, holding GPS records only
+    Rinex2,
+    /// RINEX 3.04 navigation file, holding the records of every system
+    Rinex3,
+}
+
+/// Width of the floating-point fields of the records (D19.12)
+const FIELD_WIDTH: usize = 19;
+/// Number of decimals of the floating-point fields of the records
+const FIELD_DECIMALS: usize = 12;
+
+/// Writes RINEX data as navigation file text.
+///
+/// # Arguments
+/// * `rinex` - The RINEX data to write
+/// * `writer` - Destination of the text
+/// * `format` - Version of the RINEX format to write
+///
+/// # Returns
+/// * `Ok(())` - If the data was written
+/// * `Err(io::Error)` - If the destination cannot be written, or a value is
+///   not finite
+pub(crate) fn write_rinex<W: Write>(
+    rinex: &Rinex, writer: &mut W, format: Format,
+) -> io::Result<()> {
+    match format {
+        Format::Rinex2 => {
+            write_header2(rinex, writer)?;
+            for ephemeris in &rinex.ephemerides {
+                if ephemeris.system == System::Gps {
+                    write_ephemeris(ephemeris, writer, format)?;
+                }
+            }
+        }
+        Format::Rinex3 => {
+            write_header3(rinex, writer)?;
+            for ephemeris in &rinex.ephemerides {
+                write_ephemeris(ephemeris, writer, format)?;
+            }
+            for ephemeris in &rinex.glonass_ephemerides {
+                write_glonass(ephemeris, writer)?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// Writes the header of a RINEX 2.11 GPS navigation file.
+///
+/// # Arguments
+/// * `rinex` - The RINEX data to write
+/// * `writer` - Destination of the text
+fn write_header2<W: Write>(rinex: &Rinex, writer: &mut W) -> io::Result<()> {
+    write_label(
+        writer,
+        &format!("{:>9.2}{:11}N: GPS NAV DATA", 2.11, ""),
+        "RINEX VERSION / TYPE",
+    )?;
+    write_common_header(rinex, writer)?;
+    let ion = |values: &[f64; 4]| -> io::Result<String> {
+        Ok(format!("  {}", fields(values, 12, 4)?))
+    };
+    write_label(writer, &ion(&rinex.ion_alpha)?, "ION ALPHA")?;
+    write_label(writer, &ion(&rinex.ion_beta)?, "ION BETA")?;
+    let utc = &rinex.delta_utc;
+    write_label(
+        writer,
+        &format!(
+            "   {}{:9}{:9}",
+            fields(&[utc.a0, utc.a1], FIELD_WIDTH, FIELD_DECIMALS)?,
+            utc.time,
+            utc.week
+        ),
+        "DELTA-UTC: A0,A1,T,W",
+    )?;
+    write_label(writer, &format!("{:6}", rinex.leap_seconds), "LEAP SECONDS")?;
+    write_label(writer, "", "END OF HEADER")
+}
+
+/// Writes the header of a RINEX 3.04 navigation file.
+///
+/// The satellite system of the file is the one of its records, or mixed
+/// when records of several systems are present.
+///
+/// # Arguments
+/// * `rinex` - The RINEX data to write
+/// * `writer` - Destination of the text
+fn write_header3<W: Write>(rinex: &Rinex, writer: &mut W) -> io::Result<()> {
+    let mut systems = rinex.ephemerides.iter().map(|eph| eph.system).chain(
+        (!rinex.glonass_ephemerides.is_empty()).then_some(System::Glonass),
+    );
+    let first = systems.next().unwrap_or_default();
+    let system = if systems.any(|system| system != first) {
+        "M: MIXED"
+    } else {
+        match first {
+            System::Gps => "G: GPS",
+            System::Galileo => "E: GALILEO",
+            System::BeiDou => "C: BEIDOU",
+            System::Glonass => "R: GLONASS",
+            System::Qzss => "J: QZSS",
+        }
+    };
+    write_label(
+        writer,
+        &format!("{:>9.2}{:11}{:<20}{system}", 3.04, "", "N: GNSS NAV DATA"),
+        "RINEX VERSION / TYPE",
+    )?;
+    write_common_header(rinex, writer)?;
+    let ion = |name: &str, values: &[f64]| -> io::Result<String> {
+        Ok(format!("{name:<4} {}", fields(values, 12, 4)?))
+    };
+    write_label(writer, &ion("GPSA", &rinex.ion_alpha)?, "IONOSPHERIC CORR")?;
+    write_label(writer, &ion("GPSB", &rinex.ion_beta)?, "IONOSPHERIC CORR")?;
+    if let Some(ion_galileo) = &rinex.ion_galileo {
+        write_label(writer, &ion("GAL", ion_galileo)?, "IONOSPHERIC CORR")?;
+    }
+    let utc = &rinex.delta_utc;
+    write_label(
+        writer,
+        &format!(
+            "GPUT {}{}{:7}{:5}",
+            format_d(utc.a0, 17, 10)?,
+            format_d(utc.a1, 16, 9)?,
+            utc.time,
+            utc.week
+        ),
+        "TIME SYSTEM CORR",
+    )?;
+    write_label(writer, &format!("{:6}", rinex.leap_seconds), "LEAP SECONDS")?;
+    write_label(writer, "", "END OF HEADER")
+}
+
+/// Writes the program and comment lines shared by both header versions.
+///
+/// # Arguments
+/// * `rinex` - The RINEX data to write
+/// * `writer` - Destination of the text
+fn write_common_header<W: Write>(
+    rinex: &Rinex, writer: &mut W,
+) -> io::Result<()> {
+    write_label(
+        writer,
+        &format!(
+            "{:<20.20}{:<20.20}{:<20.20}",
+            rinex.program, rinex.agency, rinex.update
+        ),
+        "PGM / RUN BY / DATE",
+    )?;
+    for comment in rinex.comments.lines() {
+        write_label(writer, comment, "COMMENT")?;
+    }
+    Ok(())
+}
+
+/// Writes a header line, with its label starting in column 61.
+///
+/// # Arguments
+/// * `writer` - Destination of the text
+/// * `content` - Content of columns 1-60, truncated to 60 characters
+/// * `label` - Header label
+fn write_label<W: Write>(
+    writer: &mut W, content: &str, label: &str,
+) -> io::Result<()> {
+    writeln!(writer, "{content:<60.60}{label}")
+}
+
+/// Writes a Keplerian ephemeris record.
+///
+/// # Arguments
+/// * `ephemeris` - The ephemeris to write
+/// * `writer` - Destination of the text
+/// * `format` - Version of the RINEX format to write
+fn write_ephemeris<W: Write>(
+    ephemeris: &Ephemeris, writer: &mut W, format: Format,
+) -> io::Result<()> {
+    let clock = &ephemeris.sv_clock;
+    let head = match format {
+        Format::Rinex2 => epoch2(ephemeris.prn, civil(ephemeris.time_of_clock)),
+        Format::Rinex3 => epoch3(
+            ephemeris.system,
+            ephemeris.prn,
+            civil(ephemeris.time_of_clock),
+        ),
+    };
+    let indent = match format {
+        Format::Rinex2 => "   ",
+        Format::Rinex3 => "    ",
+    };
+    writeln!(
+        writer,
+        "{head}{}",
+        fields(
+            &[clock.bias, clock.drift, clock.drift_rate],
+            FIELD_WIDTH,
+            FIELD_DECIMALS
+        )?
+    )?;
+    let orbits: [[f64; 4]; 7] = [
+        (&ephemeris.orbit1).into(),
+        (&ephemeris.orbit2).into(),
+        (&ephemeris.orbit3).into(),
+        (&ephemeris.orbit4).into(),
+        (&ephemeris.orbit5).into(),
+        (&ephemeris.orbit6).into(),
+        (&ephemeris.orbit7).into(),
+    ];
+    for orbit in &orbits {
+        writeln!(
+            writer,
+            "{indent}{}",
+            fields(orbit, FIELD_WIDTH, FIELD_DECIMALS)?
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a RINEX 3 GLONASS ephemeris record.
+///
+/// # Arguments
+/// * `ephemeris` - The ephemeris to write
+/// * `writer` - Destination of the text
+fn write_glonass<W: Write>(
+    ephemeris: &GlonassEphemeris, writer: &mut W,
+) -> io::Result<()> {
+    let head = epoch3(
+        System::Glonass,
+        ephemeris.prn,
+        civil(ephemeris.time_of_clock),
+    );
+    let [p, v, a] = [
+        ephemeris.position,
+        ephemeris.velocity,
+        ephemeris.acceleration,
+    ];
+    let lines = [
+        [
+            ephemeris.clock_bias,
+            ephemeris.relative_frequency_bias,
+            ephemeris.frame_time,
+        ]
+        .to_vec(),
+        vec![p[0], v[0], a[0], ephemeris.health],
+        vec![p[1], v[1], a[1], f64::from(ephemeris.frequency_channel)],
+        vec![p[2], v[2], a[2], ephemeris.age],
+    ];
+    writeln!(
+        writer,
+        "{head}{}",
+        fields(&lines[0], FIELD_WIDTH, FIELD_DECIMALS)?
+    )?;
+    for line in &lines[1..] {
+        writeln!(writer, "    {}", fields(line, FIELD_WIDTH, FIELD_DECIMALS)?)?;
+    }
+    Ok(())
+}
+
+/// Converts a record epoch to its calendar date and time.
+///
+/// Epochs are written as given, without conversion between time systems.
+///
+/// # Arguments
+/// * `timestamp` - Epoch of the record
+fn civil(timestamp: jiff::Timestamp) -> DateTime {
+    timestamp.to_zoned(TimeZone::UTC).datetime()
+}
+
+/// Formats the satellite number and epoch of a RINEX 2 record.
+///
+/// # Arguments
+/// * `prn` - Satellite number
+/// * `datetime` - Epoch of the record
+///
+/// # Returns
+/// The first 22 columns of the head line, e.g. ` 1 24  6  1  0  0  0.0`
+fn epoch2(prn: usize, datetime: DateTime) -> String {
+    let seconds = f64::from(datetime.second())
+        + f64::from(datetime.subsec_nanosecond()) * 1e-9;
+    format!(
+        "{prn:2} {:02} {:2} {:2} {:2} {:2}{seconds:5.1}",
+        datetime.year() % 100,
+        datetime.month(),
+        datetime.day(),
+        datetime.hour(),
+        datetime.minute()
+    )
+}
+
+/// Formats the satellite identifier and epoch of a RINEX 3 record.
+///
+/// # Arguments
+/// * `system` - Satellite system
+/// * `prn` - Satellite number
+/// * `datetime` - Epoch of the record
+///
+/// # Returns
+/// The first 23 columns of the head line, e.g. `G01 2022 01 01 00 00 00`
+fn epoch3(system: System, prn: usize, datetime: DateTime) -> String {
+    format!(
+        "{}{prn:02} {:04} {:02} {:02} {:02} {:02} {:02}",
+        system.letter(),
+        datetime.year(),
+        datetime.month(),
+        datetime.day(),
+        datetime.hour(),
+        datetime.minute(),
+        datetime.second()
+    )
+}
+
+/// Formats consecutive floating-point fields of the same format.
+///
+/// # Arguments
+/// * `values` - Values of the fields
+/// * `width` - Width of each field
+/// * `decimals` - Number of decimals of each field
+///
+/// # Returns
+/// * `Ok(String)` - The fields
+/// * `Err(io::Error)` - If a value is not finite
+fn fields(values: &[f64], width: usize, decimals: usize) -> io::Result<String> {
+    values
+        .iter()
+        .map(|&value| format_d(value, width, decimals))
+        .collect()
+}
+
+/// Formats a value in the Fortran `Dw.d` notation of RINEX files.
+///
+/// The mantissa lies between 0.1 and 1 and the exponent has two digits, so
+/// `0.000266515184194` reads ` 0.266515184194D-03` in D19.12. Values too
+/// small for a two-digit exponent are written as zero.
+///
+/// # Arguments
+/// * `value` - Value to format
+/// * `width` - Width of the field, right-aligned
+/// * `decimals` - Number of decimals of the mantissa
+///
+/// # Returns
+/// * `Ok(String)` - The formatted field
+/// * `Err(io::Error)` - If the value is not finite or too large
+fn format_d(value: f64, width: usize, decimals: usize) -> io::Result<String> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("value {value} cannot be written in RINEX format"),
+        )
+    };
+    if !value.is_finite() {
+        return Err(invalid());
+    }
+    let scientific = format!("{:.*e}", decimals.saturating_sub(1), value.abs());
+    let (digits, exponent) = scientific.split_once('e').ok_or_else(invalid)?;
+    let exponent = exponent.parse::<i32>().map_err(|_| invalid())? + 1;
+    let (sign, digits, exponent) = if value == 0.0 || exponent < -99 {
+        ("", "0".repeat(decimals), 0)
+    } else if exponent > 99 {
+        return Err(invalid());
+    } else {
+        (
+            if value < 0.0 { "-" } else { "" },
+            digits.replace('.', ""),
+            exponent,
+        )
+    };
+    Ok(format!(
+        "{:>width$}",
+        format!("{sign}0.{digits}D{exponent:+03}")
+    ))
+}
@@ -1,8 +1,23 @@
-use pest::Parser;
-use rinex::{error::Error, rule::*};
+use std::io::Write;
+
+use flate2::{Compression, write::GzEncoder};
+use rinex::{
+    almanac::Almanac,
+    compression::decompress,
+    ephemeris::{Ephemeris, System},
+    error::Error,
+    reader::{Record, RinexReader},
+    rule::*,
+    sp3::Sp3,
+    writer::Format,
+};
 #[test]
-fn rinex_parser() -> Result<(), Error> {
-    let _ = RinexParser::parse(Rule::rinex, RINEX_DATA).map_err(Box::new)?;
+fn rinex_reader() -> Result<(), Error> {
+    let mut reader = RinexReader::new(RINEX_DATA.as_bytes())?;
+    assert!((reader.version() - 2.0).abs() < 1e-9);
+    let records = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(records.len(), 3);
+    assert!(reader.warnings().is_empty());
     Ok(())
 }
 #[test]
@@ -10,6 +25,362 @@ fn rinex_read() -> Result<(), Error> {
     let _ = Rinex::read_string(RINEX_DATA)?;
     Ok(())
 }
+#[test]
+fn rinex3_read_mixed() -> Result<(), Error> {
+    let rinex = Rinex::read_string(RINEX3_DATA)?;
+    assert_eq!(rinex.version, "3.04");
+    assert_eq!(rinex.leap_seconds, 18);
+    assert_eq!(rinex.delta_utc.week, 2190);
+    assert!((rinex.ion_alpha[1] + 1.4901e-8).abs() < 1e-15);
+    assert!(
+        rinex
+            .ion_galileo
+            .is_some_and(|ai| (ai[0] - 28.25).abs() < 1e-9)
+    );
+    // The GLONASS record is kept apart from the Keplerian ones
+    assert_eq!(rinex.ephemerides.len(), 4);
+    let galileo = &rinex.ephemerides[1];
+    assert_eq!(galileo.system, System::Galileo);
+    assert_eq!(galileo.prn, 11);
+    assert!((galileo.sv_clock.bias + 5.1e-4).abs() < 1e-15);
+    assert!((galileo.orbit5.week - 2190.0).abs() < 1e-9);
+    assert!((galileo.orbit6.iodc + 2.3e-9).abs() < 1e-18);
+    let beidou = &rinex.ephemerides[2];
+    assert_eq!(beidou.system, System::BeiDou);
+    assert_eq!(beidou.prn, 6);
+    // The blank spare field reads as zero
+    assert!(beidou.orbit5.code_l2.abs() < 1e-18);
+    assert!((beidou.orbit5.week - 834.0).abs() < 1e-9);
+    assert!((beidou.orbit6.tgd + 5.8e-9).abs() < 1e-18);
+    // QZSS records keep the satellite number of the identifier
+    let qzss = &rinex.ephemerides[3];
+    assert_eq!(qzss.system, System::Qzss);
+    assert_eq!(qzss.prn, 2);
+    assert!((qzss.orbit2.sqrta - 6493.3).abs() < 1e-9);
+    assert!((qzss.orbit5.code_l2 - 2.0).abs() < 1e-12);
+    assert_eq!(rinex.glonass_ephemerides.len(), 1);
+    let glonass = &rinex.glonass_ephemerides[0];
+    assert_eq!(glonass.prn, 5);
+    assert!((glonass.clock_bias + 1.234_567_890_123e-4).abs() < 1e-16);
+    assert!((glonass.position[1] - 2.0e4).abs() < 1e-9);
+    assert!((glonass.velocity[2] - 1.0).abs() < 1e-12);
+    assert_eq!(glonass.frequency_channel, 1);
+    Ok(())
+}
+#[test]
+fn rinex4_read_mixed() -> Result<(), Error> {
+    let rinex = Rinex::read_string(RINEX4_DATA)?;
+    assert_eq!(rinex.version, "4.00");
+    assert_eq!(rinex.comments, "SYNTHETIC RINEX 4 NAVIGATION FILE");
+    assert_eq!(rinex.leap_seconds, 18);
+    // Parameters moved from the header to STO and ION records
+    assert_eq!(rinex.delta_utc.week, 2190);
+    assert_eq!(rinex.delta_utc.time, 518_400);
+    assert!((rinex.delta_utc.a0 + 9.313_225_746_155e-10).abs() < 1e-20);
+    assert!((rinex.ion_alpha[3] - 1.1921e-7).abs() < 1e-15);
+    assert!((rinex.ion_beta[0] - 1.1264e5).abs() < 1e-6);
+    assert!((rinex.ion_beta[3] - 8.5197e5).abs() < 1e-6);
+    assert!(
+        rinex
+            .ion_galileo
+            .is_some_and(|ai| (ai[2] - 2.3193e-3).abs() < 1e-12)
+    );
+    // The CNAV record of G01 is skipped
+    assert_eq!(rinex.ephemerides.len(), 2);
+    let gps = &rinex.ephemerides[0];
+    assert_eq!(gps.system, System::Gps);
+    assert_eq!(gps.prn, 1);
+    assert!((gps.orbit1.iode - 84.0).abs() < 1e-9);
+    assert!((gps.orbit7.tom - 511_218.0).abs() < 1e-9);
+    let galileo = &rinex.ephemerides[1];
+    assert_eq!(galileo.system, System::Galileo);
+    assert_eq!(galileo.prn, 11);
+    assert!((galileo.orbit2.sqrta - 5440.6).abs() < 1e-9);
+    assert_eq!(rinex.glonass_ephemerides.len(), 1);
+    assert!((rinex.glonass_ephemerides[0].position[2] + 5.0e3).abs() < 1e-9);
+    Ok(())
+}
+#[test]
+fn rinex_merge() -> Result<(), Error> {
+    let mut rinex = Rinex::read_string(RINEX_DATA)?;
+    rinex.merge(Rinex::read_string(RINEX3_DATA)?);
+    rinex.merge(Rinex::read_string(RINEX3_DATA)?);
+    let mut update = Rinex::read_string(RINEX_DATA)?;
+    // A data set uploaded with a new IODE is kept
+    update.ephemerides[0].orbit1.iode = 40.0;
+    rinex.merge(update);
+    // The header of the first file is kept
+    assert_eq!(rinex.delta_utc.week, 2317);
+    assert!(rinex.ion_galileo.is_some());
+    assert_eq!(rinex.ephemerides.len(), 3 + 4 + 1);
+    assert_eq!(rinex.glonass_ephemerides.len(), 1);
+    // Records are sorted by time of clock, the 2022 data sets first
+    assert!(
+        rinex
+            .ephemerides
+            .windows(2)
+            .all(|pair| pair[0].time_of_clock <= pair[1].time_of_clock)
+    );
+    assert_eq!(rinex.ephemerides[1].system, System::Galileo);
+    Ok(())
+}
+#[test]
+fn rinex_decompress() -> Result<(), Error> {
+    // The text below compressed by `compress`
+    const COMPRESSED: [u8; 30] = [
+        0x1f, 0x9d, 0x90, 0x54, 0x9e, 0x08, 0x29, 0xf2, 0x44, 0x8a, 0x93, 0x27,
+        0x54, 0x02, 0x0e, 0x2c, 0xa8, 0x90, 0xa0, 0x41, 0x84, 0x0a, 0x82, 0x48,
+        0x9c, 0x48, 0xb1, 0x62, 0x10, 0x05,
+    ];
+    let text = "TOBEORNOTTOBEORTOBEORNOT\nAAAAAAAAAAAAAAAA\n";
+    assert_eq!(decompress(COMPRESSED.to_vec())?, text);
+    assert_eq!(decompress(text.as_bytes().to_vec())?, text);
+    // Concatenated gzip members are read as a whole
+    let (head, tail) = RINEX_DATA.split_at(RINEX_DATA.len() / 2);
+    let mut gzip = Vec::new();
+    for part in [head, tail] {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(part.as_bytes())?;
+        gzip.extend(encoder.finish()?);
+    }
+    let rinex = Rinex::read_string(&decompress(gzip)?)?;
+    assert_eq!(rinex.ephemerides.len(), 3);
+    assert!(matches!(
+        decompress(b"1.0                 COMPACT RINEX FORMAT                    CRINEX VERS   / TYPE\n".to_vec()),
+        Err(Error::Decompress(_))
+    ));
+    Ok(())
+}
+#[test]
+fn rinex_reader_columns() -> Result<(), Error> {
+    // Fields that touch, a number without a leading zero and a second
+    // comment line, which a whitespace-separated parser cannot read
+    let data = RINEX_DATA
+        .replace(
+            "    0.390000000000D+02 0.776562500000D+02",
+            "   0.3900000000000D+020.7765625000000D+02",
+        )
+        .replace(" 0.133835959714D-01", "  .133835959714D-01")
+        .replacen(
+            "IGS BROADCAST EPHEMERIS FILE                                COMMENT",
+            "IGS BROADCAST EPHEMERIS FILE                                COMMENT\n\
+             SECOND COMMENT                                              COMMENT",
+            1,
+        );
+    let mut reader = RinexReader::new(data.as_bytes())?;
+    let records = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(records.len(), 3);
+    assert!(reader.warnings().is_empty());
+    let rinex = Rinex::read_string(&data)?;
+    assert!(rinex.warnings.is_empty());
+    assert_eq!(
+        rinex.comments,
+        "IGS BROADCAST EPHEMERIS FILE\nSECOND COMMENT"
+    );
+    assert_eq!(rinex.ephemerides.len(), 3);
+    let first = &rinex.ephemerides[0];
+    assert!((first.orbit1.iode - 39.0).abs() < 1e-12);
+    assert!((first.orbit1.crs - 77.65625).abs() < 1e-12);
+    assert!((first.orbit2.ecc - 0.013_383_595_971_4).abs() < 1e-15);
+    Ok(())
+}
+#[test]
+fn rinex_reader_skips_malformed() -> Result<(), Error> {
+    // Corrupts the second field of the first orbit line of PRN 2 (line 18)
+    let data = RINEX_DATA.replace("-0.209375000000D+02", "-0.2093750000X0D+02");
+    let mut reader = RinexReader::new(data.as_bytes())?;
+    assert!((reader.version() - 2.0).abs() < 1e-9);
+    let mut prns = Vec::new();
+    for record in &mut reader {
+        if let Record::Ephemeris(ephemeris) = record? {
+            prns.push(ephemeris.prn);
+        }
+    }
+    assert_eq!(prns, [1, 3]);
+    let [warning] = reader.warnings() else {
+        panic!("expected one warning, found {:?}", reader.warnings());
+    };
+    assert_eq!((warning.line, warning.column), (18, 23));
+    assert!(
+        warning
+            .to_string()
+            .starts_with("line 18, column 23: invalid")
+    );
+    // A file without a version line cannot be read at all
+    let headless = RINEX_DATA.split_once('\n').map_or("", |(_, rest)| rest);
+    let error = RinexReader::new(headless.as_bytes()).err();
+    assert!(
+        matches!(error, Some(Error::Malformed(diagnostic)) if diagnostic.line == 1)
+    );
+    Ok(())
+}
+#[test]
+fn rinex_write() -> Result<(), Error> {
+    // RINEX 2 output reproduces the broadcast file but for the version line
+    let rinex = Rinex::read_string(RINEX_DATA)?;
+    let mut text = Vec::new();
+    rinex.write(&mut text, Format::Rinex2)?;
+    let text = String::from_utf8(text)?;
+    assert!(text.starts_with("     2.11           N: GPS NAV DATA"));
+    let expected: Vec<&str> = RINEX_DATA
+        .lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    assert_eq!(text.lines().skip(1).collect::<Vec<_>>(), expected);
+
+    // RINEX 3 output reads back with the same records
+    let mut rinex = Rinex::read_string(RINEX3_DATA)?;
+    let mut text = Vec::new();
+    rinex.write(&mut text, Format::Rinex3)?;
+    let read_back = Rinex::read_string(&String::from_utf8(text)?)?;
+    assert!(read_back.warnings.is_empty());
+    assert_eq!(read_back.comments, rinex.comments);
+    assert_eq!(read_back.leap_seconds, 18);
+    assert_eq!(
+        (read_back.delta_utc.time, read_back.delta_utc.week),
+        (405_504, 2190)
+    );
+    assert!((read_back.delta_utc.a1 - rinex.delta_utc.a1).abs() < 1e-24);
+    // Ionospheric parameters have four significant digits (D12.4)
+    assert!((read_back.ion_beta[3] - 8.520e5).abs() < 1e-9);
+    assert!(
+        read_back
+            .ion_galileo
+            .is_some_and(|ai| (ai[1] - 2.344e-2).abs() < 1e-15)
+    );
+    let values = |eph: &Ephemeris| {
+        let clock = &eph.sv_clock;
+        let orbits: [[f64; 4]; 7] = [
+            (&eph.orbit1).into(),
+            (&eph.orbit2).into(),
+            (&eph.orbit3).into(),
+            (&eph.orbit4).into(),
+            (&eph.orbit5).into(),
+            (&eph.orbit6).into(),
+            (&eph.orbit7).into(),
+        ];
+        let mut values = vec![clock.bias, clock.drift, clock.drift_rate];
+        values.extend(orbits.iter().flatten());
+        values
+    };
+    assert_eq!(read_back.ephemerides.len(), rinex.ephemerides.len());
+    for (read, written) in read_back.ephemerides.iter().zip(&rinex.ephemerides)
+    {
+        assert_eq!(
+            (read.system, read.prn, read.time_of_clock),
+            (written.system, written.prn, written.time_of_clock)
+        );
+        for (a, b) in values(read).iter().zip(values(written)) {
+            assert!((a - b).abs() <= b.abs() * 1e-11);
+        }
+    }
+    let [glonass] = &read_back.glonass_ephemerides[..] else {
+        panic!("expected one GLONASS record");
+    };
+    assert!((glonass.clock_bias + 1.234_567_890_12e-4).abs() < 1e-16);
+    assert!((glonass.position[2] + 5.0e3).abs() < 1e-9);
+    assert_eq!(glonass.frequency_channel, 1);
+
+    // Values without a RINEX representation are rejected
+    rinex.ephemerides[0].sv_clock.bias = f64::NAN;
+    let result = rinex.write(Vec::new(), Format::Rinex3);
+    assert!(matches!(result, Err(Error::WriteRinex(_))));
+    Ok(())
+}
+#[test]
+fn sp3_read() -> Result<(), Error> {
+    let sp3 = Sp3::read_string(SP3_DATA)?;
+    assert_eq!(sp3.version, 'd');
+    assert_eq!(
+        (sp3.coordinate_system.as_str(), sp3.agency.as_str()),
+        ("IGS20", "IGS")
+    );
+    assert_eq!(sp3.time_system, "GPS");
+    assert!((sp3.interval - 900.0).abs() < 1e-9);
+    // Bad positions, unsupported systems and velocities are skipped
+    let satellites: Vec<_> = sp3
+        .records
+        .iter()
+        .map(|record| (record.system, record.prn))
+        .collect();
+    assert_eq!(
+        satellites,
+        [
+            (System::Gps, 1),
+            (System::Gps, 2),
+            (System::Glonass, 5),
+            (System::Gps, 1),
+            (System::Glonass, 5),
+        ]
+    );
+    let first = &sp3.records[0];
+    assert_eq!(first.time.to_string(), "2022-01-01T00:00:00Z");
+    assert!((first.position[0] + 5_836_582.46).abs() < 1e-6);
+    assert!(
+        first
+            .clock
+            .is_some_and(|clock| (clock + 29.391_335e-6).abs() < 1e-15)
+    );
+    assert_eq!(sp3.records[1].clock, None);
+    assert_eq!(sp3.records[3].time.to_string(), "2022-01-01T00:15:00Z");
+    let [warning] = &sp3.warnings[..] else {
+        panic!("expected one warning, found {:?}", sp3.warnings);
+    };
+    assert_eq!((warning.line, warning.column), (19, 5));
+
+    // Overlapping files merge without duplicates
+    let mut merged = Sp3::read_string(SP3_DATA)?;
+    merged.merge(sp3.clone());
+    assert_eq!(merged.records, sp3.records);
+    assert!(Sp3::read_string(RINEX_DATA).is_err());
+    Ok(())
+}
+#[test]
+fn almanac_read() -> Result<(), Error> {
+    let yuma = Almanac::read_yuma(YUMA_DATA)?;
+    let sem = Almanac::read_sem(SEM_DATA)?;
+    assert_eq!(yuma.entries.len(), 2);
+    assert_eq!(sem.entries.len(), 2);
+    let first = &yuma.entries[0];
+    assert_eq!((first.prn, first.health, first.week), (1, 0, 150));
+    assert!((first.toa - 589_824.0).abs() < 1e-9);
+    assert!((first.sqrt_a - 5_153.677_734).abs() < 1e-9);
+    // SEM angles are in semicircles and the inclination is an offset
+    for (y, s) in yuma.entries.iter().zip(&sem.entries) {
+        assert_eq!((y.prn, y.week), (s.prn, s.week));
+        let values = |e: &rinex::almanac::AlmanacEntry| {
+            [
+                e.eccentricity,
+                e.toa,
+                e.inclination,
+                e.omega_dot,
+                e.sqrt_a,
+                e.omega0,
+                e.perigee,
+                e.mean_anomaly,
+                e.af0,
+                e.af1,
+            ]
+        };
+        for (a, b) in values(y).iter().zip(values(s)) {
+            assert!((a - b).abs() <= b.abs() * 1e-12);
+        }
+    }
+    // Malformed values are located
+    let error =
+        Almanac::read_yuma(&YUMA_DATA.replace("5153.677734", "5153.6x")).err();
+    assert!(matches!(
+        error,
+        Some(Error::Malformed(diagnostic)) if (diagnostic.line, diagnostic.column) == (8, 29)
+    ));
+    let error = Almanac::read_sem(&SEM_DATA.replace("\n63\n", "\n6x\n")).err();
+    assert!(matches!(
+        error,
+        Some(Error::Malformed(diagnostic)) if diagnostic.line == 5
+    ));
+    Ok(())
+}
 const RINEX_DATA: &str = r"     2              NAVIGATION DATA                         RINEX VERSION / TYPE
 CCRINEXN V1.6.0 UX  CDDIS               02-JUN-24 23:31     PGM / RUN BY / DATE
 IGS BROADCAST EPHEMERIS FILE                                COMMENT
@@ -44,3 +415,177 @@ IGS BROADCAST EPHEMERIS FILE                                COMMENT
     0.511218000000D+06 0.400000000000D+01 0.000000000000D+00 0.000000000000D+00
 
     ";
+const RINEX3_DATA: &str = r"     3.04           N: GNSS NAV DATA    M: MIXED            RINEX VERSION / TYPE
+anywhere-sdr        test                20220101 000000 UTC PGM / RUN BY / DATE
+SYNTHETIC MIXED NAVIGATION FILE                             COMMENT
+GAL    2.8250E+01  2.3438E-02  2.3193E-03  0.0000E+00       IONOSPHERIC CORR
+GPSA   1.1176E-08 -1.4901E-08 -5.9605E-08  1.1921E-07       IONOSPHERIC CORR
+GPSB   1.1264E+05 -1.1469E+05 -1.3107E+05  8.5197E+05       IONOSPHERIC CORR
+GPUT -9.3132257462E-10-8.881784197E-16 405504 2190          TIME SYSTEM CORR
+    18                                                      LEAP SECONDS
+                                                            END OF HEADER
+G01 2022 01 01 00 00 00 4.691267386079E-04-1.000444171950E-11 0.000000000000E+00
+     8.400000000000E+01-1.500000000000E+01 4.200000000000E-09-1.200000000000E+00
+    -8.400000000000E-07 1.100000000000E-02 5.100000000000E-06 5.153600000000E+03
+     5.184000000000E+05 1.100000000000E-07-1.000000000000E+00-5.000000000000E-08
+     9.800000000000E-01 2.900000000000E+02 7.400000000000E-01-8.000000000000E-09
+     1.000000000000E-10 1.000000000000E+00 2.190000000000E+03 0.000000000000E+00
+     2.000000000000E+00 0.000000000000E+00-1.200000000000E-08 8.400000000000E+01
+     5.112180000000E+05 4.000000000000E+00
+E11 2022 01 01 00 00 00-5.100000000000E-04-6.800000000000E-12 0.000000000000E+00
+     1.000000000000E+02-5.000000000000E+01 3.000000000000E-09 1.000000000000E+00
+    -2.400000000000E-06 2.000000000000E-04 8.000000000000E-06 5.440600000000E+03
+     5.184000000000E+05 1.000000000000E-08 2.000000000000E+00-3.000000000000E-08
+     9.770000000000E-01 1.800000000000E+02 5.000000000000E-01-5.600000000000E-09
+    -1.000000000000E-10 5.170000000000E+02 2.190000000000E+03
+     3.120000000000E+00 0.000000000000E+00-2.000000000000E-09-2.300000000000E-09
+     5.180000000000E+02
+C06 2022 01 01 00 00 00-1.100000000000E-04 3.200000000000E-11 0.000000000000E+00
+     1.000000000000E+00 1.500000000000E+02 1.200000000000E-09 5.000000000000E-01
+     5.000000000000E-06 6.000000000000E-03 1.000000000000E-05 6.493400000000E+03
+     5.184000000000E+05 2.000000000000E-08-1.500000000000E+00 1.000000000000E-08
+     9.600000000000E-01-1.200000000000E+02-2.700000000000E+00-1.800000000000E-09
+     2.000000000000E-10                    8.340000000000E+02
+     2.000000000000E+00 0.000000000000E+00-5.800000000000E-09-2.100000000000E-09
+     5.184000000000E+05 1.000000000000E+00
+R05 2022 01 01 00 15 00-1.234567890123E-04 0.000000000000E+00 5.184000000000E+05
+     1.000000000000E+04 1.000000000000E+00 0.000000000000E+00 0.000000000000E+00
+     2.000000000000E+04 1.000000000000E+00 0.000000000000E+00 1.000000000000E+00
+    -5.000000000000E+03 1.000000000000E+00 0.000000000000E+00 0.000000000000E+00
+J02 2022 01 01 00 00 00-1.700000000000E-06 1.100000000000E-12 0.000000000000E+00
+     1.200000000000E+01 2.300000000000E+02 2.700000000000E-09 2.100000000000E+00
+     7.500000000000E-06 7.500000000000E-02 1.900000000000E-05 6.493300000000E+03
+     5.184000000000E+05-1.600000000000E-06 2.500000000000E+00 1.500000000000E-07
+     7.210000000000E-01-7.800000000000E+02-1.570000000000E+00-2.700000000000E-09
+     1.000000000000E-10 2.000000000000E+00 2.190000000000E+03 1.000000000000E+00
+     2.800000000000E+00 1.000000000000E+00-4.600000000000E-09 8.760000000000E+02
+     5.148000000000E+05 0.000000000000E+00
+";
+const RINEX4_DATA: &str = r"     4.00           N: GNSS NAV DATA    M: MIXED            RINEX VERSION / TYPE
+anywhere-sdr        test                20220101 000000 UTC PGM / RUN BY / DATE
+SYNTHETIC RINEX 4 NAVIGATION FILE                           COMMENT
+    18                                                      LEAP SECONDS
+                                                            END OF HEADER
+> EPH G01 LNAV
+G01 2022 01 01 00 00 00 4.691267386079E-04-1.000444171950E-11 0.000000000000E+00
+     8.400000000000E+01-1.500000000000E+01 4.200000000000E-09-1.200000000000E+00
+    -8.400000000000E-07 1.100000000000E-02 5.100000000000E-06 5.153600000000E+03
+     5.184000000000E+05 1.100000000000E-07-1.000000000000E+00-5.000000000000E-08
+     9.800000000000E-01 2.900000000000E+02 7.400000000000E-01-8.000000000000E-09
+     1.000000000000E-10 1.000000000000E+00 2.190000000000E+03 0.000000000000E+00
+     2.000000000000E+00 0.000000000000E+00-1.200000000000E-08 8.400000000000E+01
+     5.112180000000E+05 4.000000000000E+00
+> EPH G01 CNAV
+G01 2022 01 01 00 00 00 4.691267386079E-04-1.000444171950E-11 0.000000000000E+00
+     5.000000000000E-01-1.500000000000E+01 4.200000000000E-09-1.200000000000E+00
+    -8.400000000000E-07 1.100000000000E-02 5.100000000000E-06 5.153600000000E+03
+     5.184000000000E+05 1.100000000000E-07-1.000000000000E+00-5.000000000000E-08
+     9.800000000000E-01 2.900000000000E+02 7.400000000000E-01-8.000000000000E-09
+     1.000000000000E-10 0.000000000000E+00 0.000000000000E+00 0.000000000000E+00
+     0.000000000000E+00 0.000000000000E+00-1.200000000000E-08 0.000000000000E+00
+     0.000000000000E+00 0.000000000000E+00 0.000000000000E+00 0.000000000000E+00
+     5.112180000000E+05 2.190000000000E+03
+> EPH E11 INAV
+E11 2022 01 01 00 00 00-5.100000000000E-04-6.800000000000E-12 0.000000000000E+00
+     1.000000000000E+02-5.000000000000E+01 3.000000000000E-09 1.000000000000E+00
+    -2.400000000000E-06 2.000000000000E-04 8.000000000000E-06 5.440600000000E+03
+     5.184000000000E+05 1.000000000000E-08 2.000000000000E+00-3.000000000000E-08
+     9.770000000000E-01 1.800000000000E+02 5.000000000000E-01-5.600000000000E-09
+    -1.000000000000E-10 5.170000000000E+02 2.190000000000E+03
+     3.120000000000E+00 0.000000000000E+00-2.000000000000E-09-2.300000000000E-09
+     5.180000000000E+05
+> EPH R05 FDMA
+R05 2022 01 01 00 15 00-1.234567890123E-04 0.000000000000E+00 5.184000000000E+05
+     1.000000000000E+04 1.000000000000E+00 0.000000000000E+00 0.000000000000E+00
+     2.000000000000E+04 1.000000000000E+00 0.000000000000E+00 1.000000000000E+00
+    -5.000000000000E+03 1.000000000000E+00 0.000000000000E+00 0.000000000000E+00
+     0.000000000000E+00 0.000000000000E+00 0.000000000000E+00 0.000000000000E+00
+> STO G01 LNAV
+    2022 01 01 00 00 00 GPUT                                  UTC(USNO)
+     5.112180000000E+05-9.313225746155E-10-8.881784197001E-16 0.000000000000E+00
+> EOP G01 CNVX
+    2022 01 01 00 00 00 1.000000000000E-03 2.000000000000E-04 0.000000000000E+00
+                        3.000000000000E-03 4.000000000000E-04 0.000000000000E+00
+                       -1.000000000000E-02 2.000000000000E-04 0.000000000000E+00
+> ION G01 LNAV
+    2022 01 01 00 00 00 1.117600000000E-08-1.490100000000E-08-5.960500000000E-08
+     1.192100000000E-07 1.126400000000E+05-1.146900000000E+05-1.310700000000E+05
+     8.519700000000E+05 1.000000000000E+00
+> ION E11 IFNV
+    2022 01 01 00 00 00 2.825000000000E+01 2.343800000000E-02 2.319300000000E-03
+     0.000000000000E+00
+";
+const SP3_DATA: &str = r"#dP2022  1  1  0  0  0.00000000       2 ORBIT IGS20 FIT IGS
+## 2190 518400.00000000   900.00000000 59580 0.0000000000000
++    4   G01G02E11R05  0  0  0  0  0  0  0  0  0  0  0  0  0
+++         2  2  2  2  0  0  0  0  0  0  0  0  0  0  0  0  0
+%c M  cc GPS ccc cccc cccc cccc cccc ccccc ccccc ccccc ccccc
+%c cc cc ccc ccc cccc cccc cccc cccc ccccc ccccc ccccc ccccc
+%f  1.2500000  1.025000000  0.00000000000  0.000000000000000
+%i    0    0    0    0      0      0      0      0         0
+/* SAMPLE PRECISE ORBITS
+*  2022  1  1  0  0  0.00000000
+PG01  -5836.582460 -24975.548850   8089.197286    -29.391335
+PG02  12345.678901 -20123.456789  -9876.543210 999999.999999
+PE11      0.000000      0.000000      0.000000 999999.999999
+PR05  10234.567890  15678.901234 -17890.123456     12.345678
+PL51   1000.000000   2000.000000   3000.000000      0.000000
+VG01  -1234.567890  12345.678901  -2345.678901    999999.999999
+*  2022  1  1  0 15  0.00000000
+PG01  -5123.456789 -24678.901234  10234.567890    -29.391400
+PG02  12345.67x901 -20123.456789  -9876.543210      1.000000
+PR05  10345.678901  15789.012345 -17801.234567     12.345700
+EOF
+";
+const YUMA_DATA: &str = r"******** Week 150 almanac for PRN-01 ********
+ID:                         01
+Health:                     000
+Eccentricity:               0.1063442230E-001
+Time of Applicability(s):  589824.0000
+Orbital Inclination(rad):   0.9876025841
+Rate of Right Ascen(r/s):  -0.7828897578E-008
+SQRT(A)  (m 1/2):           5153.677734
+Right Ascen at Week(rad):  -0.2290121722E+001
+Argument of Perigee(rad):   0.660052418
+Mean Anom(rad):             0.1742610574E+001
+Af0(s):                     0.1525878906E-004
+Af1(s/s):                   0.0000000000E+000
+week:                        150
+
+******** Week 150 almanac for PRN-02 ********
+ID:                         02
+Health:                     000
+Eccentricity:               0.2034473419E-001
+Time of Applicability(s):  589824.0000
+Orbital Inclination(rad):   0.9460620880
+Rate of Right Ascen(r/s):  -0.7771752131E-008
+SQRT(A)  (m 1/2):           5153.604004
+Right Ascen at Week(rad):   0.2976163864E+001
+Argument of Perigee(rad):  -2.093561262
+Mean Anom(rad):            -0.1394786567E+001
+Af0(s):                    -0.4673004150E-003
+Af1(s/s):                  -0.3637978807E-011
+week:                        150
+
+";
+const SEM_DATA: &str = r"2 CURRENT.ALM
+150 589824
+
+1
+63
+0
+ 1.06344223000000E-02  1.43636661396879E-02 -2.49201549699773E-09
+ 5.15367773400000E+03 -7.28968384676845E-01  2.10101210048915E-01
+ 5.54690173472609E-01  1.52587890600000E-05  0.00000000000000E+00
+0
+11
+
+2
+64
+0
+ 2.03447341900000E-02  1.14091555407869E-03 -2.47382553626724E-09
+ 5.15360400400000E+03  9.47342380814149E-01 -6.66401247026012E-01
+-4.43974353392449E-01 -4.67300415000000E-04 -3.63797880700000E-12
+0
+11
+";